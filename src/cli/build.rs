@@ -11,6 +11,12 @@ pub fn build_cli() -> Command {
         .arg_required_else_help(true)
         .subcommand(subcommand_power())
         .subcommand(subcommand_get())
+        .subcommand(subcommand_hw())
+        .subcommand(subcommand_bootparams())
+        .subcommand(subcommand_lease())
+        .subcommand(subcommand_dashboard())
+        .subcommand(subcommand_diff())
+        .subcommand(subcommand_audit())
         .subcommand(Command::new("add")
             .arg_required_else_help(true)
             .about("WIP - Add hw components to cluster")
@@ -136,6 +142,7 @@ pub fn build_cli() -> Command {
                     .arg(arg!(-t --to <VALUE> "The name of the target vCluster to which the compute nodes will be moved.").required(true))
                     .arg(arg!(<XNAMES> "Comma separated list of xnames to add to a cluster.\neg: 'x1003c1s7b0n0,x1003c1s7b0n1,x1003c1s7b1n0'").required(true))
                     .arg(arg!(-d --"dry-run" "Simulates the execution of the command without making any actual changes.").action(ArgAction::SetTrue))
+                    .arg(arg!(--"health-check" <POLICY> "Check nodes health (power state, CFS configuration status, HSM enabled and flag, running CFS sessions) before moving them and apply a policy to unhealthy nodes: 'skip' does not move them, 'warn' moves them anyway, 'abort' cancels the operation. If missing, no checks are done").value_parser(["skip", "warn", "abort"]))
                 ),
         )
         .subcommand(
@@ -144,7 +151,8 @@ pub fn build_cli() -> Command {
                 .arg_required_else_help(true)
                 .about("Update nodes power status or boot params")
                 .subcommand(subcommand_update_nodes())
-                .subcommand(subcommand_update_hsm_group()),
+                .subcommand(subcommand_update_hsm_group())
+                .subcommand(subcommand_update_sat_file_lock()),
         )
         .subcommand(
             subcommand_log()
@@ -158,8 +166,30 @@ pub fn build_cli() -> Command {
                     Command::new("node")
                         .visible_alias("n")
                         .about("Connects to a node's console")
+                        .arg(arg!(-r --record "Record the console session in asciicast format in manta's data folder. Use 'manta console replay' to replay it").action(ArgAction::SetTrue))
+                        .arg(arg!(--"record-file" <FILE> "File to record the console session in asciicast format").value_parser(value_parser!(PathBuf)))
                         .arg(arg!(<XNAME> "node xname").required(true)),
                 )
+                .subcommand(
+                    Command::new("tail")
+                        .arg_required_else_help(true)
+                        .about("Follows the console of all nodes in a cluster or a list of nodes without attaching to them. Console output is interleaved and each line is prefixed with the node xname")
+                        .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "Cluster name"))
+                        .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames or hostlist expression.\neg 'x1003c1s7b0n0,x1003c1s7b0n1' or 'x1003c1s7b0n[0-1]'"))
+                        .arg(arg!(-g --grep <REGEX> ... "Only show console lines matching this regular expression. Can be used multiple times, eg --grep 'Kernel panic' --grep 'Call Trace'"))
+                        .arg(arg!(-i --"ignore-case" "Ignore case when matching '--grep' regular expressions").action(ArgAction::SetTrue).requires("grep"))
+                        .arg(arg!(-l --"log-dir" <DIR> "Folder to store the whole console output of each node in a file named after the node xname").value_parser(value_parser!(PathBuf)))
+                        .arg(arg!(-d --duration <DURATION> "Stop following the consoles after this period of time. eg '30m', '2h'. If missing, consoles are followed until Ctrl-C is pressed"))
+                        .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true)),
+                )
+                .subcommand(
+                    Command::new("replay")
+                        .arg_required_else_help(true)
+                        .about("Replays a console session recording")
+                        .arg(arg!(-s --speed <FACTOR> "Replay speed factor, eg '2' to replay twice as fast").value_parser(value_parser!(f64)).default_value("1"))
+                        .arg(arg!(-i --"idle-time-limit" <SECONDS> "Max time to wait between outputs, useful to skip long pauses").value_parser(value_parser!(f64)))
+                        .arg(arg!(<FILE> "Console recording. Either a file path or a recording name in manta's data folder").required(true)),
+                )
                 .subcommand(
                     Command::new("target-ansible")
                         .visible_aliases(["t", "ta", "target", "ansible"])
@@ -169,6 +199,7 @@ pub fn build_cli() -> Command {
                 ),
         )
         .subcommand(subcommand_delete())
+        .subcommand(subcommand_gc())
         .subcommand(subcommand_validate_local_repo())
         .subcommand(subcommand_config())
         .subcommand(Command::new("delete-session")
@@ -275,6 +306,19 @@ pub fn subcommand_delete() -> Command {
                 .group(ArgGroup::new("since_and_until").args(["since", "until"]).multiple(true).requires("until").conflicts_with("configuration-name"))
 }
 
+pub fn subcommand_gc() -> Command {
+    Command::new("gc")
+        .about("Deletes CFS configurations, CFS sessions and images not needed anymore based on a policy. Policy values are read from the '[gc]' section in the configuration file and can be overridden with the arguments below. CFS configurations used as desired configuration, by BOS sessiontemplates or to build images booting nodes are never deleted")
+        .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name. If missing, all HSM groups available are used"))
+        .arg(arg!(-k --"keep-last" <NUMBER> "Number of most recent CFS configurations to keep per HSM group or prefix").value_parser(value_parser!(usize)))
+        .arg(arg!(-p --prefix <PREFIX> ... "CFS configuration name prefix. CFS configurations matching a prefix are grouped by prefix instead of HSM group.\neg:\nmanta gc --keep-last 3 --prefix my-cluster-compute- --prefix my-cluster-uan-"))
+        .arg(arg!(--"failed-session-max-age" <DURATION> "Delete failed CFS sessions older than this.\neg '12h', '7d', '4w'"))
+        .arg(arg!(--"orphan-image-max-age" <DURATION> "Delete images not referenced by CFS sessions, BOS sessiontemplates or boot parameters older than this. Only images with the HSM group name in their name are considered.\neg '30d'"))
+        .arg(arg!(-o --output <FORMAT> "Output format for the report.").value_parser(["table", "json"]).default_value("table"))
+        .arg(arg!(-y --yes "Automatic yes to prompts; assume 'yes' as answer to all prompts and run non-interactively.").action(ArgAction::SetTrue))
+        .arg(arg!(-d --"dry-run" "Prints the report without deleting anything.").action(ArgAction::SetTrue))
+}
+
 pub fn subcommand_get_hw_components() -> Command {
     let command_get_hs_configuration_cluster = Command::new("cluster")
                 .visible_aliases(["c", "clstr"])
@@ -299,6 +343,119 @@ pub fn subcommand_get_hw_components() -> Command {
         .subcommand(command_get_hs_configuration_node)
 }
 
+pub fn subcommand_hw() -> Command {
+    Command::new("hw")
+        .arg_required_else_help(true)
+        .about("Track hardware inventory changes")
+        .subcommand(Command::new("snapshot")
+            .visible_alias("s")
+            .arg_required_else_help(true)
+            .about("Stores the hw inventory (processors, accelerators, memory DIMMs, HSN NICs and serial numbers if available) of a cluster or a list of nodes in a local file")
+            .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name"))
+            .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+            .arg(arg!(-f --file <FILE> "File to store the hw snapshot. If missing, the snapshot will be stored in manta's data folder").value_parser(value_parser!(PathBuf)))
+            .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true))
+        )
+        .subcommand(Command::new("diff")
+            .visible_alias("d")
+            .arg_required_else_help(true)
+            .about("Reports hw components replaced, missing, degraded, changed or added per node and nodes missing or added between two hw snapshots")
+            .arg(arg!(<SNAPSHOT_A> "Old hw snapshot. Either a file path or a snapshot name in manta's data folder").required(true))
+            .arg(arg!(<SNAPSHOT_B> "New hw snapshot. Either a file path or a snapshot name in manta's data folder").required(true))
+            .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+        )
+}
+
+pub fn subcommand_bootparams() -> Command {
+    Command::new("bootparams")
+        .visible_alias("bp")
+        .arg_required_else_help(true)
+        .about("Snapshot and restore BSS boot parameters (boot image, kernel, initrd and kernel parameters)")
+        .subcommand(Command::new("snapshot")
+            .visible_alias("s")
+            .arg_required_else_help(true)
+            .about("Stores the boot parameters of a cluster or a list of nodes in a local file")
+            .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name"))
+            .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+            .arg(arg!(-f --file <FILE> "File to store the boot parameters snapshot. If missing, the snapshot will be stored in manta's data folder").value_parser(value_parser!(PathBuf)))
+            .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true))
+        )
+        .subcommand(Command::new("restore")
+            .visible_alias("r")
+            .arg_required_else_help(true)
+            .about("Restores the boot parameters stored in a snapshot. Shows the changes before updating the boot parameters")
+            .arg(arg!(<SNAPSHOT> "Boot parameters snapshot. Either a file path or a snapshot name in manta's data folder").required(true))
+            .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames to restore. If missing, all nodes in the snapshot are restored.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+            .arg(arg!(-r --reboot "Reboot the nodes which boot parameters have been restored").action(ArgAction::SetTrue))
+            .arg(arg!(-y --yes "Automatic yes to prompts; assume 'yes' as answer to all prompts and run non-interactively.").action(ArgAction::SetTrue))
+        )
+}
+
+pub fn subcommand_diff() -> Command {
+    Command::new("diff")
+        .arg_required_else_help(true)
+        .about("Compare resources")
+        .subcommand(Command::new("configuration")
+            .visible_aliases(["c", "cfg", "conf", "config"])
+            .arg_required_else_help(true)
+            .about("Compares two CFS configurations. Layers are aligned by name or repo and reported as added, removed, reordered or changed. For changed layers, the commits between both layers are listed")
+            .arg(arg!(<CONFIGURATION_A> "Old CFS configuration name").required(true))
+            .arg(arg!(<CONFIGURATION_B> "New CFS configuration name").required(true))
+            .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name"))
+            .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+        )
+}
+
+pub fn subcommand_audit() -> Command {
+    Command::new("audit")
+        .arg_required_else_help(true)
+        .about("Find inconsistencies in the system")
+        .subcommand(Command::new("artefacts")
+            .visible_aliases(["a", "artifacts"])
+            .about("Cross references IMS images, S3 boot artefacts, BOS sessiontemplates, boot parameters and CFS configurations related to the HSM groups. Reports images without S3 artefacts, S3 artefacts without image, BOS sessiontemplates and boot parameters pointing to missing images or configurations, missing desired configurations and CFS configurations never used, with suggested fixes")
+            .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name. If missing, all HSM groups available are used"))
+            .arg(arg!(--"skip-s3" "Do not check S3 artefacts. Checking S3 requires downloading each image manifest").action(ArgAction::SetTrue))
+            .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+        )
+}
+
+pub fn subcommand_lease() -> Command {
+    Command::new("lease")
+        .arg_required_else_help(true)
+        .about("Temporarily reserve nodes from the parent HSM group")
+        .subcommand(Command::new("create")
+            .visible_alias("c")
+            .arg_required_else_help(true)
+            .about("Moves nodes from the parent HSM group to the target HSM group until the lease expires")
+            .arg(arg!(-t --target <TARGET_HSM_GROUP_NAME> "Target HSM group name. The HSM group receiving the nodes leased").required(true))
+            .arg(arg!(-p --parent <PARENT_HSM_GROUP_NAME> "Parent HSM group name. The HSM group offering and receiving back the nodes leased. If missing, the 'parent_hsm_group' value in the configuration file is used"))
+            .arg(arg!(-d --duration <DURATION> "Lease duration.\neg '30m', '6h', '2d', '1w'").required(true))
+            .arg(arg!(-O --owner <OWNER> "Lease owner. If missing, the current user name is used"))
+            .arg(arg!(<XNAMES> "Comma separated list of xnames or hostlist expression to lease.\neg 'x1003c1s7b0n0,x1003c1s7b0n1' or 'x1003c1s7b0n[0-1]'").required(true))
+            .arg(arg!(-D --"dry-run" "Simulates the execution of the command without making any actual changes.").action(ArgAction::SetTrue))
+        )
+        .subcommand(Command::new("list")
+            .visible_aliases(["l", "ls"])
+            .about("List leases")
+            .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "Only show leases targeting this hsm group"))
+            .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+        )
+        .subcommand(Command::new("release")
+            .visible_alias("r")
+            .about("Returns the nodes in a lease to the parent HSM group. If no lease name is provided, then all expired leases are released")
+            .arg(arg!([LEASE_NAME] "Lease name"))
+            .arg(arg!(-D --"dry-run" "Simulates the execution of the command without making any actual changes.").action(ArgAction::SetTrue))
+        )
+}
+
+pub fn subcommand_dashboard() -> Command {
+    Command::new("dashboard")
+        .visible_alias("top")
+        .about("Interactive view of nodes power, boot image, configuration status and recent CFS sessions of a cluster. Use key bindings to filter, sort, open a node console or tail CFS session logs")
+        .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name"))
+        .arg(arg!(-i --interval <SECONDS> "Refresh interval in seconds").value_parser(value_parser!(u64).range(5..)).default_value("30"))
+}
+
 pub fn subcommand_get_cfs_configuration() -> Command {
     Command::new("configurations")
         .visible_aliases(["c", "cfg", "conf", "config", "cnfgrtn", "configuration"])
@@ -339,6 +496,8 @@ pub fn subcommand_get_cfs_session() -> Command {
                     "name",
                 ]))
         .group(ArgGroup::new("session_limit").args(["most-recent", "limit"]))
+        .arg(arg!(-w --watch [SECONDS] "Keep polling every <SECONDS> (default 10) and print the values which changed").value_parser(value_parser!(u64).range(1..)).num_args(0..=1).default_missing_value("10"))
+        .arg(arg!(-u --until <CONDITION> "Stop watching when condition is met. Condition is a value all sessions must have.\neg 'status=complete', 'succeeded=true'").requires("watch"))
 }
 
 pub fn subcommand_get_bos_template() -> Command {
@@ -365,6 +524,8 @@ pub fn subcommand_get_cluster_details() -> Command {
         .arg(arg!(-s --"status" "Get cluster status:\n - OK: All nodes are operational (booted and configured)\n - OFF: At least one node is OFF\n - ON: No nodes OFF and at least one is ON\n - STANDBY: At least one node's heartbeat is lost\n - UNCONFIGURED: All nodes are READY but at least one of them is being configured\n - FAILED: At least one node configuration failed"))
         .arg(arg!(-o --output <FORMAT> "Output format. If missing it will print output data in human readable (table) format").value_parser(["table", "table-wide", "json", "summary"]).default_value("table"))
         .arg_required_else_help(true)
+        .arg(arg!(-w --watch [SECONDS] "Keep polling every <SECONDS> (default 10) and print the values which changed").value_parser(value_parser!(u64).range(1..)).num_args(0..=1).default_missing_value("10"))
+        .arg(arg!(-u --until <CONDITION> "Stop watching when condition is met. Condition is either the cluster status or a value all nodes must have.\neg 'status=OK', 'power=ON', 'configuration_status=configured'").requires("watch"))
         .arg(arg!(<HSM_GROUP_NAME> "hsm group name"))
 }
 
@@ -376,6 +537,8 @@ pub fn subcommand_get_node_details() -> Command {
         .arg(arg!(-s --"status" "Get cluster status:\n - OK: All nodes are operational (booted and configured)\n - OFF: At least one node is OFF\n - ON: No nodes OFF and at least one is ON\n - STANDBY: At least one node's heartbeat is lost\n - UNCONFIGURED: All nodes are READY but at least one of them is being configured\n - FAILED: At least one node configuration failed"))
         .arg(arg!(-o --output <FORMAT> "Output format. If missing it will print output data in human readable (table) format").value_parser(["table", "table-wide", "json", "summary"]).default_value("table"))
         .arg_required_else_help(true)
+        .arg(arg!(-w --watch [SECONDS] "Keep polling every <SECONDS> (default 10) and print the values which changed").value_parser(value_parser!(u64).range(1..)).num_args(0..=1).default_missing_value("10"))
+        .arg(arg!(-u --until <CONDITION> "Stop watching when condition is met. Condition is either the cluster status or a value all nodes must have.\neg 'status=OK', 'power=ON', 'configuration_status=configured'").requires("watch"))
         .arg(arg!(<XNAMES> "Comma separated list of xnames to retreive the kernel parameters from.\neg: 'x1001c1s0b0n1,x1001c1s0b1n0'"))
 }
 
@@ -417,6 +580,9 @@ pub fn subcommand_get_kernel_parameters() -> Command {
         .arg(arg!(-x --xnames <VALUE> "Comma separated list of xnames to retreive the kernel parameters from.\neg: 'x1001c1s0b0n1,x1001c1s0b1n0'"))
         .arg(arg!(-H --"hsm-group" <VALUE> "List kernel parameters for all nodes in a HSM group name"))
         .arg(arg!(-f --filter <VALUE> "Comma separated list of kernel parameters to filter.\neg: 'console,bad_page,crashkernel,hugepagelist,root'"))
+        .arg(arg!(-c --"compare-profile" <PROFILE_NAME> "Shows, per node, the kernel parameters missing or with different values compared to a kernel parameter profile. Only kernel parameters in the profile are compared. Kernel parameters related to the boot image are ignored").conflicts_with("filter"))
+        .arg(arg!(--strict "Also shows kernel parameters not in the profile as extra").action(ArgAction::SetTrue).requires("compare-profile"))
+        .arg(arg!(--"profile-file" <PATH> "YAML file with kernel parameter profiles, profile names as keys and kernel parameters as values. Takes precedence over profiles in the configuration file").value_parser(value_parser!(PathBuf)).requires("compare-profile"))
         .arg(arg!(-o --output <VALUE> "Output format.").value_parser(["table", "json"]).default_value("table"))
         .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true))
 }
@@ -454,6 +620,7 @@ pub fn subcommand_apply_hw_configuration() -> Command {
             .arg(arg!(-c --"create-target-hsm-group" "If the target cluster name does not exist as HSM group, create it."))
             .arg(arg!(-d --"delete-empty-parent-hsm-group" "If the target HSM group is empty after this action, remove it."))
             .arg(arg!(-u --"unpin-nodes" "It will try to get any nodes available."))
+            .arg(arg!(--"health-check" <POLICY> "Check nodes health (power state, CFS configuration status, HSM enabled and flag, running CFS sessions) before moving them and apply a policy to unhealthy nodes: 'skip' does not move them, 'warn' moves them anyway, 'abort' cancels the operation. If missing, no checks are done").value_parser(["skip", "warn", "abort"]))
         )
 }
 
@@ -476,6 +643,9 @@ pub fn subcommand_apply_session() -> Command {
         .arg(arg!(-P --"ansible-passthrough" <VALUE> "Additional parameters that are added to all Ansible calls for the session. This field is currently limited to the following Ansible parameters: \"--extra-vars\", \"--forks\", \"--skip-tags\", \"--start-at-task\", and \"--tags\". WARNING: Parameters passed to Ansible in this way should be used with caution. State will not be recorded for components when using these flags to avoid incorrect reporting of partial playbook runs.").allow_hyphen_values(true))
         .arg(arg!(-l --"ansible-limit" <VALUE> "Ansible limit. Target xnames to the CFS session. Note: ansible-limit must be a subset of hsm-group if both parameters are provided").required(true))
         .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "hsm group name"))
+        .arg(arg!(--push "Push local repos to Shasta VCS before creating the CFS session. Repos missing in Shasta VCS are created. Uncommitted changes are pushed to a throwaway branch 'manta/<user>/<timestamp>' instead of the current branch").action(ArgAction::SetTrue))
+        .arg(arg!(--"skip-preflight" "Skip local checks on the ansible playbook (YAML syntax, roles and files referenced, inventory groups) before creating the CFS session").action(ArgAction::SetTrue))
+        .arg(arg!(--"structured-logs" "Show ansible logs as a live status table per node (ok/changed/failed/unreachable) and a summary with the failed tasks at the end instead of the raw ansible output").action(ArgAction::SetTrue).requires("watch-logs"))
         .group(ArgGroup::new("hsm-group_or_ansible-limit").args(["hsm-group", "ansible-limit"]).required(true))
 }
 
@@ -582,6 +752,7 @@ pub fn subcommand_apply_sat_file(/* hsm_group: Option<&String> */) -> Command {
         .arg(arg!(-s --"sessiontemplate-only" "Only process `configurations` and `session_templates` sections in SAT file. The `images` section will be ignored.").action(ArgAction::SetTrue))
         .arg(arg!(-p --"pre-hook" <SCRIPT> "Command to run before processing SAT file. If need to pass a command with params. Use \" or \'.\neg: --pre-hook \"echo hello\""))
         .arg(arg!(-a --"post-hook" <SCRIPT> "Command to run immediately after processing SAT file successfully. Use \" or \'.\neg: --post-hook \"echo hello\"."))
+        .arg(arg!(--locked "Refuse to process the SAT file if any branch, tag or product version in the 'configurations' section resolves to a commit different than the one in the lock file ('<SAT file>.lock'). CFS configurations are created using the commits in the lock file. Without this flag, the lock file is not used").action(ArgAction::SetTrue))
     // .arg(arg!(-d --"dry-run" "Simulates the execution of the command without making any actual changes.").action(ArgAction::SetTrue))
 }
 
@@ -652,6 +823,16 @@ pub fn subcommand_update_hsm_group() -> Command {
         .arg(arg!(<HSM_GROUP_NAME> "HSM group name").required(true))
 }
 
+pub fn subcommand_update_sat_file_lock() -> Command {
+    Command::new("sat-file-lock")
+        .visible_aliases(["lock"])
+        .arg_required_else_help(true)
+        .about("Resolves branches, tags and product versions in the 'configurations' section of a SAT file to commit ids and stores them in the lock file '<SAT file>.lock'. Use 'manta apply sat-file --locked' to make sure the SAT file is applied using these commits")
+        .arg(arg!(-t --"sat-template-file" <VALUE> "SAT file with CFS configuration. The SAT file can be a jinja2 template, if this is the case, then a values file must be provided.").value_parser(value_parser!(PathBuf)).required(true))
+        .arg(arg!(-f --"values-file" <VALUE> "If the SAT file is a jinja2 template, then variables values can be expanded using this values file.").value_parser(value_parser!(PathBuf)))
+        .arg(arg!(-V --"values" <VALUE> ... "If the SAT file is a jinja2 template, then variables values can be expanded using these values. Overwrites values-file if both provided."))
+}
+
 pub fn subcommand_apply_boot_nodes() -> Command {
     Command::new("nodes")
         .visible_aliases(["n", "node"])
@@ -661,6 +842,8 @@ pub fn subcommand_apply_boot_nodes() -> Command {
         .arg(arg!(-b --"boot-image-configuration" <VALUE> "CFS configuration name related to the image to boot the nodes. The most recent image id created using this configuration will be used to boot the nodes"))
         .arg(arg!(-r --"runtime-configuration" <VALUE> "CFS configuration name to configure the nodes after booting"))
         .arg(arg!(-k --"kernel-parameters" <VALUE> "Kernel boot parameters to assign to the nodes while booting"))
+        .arg(arg!(-w --wait "Wait for the rebooted nodes to boot the new image and, if provided, to be configured with the runtime configuration. Exits with an error if any node is not ready").action(ArgAction::SetTrue))
+        .arg(arg!(--"wait-timeout" <DURATION> "Maximum time to wait for the nodes to be ready. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("30m").requires("wait"))
         .group(ArgGroup::new("boot-image_or_boot-config").args(["boot-image", "boot-image-configuration"]))
         .arg(arg!(<XNAMES> "Comma separated list of xnames which boot image will be updated.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
         .arg(arg!([CLUSTER_NAME] "Cluster name, this field should be used to validate the XNAMES belongs to CLUSTER_NAME"))
@@ -676,6 +859,8 @@ pub fn subcommand_apply_boot_cluster() -> Command {
         .arg(arg!(-r --"runtime-configuration" <VALUE> "CFS configuration name to configure the nodes after booting"))
         .arg(arg!(-k --"kernel-parameters" <VALUE> "Kernel boot parameters to assign to all cluster nodes while booting"))
         .group(ArgGroup::new("boot-image_or_boot-config").args(["boot-image", "boot-image-configuration"]))
+        .arg(arg!(-w --wait "Wait for the rebooted nodes to boot the new image and, if provided, to be configured with the runtime configuration. Exits with an error if any node is not ready").action(ArgAction::SetTrue))
+        .arg(arg!(--"wait-timeout" <DURATION> "Maximum time to wait for the nodes to be ready. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("30m").requires("wait"))
         .arg(arg!(<CLUSTER_NAME> "Cluster name").required(true))
 }
 
@@ -720,6 +905,11 @@ pub fn subcommand_power() -> Command {
                         .about("Command to power on all nodes in a cluster")
                         .arg(arg!(-r --reason <TEXT> "reason to power on"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
+                        .arg(arg!(-s --stagger <GROUP> "Power on the nodes in batches of nodes in the same cabinet or chassis to avoid power inrush").value_parser(["cabinet", "chassis"]))
+                        .arg(arg!(--"batch-size" <NUMBER> "Max number of nodes per batch when powering on staggered").value_parser(value_parser!(usize)).default_value("16").requires("stagger"))
+                        .arg(arg!(--"batch-delay" <DURATION> "Time to wait between batches when powering on staggered. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("30s").requires("stagger"))
                         .arg(arg!(<CLUSTER_NAME> "Cluster name")),
                 )
                 .subcommand(
@@ -729,6 +919,11 @@ pub fn subcommand_power() -> Command {
                         .about("Command to power on a group of nodes.\neg: 'x1001c1s0b0n1,x1001c1s0b1n0'")
                         .arg(arg!(-r --reason <TEXT> "reason to power on"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
+                        .arg(arg!(-s --stagger <GROUP> "Power on the nodes in batches of nodes in the same cabinet or chassis to avoid power inrush").value_parser(["cabinet", "chassis"]))
+                        .arg(arg!(--"batch-size" <NUMBER> "Max number of nodes per batch when powering on staggered").value_parser(value_parser!(usize)).default_value("16").requires("stagger"))
+                        .arg(arg!(--"batch-delay" <DURATION> "Time to wait between batches when powering on staggered. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("30s").requires("stagger"))
                         .arg(arg!(<NODE_NAME> "Comma separated list of xnames to power on.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'")),
                 ),
         )
@@ -744,6 +939,8 @@ pub fn subcommand_power() -> Command {
                         .arg(arg!(-f --force "force").action(ArgAction::SetTrue))
                         .arg(arg!(-r --reason <TEXT> "reason to power off"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
                         .arg(arg!(<CLUSTER_NAME> "Cluster name")),
                 )
                 .subcommand(
@@ -754,6 +951,8 @@ pub fn subcommand_power() -> Command {
                         .arg(arg!(-f --force "force").action(ArgAction::SetTrue))
                         .arg(arg!(-r --reason <TEXT> "reason to power off"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
                         .arg(arg!(<NODE_NAME> "Comma separated list of xnames to power off.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'")),
                 ),
         )
@@ -769,6 +968,8 @@ pub fn subcommand_power() -> Command {
                         .arg(arg!(-f --force "force").action(ArgAction::SetTrue))
                         .arg(arg!(-r --reason <TEXT> "reason to power reset"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
                         .arg(arg!(<CLUSTER_NAME> "Cluster name")),
                 )
                 .subcommand(
@@ -779,9 +980,47 @@ pub fn subcommand_power() -> Command {
                         .arg(arg!(-f --force "force").action(ArgAction::SetTrue))
                         .arg(arg!(-r --reason <TEXT> "reason to power reset"))
                         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .arg(arg!(--timeout <DURATION> "Maximum time to wait for the power operation to complete on each attempt. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("15m"))
+                        .arg(arg!(--retries <NUMBER> "Number of times nodes which failed the power operation are retried. Retries escalate soft operations to hard ones, eg 'soft-restart' to 'hard-restart'").value_parser(value_parser!(usize)).default_value("1"))
                         .arg(arg!(<NODE_NAME> "Comma separated list of xnames to power reset.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'")),
                 ),
         )
+        .subcommand(
+            Command::new("status")
+                .visible_alias("s")
+                .arg_required_else_help(true)
+                .about("Show the power state of all nodes in a cluster or a list of nodes")
+                .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "Cluster name"))
+                .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+                .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true)),
+        )
+        .subcommand(
+            Command::new("cap")
+                .arg_required_else_help(true)
+                .about("Command to manage power capping of cluster/node")
+                .subcommand(
+                    Command::new("get")
+                        .arg_required_else_help(true)
+                        .about("Show the power cap controls (current, min and max watts) of all nodes in a cluster or a list of nodes")
+                        .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "Cluster name"))
+                        .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+                        .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true)),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .arg_required_else_help(true)
+                        .about("Set a power cap control to all nodes in a cluster or a list of nodes")
+                        .arg(arg!(-H --"hsm-group" <HSM_GROUP_NAME> "Cluster name"))
+                        .arg(arg!(-x --xnames <XNAMES> "Comma separated list of xnames.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
+                        .arg(arg!(-c --control <NAME> "Power cap control to set. Use 'manta power cap get' to list the controls of each node, eg 'Accelerator0 Power Limit'").default_value("Node Power Limit"))
+                        .arg(arg!(-v --value <WATTS> "Power cap value in watts").value_parser(value_parser!(i64)).required(true))
+                        .arg(arg!(-y --yes "Automatic yes to prompts; assume 'yes' as answer to all prompts and run non-interactively.").action(ArgAction::SetTrue))
+                        .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+                        .group(ArgGroup::new("hsm-group_or_xnames").args(["hsm-group", "xnames"]).required(true)),
+                ),
+        )
 }
 
 pub fn subcommand_log() -> Command {
@@ -790,6 +1029,17 @@ pub fn subcommand_log() -> Command {
         .about("get cfs session logs")
         .arg(arg!([SESSION_NAME] "show logs related to session name"))
         .arg(arg!(-c --cluster <cluster_name> "Show logs most recent CFS session created for cluster."))
+        .arg(arg!(-s --structured "Show ansible logs as a live status table per node (ok/changed/failed/unreachable) and a summary with the failed tasks and error messages at the end").action(ArgAction::SetTrue).conflicts_with_all(["container", "all-containers", "since", "tail"]))
+        .arg(arg!(--search <REGEX> "Search the logs of the CFS sessions in the log archive instead of fetching the logs from the CFS session pod. Logs are archived while streaming if 'enabled = true' in the 'log_archive' section of the configuration file"))
+        .arg(arg!(--configuration <CONFIGURATION_NAME> "Only search CFS sessions using this configuration").requires("search"))
+        .arg(arg!(--xname <XNAME> "Only search CFS sessions targeting this node and lines mentioning it").requires("search"))
+        .arg(arg!(--since <DURATION> "Only show logs newer than this period of time. eg '30m', '6h', '2d'. If used with '--search', only search CFS sessions started in this period of time"))
+        .arg(arg!(--tail <LINES> "Number of lines to show from the end of the logs of each container").value_parser(value_parser!(i64)))
+        .arg(arg!(--container <CONTAINER_NAME> ... "Containers in the CFS session pod to follow, eg 'inventory', 'ansible' or 'teardown'. Logs from different containers are interleaved and prefixed with the container name"))
+        .arg(arg!(--"all-containers" "Follow all containers in the CFS session pod (git-clone, inventory, ansible, teardown and sidecars)").action(ArgAction::SetTrue))
+        .arg(arg!(--timestamps "Show the timestamp of each line. Only with '--container', '--all-containers', '--since' or '--tail'").action(ArgAction::SetTrue).requires("timestamps_options"))
+        .group(ArgGroup::new("container_selection").args(["container", "all-containers"]))
+        .group(ArgGroup::new("timestamps_options").args(["container", "all-containers", "since", "tail"]).multiple(true))
         .group(ArgGroup::new("cluster_or_session_name").args(["cluster", "SESSION_NAME"]))
 }
 
@@ -822,6 +1072,8 @@ pub fn subcommand_set_boot_image() -> Command {
         .arg(arg!(-x --xnames <VALUE> "Comma separated list of nodes to set runtime configuration.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
         .arg(arg!(-H --"hsm-group" <VALUE> "Cluster to set runtime configuration"))
         .arg(arg!(-o --output <FORMAT> "Output format.").value_parser(["table", "json"]).default_value("table"))
+        .arg(arg!(-w --wait "Wait for the rebooted nodes to boot the new image. Exits with an error if any node is not ready").action(ArgAction::SetTrue))
+        .arg(arg!(--"wait-timeout" <DURATION> "Maximum time to wait for the nodes to be ready. Use a number followed by 's', 'm', 'h', 'd' or 'w'").default_value("30m").requires("wait"))
         .group(
             ArgGroup::new("cluster_or_session_name")
             .args(["hsm-group", "xnames"])
@@ -846,8 +1098,13 @@ pub fn subcommand_set_boot_configuration() -> Command {
 pub fn subcommand_set_kernel_params() -> Command {
     Command::new("kernel-parameters")
         .visible_alias("kp")
-        .about("Set kernel boot parameters to boot a set of nodes or all nodes in a cluster. This command literally replaces boot params without preprocessing.")
-        .arg(arg!(-k --"kernel-parameters" <VALUE> "Space separated list of kernel parameters to a set of nodes or all nodes in a cluster. Do not add any parameter related to the boot image rootfs, this information needs to be configured using `manta set boot-configuration` or `manta set boot-image`").required(true))
+        .about("Set kernel boot parameters to boot a set of nodes or all nodes in a cluster. Kernel parameters related to the boot image (root, metal.server, nmd_data) are preserved. Only nodes which kernel parameters change are rebooted.")
+        .arg(arg!(-k --"kernel-parameters" <VALUE> "Space separated list of kernel parameters replacing the current ones. Kernel parameters related to the boot image rootfs are preserved, this information needs to be configured using `manta set boot-configuration` or `manta set boot-image`").conflicts_with_all(["add", "remove", "replace"]))
+        .arg(arg!(-a --add <VALUE> ... "Space separated list of kernel parameters to add. Fails if a kernel parameter already exists with a different value.\neg 'quiet console=ttyS0,115200'"))
+        .arg(arg!(-r --remove <VALUE> ... "Space separated list of kernel parameters to remove. Use 'key' to remove all values or 'key=value' to remove a specific value.\neg 'quiet console=tty0'"))
+        .arg(arg!(--replace <VALUE> ... "Space separated list of kernel parameters to update. Kernel parameters not found are added. A key with several values replaces all its values.\neg 'crashkernel=512M'"))
+        .arg(arg!(-p --profile <PROFILE_NAME> "Kernel parameter profile to apply. Kernel parameters in the profile are added or their values replaced, the rest of kernel parameters are kept").conflicts_with_all(["kernel-parameters", "add", "remove", "replace"]))
+        .arg(arg!(--"profile-file" <PATH> "YAML file with kernel parameter profiles, profile names as keys and kernel parameters as values. Takes precedence over profiles in the configuration file").value_parser(value_parser!(PathBuf)).requires("profile"))
         .arg(arg!(-x --xnames <XNAMES> "Comma separated list of nodes to set runtime configuration.\neg 'x1003c1s7b0n0,1003c1s7b0n1,x1003c1s7b1n0'"))
         .arg(arg!(-H --"hsm-group" <HSM_GROUP> "Cluster to set runtime configuration"))
         .group(
@@ -855,4 +1112,10 @@ pub fn subcommand_set_kernel_params() -> Command {
                 .args(["hsm-group", "xnames"])
                 .required(true),
         )
+        .group(
+            ArgGroup::new("kernel_parameters_operation")
+                .args(["kernel-parameters", "add", "remove", "replace", "profile"])
+                .multiple(true)
+                .required(true),
+        )
 }
@@ -1,5 +1,11 @@
+use std::collections::BTreeMap;
+
 use crate::{
-    cli::commands::power_reset_nodes, common::ims_ops::get_image_id_from_cfs_configuration_name,
+    cli::commands::power_reset_nodes,
+    common::{
+        boot_readiness_ops, ims_ops::get_image_id_from_cfs_configuration_name,
+        pcs_utils::PowerTransitionOpts,
+    },
 };
 
 use dialoguer::{theme::ColorfulTheme, Confirm};
@@ -19,6 +25,7 @@ pub async fn exec(
     new_runtime_configuration_opt: Option<&String>,
     new_kernel_parameters_opt: Option<&String>,
     xnames: Vec<&str>,
+    wait_timeout_opt: Option<chrono::Duration>,
 ) {
     let mut need_restart = false;
 
@@ -174,6 +181,17 @@ pub async fn exec(
         }
     }
 
+    // Image each node is expected to boot once rebooted
+    let expected_image_map: BTreeMap<String, String> = current_node_boot_param_vec
+        .iter()
+        .flat_map(|boot_parameter| {
+            boot_parameter
+                .hosts
+                .iter()
+                .map(|xname| (xname.clone(), boot_parameter.get_boot_image()))
+        })
+        .collect();
+
     // Update boot params
     for boot_parameter in current_node_boot_param_vec {
         let component_patch_rep = mesa::bss::bootparameters::http_client::patch(
@@ -226,8 +244,23 @@ pub async fn exec(
             None,
             true,
             "table",
+            &PowerTransitionOpts::default(),
         )
-        .await
+        .await;
+
+        if let Some(wait_timeout) = wait_timeout_opt {
+            boot_readiness_ops::wait_and_report(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                &expected_image_map,
+                new_runtime_configuration_opt.map(|elem| elem.as_str()),
+                wait_timeout,
+            )
+            .await;
+        }
+    } else if wait_timeout_opt.is_some() {
+        println!("Nodes not rebooted. Nothing to wait for");
     }
 }
 
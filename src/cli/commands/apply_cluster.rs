@@ -45,6 +45,7 @@ pub async fn exec(
         false,
         false,
         false,
+        None,
     )
     .await;
 }
@@ -105,6 +105,7 @@ pub async fn exec(
                 gitea_token,
                 &cray_product_catalog,
                 configuration_yaml,
+                None,
                 // tag,
                 false,
             )
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 
 use mesa::hsm::group::utils::update_hsm_group_members;
 
+use crate::common::node_health_ops::{self, HealthPolicy};
+
 use crate::cli::commands::apply_hw_cluster_pin::command::utils::{
     calculate_hsm_hw_component_summary, get_hsm_node_hw_component_counter,
     resolve_hw_description_to_xnames,
@@ -18,6 +20,7 @@ pub async fn exec(
     nodryrun: bool,
     create_target_hsm_group: bool,
     delete_empty_parent_hsm_group: bool,
+    health_policy_opt: Option<HealthPolicy>,
 ) {
     // *********************************************************************************************************
     // PREPREQUISITES - FORMAT USER INPUT
@@ -175,46 +178,101 @@ pub async fn exec(
     // VALIDATE USER INPUT - CHECK HARDWARE REQUIREMENTS REQUESTED BY USER CAN BE FULFILLED
     // CHECK USER HAS ACCESS TO REQUESTED HW COMPONENTS
     // CHECK USER HAS ACCESS TO ENOUGH QUANTITY OF HW RESOURCES REQUESTED
+    //
+    // CONVERT THE HARDWARE DESCRIPTION INTO A SET OF NODES IN TARGET HSM
+    //
+    // Only the nodes selected to move to the target HSM group are health checked. Unhealthy
+    // nodes are kept in the parent HSM group and the selection is done again without them
 
-    let mut combined_target_parent_hsm_node_hw_component_count_vec =
-        parent_hsm_node_hw_component_count_vec.clone();
+    // Nodes which passed the health checks
+    let mut xname_healthy_vec: Vec<String> = Vec::new();
+    // Nodes which failed the health checks
+    let mut xname_unhealthy_vec: Vec<String> = Vec::new();
 
-    for elem in &target_hsm_node_hw_component_count_vec {
-        if !parent_hsm_node_hw_component_count_vec
-            .iter()
-            .any(|(xname, _)| xname.eq(&elem.0))
-        {
-            combined_target_parent_hsm_node_hw_component_count_vec.push(elem.clone());
+    let (target_hsm_node_hw_component_count_vec, parent_hsm_node_hw_component_count_vec) = loop {
+        parent_hsm_node_hw_component_count_vec
+            .retain(|(xname, _)| !xname_unhealthy_vec.contains(xname));
+
+        let mut combined_target_parent_hsm_node_hw_component_count_vec =
+            parent_hsm_node_hw_component_count_vec.clone();
+
+        for elem in &target_hsm_node_hw_component_count_vec {
+            if !parent_hsm_node_hw_component_count_vec
+                .iter()
+                .any(|(xname, _)| xname.eq(&elem.0))
+            {
+                combined_target_parent_hsm_node_hw_component_count_vec.push(elem.clone());
+            }
         }
-    }
 
-    let combined_target_parent_hsm_hw_component_summary_hashmap =
-        calculate_hsm_hw_component_summary(&combined_target_parent_hsm_node_hw_component_count_vec);
+        let combined_target_parent_hsm_hw_component_summary_hashmap =
+            calculate_hsm_hw_component_summary(
+                &combined_target_parent_hsm_node_hw_component_count_vec,
+            );
 
-    for (hw_component, qty) in &user_defined_target_hsm_hw_component_count_hashmap {
-        if combined_target_parent_hsm_hw_component_summary_hashmap
-            .get(hw_component)
-            .is_some_and(|value| value >= qty)
-        {
-            // We are ok, user has access to enough resources to fullfill its request
-        } else {
-            // There are not enough resources to fulfill the user request
-            eprintln!("ERROR - there are not enough resources to fulfill user request.");
-            std::process::exit(1);
+        for (hw_component, qty) in &user_defined_target_hsm_hw_component_count_hashmap {
+            if combined_target_parent_hsm_hw_component_summary_hashmap
+                .get(hw_component)
+                .is_some_and(|value| value >= qty)
+            {
+                // We are ok, user has access to enough resources to fullfill its request
+            } else {
+                // There are not enough resources to fulfill the user request
+                eprintln!("ERROR - there are not enough resources to fulfill user request.");
+                std::process::exit(1);
+            }
         }
-    }
 
-    // *********************************************************************************************************
-    // CONVERT THE HARDWARE DESCRIPTION INTO A SET OF NODES IN TARGET HSM
+        let (
+            target_hsm_node_hw_component_count_selected_vec,
+            parent_hsm_node_hw_component_count_selected_vec,
+        ) = resolve_hw_description_to_xnames(
+            target_hsm_node_hw_component_count_vec.clone(),
+            parent_hsm_node_hw_component_count_vec.clone(),
+            user_defined_target_hsm_hw_component_count_hashmap.clone(),
+        )
+        .await;
 
-    let (target_hsm_node_hw_component_count_vec, parent_hsm_node_hw_component_count_vec) =
-        resolve_hw_description_to_xnames(
-            target_hsm_node_hw_component_count_vec,
-            parent_hsm_node_hw_component_count_vec,
-            user_defined_target_hsm_hw_component_count_hashmap,
+        let Some(health_policy) = health_policy_opt else {
+            break (
+                target_hsm_node_hw_component_count_selected_vec,
+                parent_hsm_node_hw_component_count_selected_vec,
+            );
+        };
+
+        let xname_to_check_vec: Vec<String> = target_hsm_node_hw_component_count_selected_vec
+            .iter()
+            .map(|(xname, _)| xname)
+            .filter(|xname| {
+                !target_hsm_group_member_vec.contains(xname) && !xname_healthy_vec.contains(xname)
+            })
+            .cloned()
+            .collect();
+
+        let xname_failed_vec = node_health_ops::gate_nodes(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_to_check_vec,
+            health_policy,
         )
         .await;
 
+        if xname_failed_vec.is_empty() {
+            break (
+                target_hsm_node_hw_component_count_selected_vec,
+                parent_hsm_node_hw_component_count_selected_vec,
+            );
+        }
+
+        xname_healthy_vec.extend(
+            xname_to_check_vec
+                .into_iter()
+                .filter(|xname| !xname_failed_vec.contains(xname)),
+        );
+        xname_unhealthy_vec.extend(xname_failed_vec);
+    };
+
     // Calculate hw component counters (summary) across all node within the HSM group
     let target_hsm_hw_component_summary_hashmap =
         calculate_hsm_hw_component_summary(&target_hsm_node_hw_component_count_vec);
@@ -228,11 +286,13 @@ pub async fn exec(
         .map(|(xname, _)| xname)
         .collect::<Vec<String>>();
 
-    let parent_hsm_node_vec = parent_hsm_node_hw_component_count_vec
+    let mut parent_hsm_node_vec = parent_hsm_node_hw_component_count_vec
         .into_iter()
         .map(|(xname, _)| xname)
         .collect::<Vec<String>>();
 
+    parent_hsm_node_vec.extend(xname_unhealthy_vec);
+
     // *********************************************************************************************************
     // UPDATE TARGET HSM GROUP IN CSM
     log::info!(
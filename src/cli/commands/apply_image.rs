@@ -138,9 +138,10 @@ pub async fn exec(
     let kube_client = kubernetes::get_k8s_client_programmatically(k8s_api_url, shasta_k8s_secrets)
         .await
         .unwrap();
-    let cray_product_catalog = kubernetes::get_configmap(kube_client, "cray-product-catalog")
-        .await
-        .unwrap();
+    let cray_product_catalog =
+        kubernetes::get_configmap(kube_client.clone(), "cray-product-catalog")
+            .await
+            .unwrap();
 
     let mut cfs_configuration_hashmap = HashMap::new();
 
@@ -154,6 +155,7 @@ pub async fn exec(
                 gitea_token,
                 &cray_product_catalog,
                 configuration_yaml,
+                None,
                 // tag,
                 false,
             )
@@ -185,6 +187,7 @@ pub async fn exec(
             ansible_passthrough_opt,
             false,
             false,
+            Some(&kube_client),
         )
         .await;
 
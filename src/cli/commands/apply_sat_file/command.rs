@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
 use dialoguer::theme::ColorfulTheme;
 use mesa::{
@@ -13,7 +13,13 @@ use mesa::{
 use serde_yaml::Value;
 use termion::color;
 
-use crate::cli::commands::{apply_hw_cluster_pin, apply_sat_file::utils};
+use crate::cli::commands::{
+    apply_hw_cluster_pin,
+    apply_sat_file::{
+        lock::{self, SatFileLock},
+        utils,
+    },
+};
 
 pub async fn exec(
     shasta_token: &str,
@@ -39,6 +45,7 @@ pub async fn exec(
     session_template_only: bool,
     debug_on_failure: bool,
     dry_run: bool,
+    locked_file_path_opt: Option<&Path>,
 ) {
     // Validate Pre-hook
     if prehook.is_some() {
@@ -152,9 +159,10 @@ pub async fn exec(
         .unwrap();
 
     // Get HPE product catalog from k8s
-    let cray_product_catalog = kubernetes::get_configmap(kube_client, "cray-product-catalog")
-        .await
-        .unwrap();
+    let cray_product_catalog =
+        kubernetes::get_configmap(kube_client.clone(), "cray-product-catalog")
+            .await
+            .unwrap();
 
     // TODO: multiple API calls to CSM sequentially
     //
@@ -222,6 +230,55 @@ pub async fn exec(
     )
     .await;
 
+    // Resolve branches, tags and product versions in 'configurations' section to commit ids and
+    // check them against the lock file. CFS configurations are then pinned to the locked commits
+    let sat_file_lock_opt = if let Some(lock_file_path) = locked_file_path_opt {
+        let sat_file_lock = match SatFileLock::read(lock_file_path) {
+            Ok(Some(sat_file_lock)) => sat_file_lock,
+            Ok(None) => {
+                eprintln!(
+                    "ERROR - Lock file '{}' not found. Please run 'manta update sat-file-lock' to create it. Exit",
+                    lock_file_path.display()
+                );
+                std::process::exit(1);
+            }
+            Err(error) => {
+                eprintln!("ERROR - {}. Exit", error);
+                std::process::exit(1);
+            }
+        };
+
+        let lock_entry_vec = lock::resolve(
+            shasta_root_cert,
+            gitea_base_url,
+            gitea_token,
+            &cray_product_catalog,
+            configuration_yaml_vec_opt.unwrap_or(&Vec::new()),
+        )
+        .await;
+
+        let lock_entry_diff_vec = sat_file_lock.diff(&lock_entry_vec);
+
+        if !lock_entry_diff_vec.is_empty() {
+            eprintln!(
+                "ERROR - The following references in the SAT file do not match the lock file '{}':",
+                lock_file_path.display()
+            );
+            lock::print_diff_table(&lock_entry_diff_vec);
+            eprintln!("Please review the changes and run 'manta update sat-file-lock' to update the lock file. Exit");
+            std::process::exit(1);
+        }
+
+        println!(
+            "SAT file configurations match lock file '{}'",
+            lock_file_path.display()
+        );
+
+        Some(sat_file_lock)
+    } else {
+        None
+    };
+
     // PROCESS SAT FILE
     //
     // Process "hardware" section in SAT file
@@ -251,6 +308,7 @@ pub async fn exec(
                     true,
                     false,
                     false,
+                    None,
                 )
                 .await;
             } else if let Some(nodes) = hw_component_pattern
@@ -307,6 +365,7 @@ pub async fn exec(
                 gitea_token,
                 &cray_product_catalog,
                 configuration_yaml,
+                sat_file_lock_opt.as_ref(),
                 // tag,
                 dry_run,
             )
@@ -347,6 +406,7 @@ pub async fn exec(
                 ansible_passthrough_opt,
                 debug_on_failure,
                 dry_run,
+                Some(&kube_client),
             )
             .await;
 
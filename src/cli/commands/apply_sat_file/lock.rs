@@ -0,0 +1,333 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use comfy_table::Table;
+use mesa::cfs::configuration::mesa::r#struct::cfs_configuration_request::v3::CfsConfigurationRequest;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+/// Git reference (branch or tag) or product version in a SAT file configuration layer and the
+/// commit it resolved to. Layers pointing to a commit id are already reproducible and are not
+/// part of the lock file
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockEntry {
+    /// Git repo url or 'product:<product name>'
+    pub source: String,
+    /// 'branch:<name>', 'tag:<name>' or 'version:<product version>' (followed by
+    /// ',branch:<name>' if the product layer also defines a branch). Product layers without
+    /// version use the latest version in the Cray product catalog
+    pub reference: String,
+    pub commit: String,
+}
+
+/// Lock file with the commits the layers in a SAT file resolved to
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SatFileLock {
+    pub layers: Vec<LockEntry>,
+}
+
+/// Lock file lives next to the SAT file, eg 'cluster.yaml' -> 'cluster.yaml.lock'
+pub fn get_lock_file_path(sat_file_path: &Path) -> PathBuf {
+    let mut lock_file_path = sat_file_path.as_os_str().to_owned();
+    lock_file_path.push(".lock");
+
+    PathBuf::from(lock_file_path)
+}
+
+/// Compares product versions component by component, eg '2.4.139' < '2.10.0'. Numeric
+/// components are compared as numbers, the rest as text
+pub fn compare_versions(version_a: &str, version_b: &str) -> Ordering {
+    let get_component_vec = |version: &str| -> Vec<String> {
+        version.split(['.', '-', '+']).map(str::to_string).collect()
+    };
+
+    let component_a_vec = get_component_vec(version_a);
+    let component_b_vec = get_component_vec(version_b);
+
+    for (component_a, component_b) in component_a_vec.iter().zip(component_b_vec.iter()) {
+        let ordering = match (component_a.parse::<u64>(), component_b.parse::<u64>()) {
+            (Ok(number_a), Ok(number_b)) => number_a.cmp(&number_b),
+            _ => component_a.cmp(component_b),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    component_a_vec.len().cmp(&component_b_vec.len())
+}
+
+/// Returns the latest version of a product in the Cray product catalog. This is the version
+/// used by product layers in a SAT file without version
+pub fn get_latest_product_version(
+    cray_product_catalog: &BTreeMap<String, String>,
+    product_name: &str,
+) -> Option<String> {
+    let product_yaml: Value = serde_yaml::from_str(cray_product_catalog.get(product_name)?).ok()?;
+
+    product_yaml
+        .as_mapping()?
+        .iter()
+        .filter(|(_, product_version_yaml)| product_version_yaml.get("configuration").is_some())
+        .filter_map(|(version, _)| version.as_str())
+        .max_by(|version_a, version_b| compare_versions(version_a, version_b))
+        .map(str::to_string)
+}
+
+/// Returns the version of a product layer in a SAT file, the latest version in the Cray product
+/// catalog if the layer has no version
+pub fn get_product_version(
+    product_yaml: &Value,
+    cray_product_catalog: &BTreeMap<String, String>,
+) -> Option<String> {
+    match product_yaml["version"].as_str() {
+        Some(version) => Some(version.to_string()),
+        None => get_latest_product_version(cray_product_catalog, product_yaml["name"].as_str()?),
+    }
+}
+
+/// Returns a copy of a SAT file configuration where product layers without version use the latest
+/// version in the Cray product catalog
+pub fn set_product_versions(
+    configuration_yaml: &Value,
+    cray_product_catalog: &BTreeMap<String, String>,
+) -> Value {
+    let mut configuration_yaml = configuration_yaml.clone();
+
+    if let Some(layer_yaml_vec) = configuration_yaml
+        .get_mut("layers")
+        .and_then(Value::as_sequence_mut)
+    {
+        for layer_yaml in layer_yaml_vec {
+            let Some(product_yaml) = layer_yaml.get_mut("product") else {
+                continue;
+            };
+
+            if product_yaml.get("version").is_some() {
+                continue;
+            }
+
+            if let (Some(version), Some(product_mapping)) = (
+                get_product_version(product_yaml, cray_product_catalog),
+                product_yaml.as_mapping_mut(),
+            ) {
+                product_mapping.insert(Value::from("version"), Value::from(version));
+            }
+        }
+    }
+
+    configuration_yaml
+}
+
+/// Returns the source and the reference of a configuration layer in a SAT file or None if the
+/// layer points to a commit id. Product layers without version are referenced by the latest
+/// version in the Cray product catalog
+pub fn get_source_and_reference(
+    layer_yaml: &Value,
+    cray_product_catalog: &BTreeMap<String, String>,
+) -> Option<(String, String)> {
+    if let Some(git_yaml) = layer_yaml.get("git") {
+        if git_yaml.get("commit").is_some() {
+            return None;
+        }
+
+        let url = git_yaml["url"].as_str()?.to_string();
+
+        let reference = if let Some(branch) = git_yaml["branch"].as_str() {
+            format!("branch:{}", branch)
+        } else {
+            format!("tag:{}", git_yaml["tag"].as_str()?)
+        };
+
+        Some((url, reference))
+    } else if let Some(product_yaml) = layer_yaml.get("product") {
+        if product_yaml.get("commit").is_some() {
+            return None;
+        }
+
+        let mut reference = format!(
+            "version:{}",
+            get_product_version(product_yaml, cray_product_catalog)?
+        );
+
+        if let Some(branch) = product_yaml["branch"].as_str() {
+            reference.push_str(&format!(",branch:{}", branch));
+        }
+
+        Some((
+            format!("product:{}", product_yaml["name"].as_str()?),
+            reference,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Resolves the branches, tags and product versions in the 'configurations' section of a SAT file
+/// to commit ids. Uses the same logic as when creating the CFS configurations
+pub async fn resolve(
+    shasta_root_cert: &[u8],
+    gitea_base_url: &str,
+    gitea_token: &str,
+    cray_product_catalog: &BTreeMap<String, String>,
+    configuration_yaml_vec: &[Value],
+) -> Vec<LockEntry> {
+    let mut lock_entry_vec = Vec::new();
+
+    for configuration_yaml in configuration_yaml_vec {
+        let configuration_yaml = set_product_versions(configuration_yaml, cray_product_catalog);
+
+        let (_, cfs_configuration) = CfsConfigurationRequest::from_sat_file_serde_yaml(
+            shasta_root_cert,
+            gitea_base_url,
+            gitea_token,
+            &configuration_yaml,
+            cray_product_catalog,
+        )
+        .await;
+
+        let layer_yaml_vec = configuration_yaml["layers"]
+            .as_sequence()
+            .cloned()
+            .unwrap_or_default();
+
+        for (layer_yaml, layer) in layer_yaml_vec
+            .iter()
+            .zip(cfs_configuration.layers.unwrap_or_default())
+        {
+            if let (Some((source, reference)), Some(commit)) = (
+                get_source_and_reference(layer_yaml, cray_product_catalog),
+                layer.commit,
+            ) {
+                lock_entry_vec.push(LockEntry {
+                    source,
+                    reference,
+                    commit,
+                });
+            }
+        }
+    }
+
+    lock_entry_vec.sort();
+    lock_entry_vec.dedup();
+
+    lock_entry_vec
+}
+
+impl SatFileLock {
+    /// Returns None if the lock file does not exists
+    pub fn read(lock_file_path: &Path) -> Result<Option<Self>, String> {
+        if !lock_file_path.exists() {
+            return Ok(None);
+        }
+
+        let lock_file_content = std::fs::read_to_string(lock_file_path)
+            .map_err(|error| format!("Could not read lock file: {}", error))?;
+
+        serde_yaml::from_str(&lock_file_content)
+            .map(Some)
+            .map_err(|error| format!("Could not parse lock file: {}", error))
+    }
+
+    pub fn write(&self, lock_file_path: &Path) -> Result<(), String> {
+        let lock_file_content = serde_yaml::to_string(self)
+            .map_err(|error| format!("Could not serialize lock file: {}", error))?;
+
+        std::fs::write(
+            lock_file_path,
+            format!(
+                "# Generated by manta. Commits the SAT file configuration layers resolved to.\n{}",
+                lock_file_content
+            ),
+        )
+        .map_err(|error| format!("Could not write lock file: {}", error))
+    }
+
+    pub fn get(&self, source: &str, reference: &str) -> Option<&LockEntry> {
+        self.layers
+            .iter()
+            .find(|lock_entry| lock_entry.source == source && lock_entry.reference == reference)
+    }
+
+    /// Replaces the entries with the ones resolved from the SAT file. Entries of layers no longer
+    /// in the SAT file are dropped
+    pub fn merge(&mut self, lock_entry_vec: &[LockEntry]) {
+        self.layers = lock_entry_vec.to_vec();
+
+        self.layers.sort();
+        self.layers.dedup();
+    }
+
+    /// Sets the commits in the lock file to the layers of a CFS configuration created from a SAT
+    /// file configuration, so the CFS configuration does not use a branch which moved after the
+    /// SAT file was checked against the lock file
+    pub fn pin(
+        &self,
+        configuration_yaml: &Value,
+        cfs_configuration: &mut CfsConfigurationRequest,
+        cray_product_catalog: &BTreeMap<String, String>,
+    ) {
+        let layer_yaml_vec = configuration_yaml["layers"]
+            .as_sequence()
+            .cloned()
+            .unwrap_or_default();
+
+        for (layer_yaml, layer) in layer_yaml_vec
+            .iter()
+            .zip(cfs_configuration.layers.iter_mut().flatten())
+        {
+            if let Some(locked_entry) = get_source_and_reference(layer_yaml, cray_product_catalog)
+                .and_then(|(source, reference)| self.get(&source, &reference))
+            {
+                layer.commit = Some(locked_entry.commit.clone());
+                layer.branch = None;
+            }
+        }
+    }
+
+    /// Returns the entries which are missing in the lock file or which resolved to a different
+    /// commit, together with the commit in the lock file
+    pub fn diff<'a>(
+        &self,
+        lock_entry_vec: &'a [LockEntry],
+    ) -> Vec<(&'a LockEntry, Option<String>)> {
+        lock_entry_vec
+            .iter()
+            .filter_map(
+                |lock_entry| match self.get(&lock_entry.source, &lock_entry.reference) {
+                    Some(locked_entry) if locked_entry.commit == lock_entry.commit => None,
+                    Some(locked_entry) => Some((lock_entry, Some(locked_entry.commit.clone()))),
+                    None => Some((lock_entry, None)),
+                },
+            )
+            .collect()
+    }
+}
+
+pub fn print_diff_table(lock_entry_diff_vec: &[(&LockEntry, Option<String>)]) {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Source",
+        "Reference",
+        "Locked commit",
+        "Current commit",
+    ]);
+
+    for (lock_entry, locked_commit_opt) in lock_entry_diff_vec {
+        table.add_row(vec![
+            lock_entry.source.clone(),
+            lock_entry.reference.clone(),
+            locked_commit_opt
+                .clone()
+                .unwrap_or("(not locked)".to_string()),
+            lock_entry.commit.clone(),
+        ]);
+    }
+
+    println!("{table}");
+}
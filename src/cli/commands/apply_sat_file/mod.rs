@@ -1,4 +1,6 @@
 pub mod command;
+pub mod lock;
+pub mod triage;
 pub mod utils;
 // -- TESTS --
 #[cfg(test)]
@@ -1,13 +1,21 @@
 use std::collections::BTreeMap;
 
 use mesa::{
-    cfs::configuration::mesa::r#struct::cfs_configuration_response::v3::{
-        CfsConfigurationResponse, Layer,
+    cfs::configuration::mesa::r#struct::{
+        cfs_configuration_request::v3::{
+            CfsConfigurationRequest, Layer as CfsConfigurationRequestLayer,
+        },
+        cfs_configuration_response::v3::{CfsConfigurationResponse, Layer},
     },
     error::Error,
     ims::{image::r#struct::Image, recipe::r#struct::RecipeGetResponse},
 };
 
+use crate::cli::commands::apply_sat_file::lock::{
+    compare_versions, get_lock_file_path, get_source_and_reference, set_product_versions,
+    LockEntry, SatFileLock,
+};
+use crate::cli::commands::apply_sat_file::triage::get_last_ansible_failure;
 use crate::cli::commands::apply_sat_file::utils::{
     get_image_name_or_ref_name_to_process, get_next_image_in_sat_file_to_process,
     render_jinja2_sat_file_yaml, validate_sat_file_images_section,
@@ -881,3 +889,264 @@ fn test_sat_file_image_section_pass_if_configuration_missing() {
 
     assert!(validation_rslt.is_ok());
 }
+
+/// Test layers pointing to branches, tags and product versions are locked while layers pointing
+/// to commit ids are not
+#[test]
+fn test_lock_source_and_reference() {
+    let configuration_yaml: serde_yaml::Value = serde_yaml::from_str(
+        r#"
+        name: test-config
+        layers:
+        - name: branch-layer
+          playbook: site.yml
+          git:
+            url: https://api-gw-service-nmn.local/vcs/cray/repo.git
+            branch: main
+        - name: tag-layer
+          playbook: site.yml
+          git:
+            url: https://api-gw-service-nmn.local/vcs/cray/repo.git
+            tag: v1.0.0
+        - name: commit-layer
+          playbook: site.yml
+          git:
+            url: https://api-gw-service-nmn.local/vcs/cray/repo.git
+            commit: 1234567890abcdef
+        - playbook: site.yml
+          product:
+            name: cos
+            version: 2.4.139
+            branch: integration
+        - playbook: site.yml
+          product:
+            name: cos
+            version: 2.4.139
+            commit: 1234567890abcdef
+        - playbook: site.yml
+          product:
+            name: cos
+        "#,
+    )
+    .unwrap();
+
+    let cray_product_catalog = BTreeMap::from([(
+        "cos".to_string(),
+        r#"
+        2.4.139:
+          configuration:
+            clone_url: https://vcs.local/vcs/cray/cos-config-management.git
+            commit: 1234567890abcdef
+        2.10.2:
+          configuration:
+            clone_url: https://vcs.local/vcs/cray/cos-config-management.git
+            commit: abcdef1234567890
+        2.11.0:
+          images: {}
+        "#
+        .to_string(),
+    )]);
+
+    let source_and_reference_vec: Vec<Option<(String, String)>> = configuration_yaml["layers"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|layer_yaml| get_source_and_reference(layer_yaml, &cray_product_catalog))
+        .collect();
+
+    let url = "https://api-gw-service-nmn.local/vcs/cray/repo.git".to_string();
+
+    assert_eq!(
+        source_and_reference_vec,
+        vec![
+            Some((url.clone(), "branch:main".to_string())),
+            Some((url, "tag:v1.0.0".to_string())),
+            None,
+            Some((
+                "product:cos".to_string(),
+                "version:2.4.139,branch:integration".to_string()
+            )),
+            None,
+            // Latest version with a configuration
+            Some(("product:cos".to_string(), "version:2.10.2".to_string())),
+        ]
+    );
+
+    assert_eq!(
+        set_product_versions(&configuration_yaml, &cray_product_catalog)["layers"][5]["product"]
+            ["version"]
+            .as_str(),
+        Some("2.10.2")
+    );
+
+    // Product not in the catalog
+    assert_eq!(
+        get_source_and_reference(
+            &configuration_yaml["layers"][5],
+            &BTreeMap::<String, String>::new()
+        ),
+        None
+    );
+
+    assert_eq!(
+        get_lock_file_path(std::path::Path::new("/tmp/cluster.yaml")),
+        std::path::PathBuf::from("/tmp/cluster.yaml.lock")
+    );
+}
+
+/// Test lock file detects branches which moved and references not locked yet
+#[test]
+fn test_lock_diff_and_merge() {
+    let lock_entry = |source: &str, reference: &str, commit: &str| LockEntry {
+        source: source.to_string(),
+        reference: reference.to_string(),
+        commit: commit.to_string(),
+    };
+
+    let mut sat_file_lock = SatFileLock {
+        layers: vec![
+            lock_entry("repo-a", "branch:main", "aaa"),
+            lock_entry("repo-b", "tag:v1", "bbb"),
+        ],
+    };
+
+    let lock_entry_vec = vec![
+        lock_entry("repo-a", "branch:main", "ccc"),
+        lock_entry("repo-b", "tag:v1", "bbb"),
+        lock_entry("product:cos", "version:2.4.139", "ddd"),
+    ];
+
+    let lock_entry_diff_vec = sat_file_lock.diff(&lock_entry_vec);
+
+    assert_eq!(
+        lock_entry_diff_vec,
+        vec![
+            (&lock_entry_vec[0], Some("aaa".to_string())),
+            (&lock_entry_vec[2], None),
+        ]
+    );
+
+    sat_file_lock.merge(&lock_entry_vec);
+
+    assert!(sat_file_lock.diff(&lock_entry_vec).is_empty());
+    assert_eq!(sat_file_lock.layers.len(), 3);
+
+    // Layers removed from the SAT file are dropped from the lock file
+    sat_file_lock.merge(&lock_entry_vec[1..]);
+
+    assert_eq!(sat_file_lock.layers.len(), 2);
+    assert!(sat_file_lock.get("repo-a", "branch:main").is_none());
+}
+
+/// Test CFS configuration layers are pinned to the commits in the lock file
+#[test]
+fn test_lock_pin() {
+    let configuration_yaml: serde_yaml::Value = serde_yaml::from_str(
+        r#"
+        name: test-config
+        layers:
+        - name: branch-layer
+          playbook: site.yml
+          git:
+            url: https://api-gw-service-nmn.local/vcs/cray/repo.git
+            branch: main
+        - name: commit-layer
+          playbook: site.yml
+          git:
+            url: https://api-gw-service-nmn.local/vcs/cray/repo.git
+            commit: 1234567890abcdef
+        "#,
+    )
+    .unwrap();
+
+    let layer = |commit: &str, branch_opt: Option<&str>| {
+        CfsConfigurationRequestLayer::new(
+            Some("layer".to_string()),
+            Some("https://api-gw-service-nmn.local/vcs/cray/repo.git".to_string()),
+            None,
+            "site.yml".to_string(),
+            Some(commit.to_string()),
+            branch_opt.map(str::to_string),
+            None,
+        )
+    };
+
+    let mut cfs_configuration = CfsConfigurationRequest {
+        description: None,
+        layers: Some(vec![
+            layer("moved", Some("main")),
+            layer("1234567890abcdef", None),
+        ]),
+        additional_inventory: None,
+    };
+
+    let sat_file_lock = SatFileLock {
+        layers: vec![LockEntry {
+            source: "https://api-gw-service-nmn.local/vcs/cray/repo.git".to_string(),
+            reference: "branch:main".to_string(),
+            commit: "locked".to_string(),
+        }],
+    };
+
+    sat_file_lock.pin(
+        &configuration_yaml,
+        &mut cfs_configuration,
+        &BTreeMap::new(),
+    );
+
+    let layer_vec = cfs_configuration.layers.unwrap();
+
+    assert_eq!(layer_vec[0].commit.as_deref(), Some("locked"));
+    assert_eq!(layer_vec[0].branch, None);
+    assert_eq!(layer_vec[1].commit.as_deref(), Some("1234567890abcdef"));
+}
+
+/// Test the triage report finds the last failed ansible task, its output and the layer running it
+#[test]
+fn test_get_last_ansible_failure() {
+    let ansible_log = r#"Running playbook /inventory/layer0/site.yml
+TASK [common : install packages] ***********************************************
+fatal: [x3000c0s1b0n0]: FAILED! => {"msg": "ignored error"}
+...ignoring
+Running playbook /inventory/layer1/compute_nodes.yml
+TASK [network : check layer2 connectivity] *************************************
+ok: [x3000c0s1b0n0]
+TASK [cpe : configure modules] *************************************************
+fatal: [x3000c0s1b0n0]: FAILED! => {"changed": false, "msg": "No package matching 'cray-pe' found"}
+  stderr: repo metadata could not be downloaded
+
+PLAY RECAP *********************************************************************
+x3000c0s1b0n0              : ok=3    changed=0    unreachable=0    failed=1    skipped=0    rescued=0    ignored=1
+"#;
+
+    let ansible_failure = get_last_ansible_failure(ansible_log).unwrap();
+
+    assert_eq!(ansible_failure.layer_index_opt, Some(1));
+    assert_eq!(ansible_failure.host, "x3000c0s1b0n0");
+    assert_eq!(ansible_failure.failed_task.task, "cpe : configure modules");
+    assert_eq!(
+        ansible_failure.failed_task.message,
+        "No package matching 'cray-pe' found"
+    );
+    assert_eq!(ansible_failure.output_line_vec.len(), 3);
+    assert_eq!(
+        ansible_failure.output_line_vec[1],
+        "  stderr: repo metadata could not be downloaded"
+    );
+
+    assert!(get_last_ansible_failure(
+        "TASK [common : install packages] *****\nok: [x3000c0s1b0n0]\n"
+    )
+    .is_none());
+}
+
+#[test]
+fn test_compare_versions() {
+    use std::cmp::Ordering;
+
+    assert_eq!(compare_versions("2.4.139", "2.10.0"), Ordering::Less);
+    assert_eq!(compare_versions("2.10.0", "2.4.139"), Ordering::Greater);
+    assert_eq!(compare_versions("1.2.3", "1.2.3"), Ordering::Equal);
+    assert_eq!(compare_versions("1.2", "1.2.1"), Ordering::Less);
+    assert_eq!(compare_versions("1.2.0-rc1", "1.2.0-rc2"), Ordering::Less);
+}
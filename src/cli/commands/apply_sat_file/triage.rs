@@ -0,0 +1,380 @@
+use std::{fmt::Write as _, path::PathBuf, sync::LazyLock};
+
+use chrono::Local;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{ListParams, LogParams};
+use mesa::cfs::{
+    self, configuration::mesa::r#struct::cfs_configuration_response::v3::Layer,
+    session::mesa::r#struct::v3::CfsSessionGetResponse,
+};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::cli::commands::log::ansible_parser::{AnsibleEvent, AnsibleLogParser, FailedTask};
+
+/// Max number of lines of output kept for the failed task
+const FAILED_TASK_OUTPUT_MAX_LINES: usize = 50;
+
+/// Layer folder in the CFS ansible container, eg '/inventory/layer0'
+static LAYER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/inventory/layer(\d+)").unwrap());
+
+/// Last ansible task failed in a CFS session and the layer running it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsibleFailure {
+    /// Layer index as in '/inventory/layer<index>'
+    pub layer_index_opt: Option<usize>,
+    pub host: String,
+    pub failed_task: FailedTask,
+    /// Ansible output from the failed result until the next task
+    pub output_line_vec: Vec<String>,
+}
+
+/// Details about an image build (CFS session target image) failure
+pub struct TriageReport {
+    pub image_name: String,
+    pub cfs_session: CfsSessionGetResponse,
+    pub layer_vec: Vec<Layer>,
+    pub ansible_failure_opt: Option<AnsibleFailure>,
+    pub ims_job_opt: Option<Value>,
+}
+
+/// Returns the last failed task in the ansible logs of a CFS session. The layer is the last one
+/// mentioned in the logs ('/inventory/layer<index>') before the failure
+pub fn get_last_ansible_failure(ansible_log: &str) -> Option<AnsibleFailure> {
+    let mut parser = AnsibleLogParser::new();
+
+    let mut layer_index_opt: Option<usize> = None;
+    let mut ansible_failure_opt: Option<AnsibleFailure> = None;
+    // Capture the output of the failure until the next task
+    let mut capture_output = false;
+
+    for line in ansible_log.lines() {
+        if let Some(captures) = LAYER_REGEX.captures(line) {
+            layer_index_opt = captures[1].parse().ok();
+        }
+
+        match parser.parse_line(line) {
+            Some(AnsibleEvent::HostFailed(host, failed_task)) => {
+                ansible_failure_opt = Some(AnsibleFailure {
+                    layer_index_opt,
+                    host,
+                    failed_task,
+                    output_line_vec: vec![line.to_string()],
+                });
+
+                capture_output = true;
+            }
+            Some(AnsibleEvent::HostResult(_)) if line.trim_start().starts_with("...ignoring") => {
+                // Failure ignored by ansible
+                ansible_failure_opt = None;
+                capture_output = false;
+            }
+            Some(_) => capture_output = false,
+            None => {
+                if let Some(ansible_failure) = ansible_failure_opt.as_mut() {
+                    if capture_output
+                        && ansible_failure.output_line_vec.len() < FAILED_TASK_OUTPUT_MAX_LINES
+                    {
+                        ansible_failure.output_line_vec.push(line.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    ansible_failure_opt
+}
+
+/// Returns the logs of the ansible container of a CFS session, None if the pod is gone
+async fn get_cfs_session_ansible_log(
+    kube_client: &kube::Client,
+    cfs_session_name: &str,
+) -> Option<String> {
+    let pods_api: kube::Api<Pod> = kube::Api::namespaced(kube_client.clone(), "services");
+
+    let params = ListParams::default()
+        .limit(1)
+        .labels(&format!("cfsession={}", cfs_session_name));
+
+    let pod_name = pods_api
+        .list(&params)
+        .await
+        .ok()?
+        .items
+        .first()?
+        .metadata
+        .name
+        .clone()?;
+
+    let log_params = LogParams {
+        container: Some("ansible".to_string()),
+        ..Default::default()
+    };
+
+    match pods_api.logs(&pod_name, &log_params).await {
+        Ok(log) => Some(log),
+        Err(error) => {
+            log::warn!(
+                "Could not get ansible logs for CFS session '{}': {}",
+                cfs_session_name,
+                error
+            );
+            None
+        }
+    }
+}
+
+impl TriageReport {
+    /// Collects the CFS configuration layers, the ansible logs and the IMS job related to a
+    /// failed CFS session. Missing details are left out of the report
+    pub async fn new(
+        shasta_token: &str,
+        shasta_base_url: &str,
+        shasta_root_cert: &[u8],
+        kube_client_opt: Option<&kube::Client>,
+        image_name: &str,
+        cfs_session: CfsSessionGetResponse,
+    ) -> Self {
+        let cfs_session_name = cfs_session.name.clone().unwrap_or_default();
+
+        let layer_vec = match cfs_session.get_configuration_name() {
+            Some(configuration_name) => cfs::configuration::mesa::http_client::get(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                Some(&configuration_name),
+            )
+            .await
+            .ok()
+            .and_then(|cfs_configuration_vec| cfs_configuration_vec.first().cloned())
+            .map(|cfs_configuration| cfs_configuration.layers)
+            .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let ansible_failure_opt = match kube_client_opt {
+            Some(kube_client) => get_cfs_session_ansible_log(kube_client, &cfs_session_name)
+                .await
+                .and_then(|ansible_log| get_last_ansible_failure(&ansible_log)),
+            None => None,
+        };
+
+        let ims_job_id_opt = cfs_session
+            .status
+            .as_ref()
+            .and_then(|status| status.session.as_ref())
+            .and_then(|session| session.ims_job.clone());
+
+        let ims_job_opt = match ims_job_id_opt {
+            Some(ims_job_id) => mesa::ims::job::http_client::get(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                Some(&ims_job_id),
+            )
+            .await
+            .ok(),
+            None => None,
+        };
+
+        TriageReport {
+            image_name: image_name.to_string(),
+            cfs_session,
+            layer_vec,
+            ansible_failure_opt,
+            ims_job_opt,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let cfs_session_name = self.cfs_session.name.clone().unwrap_or_default();
+
+        let mut report = String::new();
+
+        let _ = writeln!(report, "# Image build failure report\n");
+        let _ = writeln!(
+            report,
+            "Generated:     {}",
+            Local::now().format("%d/%m/%Y %H:%M:%S")
+        );
+        let _ = writeln!(report, "Image:         {}", self.image_name);
+        let _ = writeln!(report, "CFS session:   {}", cfs_session_name);
+        let _ = writeln!(
+            report,
+            "Configuration: {}",
+            self.cfs_session
+                .get_configuration_name()
+                .unwrap_or("Not defined".to_string())
+        );
+
+        let _ = writeln!(report, "\n## CFS session status\n");
+
+        if let Some(session) = self
+            .cfs_session
+            .status
+            .as_ref()
+            .and_then(|status| status.session.as_ref())
+        {
+            let not_defined = "Not defined".to_string();
+
+            let _ = writeln!(
+                report,
+                "Status:          {}",
+                session.status.as_ref().unwrap_or(&not_defined)
+            );
+            let _ = writeln!(
+                report,
+                "Succeeded:       {}",
+                session.succeeded.as_ref().unwrap_or(&not_defined)
+            );
+            let _ = writeln!(
+                report,
+                "Start time:      {}",
+                session.start_time.as_ref().unwrap_or(&not_defined)
+            );
+            let _ = writeln!(
+                report,
+                "Completion time: {}",
+                session.completion_time.as_ref().unwrap_or(&not_defined)
+            );
+            let _ = writeln!(
+                report,
+                "Job:             {}",
+                session.job.as_ref().unwrap_or(&not_defined)
+            );
+            let _ = writeln!(
+                report,
+                "IMS job:         {}",
+                session.ims_job.as_ref().unwrap_or(&not_defined)
+            );
+        } else {
+            let _ = writeln!(report, "Status not available");
+        }
+
+        let _ = writeln!(report, "\n## Failing layer\n");
+
+        let layer_index_opt = self
+            .ansible_failure_opt
+            .as_ref()
+            .and_then(|ansible_failure| ansible_failure.layer_index_opt)
+            .or(if self.layer_vec.len() == 1 {
+                Some(0)
+            } else {
+                None
+            });
+
+        match layer_index_opt.and_then(|layer_index| {
+            self.layer_vec
+                .get(layer_index)
+                .map(|layer| (layer_index, layer))
+        }) {
+            Some((layer_index, layer)) => {
+                let _ = writeln!(report, "Layer:    {} ({})", layer_index, layer.name);
+                let _ = writeln!(report, "Repo:     {}", layer.clone_url);
+                let _ = writeln!(
+                    report,
+                    "Commit:   {}",
+                    layer.commit.as_deref().unwrap_or("Not defined")
+                );
+                let _ = writeln!(report, "Playbook: {}", layer.playbook);
+            }
+            None => {
+                let _ = writeln!(
+                    report,
+                    "Could not find the failing layer in the logs. Layers:"
+                );
+
+                for (layer_index, layer) in self.layer_vec.iter().enumerate() {
+                    let _ = writeln!(
+                        report,
+                        "  {}: {} ({} - {})",
+                        layer_index, layer.name, layer.clone_url, layer.playbook
+                    );
+                }
+            }
+        }
+
+        let _ = writeln!(report, "\n## Last failed ansible task\n");
+
+        match &self.ansible_failure_opt {
+            Some(ansible_failure) => {
+                let _ = writeln!(report, "Host:  {}", ansible_failure.host);
+                let _ = writeln!(report, "Task:  {}", ansible_failure.failed_task.task);
+                let _ = writeln!(report, "Error: {}", ansible_failure.failed_task.message);
+                let _ = writeln!(
+                    report,
+                    "Output:\n```\n{}\n```",
+                    ansible_failure.output_line_vec.join("\n")
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    report,
+                    "No failed task found (ansible logs not available or the failure happened outside ansible)"
+                );
+            }
+        }
+
+        let _ = writeln!(report, "\n## IMS job\n");
+
+        match &self.ims_job_opt {
+            Some(ims_job) => {
+                for field in [
+                    "id",
+                    "status",
+                    "job_type",
+                    "kubernetes_job",
+                    "image_root_archive_name",
+                    "resultant_image_id",
+                ] {
+                    let _ = writeln!(
+                        report,
+                        "{:24} {}",
+                        format!("{}:", field),
+                        ims_job[field].as_str().unwrap_or("Not defined")
+                    );
+                }
+
+                if let Some(ssh_containers) = ims_job["ssh_containers"].as_array() {
+                    let _ = writeln!(
+                        report,
+                        "ssh_containers:\n```\n{}\n```",
+                        serde_json::to_string_pretty(ssh_containers).unwrap_or_default()
+                    );
+                }
+            }
+            None => {
+                let _ = writeln!(report, "IMS job not available");
+            }
+        }
+
+        let _ = writeln!(report, "\n## Debug\n");
+        let _ = writeln!(
+            report,
+            "Open a console to the image being built:\n  manta console target-ansible {}",
+            cfs_session_name
+        );
+        let _ = writeln!(
+            report,
+            "CFS session logs:\n  manta log {} --all-containers",
+            cfs_session_name
+        );
+
+        report
+    }
+
+    /// Saves the report in the current directory. Returns the file path
+    pub fn write(&self) -> Result<PathBuf, String> {
+        let report_file_path = PathBuf::from(format!(
+            "{}-triage-{}.md",
+            self.cfs_session.name.clone().unwrap_or_default(),
+            Local::now().format("%Y%m%d%H%M%S")
+        ));
+
+        std::fs::write(&report_file_path, self.render())
+            .map_err(|error| format!("Could not write triage report: {}", error))?;
+
+        Ok(report_file_path)
+    }
+}
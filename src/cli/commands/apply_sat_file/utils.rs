@@ -26,6 +26,8 @@ use uuid::Uuid;
 
 use crate::cli::process::validate_target_hsm_members;
 
+use super::{lock::SatFileLock, triage::TriageReport};
+
 use self::sessiontemplate::SessionTemplate;
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -371,7 +373,8 @@ pub mod configuration {
         pub layer_type: LayerType,
     }
 
-    fn default_playbook() -> String {
+    /// Playbook CFS runs if a layer does not define one
+    pub fn default_playbook() -> String {
         "site.yml".to_string()
     }
 
@@ -627,6 +630,7 @@ pub async fn create_cfs_configuration_from_sat_file(
     gitea_token: &str,
     cray_product_catalog: &BTreeMap<String, String>,
     sat_file_configuration_yaml: &serde_yaml::Value,
+    sat_file_lock_opt: Option<&SatFileLock>,
     // tag: &str,
     dry_run: bool,
 ) -> Result<CfsConfigurationResponse, Error> {
@@ -645,6 +649,14 @@ pub async fn create_cfs_configuration_from_sat_file(
         )
         .await;
 
+    if let Some(sat_file_lock) = sat_file_lock_opt {
+        sat_file_lock.pin(
+            sat_file_configuration_yaml,
+            &mut cfs_configuration,
+            cray_product_catalog,
+        );
+    }
+
     if !dry_run {
         cfs::configuration::mesa::utils::create(
             shasta_token,
@@ -735,6 +747,7 @@ pub async fn import_images_section_in_sat_file(
     ansible_passthrough_opt: Option<&String>,
     debug_on_failure: bool, // tag: &str,
     dry_run: bool,
+    kube_client_opt: Option<&kube::Client>,
 ) -> HashMap<String, serde_yaml::Value> {
     // Get an image to process (the image either has no dependency or it's image dependency has
     // already ben processed)
@@ -762,6 +775,7 @@ pub async fn import_images_section_in_sat_file(
             ref_name_processed_hashmap,
             debug_on_failure,
             dry_run,
+            kube_client_opt,
         )
         .await
         .unwrap();
@@ -796,6 +810,7 @@ pub async fn create_image_from_sat_file_serde_yaml(
     ref_name_image_id_hashmap: &HashMap<String, String>,
     debug_on_failure: bool,
     dry_run: bool,
+    kube_client_opt: Option<&kube::Client>,
 ) -> Result<String, Error> {
     // Collect CFS session details from SAT file
     // Get CFS session name from SAT file
@@ -1029,9 +1044,30 @@ pub async fn create_image_from_sat_file_serde_yaml(
 
             if !cfs_session.is_success() {
                 eprintln!(
-                    "Error: CFS session '{}' failed. Exit",
-                    cfs_session.name.unwrap()
+                    "Error: CFS session '{}' failed",
+                    cfs_session.name.as_ref().unwrap()
                 );
+
+                let triage_report = TriageReport::new(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    kube_client_opt,
+                    &image_name,
+                    cfs_session,
+                )
+                .await;
+
+                eprintln!("{}", triage_report.render());
+
+                match triage_report.write() {
+                    Ok(report_file_path) => {
+                        eprintln!("Triage report saved in '{}'", report_file_path.display())
+                    }
+                    Err(error) => eprintln!("WARNING - {}", error),
+                }
+
+                eprintln!("Exit");
                 std::process::exit(1);
             }
 
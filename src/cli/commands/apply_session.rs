@@ -15,7 +15,16 @@ use mesa::{
 use k8s_openapi::chrono;
 use substring::Substring;
 
-use crate::common::local_git_repo;
+use crate::{
+    cli::commands::{
+        apply_sat_file::utils::configuration,
+        log::{archive::LogArchive, command as log_command},
+    },
+    common::{
+        ansible_playbook_ops::{self, Severity},
+        gitea_ops, local_git_repo,
+    },
+};
 
 /// Creates a CFS session target dynamic
 /// Returns a tuple like (<cfs configuration name>, <cfs session name>)
@@ -36,6 +45,10 @@ pub async fn exec(
     ansible_verbosity: Option<String>,
     ansible_passthrough: Option<String>,
     watch_logs: bool,
+    skip_preflight: bool,
+    push: bool,
+    structured_logs: bool,
+    log_archive: &LogArchive,
 ) -> (String, String) {
     /* let included: HashSet<String>;
     let excluded: HashSet<String>; */
@@ -127,6 +140,40 @@ pub async fn exec(
     xname_list.sort();
     xname_list.dedup();
 
+    // * Check ansible playbooks locally before creating the CFS session
+    if !skip_preflight {
+        let inventory_group_vec = ansible_playbook_ops::get_cfs_inventory_group_vec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_list,
+        )
+        .await;
+
+        let preflight_issue_vec = repos_paths
+            .iter()
+            .flat_map(|repo_path| {
+                ansible_playbook_ops::preflight(
+                    repo_path,
+                    &configuration::default_playbook(),
+                    &inventory_group_vec,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if !preflight_issue_vec.is_empty() {
+            ansible_playbook_ops::print_table(&preflight_issue_vec);
+        }
+
+        if preflight_issue_vec
+            .iter()
+            .any(|preflight_issue| preflight_issue.severity == Severity::Error)
+        {
+            eprintln!("ERROR - Ansible playbook pre-flight checks failed. Fix the errors above or use '--skip-preflight'. Exit");
+            std::process::exit(1);
+        }
+    }
+
     log::info!("Replacing '_' with '-' in repo name.");
     let cfs_configuration_name = str::replace(&cfs_configuration_name, "_", "-");
 
@@ -147,6 +194,7 @@ pub async fn exec(
                 .unwrap_or(0),
         ),
         ansible_passthrough,
+        push,
     )
     .await
     .unwrap();
@@ -171,29 +219,65 @@ pub async fn exec(
             .await
             .unwrap();
 
-        // Get CFS session logs
-        let logs_stream_rslt = kubernetes::get_cfs_session_container_git_clone_logs_stream(
-            client.clone(),
-            &cfs_session_name,
-        )
-        .await;
+        if structured_logs || log_archive.enabled {
+            let cfs_session_vec = cfs::session::mesa::http_client::get(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                None,
+                None,
+                None,
+                Some(&cfs_session_name),
+                None,
+            )
+            .await
+            .unwrap_or_default();
+
+            match cfs_session_vec.first() {
+                Some(cfs_session) => {
+                    if let Err(error) = log_command::print_cfs_session_logs(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        client,
+                        cfs_session,
+                        structured_logs,
+                        log_archive,
+                    )
+                    .await
+                    {
+                        log::error!("{}", error);
+                    }
+                }
+                None => log::error!("CFS session '{}' not found", cfs_session_name),
+            }
+        } else {
+            // Get CFS session logs
+            let logs_stream_rslt = kubernetes::get_cfs_session_container_git_clone_logs_stream(
+                client.clone(),
+                &cfs_session_name,
+            )
+            .await;
 
-        match logs_stream_rslt {
-            Ok(mut logs_stream) => {
-                while let Some(line) = logs_stream.try_next().await.unwrap() {
-                    println!("{}", line);
+            match logs_stream_rslt {
+                Ok(mut logs_stream) => {
+                    while let Some(line) = logs_stream.try_next().await.unwrap() {
+                        println!("{}", line);
+                    }
                 }
+                Err(error_msg) => log::error!("{}", error_msg),
             }
-            Err(error_msg) => log::error!("{}", error_msg),
-        }
 
-        let mut logs_stream =
-            kubernetes::get_cfs_session_container_ansible_logs_stream(client, &cfs_session_name)
-                .await
-                .unwrap();
+            let mut logs_stream = kubernetes::get_cfs_session_container_ansible_logs_stream(
+                client,
+                &cfs_session_name,
+            )
+            .await
+            .unwrap();
 
-        while let Some(line) = logs_stream.try_next().await.unwrap() {
-            println!("{}", line);
+            while let Some(line) = logs_stream.try_next().await.unwrap() {
+                println!("{}", line);
+            }
         }
     }
     // * End Create CFS session
@@ -219,6 +303,7 @@ pub async fn check_nodes_are_ready_to_run_cfs_configuration_and_run_cfs_session(
     limit: Option<String>,
     ansible_verbosity: Option<u8>,
     ansible_passthrough: Option<String>,
+    push: bool,
 ) -> Result<String, Error> {
     // Get ALL sessions
     let cfs_sessions = mesa::cfs::session::mesa::http_client::get(
@@ -360,8 +445,9 @@ pub async fn check_nodes_are_ready_to_run_cfs_configuration_and_run_cfs_session(
 
         log::info!("Checking local repo status ({})", &repo.path().display());
 
-        // Check if all changes in local repo has been commited locally
-        if !local_git_repo::untracked_changed_local_files(&repo).unwrap() {
+        // Check if all changes in local repo has been commited locally. Uncommitted changes are
+        // pushed to a throwaway branch if user wants to push the local repo
+        if !local_git_repo::untracked_changed_local_files(&repo).unwrap() && !push {
             if Confirm::with_theme(&ColorfulTheme::default())
                 .with_prompt("Your local repo has uncommitted changes. Do you want to continue?")
                 .interact()
@@ -438,6 +524,28 @@ pub async fn check_nodes_are_ready_to_run_cfs_configuration_and_run_cfs_session(
     // git2_rs_utils::local::fetch_and_check_conflicts(&repo)?;
     // log::debug!("No conflicts");
 
+    // Push local repos to Shasta VCS
+    let mut commit_id_opt_vec = Vec::new();
+
+    for repo_path in &repos {
+        let commit_id_opt = if push {
+            Some(
+                push_local_repo(
+                    gitea_token,
+                    gitea_base_url,
+                    shasta_token,
+                    shasta_root_cert,
+                    repo_path,
+                )
+                .await,
+            )
+        } else {
+            None
+        };
+
+        commit_id_opt_vec.push(commit_id_opt);
+    }
+
     log::info!("Creating CFS configuration {}", cfs_configuration_name);
 
     let cfs_configuration = crate::common::cfs_configuration_utils::create_from_repos(
@@ -445,6 +553,7 @@ pub async fn check_nodes_are_ready_to_run_cfs_configuration_and_run_cfs_session(
         gitea_base_url,
         shasta_root_cert,
         repos,
+        commit_id_opt_vec,
         &cfs_configuration_name.to_string(),
     )
     .await;
@@ -523,3 +632,231 @@ pub async fn check_nodes_are_ready_to_run_cfs_configuration_and_run_cfs_session(
 
     Ok(String::from(cfs_session_name))
 }
+
+/// Pushes a local repo to Shasta VCS and returns the commit id to use in the CFS configuration
+/// layer. The repo is created in Shasta VCS if missing. If the local repo has uncommitted
+/// changes, then they are committed to a throwaway branch 'manta/<user>/<timestamp>' which is
+/// pushed instead of the current branch
+async fn push_local_repo(
+    gitea_token: &str,
+    gitea_base_url: &str,
+    shasta_token: &str,
+    shasta_root_cert: &[u8],
+    repo_path: &PathBuf,
+) -> String {
+    let repo = match local_git_repo::get_repo(&repo_path.to_string_lossy()) {
+        Ok(repo) => repo,
+        Err(_) => {
+            eprintln!(
+                "ERROR - Could not find a git repo in {}. Exit",
+                repo_path.to_string_lossy()
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let repo_ref_origin = match repo.find_remote("origin") {
+        Ok(repo_ref_origin) => repo_ref_origin,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not find remote 'origin' in repo {}. Reason:\n{}\nExit",
+                repo_path.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let Some(repo_ref_origin_url) = repo_ref_origin.url() else {
+        eprintln!(
+            "ERROR - Remote 'origin' in repo {} has no valid url. Exit",
+            repo_path.to_string_lossy()
+        );
+        std::process::exit(1);
+    };
+
+    let repo_name = gitea_ops::get_repo_name_from_url(repo_ref_origin_url);
+
+    // Create repo in Shasta VCS if missing
+    match gitea_ops::get_repo(gitea_base_url, gitea_token, shasta_root_cert, repo_name).await {
+        Ok(Some(_)) => log::info!("Repo '{}' found in Shasta VCS", repo_name),
+        Ok(None) => {
+            println!("Repo '{}' not found in Shasta VCS. Creating it", repo_name);
+
+            if let Err(error) =
+                gitea_ops::create_repo(gitea_base_url, gitea_token, shasta_root_cert, repo_name)
+                    .await
+            {
+                eprintln!(
+                    "ERROR - Could not create repo '{}' in Shasta VCS. Reason:\n{}\nExit",
+                    repo_name, error
+                );
+                std::process::exit(1);
+            }
+        }
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not get repo '{}' from Shasta VCS. Reason:\n{}\nExit",
+                repo_name, error
+            );
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(error) = local_git_repo::set_ssl_root_cert(shasta_root_cert) {
+        eprintln!(
+            "ERROR - Could not configure Shasta root CA certificate for git. Reason:\n{}\nExit",
+            error
+        );
+        std::process::exit(1);
+    }
+
+    let remote_url = format!(
+        "{}/{}/{}.git",
+        gitea_base_url,
+        gitea_ops::GITEA_ORGANIZATION,
+        repo_name
+    );
+
+    let is_work_tree_clean = match local_git_repo::untracked_changed_local_files(&repo) {
+        Ok(is_work_tree_clean) => is_work_tree_clean,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not get status of repo '{}'. Reason:\n{}\nExit",
+                repo_name, error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let (branch_name, commit_id) = if !is_work_tree_clean {
+        // Commit uncommitted changes to a throwaway branch
+        let username = get_claims_from_jwt_token(shasta_token)
+            .ok()
+            .and_then(|jwt_claims| {
+                jwt_claims["preferred_username"]
+                    .as_str()
+                    .map(|username| username.to_string())
+            })
+            .unwrap_or(std::env::var("USER").unwrap_or("unknown".to_string()));
+
+        let branch_name = format!(
+            "manta/{}/{}",
+            username.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "-"),
+            chrono::Utc::now().format("%Y%m%d%H%M%S")
+        );
+
+        let commit_id = match local_git_repo::commit_work_tree_to_branch(
+            &repo,
+            &branch_name,
+            &format!(
+                "manta: uncommitted changes for CFS session ({})",
+                branch_name
+            ),
+        ) {
+            Ok(commit_id) => commit_id,
+            Err(error) => {
+                eprintln!(
+                    "ERROR - Could not commit local changes in repo '{}'. Reason:\n{}\nExit",
+                    repo_name, error
+                );
+                std::process::exit(1);
+            }
+        };
+
+        println!(
+            "Repo '{}' has uncommitted changes, pushing them to throwaway branch '{}'",
+            repo_name, branch_name
+        );
+
+        (branch_name, commit_id)
+    } else {
+        let branch_name = match local_git_repo::get_head_branch_name(&repo) {
+            Ok(branch_name) => branch_name,
+            Err(error) => {
+                eprintln!(
+                    "ERROR - Could not push repo '{}'. Reason:\n{}\nExit",
+                    repo_name, error
+                );
+                std::process::exit(1);
+            }
+        };
+
+        let commit_id = match local_git_repo::get_last_commit(&repo) {
+            Ok(commit) => commit.id(),
+            Err(error) => {
+                eprintln!(
+                    "ERROR - Could not get last commit of repo '{}'. Reason:\n{}\nExit",
+                    repo_name, error
+                );
+                std::process::exit(1);
+            }
+        };
+
+        // Check local branch can be pushed to remote
+        let remote_commit_opt =
+            match local_git_repo::fetch_branch(&repo, &remote_url, &branch_name, gitea_token) {
+                Ok(remote_commit_opt) => remote_commit_opt,
+                Err(error) => {
+                    eprintln!(
+                        "ERROR - Could not fetch branch '{}' from Shasta VCS. Reason:\n{}\nExit",
+                        branch_name, error
+                    );
+                    std::process::exit(1);
+                }
+            };
+
+        if let Some(remote_commit) = remote_commit_opt {
+            if remote_commit.id() == commit_id {
+                println!(
+                    "Branch '{}' in repo '{}' already up to date in Shasta VCS",
+                    branch_name, repo_name
+                );
+                return commit_id.to_string();
+            }
+
+            if !repo
+                .graph_descendant_of(commit_id, remote_commit.id())
+                .unwrap_or(false)
+            {
+                let local_commit = match repo.find_annotated_commit(commit_id) {
+                    Ok(local_commit) => local_commit,
+                    Err(error) => {
+                        eprintln!(
+                            "ERROR - Could not find commit '{}' in repo '{}'. Reason:\n{}\nExit",
+                            commit_id, repo_name, error
+                        );
+                        std::process::exit(1);
+                    }
+                };
+
+                match local_git_repo::has_conflicts(&repo, &local_commit, &remote_commit) {
+                    Ok(_) => eprintln!(
+                        "ERROR - Branch '{}' in repo '{}' diverged from Shasta VCS. Changes can be merged without conflicts, please pull/rebase and try again. Exit",
+                        branch_name, repo_name
+                    ),
+                    Err(error) => eprintln!("ERROR - {}\nExit", error),
+                }
+
+                std::process::exit(1);
+            }
+        }
+
+        (branch_name, commit_id)
+    };
+
+    println!(
+        "Pushing branch '{}' of repo '{}' to Shasta VCS",
+        branch_name, repo_name
+    );
+
+    if let Err(error) = local_git_repo::push_branch(&repo, &remote_url, &branch_name, gitea_token) {
+        eprintln!(
+            "ERROR - Could not push branch '{}' to Shasta VCS. Reason:\n{}\nExit",
+            branch_name, error
+        );
+        std::process::exit(1);
+    }
+
+    commit_id.to_string()
+}
@@ -0,0 +1,634 @@
+use std::collections::{BTreeMap, HashSet};
+
+use comfy_table::{Cell, Color, Table};
+use mesa::{
+    bss::bootparameters::BootParameters,
+    cfs::{
+        component::shasta::r#struct::v2::ComponentResponse,
+        configuration::mesa::r#struct::cfs_configuration_response::v3::CfsConfigurationResponse,
+        session::mesa::r#struct::v3::CfsSessionGetResponse,
+    },
+    ims::image::r#struct::Image,
+};
+use serde::Serialize;
+use serde_json::Value;
+use strum_macros::Display;
+
+use crate::{
+    cli::commands::{
+        delete_sessions::command::is_cfs_configuration_a_desired_configuration,
+        gc::command::{is_hsm_group_in_name, GcReferences},
+    },
+    common::s3_ops,
+};
+
+/// Bucket where IMS stores the boot artefacts
+const BOOT_IMAGES_BUCKET: &str = "boot-images";
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+pub enum FindingKind {
+    #[strum(serialize = "IMS image without S3 artefacts")]
+    ImageWithoutS3Artefacts,
+    #[strum(serialize = "S3 artefacts without IMS image")]
+    S3ArtefactsWithoutImage,
+    #[strum(serialize = "BOS sessiontemplate with missing image")]
+    BosSessiontemplateMissingImage,
+    #[strum(serialize = "BOS sessiontemplate with missing configuration")]
+    BosSessiontemplateMissingConfiguration,
+    #[strum(serialize = "Boot parameters with missing image")]
+    BootParametersMissingImage,
+    #[strum(serialize = "Desired configuration missing")]
+    DesiredConfigurationMissing,
+    #[strum(serialize = "CFS configuration not used")]
+    ConfigurationNotUsed,
+}
+
+/// Inconsistency found between IMS, S3, BOS, BSS and CFS
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// Image id, BOS sessiontemplate name or CFS configuration name
+    pub artefact: String,
+    pub detail: String,
+    pub suggested_fix: String,
+}
+
+/// Cross references IMS, S3, BOS, BSS and CFS for the HSM groups and prints the inconsistencies
+/// found. S3 objects are checked through the image manifests, objects not referenced by any IMS
+/// image, BOS sessiontemplate or boot parameters can't be found
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_group_name_vec: &[String],
+    skip_s3: bool,
+    output: &str,
+) {
+    // COLLECT SITE WIDE DATA
+    //
+    let (
+        cfs_configuration_vec_opt,
+        cfs_session_vec_opt,
+        bos_sessiontemplate_vec_opt,
+        image_vec_opt,
+        cfs_component_vec_opt,
+    ) = mesa::common::utils::get_configurations_sessions_bos_sessiontemplates_images_components(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        true,
+        true,
+        true,
+        true,
+        true,
+    )
+    .await;
+
+    let (
+        Some(cfs_configuration_vec),
+        Some(cfs_session_vec),
+        Some(bos_sessiontemplate_vec),
+        Some(image_vec),
+        Some(cfs_component_vec),
+    ) = (
+        cfs_configuration_vec_opt,
+        cfs_session_vec_opt,
+        bos_sessiontemplate_vec_opt,
+        image_vec_opt,
+        cfs_component_vec_opt,
+    )
+    else {
+        eprintln!("ERROR - Problem fetching CFS configurations, CFS sessions, BOS sessiontemplates, IMS images or CFS components. Exit");
+        std::process::exit(1);
+    };
+
+    let boot_param_vec: Vec<BootParameters> = match mesa::bss::bootparameters::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &vec![],
+    )
+    .await
+    {
+        Ok(boot_param_vec) => boot_param_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not fetch boot parameters. Reason:\n{}",
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let xname_vec = mesa::hsm::group::utils::get_member_vec_from_hsm_name_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_group_name_vec.to_vec(),
+    )
+    .await;
+
+    // FILTER DATA RELATED TO THE HSM GROUPS
+    //
+    let mut hsm_cfs_configuration_vec = cfs_configuration_vec.clone();
+
+    mesa::cfs::configuration::mesa::utils::filter(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &mut hsm_cfs_configuration_vec,
+        None,
+        hsm_group_name_vec,
+        None,
+    )
+    .await;
+
+    let mut hsm_bos_sessiontemplate_vec = bos_sessiontemplate_vec.clone();
+
+    mesa::bos::template::mesa::utils::filter(
+        &mut hsm_bos_sessiontemplate_vec,
+        hsm_group_name_vec,
+        &Vec::new(),
+        None,
+    )
+    .await;
+
+    let gc_references = GcReferences::new(&bos_sessiontemplate_vec, &boot_param_vec);
+
+    let hsm_cfs_configuration_name_vec: Vec<String> = hsm_cfs_configuration_vec
+        .into_iter()
+        .map(|cfs_configuration| cfs_configuration.name)
+        .collect();
+
+    let hsm_bos_sessiontemplate_name_vec: Vec<String> = hsm_bos_sessiontemplate_vec
+        .into_iter()
+        .filter_map(|bos_sessiontemplate| bos_sessiontemplate.name)
+        .collect();
+
+    let mut finding_vec = audit(
+        hsm_group_name_vec,
+        &xname_vec,
+        &hsm_cfs_configuration_name_vec,
+        &hsm_bos_sessiontemplate_name_vec,
+        &cfs_configuration_vec,
+        &cfs_session_vec,
+        &cfs_component_vec,
+        &image_vec,
+        &gc_references,
+    );
+
+    // CHECK S3 ARTEFACTS
+    //
+    if !skip_s3 {
+        let image_id_in_scope_set = get_image_id_in_scope_set(
+            hsm_group_name_vec,
+            &xname_vec,
+            &hsm_bos_sessiontemplate_name_vec,
+            &cfs_session_vec,
+            &gc_references,
+        );
+
+        let hsm_image_vec: Vec<&Image> = image_vec
+            .iter()
+            .filter(|image| {
+                image
+                    .id
+                    .as_ref()
+                    .is_some_and(|image_id| image_id_in_scope_set.contains(image_id))
+                    || hsm_group_name_vec
+                        .iter()
+                        .any(|hsm_group_name| is_hsm_group_in_name(&image.name, hsm_group_name))
+            })
+            .collect();
+
+        // Images referenced but not in IMS
+        let image_id_missing_vec: Vec<String> = image_id_in_scope_set
+            .into_iter()
+            .filter(|image_id| {
+                !image_id.is_empty()
+                    && !image_vec
+                        .iter()
+                        .any(|image| image.id.as_ref() == Some(image_id))
+            })
+            .collect();
+
+        match audit_s3(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &hsm_image_vec,
+            &image_id_missing_vec,
+        )
+        .await
+        {
+            Ok(s3_finding_vec) => finding_vec.extend(s3_finding_vec),
+            Err(error) => eprintln!("WARNING - Could not check S3 artefacts. Reason:\n{}", error),
+        }
+    }
+
+    finding_vec.sort_by(|finding_1, finding_2| {
+        (finding_1.kind, &finding_1.artefact).cmp(&(finding_2.kind, &finding_2.artefact))
+    });
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&finding_vec)
+                .expect("ERROR - Could not serialize audit report")
+        );
+    } else if finding_vec.is_empty() {
+        println!("No inconsistencies found");
+    } else {
+        print_table(&finding_vec);
+    }
+}
+
+/// Returns the inconsistencies between IMS, BOS, BSS and CFS related to the HSM groups. Checks
+/// against S3 are done in 'audit_s3'
+pub fn audit(
+    hsm_group_name_vec: &[String],
+    xname_vec: &[String],
+    hsm_cfs_configuration_name_vec: &[String],
+    hsm_bos_sessiontemplate_name_vec: &[String],
+    cfs_configuration_vec: &[CfsConfigurationResponse],
+    cfs_session_vec: &[CfsSessionGetResponse],
+    cfs_component_vec: &Vec<ComponentResponse>,
+    image_vec: &[Image],
+    gc_references: &GcReferences,
+) -> Vec<Finding> {
+    let mut finding_vec = Vec::new();
+
+    let image_id_set: HashSet<&str> = image_vec
+        .iter()
+        .filter_map(|image| image.id.as_deref())
+        .collect();
+
+    let cfs_configuration_name_set: HashSet<&str> = cfs_configuration_vec
+        .iter()
+        .map(|cfs_configuration| cfs_configuration.name.as_str())
+        .collect();
+
+    // BOS SESSIONTEMPLATES POINTING TO MISSING IMAGES OR CONFIGURATIONS
+    //
+    for (bos_sessiontemplate_name, cfs_configuration_name_opt, image_id_vec) in gc_references
+        .bos_sessiontemplate_vec
+        .iter()
+        .filter(|(bos_sessiontemplate_name, _, _)| {
+            hsm_bos_sessiontemplate_name_vec.contains(bos_sessiontemplate_name)
+        })
+    {
+        for image_id in image_id_vec
+            .iter()
+            .filter(|image_id| !image_id_set.contains(image_id.as_str()))
+        {
+            finding_vec.push(Finding {
+                kind: FindingKind::BosSessiontemplateMissingImage,
+                artefact: bos_sessiontemplate_name.clone(),
+                detail: format!("Boot image '{}' not found in IMS", image_id),
+                suggested_fix: "Update the BOS sessiontemplate boot sets with an existing image or delete the BOS sessiontemplate".to_string(),
+            });
+        }
+
+        if let Some(cfs_configuration_name) =
+            cfs_configuration_name_opt
+                .as_ref()
+                .filter(|cfs_configuration_name| {
+                    !cfs_configuration_name.is_empty()
+                        && !cfs_configuration_name_set.contains(cfs_configuration_name.as_str())
+                })
+        {
+            finding_vec.push(Finding {
+                kind: FindingKind::BosSessiontemplateMissingConfiguration,
+                artefact: bos_sessiontemplate_name.clone(),
+                detail: format!("CFS configuration '{}' not found", cfs_configuration_name),
+                suggested_fix: "Update the BOS sessiontemplate CFS configuration with an existing one or delete the BOS sessiontemplate".to_string(),
+            });
+        }
+    }
+
+    // BOOT PARAMETERS POINTING TO MISSING IMAGES
+    //
+    for (image_id, host_vec) in &gc_references.boot_image_map {
+        if image_id.is_empty() || image_id_set.contains(image_id.as_str()) {
+            continue;
+        }
+
+        let mut hsm_host_vec: Vec<&String> = host_vec
+            .iter()
+            .filter(|host| xname_vec.contains(host))
+            .collect();
+
+        if hsm_host_vec.is_empty() {
+            continue;
+        }
+
+        hsm_host_vec.sort();
+
+        let xnames = hsm_host_vec
+            .iter()
+            .map(|host| host.as_str())
+            .collect::<Vec<&str>>()
+            .join(",");
+
+        finding_vec.push(Finding {
+            kind: FindingKind::BootParametersMissingImage,
+            artefact: xnames.clone(),
+            detail: format!("Boot image '{}' not found in IMS", image_id),
+            suggested_fix: format!(
+                "manta set boot-image --image-id <IMAGE_ID> --xnames {}",
+                xnames
+            ),
+        });
+    }
+
+    // DESIRED CONFIGURATIONS MISSING
+    //
+    let mut missing_desired_configuration_map: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for cfs_component in cfs_component_vec {
+        let (Some(xname), Some(desired_config)) = (
+            cfs_component.id.as_deref(),
+            cfs_component.desired_config.as_deref(),
+        ) else {
+            continue;
+        };
+
+        if !desired_config.is_empty()
+            && xname_vec.iter().any(|elem| elem == xname)
+            && !cfs_configuration_name_set.contains(desired_config)
+        {
+            missing_desired_configuration_map
+                .entry(desired_config)
+                .or_default()
+                .push(xname);
+        }
+    }
+
+    for (desired_config, mut xname_missing_vec) in missing_desired_configuration_map {
+        xname_missing_vec.sort();
+
+        let xnames = xname_missing_vec.join(",");
+
+        finding_vec.push(Finding {
+            kind: FindingKind::DesiredConfigurationMissing,
+            artefact: desired_config.to_string(),
+            detail: format!("Desired configuration of nodes {} not found", xnames),
+            suggested_fix: format!(
+                "manta set runtime-configuration --configuration <CONFIGURATION> --xnames {}",
+                xnames
+            ),
+        });
+    }
+
+    // CFS CONFIGURATIONS NEVER USED
+    //
+    for cfs_configuration_name in hsm_cfs_configuration_name_vec {
+        let used_by_cfs_session = cfs_session_vec.iter().any(|cfs_session| {
+            cfs_session.get_configuration_name().as_ref() == Some(cfs_configuration_name)
+        });
+
+        if used_by_cfs_session
+            || gc_references
+                .get_bos_sessiontemplate_using_configuration(cfs_configuration_name)
+                .is_some()
+            || is_cfs_configuration_a_desired_configuration(
+                cfs_component_vec,
+                cfs_configuration_name,
+            )
+        {
+            continue;
+        }
+
+        finding_vec.push(Finding {
+            kind: FindingKind::ConfigurationNotUsed,
+            artefact: cfs_configuration_name.clone(),
+            detail: "Not used by any CFS session, BOS sessiontemplate or node".to_string(),
+            suggested_fix: format!(
+                "manta delete --hsm-group {} --configuration-name {}",
+                hsm_group_name_vec
+                    .iter()
+                    .find(|hsm_group_name| is_hsm_group_in_name(
+                        cfs_configuration_name,
+                        hsm_group_name
+                    ))
+                    .or(hsm_group_name_vec.first())
+                    .map(|hsm_group_name| hsm_group_name.as_str())
+                    .unwrap_or("<HSM_GROUP_NAME>"),
+                cfs_configuration_name
+            ),
+        });
+    }
+
+    finding_vec
+}
+
+/// Returns the ids of the images related to the HSM groups. These are the images booting nodes
+/// in the HSM groups, used by their BOS sessiontemplates or built by CFS sessions targeting them.
+/// Images may not exist in IMS
+pub fn get_image_id_in_scope_set(
+    hsm_group_name_vec: &[String],
+    xname_vec: &[String],
+    hsm_bos_sessiontemplate_name_vec: &[String],
+    cfs_session_vec: &[CfsSessionGetResponse],
+    gc_references: &GcReferences,
+) -> HashSet<String> {
+    let mut image_id_in_scope_set: HashSet<String> = HashSet::new();
+
+    for (bos_sessiontemplate_name, _, image_id_vec) in &gc_references.bos_sessiontemplate_vec {
+        if hsm_bos_sessiontemplate_name_vec.contains(bos_sessiontemplate_name) {
+            image_id_in_scope_set.extend(image_id_vec.iter().cloned());
+        }
+    }
+
+    for (image_id, host_vec) in &gc_references.boot_image_map {
+        if host_vec.iter().any(|host| xname_vec.contains(host)) {
+            image_id_in_scope_set.insert(image_id.clone());
+        }
+    }
+
+    for cfs_session in cfs_session_vec {
+        if cfs_session.get_target_hsm().is_some_and(|target_hsm_vec| {
+            target_hsm_vec
+                .iter()
+                .any(|target_hsm| hsm_group_name_vec.contains(target_hsm))
+        }) {
+            image_id_in_scope_set.extend(cfs_session.get_result_id_vec());
+        }
+    }
+
+    image_id_in_scope_set
+}
+
+/// Checks the IMS images have their manifest, rootfs, kernel and initrd in S3 and whether the
+/// images missing in IMS still have their manifest in S3. Fails on S3 errors other than objects
+/// not found
+async fn audit_s3(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    image_vec: &[&Image],
+    image_id_missing_vec: &[String],
+) -> Result<Vec<Finding>, String> {
+    let sts_value = mesa::ims::s3::s3_auth(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let client = s3_ops::get_client(&sts_value)?;
+
+    let mut finding_vec = Vec::new();
+
+    for image in image_vec {
+        let image_id = image.id.clone().unwrap_or_default();
+
+        let manifest_path = image
+            .link
+            .as_ref()
+            .map(|link| link.path.clone())
+            .unwrap_or(format!(
+                "s3://{}/{}/manifest.json",
+                BOOT_IMAGES_BUCKET, image_id
+            ));
+
+        let Some((bucket, object_path)) = split_s3_path(&manifest_path) else {
+            finding_vec.push(Finding {
+                kind: FindingKind::ImageWithoutS3Artefacts,
+                artefact: image_id,
+                detail: format!(
+                    "Image '{}' manifest path '{}' not valid",
+                    image.name, manifest_path
+                ),
+                suggested_fix: "Update the IMS image link or delete the image".to_string(),
+            });
+            continue;
+        };
+
+        log::info!(
+            "Checking S3 object '{}' in bucket '{}'",
+            object_path,
+            bucket
+        );
+
+        let Some(manifest_content) =
+            s3_ops::get_object_content(&client, bucket, object_path).await?
+        else {
+            finding_vec.push(Finding {
+                kind: FindingKind::ImageWithoutS3Artefacts,
+                artefact: image_id.clone(),
+                detail: format!(
+                    "Image '{}' manifest '{}' not found in S3",
+                    image.name, manifest_path
+                ),
+                suggested_fix: "Rebuild the image or delete the IMS image if not used".to_string(),
+            });
+            continue;
+        };
+
+        let manifest: Value = serde_json::from_slice(&manifest_content).unwrap_or_default();
+
+        let mut artefact_missing_vec = Vec::new();
+
+        for (artefact, artefact_path_opt) in get_boot_artefact_path_vec(&manifest) {
+            let exists = match artefact_path_opt.as_deref().and_then(split_s3_path) {
+                Some((bucket, object_path)) => {
+                    s3_ops::object_exists(&client, bucket, object_path).await?
+                }
+                None => false,
+            };
+
+            if !exists {
+                artefact_missing_vec.push(artefact);
+            }
+        }
+
+        if !artefact_missing_vec.is_empty() {
+            finding_vec.push(Finding {
+                kind: FindingKind::ImageWithoutS3Artefacts,
+                artefact: image_id.clone(),
+                detail: format!(
+                    "Image '{}' {} not found in S3",
+                    image.name,
+                    artefact_missing_vec.join(", ")
+                ),
+                suggested_fix: "Rebuild the image or delete the IMS image if not used".to_string(),
+            });
+        }
+    }
+
+    for image_id in image_id_missing_vec {
+        let object_path = format!("{}/manifest.json", image_id);
+
+        if s3_ops::object_exists(&client, BOOT_IMAGES_BUCKET, &object_path).await? {
+            finding_vec.push(Finding {
+                kind: FindingKind::S3ArtefactsWithoutImage,
+                artefact: image_id.clone(),
+                detail: format!(
+                    "Manifest 's3://{}/{}' exists but there is no IMS image",
+                    BOOT_IMAGES_BUCKET, object_path
+                ),
+                suggested_fix: "Register the image in IMS or delete the S3 objects".to_string(),
+            });
+        }
+    }
+
+    Ok(finding_vec)
+}
+
+/// Returns the S3 path of the rootfs, kernel and initrd listed in an IMS image manifest. Path is
+/// None if the artefact is not in the manifest
+pub fn get_boot_artefact_path_vec(manifest: &Value) -> Vec<(&'static str, Option<String>)> {
+    let artifact_vec = manifest["artifacts"]
+        .as_array()
+        .map(|artifact_vec| artifact_vec.as_slice())
+        .unwrap_or_default();
+
+    ["rootfs", "kernel", "initrd"]
+        .into_iter()
+        .map(|artefact| {
+            let artefact_type = format!("application/vnd.cray.image.{}", artefact);
+
+            let artefact_path_opt = artifact_vec
+                .iter()
+                .find(|artifact| {
+                    artifact["type"]
+                        .as_str()
+                        .is_some_and(|r#type| r#type.starts_with(&artefact_type))
+                })
+                .and_then(|artifact| artifact.pointer("/link/path"))
+                .and_then(|path| path.as_str())
+                .map(|path| path.to_string());
+
+            (artefact, artefact_path_opt)
+        })
+        .collect()
+}
+
+/// Splits a path like 's3://boot-images/<image id>/manifest.json' in bucket and object path
+pub fn split_s3_path(s3_path: &str) -> Option<(&str, &str)> {
+    let (bucket, object_path) = s3_path.strip_prefix("s3://")?.split_once('/')?;
+
+    if bucket.is_empty() || object_path.is_empty() {
+        None
+    } else {
+        Some((bucket, object_path))
+    }
+}
+
+pub fn print_table(finding_vec: &[Finding]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["Finding", "Artefact", "Detail", "Suggested fix"]);
+
+    for finding in finding_vec {
+        table.add_row(vec![
+            Cell::new(finding.kind).fg(match finding.kind {
+                FindingKind::ConfigurationNotUsed => Color::Yellow,
+                _ => Color::Red,
+            }),
+            Cell::new(&finding.artefact),
+            Cell::new(&finding.detail),
+            Cell::new(&finding.suggested_fix),
+        ]);
+    }
+
+    println!("{table}");
+}
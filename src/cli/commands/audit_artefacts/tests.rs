@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use crate::cli::commands::{
+    gc::command::GcReferences,
+    test_fixtures::{get_cfs_component, get_cfs_configuration, get_cfs_session, get_image},
+};
+
+use super::command::{
+    audit, get_boot_artefact_path_vec, get_image_id_in_scope_set, split_s3_path, FindingKind,
+};
+
+fn get_gc_references() -> GcReferences {
+    GcReferences {
+        bos_sessiontemplate_vec: vec![
+            (
+                "zinal-template".to_string(),
+                Some("zinal-v1".to_string()),
+                vec!["image-1".to_string(), "image-deleted".to_string()],
+            ),
+            (
+                "zinal-template-old".to_string(),
+                Some("zinal-v0".to_string()),
+                vec!["image-1".to_string()],
+            ),
+            // Not related to the HSM group
+            (
+                "psi-template".to_string(),
+                Some("psi-v0".to_string()),
+                vec!["image-psi-deleted".to_string()],
+            ),
+        ],
+        boot_image_map: HashMap::from([
+            ("image-1".to_string(), vec!["x1000c0s0b0n0".to_string()]),
+            (
+                "image-deleted".to_string(),
+                vec!["x1000c0s0b0n1".to_string(), "x1000c0s0b0n2".to_string()],
+            ),
+            (
+                "image-psi-deleted".to_string(),
+                vec!["x2000c0s0b0n0".to_string()],
+            ),
+        ]),
+    }
+}
+
+#[test]
+fn test_audit_artefacts() {
+    let hsm_group_name_vec = vec!["zinal".to_string()];
+    let xname_vec = vec![
+        "x1000c0s0b0n0".to_string(),
+        "x1000c0s0b0n1".to_string(),
+        "x1000c0s0b0n2".to_string(),
+    ];
+
+    let hsm_cfs_configuration_name_vec = vec![
+        "zinal-v1".to_string(),
+        "zinal-v2".to_string(),
+        "zinal-v3".to_string(),
+        "zinal-v4".to_string(),
+    ];
+
+    let hsm_bos_sessiontemplate_name_vec = vec![
+        "zinal-template".to_string(),
+        "zinal-template-old".to_string(),
+    ];
+
+    let cfs_configuration_vec = vec![
+        get_cfs_configuration("zinal-v1", "2024-01-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v2", "2024-01-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v3", "2024-01-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v4", "2024-01-01T00:00:00Z"),
+    ];
+
+    let cfs_session_vec = vec![get_cfs_session(
+        "session-1",
+        "zinal-v2",
+        "zinal",
+        "image",
+        "true",
+        "2024-01-01T00:00:00",
+        Some("image-1"),
+    )];
+
+    let cfs_component_vec = vec![
+        get_cfs_component("x1000c0s0b0n0", "zinal-v3"),
+        get_cfs_component("x1000c0s0b0n1", "zinal-deleted"),
+        get_cfs_component("x1000c0s0b0n2", ""),
+    ];
+
+    let image_vec = vec![get_image(
+        "image-1",
+        "zinal-image-1",
+        "2024-01-01T00:00:00Z",
+    )];
+
+    let finding_vec = audit(
+        &hsm_group_name_vec,
+        &xname_vec,
+        &hsm_cfs_configuration_name_vec,
+        &hsm_bos_sessiontemplate_name_vec,
+        &cfs_configuration_vec,
+        &cfs_session_vec,
+        &cfs_component_vec,
+        &image_vec,
+        &get_gc_references(),
+    );
+
+    let get_artefact_vec = |finding_kind: FindingKind| -> Vec<String> {
+        let mut artefact_vec: Vec<String> = finding_vec
+            .iter()
+            .filter(|finding| finding.kind == finding_kind)
+            .map(|finding| finding.artefact.clone())
+            .collect();
+        artefact_vec.sort();
+        artefact_vec
+    };
+
+    assert_eq!(
+        get_artefact_vec(FindingKind::BosSessiontemplateMissingImage),
+        vec!["zinal-template"]
+    );
+    assert_eq!(
+        get_artefact_vec(FindingKind::BosSessiontemplateMissingConfiguration),
+        vec!["zinal-template-old"]
+    );
+    assert_eq!(
+        get_artefact_vec(FindingKind::BootParametersMissingImage),
+        vec!["x1000c0s0b0n1,x1000c0s0b0n2"]
+    );
+    assert_eq!(
+        get_artefact_vec(FindingKind::DesiredConfigurationMissing),
+        vec!["zinal-deleted"]
+    );
+    // 'zinal-v1' used by BOS sessiontemplate, 'zinal-v2' by CFS session and 'zinal-v3' as
+    // desired configuration
+    assert_eq!(
+        get_artefact_vec(FindingKind::ConfigurationNotUsed),
+        vec!["zinal-v4"]
+    );
+}
+
+#[test]
+fn test_audit_get_image_id_in_scope_set() {
+    let hsm_group_name_vec = vec!["zinal".to_string()];
+    let xname_vec = vec!["x1000c0s0b0n1".to_string()];
+    let hsm_bos_sessiontemplate_name_vec = vec!["zinal-template-old".to_string()];
+
+    let cfs_session_vec = vec![
+        get_cfs_session(
+            "session-1",
+            "zinal-v2",
+            "zinal",
+            "image",
+            "true",
+            "2024-01-01T00:00:00",
+            Some("image-built"),
+        ),
+        get_cfs_session(
+            "session-2",
+            "psi-v2",
+            "psi",
+            "image",
+            "true",
+            "2024-01-01T00:00:00",
+            Some("image-psi"),
+        ),
+    ];
+
+    let mut image_id_vec: Vec<String> = get_image_id_in_scope_set(
+        &hsm_group_name_vec,
+        &xname_vec,
+        &hsm_bos_sessiontemplate_name_vec,
+        &cfs_session_vec,
+        &get_gc_references(),
+    )
+    .into_iter()
+    .collect();
+
+    image_id_vec.sort();
+
+    assert_eq!(
+        image_id_vec,
+        vec!["image-1", "image-built", "image-deleted"]
+    );
+}
+
+#[test]
+fn test_split_s3_path() {
+    assert_eq!(
+        split_s3_path("s3://boot-images/1234-abcd/manifest.json"),
+        Some(("boot-images", "1234-abcd/manifest.json"))
+    );
+    assert_eq!(split_s3_path("boot-images/1234-abcd/manifest.json"), None);
+    assert_eq!(split_s3_path("s3://boot-images/"), None);
+}
+
+/// Test rootfs, kernel and initrd paths are taken from the IMS image manifest
+#[test]
+fn test_get_boot_artefact_path_vec() {
+    let manifest = json!({
+        "version": "1.0",
+        "artifacts": [
+            {
+                "link": { "path": "s3://boot-images/1234-abcd/rootfs", "type": "s3" },
+                "type": "application/vnd.cray.image.rootfs.squashfs"
+            },
+            {
+                "link": { "path": "s3://boot-images/1234-abcd/kernel", "type": "s3" },
+                "type": "application/vnd.cray.image.kernel"
+            }
+        ]
+    });
+
+    assert_eq!(
+        get_boot_artefact_path_vec(&manifest),
+        vec![
+            (
+                "rootfs",
+                Some("s3://boot-images/1234-abcd/rootfs".to_string())
+            ),
+            (
+                "kernel",
+                Some("s3://boot-images/1234-abcd/kernel".to_string())
+            ),
+            ("initrd", None),
+        ]
+    );
+
+    assert_eq!(
+        get_boot_artefact_path_vec(&json!({})),
+        vec![("rootfs", None), ("kernel", None), ("initrd", None)]
+    );
+}
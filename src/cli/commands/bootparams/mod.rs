@@ -0,0 +1,4 @@
+pub mod restore;
+pub mod snapshot;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,265 @@
+use comfy_table::Table;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use mesa::bss::{self, bootparameters::BootParameters};
+use serde::Serialize;
+
+use crate::{
+    cli::commands::set_kernel_parameters::command::parse_kernel_params,
+    common::pcs_utils::PowerTransitionOpts,
+};
+
+use super::snapshot::{BootParamsSnapshot, NodeBootParams};
+
+/// Changes needed to bring the boot parameters of a node back to the snapshot
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BootParamsDiff {
+    pub xname: String,
+    /// Current and snapshot boot image id
+    pub image_opt: Option<(String, String)>,
+    /// Current and snapshot kernel path
+    pub kernel_opt: Option<(String, String)>,
+    /// Current and snapshot initrd path
+    pub initrd_opt: Option<(String, String)>,
+    pub params_removed: Vec<String>,
+    pub params_added: Vec<String>,
+}
+
+/// Returns the changes needed to restore the snapshot, None if the node already matches it
+pub fn diff(
+    current_boot_parameters: &BootParameters,
+    node_boot_params: &NodeBootParams,
+) -> Option<BootParamsDiff> {
+    let changed = |current: &str, snapshot: &str| -> Option<(String, String)> {
+        (current != snapshot).then(|| (current.to_string(), snapshot.to_string()))
+    };
+
+    let current_kernel_param_vec = parse_kernel_params(&current_boot_parameters.params);
+    let snapshot_kernel_param_vec = parse_kernel_params(&node_boot_params.params);
+
+    let boot_params_diff = BootParamsDiff {
+        xname: node_boot_params.xname.clone(),
+        image_opt: changed(
+            &current_boot_parameters.get_boot_image(),
+            &node_boot_params.image_id,
+        ),
+        kernel_opt: changed(&current_boot_parameters.kernel, &node_boot_params.kernel),
+        initrd_opt: changed(&current_boot_parameters.initrd, &node_boot_params.initrd),
+        params_removed: current_kernel_param_vec
+            .iter()
+            .filter(|kernel_param| !snapshot_kernel_param_vec.contains(kernel_param))
+            .map(|kernel_param| kernel_param.to_string())
+            .collect(),
+        params_added: snapshot_kernel_param_vec
+            .iter()
+            .filter(|kernel_param| !current_kernel_param_vec.contains(kernel_param))
+            .map(|kernel_param| kernel_param.to_string())
+            .collect(),
+    };
+
+    // Kernel parameters reordered are also restored
+    if boot_params_diff.kernel_opt.is_none()
+        && boot_params_diff.initrd_opt.is_none()
+        && current_kernel_param_vec == snapshot_kernel_param_vec
+    {
+        None
+    } else {
+        Some(boot_params_diff)
+    }
+}
+
+/// Reapplies the boot parameters stored in a snapshot. Shows the changes and asks for
+/// confirmation before updating BSS. Nodes are only rebooted if requested
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    boot_params_snapshot: &BootParamsSnapshot,
+    xname_vec_opt: Option<&Vec<String>>,
+    yes: bool,
+    reboot: bool,
+) {
+    let node_boot_params_vec: Vec<&NodeBootParams> = boot_params_snapshot
+        .nodes
+        .iter()
+        .filter(|node_boot_params| {
+            xname_vec_opt.is_none_or(|xname_vec| xname_vec.contains(&node_boot_params.xname))
+        })
+        .collect();
+
+    if let Some(xname_vec) = xname_vec_opt {
+        for xname in xname_vec {
+            if !node_boot_params_vec
+                .iter()
+                .any(|node_boot_params| &node_boot_params.xname == xname)
+            {
+                eprintln!(
+                    "WARNING - Node '{}' not found in boot parameters snapshot '{}'. Skipping",
+                    xname, boot_params_snapshot.name
+                );
+            }
+        }
+    }
+
+    let xname_vec: Vec<String> = node_boot_params_vec
+        .iter()
+        .map(|node_boot_params| node_boot_params.xname.clone())
+        .collect();
+
+    if xname_vec.is_empty() {
+        eprintln!("ERROR - No nodes to restore. Exit");
+        std::process::exit(1);
+    }
+
+    let current_boot_parameter_vec: Vec<BootParameters> =
+        match bss::bootparameters::http_client::get(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_vec,
+        )
+        .await
+        {
+            Ok(boot_parameter_vec) => boot_parameter_vec,
+            Err(error) => {
+                eprintln!(
+                    "ERROR - Could not fetch boot parameters. Reason:\n{}\nExit",
+                    error
+                );
+                std::process::exit(1);
+            }
+        };
+
+    // Calculate changes
+    let mut boot_parameter_to_restore_vec: Vec<(BootParameters, BootParamsDiff)> = Vec::new();
+
+    for node_boot_params in node_boot_params_vec {
+        let Some(current_boot_parameters) = current_boot_parameter_vec
+            .iter()
+            .find(|boot_parameters| boot_parameters.hosts.contains(&node_boot_params.xname))
+        else {
+            eprintln!(
+                "WARNING - Boot parameters for node '{}' not found in BSS. Skipping",
+                node_boot_params.xname
+            );
+            continue;
+        };
+
+        if let Some(boot_params_diff) = diff(current_boot_parameters, node_boot_params) {
+            let mut boot_parameters = current_boot_parameters.clone();
+            boot_parameters.hosts = vec![node_boot_params.xname.clone()];
+            boot_parameters.kernel = node_boot_params.kernel.clone();
+            boot_parameters.initrd = node_boot_params.initrd.clone();
+            boot_parameters.params = node_boot_params.params.clone();
+
+            boot_parameter_to_restore_vec.push((boot_parameters, boot_params_diff));
+        }
+    }
+
+    if boot_parameter_to_restore_vec.is_empty() {
+        println!(
+            "Boot parameters already match snapshot '{}'. Nothing to change",
+            boot_params_snapshot.name
+        );
+        return;
+    }
+
+    println!(
+        "Restore boot parameters from snapshot '{}' created on {}",
+        boot_params_snapshot.name, boot_params_snapshot.created
+    );
+
+    print_table(
+        &boot_parameter_to_restore_vec
+            .iter()
+            .map(|(_, boot_params_diff)| boot_params_diff)
+            .collect::<Vec<&BootParamsDiff>>(),
+    );
+
+    if !yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Please review the changes above and confirm to restore boot parameters")
+            .interact()
+            .unwrap()
+    {
+        println!("Cancelled by user. Aborting.");
+        std::process::exit(0);
+    }
+
+    // Restore boot parameters
+    let mut xname_restored_vec: Vec<String> = Vec::new();
+
+    for (boot_parameters, boot_params_diff) in boot_parameter_to_restore_vec {
+        match bss::bootparameters::http_client::patch(
+            shasta_base_url,
+            shasta_token,
+            shasta_root_cert,
+            &boot_parameters,
+        )
+        .await
+        {
+            Ok(_) => xname_restored_vec.push(boot_params_diff.xname),
+            Err(error) => eprintln!(
+                "ERROR - Could not restore boot parameters for node '{}'. Reason:\n{}",
+                boot_params_diff.xname, error
+            ),
+        }
+    }
+
+    println!(
+        "Boot parameters restored for {} nodes",
+        xname_restored_vec.len()
+    );
+
+    if reboot && !xname_restored_vec.is_empty() {
+        crate::cli::commands::power_reset_nodes::exec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_restored_vec,
+            None,
+            true,
+            "table",
+            &PowerTransitionOpts::default(),
+        )
+        .await;
+    } else if !xname_restored_vec.is_empty() {
+        println!("Reboot the nodes to boot with the restored boot parameters");
+    }
+}
+
+pub fn print_table(boot_params_diff_vec: &[&BootParamsDiff]) {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "XNAME",
+        "Boot image",
+        "Kernel/initrd",
+        "Kernel params removed",
+        "Kernel params added",
+    ]);
+
+    for boot_params_diff in boot_params_diff_vec {
+        let image = boot_params_diff
+            .image_opt
+            .as_ref()
+            .map(|(current, snapshot)| format!("{} -> {}", current, snapshot))
+            .unwrap_or_default();
+
+        let kernel_initrd = [&boot_params_diff.kernel_opt, &boot_params_diff.initrd_opt]
+            .into_iter()
+            .flatten()
+            .map(|(current, snapshot)| format!("{} -> {}", current, snapshot))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        table.add_row(vec![
+            boot_params_diff.xname.clone(),
+            image,
+            kernel_initrd,
+            boot_params_diff.params_removed.join("\n"),
+            boot_params_diff.params_added.join("\n"),
+        ]);
+    }
+
+    println!("{table}");
+}
@@ -0,0 +1,142 @@
+use std::{fs::File, path::PathBuf};
+
+use mesa::bss::{self, bootparameters::BootParameters};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{config_ops, snapshot_ops};
+
+/// BSS boot parameters of a list of nodes at a point in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BootParamsSnapshot {
+    pub name: String,
+    pub created: String,
+    pub nodes: Vec<NodeBootParams>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NodeBootParams {
+    pub xname: String,
+    pub image_id: String,
+    pub kernel: String,
+    pub initrd: String,
+    pub params: String,
+}
+
+impl NodeBootParams {
+    /// Returns a snapshot entry per host in the boot parameters
+    pub fn from_boot_parameters(boot_parameters: &BootParameters) -> Vec<Self> {
+        boot_parameters
+            .hosts
+            .iter()
+            .map(|xname| NodeBootParams {
+                xname: xname.clone(),
+                image_id: boot_parameters.get_boot_image(),
+                kernel: boot_parameters.kernel.clone(),
+                initrd: boot_parameters.initrd.clone(),
+                params: boot_parameters.params.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Fetches the BSS boot parameters of a list of nodes and stores them in a local file. If the
+/// user does not provide a file path, the snapshot is stored in manta's data folder
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    snapshot_name: &str,
+    xname_vec: &[String],
+    file_path_opt: Option<&PathBuf>,
+) {
+    let boot_parameter_vec: Vec<BootParameters> = match bss::bootparameters::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        xname_vec,
+    )
+    .await
+    {
+        Ok(boot_parameter_vec) => boot_parameter_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not fetch boot parameters. Reason:\n{}\nExit",
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let mut node_boot_params_vec: Vec<NodeBootParams> = boot_parameter_vec
+        .iter()
+        .flat_map(NodeBootParams::from_boot_parameters)
+        .filter(|node_boot_params| xname_vec.contains(&node_boot_params.xname))
+        .collect();
+
+    node_boot_params_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    for xname in xname_vec {
+        if !node_boot_params_vec
+            .iter()
+            .any(|node_boot_params| &node_boot_params.xname == xname)
+        {
+            eprintln!(
+                "WARNING - Boot parameters for node '{}' not found. Skipping",
+                xname
+            );
+        }
+    }
+
+    let timestamp = chrono::Utc::now();
+
+    let boot_params_snapshot = BootParamsSnapshot {
+        name: snapshot_name.to_string(),
+        created: timestamp.to_rfc3339(),
+        nodes: node_boot_params_vec,
+    };
+
+    let file_path = if let Some(file_path) = file_path_opt {
+        file_path.clone()
+    } else {
+        snapshot_ops::get_new_snapshot_file_path(
+            &config_ops::get_default_boot_parameters_snapshot_dir_path(),
+            snapshot_name,
+            &timestamp,
+        )
+    };
+
+    if let Some(parent_dir) = file_path.parent() {
+        let _ = std::fs::create_dir_all(parent_dir);
+    }
+
+    let file = match File::create(&file_path) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not create boot parameters snapshot file '{}'. Reason:\n{}\nExit",
+                file_path.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    serde_json::to_writer_pretty(&file, &boot_params_snapshot).unwrap();
+
+    println!(
+        "Boot parameters snapshot for {} nodes stored in '{}'",
+        boot_params_snapshot.nodes.len(),
+        file_path.to_string_lossy()
+    );
+}
+
+/// Reads a boot parameters snapshot. The snapshot can be either a path to a file or the name of
+/// a snapshot stored in manta's boot parameters snapshot folder, in which case the latest one is
+/// read
+pub fn read_snapshot(snapshot: &str) -> Result<BootParamsSnapshot, String> {
+    snapshot_ops::read_snapshot(
+        snapshot,
+        &config_ops::get_default_boot_parameters_snapshot_dir_path(),
+        "boot parameters",
+    )
+}
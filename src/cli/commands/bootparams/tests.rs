@@ -0,0 +1,118 @@
+use mesa::bss::bootparameters::BootParameters;
+
+use super::{
+    restore::diff,
+    snapshot::{read_snapshot, BootParamsSnapshot, NodeBootParams},
+};
+
+fn get_boot_parameters(hosts: Vec<&str>, image_id: &str, params: &str) -> BootParameters {
+    BootParameters {
+        hosts: hosts.into_iter().map(|host| host.to_string()).collect(),
+        params: params.to_string(),
+        kernel: format!("s3://boot-images/{}/kernel", image_id),
+        initrd: format!("s3://boot-images/{}/initrd", image_id),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_node_boot_params_from_boot_parameters() {
+    let node_boot_params_vec = NodeBootParams::from_boot_parameters(&get_boot_parameters(
+        vec!["x1000c0s0b0n0", "x1000c0s0b0n1"],
+        "image-1",
+        "quiet",
+    ));
+
+    assert_eq!(node_boot_params_vec.len(), 2);
+    assert_eq!(node_boot_params_vec[1].xname, "x1000c0s0b0n1");
+    assert_eq!(node_boot_params_vec[1].image_id, "image-1");
+    assert_eq!(
+        node_boot_params_vec[1].kernel,
+        "s3://boot-images/image-1/kernel"
+    );
+}
+
+#[test]
+fn test_boot_params_diff() {
+    let node_boot_params = NodeBootParams::from_boot_parameters(&get_boot_parameters(
+        vec!["x1000c0s0b0n0"],
+        "image-1",
+        "quiet crashkernel=360M",
+    ))
+    .remove(0);
+
+    // Same boot parameters
+    assert_eq!(
+        diff(
+            &get_boot_parameters(vec!["x1000c0s0b0n0"], "image-1", "quiet  crashkernel=360M"),
+            &node_boot_params
+        ),
+        None
+    );
+
+    let boot_params_diff = diff(
+        &get_boot_parameters(
+            vec!["x1000c0s0b0n0"],
+            "image-2",
+            "quiet crashkernel=512M bad_page=panic",
+        ),
+        &node_boot_params,
+    )
+    .unwrap();
+
+    assert_eq!(
+        boot_params_diff.image_opt,
+        Some(("image-2".to_string(), "image-1".to_string()))
+    );
+    assert!(boot_params_diff.kernel_opt.is_some());
+    assert!(boot_params_diff.initrd_opt.is_some());
+    assert_eq!(
+        boot_params_diff.params_removed,
+        vec!["crashkernel=512M", "bad_page=panic"]
+    );
+    assert_eq!(boot_params_diff.params_added, vec!["crashkernel=360M"]);
+
+    // Kernel parameters reordered
+    let boot_params_diff = diff(
+        &get_boot_parameters(vec!["x1000c0s0b0n0"], "image-1", "crashkernel=360M quiet"),
+        &node_boot_params,
+    )
+    .unwrap();
+
+    assert!(boot_params_diff.image_opt.is_none());
+    assert!(boot_params_diff.params_removed.is_empty());
+    assert!(boot_params_diff.params_added.is_empty());
+}
+
+#[test]
+fn test_read_boot_params_snapshot() {
+    let boot_params_snapshot = BootParamsSnapshot {
+        name: "zinal".to_string(),
+        created: "2024-06-01T00:00:00+00:00".to_string(),
+        nodes: NodeBootParams::from_boot_parameters(&get_boot_parameters(
+            vec!["x1000c0s0b0n0"],
+            "image-1",
+            "quiet",
+        )),
+    };
+
+    let file_path = std::env::temp_dir().join(format!(
+        "manta-boot-parameters-snapshot-{}.json",
+        uuid::Uuid::new_v4()
+    ));
+
+    std::fs::write(
+        &file_path,
+        serde_json::to_string_pretty(&boot_params_snapshot).unwrap(),
+    )
+    .unwrap();
+
+    let boot_params_snapshot_read = read_snapshot(&file_path.to_string_lossy()).unwrap();
+
+    assert_eq!(boot_params_snapshot_read.name, "zinal");
+    assert_eq!(boot_params_snapshot_read.nodes, boot_params_snapshot.nodes);
+
+    std::fs::remove_file(&file_path).unwrap();
+
+    assert!(read_snapshot("manta-boot-parameters-snapshot-not-found").is_err());
+}
@@ -0,0 +1,595 @@
+use futures::StreamExt;
+use kube::api::AttachedProcess;
+
+use mesa::node::{self, console};
+use termion::color;
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    select,
+};
+
+use std::path::PathBuf;
+
+use crate::{
+    cli::commands::console_recording::recorder::{
+        get_default_recording_file_path, ConsoleRecorder,
+    },
+    common::terminal_ops,
+};
+
+pub async fn exec(
+    hsm_group: Option<&String>,
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    xname: &str,
+    record_file_opt: Option<PathBuf>,
+) {
+    if hsm_group.is_some() {
+        // Check user has provided valid XNAMES
+        if !node::utils::validate_xnames(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &[xname],
+            hsm_group,
+        )
+        .await
+        {
+            eprintln!("xname/s invalid. Exit");
+            std::process::exit(1);
+        }
+    } else {
+        // no hsm_group value provided
+        // included = xnames.clone();
+        node::utils::validate_xname_format(xname);
+    }
+
+    // Record console session
+    let mut recorder_opt = record_file_opt.map(|record_file| {
+        let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+
+        ConsoleRecorder::new(&record_file, width, height, xname).unwrap_or_else(|error| {
+            eprintln!(
+                "ERROR - Could not create console recording file '{}'. Reason:\n{}\nExit",
+                record_file.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        })
+    });
+
+    let console_rslt = connect_to_console(
+        // included.iter().next().unwrap(),
+        &xname.to_string(),
+        vault_base_url,
+        vault_secret_path,
+        vault_role_id,
+        k8s_api_url,
+        recorder_opt.as_mut(),
+    )
+    .await;
+
+    match console_rslt {
+        Ok(_) => {
+            crossterm::terminal::disable_raw_mode().unwrap();
+            log::info!("Console closed");
+        }
+        Err(error) => {
+            crossterm::terminal::disable_raw_mode().unwrap();
+            log::error!("{:?}", error);
+        }
+    }
+
+    if let Some(recorder) = recorder_opt {
+        println!(
+            "Console session recorded in '{}'",
+            recorder.file_path.to_string_lossy()
+        );
+    }
+}
+
+/// Key to open the console menu (Ctrl-])
+pub const ESCAPE_KEY: u8 = 0x1d;
+
+/// conman escape sequence to send a serial break to the node
+const CONMAN_BREAK: &[u8] = b"&B";
+
+/// Number of times we try to reconnect to the console before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+const CONSOLE_MENU: &str = "\r\n--- Console menu ---\r\n\
+    \x20 d  detach from the console\r\n\
+    \x20 b  send break\r\n\
+    \x20 s  send sysrq (followed by the sysrq key, eg 's' then 't')\r\n\
+    \x20 l  toggle console recording\r\n\
+    \x20 r  reconnect to the console\r\n\
+    \x20 ^] send Ctrl-] to the console\r\n\
+    Any other key goes back to the console\r\n";
+
+/// Actions resulting from the user input while attached to a console
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleAction {
+    /// Bytes to forward to the console
+    Send(Vec<u8>),
+    ShowMenu,
+    Resume,
+    Detach,
+    SendBreak,
+    /// Waiting for the sysrq key
+    ShowSysrqPrompt,
+    SendSysrq(u8),
+    ToggleRecording,
+    Reconnect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum EscapeState {
+    #[default]
+    Normal,
+    Menu,
+    Sysrq,
+}
+
+/// Intercepts the console menu key in the user input. Everything else is forwarded to the
+/// console
+#[derive(Debug, Default)]
+pub struct ConsoleInputHandler {
+    state: EscapeState,
+    last_byte_opt: Option<u8>,
+    /// User typed conman's exit sequence '&.'
+    pub exit_requested: bool,
+}
+
+impl ConsoleInputHandler {
+    pub fn process(&mut self, data: &[u8]) -> Vec<ConsoleAction> {
+        let mut action_vec = Vec::new();
+        let mut send_buffer = Vec::new();
+
+        for &byte in data {
+            match self.state {
+                EscapeState::Normal if byte == ESCAPE_KEY => {
+                    if !send_buffer.is_empty() {
+                        action_vec.push(ConsoleAction::Send(std::mem::take(&mut send_buffer)));
+                    }
+
+                    self.state = EscapeState::Menu;
+                    action_vec.push(ConsoleAction::ShowMenu);
+                }
+                EscapeState::Normal => {
+                    if self.last_byte_opt == Some(b'&') && byte == b'.' {
+                        self.exit_requested = true;
+                    }
+
+                    self.last_byte_opt = Some(byte);
+                    send_buffer.push(byte);
+                }
+                EscapeState::Menu => {
+                    self.state = EscapeState::Normal;
+
+                    let action = match byte {
+                        b'd' | b'.' => ConsoleAction::Detach,
+                        b'b' => ConsoleAction::SendBreak,
+                        b's' => {
+                            self.state = EscapeState::Sysrq;
+                            ConsoleAction::ShowSysrqPrompt
+                        }
+                        b'l' => ConsoleAction::ToggleRecording,
+                        b'r' => ConsoleAction::Reconnect,
+                        ESCAPE_KEY => ConsoleAction::Send(vec![ESCAPE_KEY]),
+                        _ => ConsoleAction::Resume,
+                    };
+
+                    action_vec.push(action);
+                }
+                EscapeState::Sysrq => {
+                    self.state = EscapeState::Normal;
+
+                    // Escape cancels the sysrq
+                    if byte == 0x1b {
+                        action_vec.push(ConsoleAction::Resume);
+                    } else {
+                        action_vec.push(ConsoleAction::SendSysrq(byte));
+                    }
+                }
+            }
+        }
+
+        if !send_buffer.is_empty() {
+            action_vec.push(ConsoleAction::Send(send_buffer));
+        }
+
+        action_vec
+    }
+}
+
+/// Reason a connection to the console ended
+enum SessionEnd {
+    Detach,
+    Reconnect,
+    ConnectionLost,
+}
+
+/// Attaches the terminal to the console of a node. Ctrl-] opens a menu to detach, send a break or
+/// a sysrq, toggle the console recording or reconnect. If the connection to the console drops,
+/// it reconnects automatically
+pub async fn connect_to_console(
+    xname: &String,
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    mut recorder_opt: Option<&mut ConsoleRecorder>,
+) -> Result<(), anyhow::Error> {
+    log::info!("xname: {}", xname);
+
+    let mut stdin = tokio_util::io::ReaderStream::new(tokio::io::stdin());
+    let mut stdout = tokio::io::stdout();
+
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    // Recording started from the console menu
+    let mut menu_recorder_opt: Option<ConsoleRecorder> = None;
+    let mut recording = recorder_opt.is_some();
+
+    let mut attempt = 0;
+    let mut first_connection = true;
+    let mut reconnecting = false;
+
+    loop {
+        if reconnecting {
+            attempt += 1;
+
+            if attempt > MAX_RECONNECT_ATTEMPTS {
+                crossterm::terminal::disable_raw_mode()?;
+                return Err(anyhow::anyhow!(
+                    "Could not reconnect to console of node '{}' after {} attempts",
+                    xname,
+                    MAX_RECONNECT_ATTEMPTS
+                ));
+            }
+
+            print_status(
+                &mut stdout,
+                &format!(
+                    "Reconnecting to {} ({}/{})",
+                    xname, attempt, MAX_RECONNECT_ATTEMPTS
+                ),
+            )
+            .await?;
+
+            tokio::time::sleep(RECONNECT_DELAY * attempt).await;
+        }
+
+        let mut attached = match attach_to_console(
+            xname,
+            vault_base_url,
+            vault_secret_path,
+            vault_role_id,
+            k8s_api_url,
+        )
+        .await
+        {
+            Ok(attached) => attached,
+            Err(error) if first_connection => return Err(error),
+            Err(error) => {
+                log::warn!("Could not reconnect to console: {:?}", error);
+                reconnecting = true;
+                continue;
+            }
+        };
+
+        if first_connection {
+            println!(
+                "Connected to {}{}{}!",
+                color::Fg(color::Blue),
+                xname,
+                color::Fg(color::Reset)
+            );
+            println!(
+                "Use {}&.{} key combination to exit the console or {}Ctrl-]{} to open the console menu.",
+                color::Fg(color::Green),
+                color::Fg(color::Reset),
+                color::Fg(color::Green),
+                color::Fg(color::Reset)
+            );
+
+            crossterm::terminal::enable_raw_mode()?;
+
+            first_connection = false;
+        } else {
+            print_status(&mut stdout, &format!("Reconnected to {}", xname)).await?;
+        }
+
+        reconnecting = false;
+
+        let mut output = tokio_util::io::ReaderStream::new(
+            attached
+                .stdout()
+                .ok_or_else(|| anyhow::anyhow!("Console stdout not available"))?,
+        );
+        let mut input = attached
+            .stdin()
+            .ok_or_else(|| anyhow::anyhow!("Console stdin not available"))?;
+
+        let term_tx = attached
+            .terminal_size()
+            .ok_or_else(|| anyhow::anyhow!("Console terminal size not available"))?;
+
+        let handle_terminal_size_handle = tokio::spawn(terminal_ops::handle_terminal_size(term_tx));
+
+        let session_end = 'session: loop {
+            select! {
+                message = stdin.next() => {
+                    match message {
+                        Some(Ok(message)) => {
+                            for action in console_input_handler.process(&message) {
+                                match action {
+                                    ConsoleAction::Send(data) => {
+                                        if let Err(error) = send_to_console(&mut input, &[&data]).await {
+                                            log::warn!("Could not write to console: {}", error);
+                                            break 'session SessionEnd::ConnectionLost;
+                                        }
+                                    }
+                                    ConsoleAction::ShowMenu => {
+                                        stdout.write_all(CONSOLE_MENU.as_bytes()).await?;
+                                        stdout.flush().await?;
+                                    }
+                                    ConsoleAction::Resume => {
+                                        print_status(&mut stdout, "Back to console").await?
+                                    }
+                                    ConsoleAction::Detach => break 'session SessionEnd::Detach,
+                                    ConsoleAction::SendBreak => {
+                                        if let Err(error) = send_to_console(&mut input, &[CONMAN_BREAK]).await {
+                                            log::warn!("Could not write to console: {}", error);
+                                            break 'session SessionEnd::ConnectionLost;
+                                        }
+
+                                        print_status(&mut stdout, "Break sent").await?;
+                                    }
+                                    ConsoleAction::ShowSysrqPrompt => {
+                                        print_status(&mut stdout, "SysRq key (Esc to cancel):").await?
+                                    }
+                                    ConsoleAction::SendSysrq(key) => {
+                                        // A break followed by a key is a sysrq in a serial console
+                                        if let Err(error) = send_to_console(&mut input, &[CONMAN_BREAK, &[key]]).await {
+                                            log::warn!("Could not write to console: {}", error);
+                                            break 'session SessionEnd::ConnectionLost;
+                                        }
+
+                                        print_status(
+                                            &mut stdout,
+                                            &format!("SysRq '{}' sent", char::from(key)),
+                                        )
+                                        .await?;
+                                    }
+                                    ConsoleAction::ToggleRecording => {
+                                        recording = !recording;
+
+                                        if recording
+                                            && recorder_opt.is_none()
+                                            && menu_recorder_opt.is_none()
+                                        {
+                                            menu_recorder_opt = start_recording(xname);
+                                            recording = menu_recorder_opt.is_some();
+                                        }
+
+                                        let file_path_opt = recorder_opt
+                                            .as_deref()
+                                            .or(menu_recorder_opt.as_ref())
+                                            .map(|recorder| recorder.file_path.to_string_lossy().to_string());
+
+                                        let message = match (recording, file_path_opt) {
+                                            (true, Some(file_path)) => format!("Recording console in '{}'", file_path),
+                                            (false, Some(file_path)) => format!("Recording paused. Console recorded in '{}'", file_path),
+                                            _ => "Could not start console recording".to_string(),
+                                        };
+
+                                        print_status(&mut stdout, &message).await?;
+                                    }
+                                    ConsoleAction::Reconnect => break 'session SessionEnd::Reconnect,
+                                }
+                            }
+                        },
+                        Some(Err(message)) => {
+                           crossterm::terminal::disable_raw_mode()?;
+                           log::error!("ERROR: Console stdin {:?}", &message);
+                           break 'session SessionEnd::Detach
+                        },
+                        None => {
+                            crossterm::terminal::disable_raw_mode()?;
+                            log::info!("NONE (No input): Console stdin");
+                            break 'session SessionEnd::Detach
+                        },
+                    }
+                },
+                message = output.next() => {
+                    match message {
+                        Some(Ok(message)) => {
+                            attempt = 0;
+
+                            stdout.write_all(&message).await?;
+                            stdout.flush().await?;
+
+                            if recording {
+                                if let Some(recorder) =
+                                    recorder_opt.as_deref_mut().or(menu_recorder_opt.as_mut())
+                                {
+                                    if let Err(error) = recorder.record_output(&message) {
+                                        log::warn!("Could not record console output: {}", error);
+                                    }
+                                }
+                            }
+                        },
+                        Some(Err(message)) => {
+                           log::error!("ERROR: Console stdout: {:?}", &message);
+                           break 'session SessionEnd::ConnectionLost
+                        },
+                        None => {
+                            // conman exits when the user types '&.'
+                            if console_input_handler.exit_requested {
+                                log::info!("Exit console");
+                                break 'session SessionEnd::Detach
+                            }
+
+                            log::warn!("Console stream closed");
+                            break 'session SessionEnd::ConnectionLost
+                        },
+                    }
+                },
+            };
+        };
+
+        handle_terminal_size_handle.abort();
+
+        match session_end {
+            SessionEnd::Detach => break,
+            SessionEnd::Reconnect => {
+                attempt = 0;
+                print_status(&mut stdout, &format!("Reconnecting to {}", xname)).await?;
+            }
+            SessionEnd::ConnectionLost => {
+                print_status(&mut stdout, &format!("Connection to {} lost", xname)).await?;
+
+                reconnecting = true;
+            }
+        }
+    }
+
+    crossterm::terminal::disable_raw_mode()?;
+
+    if let Some(recorder) = menu_recorder_opt {
+        println!(
+            "Console session recorded in '{}'",
+            recorder.file_path.to_string_lossy()
+        );
+    }
+
+    Ok(())
+
+    /* let mut stdin_writer = attached.stdin().unwrap();
+    let mut stdout_stream = ReaderStream::new(attached.stdout().unwrap());
+
+    let mut stdin = std::io::stdin();
+    let mut stdout = stdout().into_raw_mode().unwrap();
+
+    let rt = Runtime::new().unwrap();
+    rt.spawn(async move {
+        let mut next_stdout;
+
+        loop {
+            next_stdout = stdout_stream.next().await;
+            match next_stdout {
+                Some(next_from_remote_stdout) => {
+                    // Print stream to stdout while steam lives
+                    match next_from_remote_stdout {
+                        Ok(remote_stdout) => {
+                            print!("{}", String::from_utf8_lossy(&remote_stdout));
+                            stdout.flush().unwrap();
+                        }
+                        Err(e) => {
+                            log::warn!("There was an error reading stream input");
+                            eprintln!("{:?}", e);
+                            stdout.suspend_raw_mode().unwrap();
+                            // std::process::exit(1);
+                        }
+                    }
+                }
+                None => {
+                    // Stream has finished. Reseting terminal and Exiting application.
+                    stdout.suspend_raw_mode().unwrap();
+                    std::process::exit(0);
+                }
+            }
+        }
+    });
+
+    loop {
+        let mut buffer = [0; 1];
+
+        let n = stdin.read(&mut buffer[..])?;
+
+        stdin_writer
+            .write_all(
+                String::from_utf8(buffer[..n].to_vec())
+                    .unwrap()
+                    .to_string()
+                    .as_bytes(),
+            )
+            .await?;
+    } */
+}
+
+/// Attaches to the conman container of the node console. Attaching panics if the console pod
+/// can't be reached, the attachment runs in a separate task to return it as an error instead
+async fn attach_to_console(
+    xname: &str,
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+) -> Result<AttachedProcess, anyhow::Error> {
+    let xname = xname.to_string();
+    let vault_base_url = vault_base_url.to_string();
+    let vault_secret_path = vault_secret_path.to_string();
+    let vault_role_id = vault_role_id.to_string();
+    let k8s_api_url = k8s_api_url.to_string();
+
+    tokio::spawn(async move {
+        console::get_container_attachment_to_conman(
+            &xname,
+            &vault_base_url,
+            &vault_secret_path,
+            &vault_role_id,
+            &k8s_api_url,
+        )
+        .await
+    })
+    .await
+    .map_err(|error| anyhow::anyhow!("Could not connect to console: {}", error))
+}
+
+/// Writes to the console input. Errors mean the connection to the console was lost
+async fn send_to_console(
+    input: &mut (impl AsyncWrite + Unpin),
+    data_vec: &[&[u8]],
+) -> Result<(), std::io::Error> {
+    for data in data_vec {
+        input.write_all(data).await?;
+    }
+
+    input.flush().await
+}
+
+/// Prints a status line in the middle of a console session. Terminal is in raw mode
+async fn print_status(stdout: &mut tokio::io::Stdout, message: &str) -> Result<(), std::io::Error> {
+    stdout
+        .write_all(format!("\r\n--- {} ---\r\n", message).as_bytes())
+        .await?;
+    stdout.flush().await
+}
+
+/// Starts recording a console session from the console menu
+fn start_recording(xname: &str) -> Option<ConsoleRecorder> {
+    let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+
+    let file_path = get_default_recording_file_path(xname);
+
+    match ConsoleRecorder::new(&file_path, width, height, xname) {
+        Ok(recorder) => Some(recorder),
+        Err(error) => {
+            log::error!(
+                "Could not create console recording file '{}': {}",
+                file_path.to_string_lossy(),
+                error
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::command::{ConsoleAction, ConsoleInputHandler, ESCAPE_KEY};
+
+#[test]
+fn test_console_input_handler_forwards_input() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    assert_eq!(
+        console_input_handler.process(b"root\r"),
+        vec![ConsoleAction::Send(b"root\r".to_vec())]
+    );
+    assert!(!console_input_handler.exit_requested);
+}
+
+#[test]
+fn test_console_input_handler_menu() {
+    let cases = [
+        (b'd', ConsoleAction::Detach),
+        (b'.', ConsoleAction::Detach),
+        (b'b', ConsoleAction::SendBreak),
+        (b'l', ConsoleAction::ToggleRecording),
+        (b'r', ConsoleAction::Reconnect),
+        (b'x', ConsoleAction::Resume),
+    ];
+
+    for (key, action) in cases {
+        let mut console_input_handler = ConsoleInputHandler::default();
+
+        assert_eq!(
+            console_input_handler.process(&[b'l', b's', ESCAPE_KEY, key]),
+            vec![
+                ConsoleAction::Send(b"ls".to_vec()),
+                ConsoleAction::ShowMenu,
+                action
+            ]
+        );
+
+        // Menu is closed after a key is pressed
+        assert_eq!(
+            console_input_handler.process(b"d"),
+            vec![ConsoleAction::Send(b"d".to_vec())]
+        );
+    }
+}
+
+#[test]
+fn test_console_input_handler_menu_key_split_between_chunks() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    assert_eq!(
+        console_input_handler.process(&[ESCAPE_KEY]),
+        vec![ConsoleAction::ShowMenu]
+    );
+    assert_eq!(
+        console_input_handler.process(b"b"),
+        vec![ConsoleAction::SendBreak]
+    );
+}
+
+#[test]
+fn test_console_input_handler_escape_key_passthrough() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    assert_eq!(
+        console_input_handler.process(&[ESCAPE_KEY, ESCAPE_KEY, b'a']),
+        vec![
+            ConsoleAction::ShowMenu,
+            ConsoleAction::Send(vec![ESCAPE_KEY]),
+            ConsoleAction::Send(b"a".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn test_console_input_handler_sysrq() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    assert_eq!(
+        console_input_handler.process(&[ESCAPE_KEY, b's']),
+        vec![ConsoleAction::ShowMenu, ConsoleAction::ShowSysrqPrompt]
+    );
+    assert_eq!(
+        console_input_handler.process(b"t"),
+        vec![ConsoleAction::SendSysrq(b't')]
+    );
+}
+
+#[test]
+fn test_console_input_handler_sysrq_cancel() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    // Esc cancels the sysrq
+    assert_eq!(
+        console_input_handler.process(&[ESCAPE_KEY, b's', 0x1b, b'c']),
+        vec![
+            ConsoleAction::ShowMenu,
+            ConsoleAction::ShowSysrqPrompt,
+            ConsoleAction::Resume,
+            ConsoleAction::Send(b"c".to_vec())
+        ]
+    );
+}
+
+#[test]
+fn test_console_input_handler_detects_conman_exit() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    console_input_handler.process(b"ls&");
+    assert!(!console_input_handler.exit_requested);
+
+    // '&.' split between two chunks
+    assert_eq!(
+        console_input_handler.process(b"."),
+        vec![ConsoleAction::Send(b".".to_vec())]
+    );
+    assert!(console_input_handler.exit_requested);
+}
+
+#[test]
+fn test_console_input_handler_detects_conman_exit_around_menu() {
+    let mut console_input_handler = ConsoleInputHandler::default();
+
+    // Menu keys are not forwarded, conman gets '&.'
+    console_input_handler.process(&[b'&', ESCAPE_KEY, b'x', b'.']);
+
+    assert!(console_input_handler.exit_requested);
+}
@@ -0,0 +1,4 @@
+pub mod recorder;
+pub mod replay;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,116 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::config_ops;
+
+/// Header of an asciicast v2 recording
+/// https://docs.asciinema.org/manual/asciicast/v2/
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AsciicastHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Records a console session in asciicast v2 format. Each chunk of console output is stored with
+/// the seconds elapsed since the recording started
+pub struct ConsoleRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    /// Bytes of an incomplete UTF-8 character at the end of the last chunk
+    pending: Vec<u8>,
+    pub file_path: PathBuf,
+}
+
+impl ConsoleRecorder {
+    pub fn new(
+        file_path: &Path,
+        width: u16,
+        height: u16,
+        title: &str,
+    ) -> Result<Self, std::io::Error> {
+        if let Some(parent_dir) = file_path.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(file_path)?);
+
+        let header = AsciicastHeader {
+            version: 2,
+            width,
+            height,
+            timestamp: Some(chrono::Utc::now().timestamp()),
+            title: Some(title.to_string()),
+        };
+
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        writer.flush()?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            pending: Vec::new(),
+            file_path: file_path.to_path_buf(),
+        })
+    }
+
+    pub fn record_output(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
+        let time = self.start.elapsed().as_secs_f64();
+
+        self.record_output_at(time, data)
+    }
+
+    /// Stores an output event. Incomplete UTF-8 characters at the end of the data are kept until
+    /// the next chunk arrives so they are not stored as invalid characters
+    pub fn record_output_at(&mut self, time: f64, data: &[u8]) -> Result<(), std::io::Error> {
+        self.pending.extend_from_slice(data);
+
+        let valid_up_to = match std::str::from_utf8(&self.pending) {
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            _ => self.pending.len(),
+        };
+
+        if valid_up_to == 0 {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(&self.pending[..valid_up_to]).to_string();
+        self.pending.drain(..valid_up_to);
+
+        writeln!(
+            self.writer,
+            "{}",
+            serde_json::to_string(&(round_time(time), "o", text))?
+        )?;
+
+        self.writer.flush()
+    }
+}
+
+/// Keeps microsecond precision, enough for replaying and keeps recordings small
+fn round_time(time: f64) -> f64 {
+    (time * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// Returns the default path of a recording, eg '<data dir>/console_recordings/x1000c0s0b0n0_20240601120000.cast'
+pub fn get_default_recording_file_path(xname: &str) -> PathBuf {
+    let mut file_path = config_ops::get_default_console_recording_dir_path();
+
+    file_path.push(format!(
+        "{}_{}.cast",
+        xname,
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+
+    file_path
+}
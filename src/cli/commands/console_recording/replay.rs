@@ -0,0 +1,158 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::common::config_ops;
+
+use super::recorder::AsciicastHeader;
+
+/// Output or input event of an asciicast v2 recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciicastEvent {
+    pub time: f64,
+    pub event_type: String,
+    pub data: String,
+}
+
+/// Reads an asciicast v2 recording. The recording can be either a path to a file or the name of a
+/// file stored in manta's console recordings folder
+pub fn read_recording(recording: &str) -> Result<(AsciicastHeader, Vec<AsciicastEvent>), String> {
+    let mut file_path = PathBuf::from(recording);
+
+    if !file_path.exists() {
+        file_path = config_ops::get_default_console_recording_dir_path();
+        file_path.push(recording);
+
+        if file_path.extension().is_none() {
+            file_path.set_extension("cast");
+        }
+    }
+
+    let file = File::open(&file_path).map_err(|error| {
+        format!(
+            "Could not open console recording '{}'. Reason: {}",
+            file_path.to_string_lossy(),
+            error
+        )
+    })?;
+
+    parse_recording(&file_path, BufReader::new(file))
+}
+
+pub fn parse_recording(
+    file_path: &Path,
+    reader: impl BufRead,
+) -> Result<(AsciicastHeader, Vec<AsciicastEvent>), String> {
+    let mut line_iter = reader.lines().enumerate();
+
+    let parse_error = |line_number: usize, error: String| {
+        format!(
+            "Could not parse console recording '{}' line {}. Reason: {}",
+            file_path.to_string_lossy(),
+            line_number + 1,
+            error
+        )
+    };
+
+    let header: AsciicastHeader = match line_iter.next() {
+        Some((line_number, line)) => {
+            let line = line.map_err(|error| parse_error(line_number, error.to_string()))?;
+
+            serde_json::from_str(&line)
+                .map_err(|error| parse_error(line_number, error.to_string()))?
+        }
+        None => return Err(parse_error(0, "empty file".to_string())),
+    };
+
+    if header.version != 2 {
+        return Err(parse_error(
+            0,
+            format!("asciicast version {} not supported", header.version),
+        ));
+    }
+
+    let mut event_vec: Vec<AsciicastEvent> = Vec::new();
+
+    for (line_number, line) in line_iter {
+        let line = line.map_err(|error| parse_error(line_number, error.to_string()))?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (time, event_type, data): (f64, String, String) = serde_json::from_str(&line)
+            .map_err(|error| parse_error(line_number, error.to_string()))?;
+
+        event_vec.push(AsciicastEvent {
+            time,
+            event_type,
+            data,
+        });
+    }
+
+    Ok((header, event_vec))
+}
+
+/// Seconds to wait before printing an event. Pauses longer than the idle time limit are shortened
+pub fn get_delay(
+    previous_time: f64,
+    time: f64,
+    speed: f64,
+    idle_time_limit_opt: Option<f64>,
+) -> f64 {
+    let mut delay = (time - previous_time).max(0.0);
+
+    if let Some(idle_time_limit) = idle_time_limit_opt {
+        delay = delay.min(idle_time_limit);
+    }
+
+    delay / speed
+}
+
+/// Replays the output of a console recording with the original timing
+pub async fn exec(recording: &str, speed: f64, idle_time_limit_opt: Option<f64>) {
+    let (header, event_vec) = match read_recording(recording) {
+        Ok(recording) => recording,
+        Err(error) => {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        }
+    };
+
+    if speed <= 0.0 {
+        eprintln!("ERROR - Replay speed must be greater than 0. Exit");
+        std::process::exit(1);
+    }
+
+    eprintln!(
+        "Replaying '{}' ({}x{}) recorded on {}",
+        header.title.as_deref().unwrap_or(recording),
+        header.width,
+        header.height,
+        header
+            .timestamp
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map(|date| date.to_rfc3339())
+            .unwrap_or_else(|| "unknown date".to_string())
+    );
+
+    let mut stdout = std::io::stdout();
+    let mut previous_time = 0.0;
+
+    for event in event_vec.iter().filter(|event| event.event_type == "o") {
+        let delay = get_delay(previous_time, event.time, speed, idle_time_limit_opt);
+
+        if delay > 0.0 {
+            tokio::time::sleep(std::time::Duration::from_secs_f64(delay)).await;
+        }
+
+        previous_time = event.time;
+
+        let _ = stdout.write_all(event.data.as_bytes());
+        let _ = stdout.flush();
+    }
+
+    eprintln!("\r\nEnd of console recording");
+}
@@ -0,0 +1,97 @@
+use std::{io::Cursor, path::Path};
+
+use super::{
+    recorder::ConsoleRecorder,
+    replay::{get_delay, parse_recording, read_recording, AsciicastEvent},
+};
+
+#[test]
+fn test_record_and_read_console_session() {
+    let file_path = std::env::temp_dir().join(format!(
+        "manta-console-recording-{}.cast",
+        uuid::Uuid::new_v4()
+    ));
+
+    let mut recorder = ConsoleRecorder::new(&file_path, 120, 40, "x1000c0s0b0n0").unwrap();
+
+    recorder.record_output_at(0.5, b"login: ").unwrap();
+    // 'é' split between two chunks
+    recorder.record_output_at(1.25, b"caf\xc3").unwrap();
+    recorder.record_output_at(1.5, b"\xa9\r\n").unwrap();
+
+    let (header, event_vec) = read_recording(&file_path.to_string_lossy()).unwrap();
+
+    std::fs::remove_file(&file_path).unwrap();
+
+    assert_eq!(header.version, 2);
+    assert_eq!(header.width, 120);
+    assert_eq!(header.height, 40);
+    assert_eq!(header.title.as_deref(), Some("x1000c0s0b0n0"));
+
+    assert_eq!(
+        event_vec,
+        vec![
+            AsciicastEvent {
+                time: 0.5,
+                event_type: "o".to_string(),
+                data: "login: ".to_string()
+            },
+            AsciicastEvent {
+                time: 1.25,
+                event_type: "o".to_string(),
+                data: "caf".to_string()
+            },
+            AsciicastEvent {
+                time: 1.5,
+                event_type: "o".to_string(),
+                data: "é\r\n".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_recording() {
+    let recording = r#"{"version": 2, "width": 80, "height": 24, "timestamp": 1717243200}
+[0.1, "o", "Booting"]
+
+[2.0, "i", "\r"]
+"#;
+
+    let (header, event_vec) =
+        parse_recording(Path::new("test.cast"), Cursor::new(recording)).unwrap();
+
+    assert_eq!(header.timestamp, Some(1717243200));
+    assert_eq!(header.title, None);
+    assert_eq!(event_vec.len(), 2);
+    assert_eq!(event_vec[1].event_type, "i");
+
+    // Wrong version
+    assert!(parse_recording(
+        Path::new("test.cast"),
+        Cursor::new(r#"{"version": 1, "width": 80, "height": 24}"#)
+    )
+    .is_err());
+
+    // Wrong event
+    let error = parse_recording(
+        Path::new("test.cast"),
+        Cursor::new("{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.1, \"o\"]\n"),
+    )
+    .unwrap_err();
+
+    assert!(error.contains("line 2"));
+
+    assert!(read_recording("manta-console-recording-not-found").is_err());
+}
+
+#[test]
+fn test_replay_delay() {
+    assert_eq!(get_delay(1.0, 3.0, 1.0, None), 2.0);
+    assert_eq!(get_delay(1.0, 3.0, 2.0, None), 1.0);
+    // Idle time limit
+    assert_eq!(get_delay(1.0, 31.0, 1.0, Some(2.5)), 2.5);
+    assert_eq!(get_delay(1.0, 31.0, 2.0, Some(2.5)), 1.25);
+    // Events out of order
+    assert_eq!(get_delay(3.0, 1.0, 1.0, None), 0.0);
+}
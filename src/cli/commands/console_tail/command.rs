@@ -0,0 +1,500 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, IsTerminal, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use comfy_table::Table;
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{AttachParams, AttachedProcess, ListParams},
+    Api,
+};
+use mesa::common::kubernetes;
+use regex::Regex;
+use serde_json::Value;
+use termion::color;
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::common::vault::http_client::fetch_shasta_k8s_secrets;
+
+/// Max number of consoles being connected at the same time
+const MAX_CONCURRENT_CONNECTIONS: usize = 10;
+
+/// Colors used for the xname prefixes
+const PREFIX_COLOR_VEC: [u8; 6] = [6, 2, 3, 5, 4, 1];
+
+/// Line of console output coming from a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleLine {
+    pub xname: String,
+    pub line: String,
+}
+
+/// Connection status of a node console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleStatus {
+    ConnectionFailed,
+    ReadFailed,
+    Closed,
+}
+
+impl ConsoleStatus {
+    pub fn message(&self) -> &str {
+        match self {
+            ConsoleStatus::ConnectionFailed => "*** Could not connect to console ***",
+            ConsoleStatus::ReadFailed => "*** Could not read console output ***",
+            ConsoleStatus::Closed => "*** Console closed ***",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            ConsoleStatus::ConnectionFailed => "Could not connect",
+            ConsoleStatus::ReadFailed => "Could not read",
+            ConsoleStatus::Closed => "Closed",
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        !matches!(self, ConsoleStatus::Closed)
+    }
+}
+
+/// Event sent by the tasks following the consoles. Console lines go through the filters,
+/// status changes are always reported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleEvent {
+    Line(ConsoleLine),
+    Status {
+        xname: String,
+        status: ConsoleStatus,
+    },
+}
+
+/// Splits a stream of console output in lines without carriage returns and terminal escape
+/// sequences. Bytes after the last new line are kept until the next chunk arrives
+#[derive(Debug)]
+pub struct ConsoleLineSplitter {
+    buffer: Vec<u8>,
+    escape_sequence_regex: Regex,
+}
+
+impl Default for ConsoleLineSplitter {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            escape_sequence_regex: Regex::new(
+                r"\x1b\[[0-9;?]*[A-Za-z]|\x1b[()][A-Za-z0-9]|\x1b[=>]",
+            )
+            .unwrap(),
+        }
+    }
+}
+
+impl ConsoleLineSplitter {
+    pub fn push(&mut self, data: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(data);
+
+        let mut line_vec = Vec::new();
+
+        while let Some(position) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line_bytes: Vec<u8> = self.buffer.drain(..=position).collect();
+
+            line_vec.push(self.clean_line(&line_bytes[..position]));
+        }
+
+        line_vec
+    }
+
+    /// Returns the output received after the last new line, if any
+    pub fn flush(&mut self) -> Option<String> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            let line_bytes = std::mem::take(&mut self.buffer);
+
+            Some(self.clean_line(&line_bytes))
+        }
+    }
+
+    fn clean_line(&self, line_bytes: &[u8]) -> String {
+        let line = String::from_utf8_lossy(line_bytes);
+
+        self.escape_sequence_regex
+            .replace_all(&line, "")
+            .replace('\r', "")
+    }
+}
+
+/// Returns true if the line matches any of the filters. Lines always match if there are no
+/// filters
+pub fn is_match(line: &str, filter_vec: &[Regex]) -> bool {
+    filter_vec.is_empty() || filter_vec.iter().any(|filter| filter.is_match(line))
+}
+
+/// Follows the console of a list of nodes. Console output of all nodes is interleaved and each
+/// line is prefixed with the node xname. Only lines matching the filters are printed while the
+/// whole console output is stored in a log file per node if a log folder is provided. Consoles
+/// are opened in read-only monitor mode, nothing is sent to them. Stops after 'duration_opt' or
+/// when the user presses Ctrl-C
+pub async fn exec(
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    xname_vec: &[String],
+    filter_vec: &[Regex],
+    log_dir_opt: Option<&Path>,
+    duration_opt: Option<chrono::Duration>,
+) {
+    let mut log_file_map: HashMap<String, BufWriter<File>> = HashMap::new();
+
+    if let Some(log_dir) = log_dir_opt {
+        if let Err(error) = std::fs::create_dir_all(log_dir) {
+            eprintln!(
+                "ERROR - Could not create folder '{}'. Reason:\n{}\nExit",
+                log_dir.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        }
+
+        for xname in xname_vec {
+            let log_file_path = log_dir.join(format!("{}.log", xname));
+
+            match File::create(&log_file_path) {
+                Ok(log_file) => {
+                    log_file_map.insert(xname.clone(), BufWriter::new(log_file));
+                }
+                Err(error) => {
+                    eprintln!(
+                        "ERROR - Could not create file '{}'. Reason:\n{}\nExit",
+                        log_file_path.to_string_lossy(),
+                        error
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    let shasta_k8s_secrets =
+        fetch_shasta_k8s_secrets(vault_base_url, vault_secret_path, vault_role_id).await;
+
+    let client =
+        match kubernetes::get_k8s_client_programmatically(k8s_api_url, shasta_k8s_secrets).await {
+            Ok(client) => client,
+            Err(error) => {
+                eprintln!(
+                    "ERROR - Could not create k8s client. Reason:\n{}\nExit",
+                    error
+                );
+                std::process::exit(1);
+            }
+        };
+
+    let pods_api: Api<Pod> = Api::namespaced(client, "services");
+
+    let console_operator_pod_name = match get_console_operator_pod_name(&pods_api).await {
+        Ok(console_operator_pod_name) => console_operator_pod_name,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not find console operator pod. Reason:\n{}\nExit",
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<ConsoleEvent>();
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_CONNECTIONS));
+
+    for xname in xname_vec {
+        tokio::spawn(follow_console(
+            pods_api.clone(),
+            console_operator_pod_name.clone(),
+            xname.clone(),
+            semaphore.clone(),
+            tx.clone(),
+        ));
+    }
+
+    // Channel closes once all consoles are closed
+    drop(tx);
+
+    eprintln!(
+        "Following console of {} node/s. Press Ctrl-C to stop.",
+        xname_vec.len()
+    );
+
+    let prefix_width = xname_vec
+        .iter()
+        .map(|xname| xname.len())
+        .max()
+        .unwrap_or_default();
+
+    let is_terminal = std::io::stdout().is_terminal();
+
+    let duration = duration_opt
+        .and_then(|duration| duration.to_std().ok())
+        .unwrap_or(std::time::Duration::MAX);
+
+    let deadline = tokio::time::sleep(duration);
+    tokio::pin!(deadline);
+
+    let get_prefix = |xname: &str| {
+        let xname_index = xname_vec
+            .iter()
+            .position(|node| node == xname)
+            .unwrap_or_default();
+
+        let prefix = format!("[{:width$}]", xname, width = prefix_width);
+
+        if is_terminal {
+            format!(
+                "{}{}{}",
+                color::Fg(color::AnsiValue(
+                    PREFIX_COLOR_VEC[xname_index % PREFIX_COLOR_VEC.len()]
+                )),
+                prefix,
+                color::Fg(color::Reset)
+            )
+        } else {
+            prefix
+        }
+    };
+
+    // Number of lines matching the filters per node
+    let mut match_count_map: BTreeMap<&String, usize> =
+        xname_vec.iter().map(|xname| (xname, 0)).collect();
+
+    // Last console status per node, nodes not in the map are still connected
+    let mut console_status_map: HashMap<String, ConsoleStatus> = HashMap::new();
+
+    loop {
+        let console_event = tokio::select! {
+            console_event_opt = rx.recv() => match console_event_opt {
+                Some(console_event) => console_event,
+                None => break,
+            },
+            _ = &mut deadline => break,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+
+        let console_line = match console_event {
+            ConsoleEvent::Line(console_line) => console_line,
+            ConsoleEvent::Status { xname, status } => {
+                // Status is not console output, it is not filtered
+                if let Some(log_file) = log_file_map.get_mut(&xname) {
+                    let _ = writeln!(log_file, "{}", status.message());
+                }
+
+                eprintln!("{} {}", get_prefix(&xname), status.message());
+
+                console_status_map.insert(xname, status);
+
+                continue;
+            }
+        };
+
+        if let Some(log_file) = log_file_map.get_mut(&console_line.xname) {
+            if let Err(error) = writeln!(log_file, "{}", console_line.line) {
+                log::warn!(
+                    "Could not write console output of node '{}' to log file: {}",
+                    console_line.xname,
+                    error
+                );
+            }
+        }
+
+        if !is_match(&console_line.line, filter_vec) {
+            continue;
+        }
+
+        if let Some(match_count) = match_count_map.get_mut(&console_line.xname) {
+            *match_count += 1;
+        }
+
+        println!("{} {}", get_prefix(&console_line.xname), console_line.line);
+    }
+
+    for log_file in log_file_map.values_mut() {
+        if let Err(error) = log_file.flush() {
+            log::warn!("Could not write console output to log file: {}", error);
+        }
+    }
+
+    let is_console_error = console_status_map.values().any(|status| status.is_error());
+
+    if !filter_vec.is_empty() || is_console_error {
+        let mut table = Table::new();
+
+        table.set_header(vec!["XNAME", "Lines matching", "Console"]);
+
+        for (xname, match_count) in match_count_map {
+            let console_status = console_status_map
+                .get(xname)
+                .map(|status| status.description())
+                .unwrap_or("Connected");
+
+            table.add_row(vec![
+                xname.clone(),
+                match_count.to_string(),
+                console_status.to_string(),
+            ]);
+        }
+
+        println!("{table}");
+    }
+
+    if let Some(log_dir) = log_dir_opt {
+        eprintln!(
+            "Console output stored in folder '{}'",
+            log_dir.to_string_lossy()
+        );
+    }
+}
+
+async fn get_console_operator_pod_name(pods_api: &Api<Pod>) -> Result<String, anyhow::Error> {
+    let params = ListParams::default()
+        .limit(1)
+        .labels("app.kubernetes.io/name=cray-console-operator");
+
+    pods_api
+        .list(&params)
+        .await?
+        .items
+        .first()
+        .and_then(|pod| pod.metadata.name.clone())
+        .ok_or_else(|| anyhow::anyhow!("No console operator pod found"))
+}
+
+/// Opens a console in monitor mode ('conman -m'). Monitor mode is read-only, the console is not
+/// joined and nothing can be sent to it
+async fn attach_to_console_monitor(
+    pods_api: &Api<Pod>,
+    console_operator_pod_name: &str,
+    xname: &str,
+) -> Result<AttachedProcess, anyhow::Error> {
+    // Get the console pod managing the node console
+    let mut attached = pods_api
+        .exec(
+            console_operator_pod_name,
+            vec!["sh", "-c", &format!("/app/get-node {}", xname)],
+            &AttachParams::default()
+                .container("cray-console-operator")
+                .stderr(false),
+        )
+        .await?;
+
+    let stdout = attached
+        .stdout()
+        .ok_or_else(|| anyhow::anyhow!("Console operator stdout not available"))?;
+
+    let output = tokio_util::io::ReaderStream::new(stdout)
+        .next()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("No output from console operator"))??;
+
+    let output_json: Value = serde_json::from_slice(&output)?;
+
+    let console_pod_name = output_json["podname"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Console pod not found for node '{}'", xname))?;
+
+    log::info!("Console pod for node '{}': {}", xname, console_pod_name);
+
+    let attached = pods_api
+        .exec(
+            console_pod_name,
+            vec!["conman", "-m", xname],
+            &AttachParams::default()
+                .container("cray-console-node")
+                .stdin(false)
+                .stdout(true)
+                .stderr(false),
+        )
+        .await?;
+
+    Ok(attached)
+}
+
+/// Streams the console output of a node to the channel line by line. Only reads from the
+/// console, nothing is written to it
+async fn follow_console(
+    pods_api: Api<Pod>,
+    console_operator_pod_name: String,
+    xname: String,
+    semaphore: Arc<Semaphore>,
+    tx: mpsc::UnboundedSender<ConsoleEvent>,
+) {
+    let send_status = |status: ConsoleStatus| {
+        let _ = tx.send(ConsoleEvent::Status {
+            xname: xname.clone(),
+            status,
+        });
+    };
+
+    let attached_rslt = {
+        // Limit the number of consoles being connected at the same time
+        let _permit = semaphore.acquire().await;
+
+        attach_to_console_monitor(&pods_api, &console_operator_pod_name, &xname).await
+    };
+
+    let mut attached = match attached_rslt {
+        Ok(attached) => attached,
+        Err(error) => {
+            log::error!(
+                "Could not connect to console of node '{}': {}",
+                xname,
+                error
+            );
+            send_status(ConsoleStatus::ConnectionFailed);
+            return;
+        }
+    };
+    let Some(stdout) = attached.stdout() else {
+        send_status(ConsoleStatus::ReadFailed);
+        return;
+    };
+
+    let mut output = tokio_util::io::ReaderStream::new(stdout);
+    let mut console_line_splitter = ConsoleLineSplitter::default();
+
+    while let Some(message) = output.next().await {
+        match message {
+            Ok(message) => {
+                for line in console_line_splitter.push(&message) {
+                    if tx
+                        .send(ConsoleEvent::Line(ConsoleLine {
+                            xname: xname.clone(),
+                            line,
+                        }))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            Err(error) => {
+                log::warn!("Console stream of node '{}' dropped: {}", xname, error);
+                break;
+            }
+        }
+    }
+
+    if let Some(line) = console_line_splitter.flush() {
+        let _ = tx.send(ConsoleEvent::Line(ConsoleLine {
+            xname: xname.clone(),
+            line,
+        }));
+    }
+
+    send_status(ConsoleStatus::Closed);
+}
@@ -0,0 +1,63 @@
+use regex::Regex;
+
+use super::command::{is_match, ConsoleLineSplitter, ConsoleStatus};
+
+#[test]
+fn test_console_line_splitter_keeps_partial_lines() {
+    let mut console_line_splitter = ConsoleLineSplitter::default();
+
+    assert_eq!(
+        console_line_splitter.push(b"Booting Linux\r\nx1000c0s0b0n0 log"),
+        vec!["Booting Linux".to_string()]
+    );
+    assert!(console_line_splitter.push(b"in: ").is_empty());
+    assert_eq!(
+        console_line_splitter.flush(),
+        Some("x1000c0s0b0n0 login: ".to_string())
+    );
+    assert_eq!(console_line_splitter.flush(), None);
+}
+
+#[test]
+fn test_console_line_splitter_removes_escape_sequences() {
+    let mut console_line_splitter = ConsoleLineSplitter::default();
+
+    assert_eq!(
+        console_line_splitter.push(b"\x1b[0;32m  OK  \x1b[0m] Started sshd\r\n\x1b[2J\x1b[H\n"),
+        vec!["  OK  ] Started sshd".to_string(), "".to_string()]
+    );
+}
+
+#[test]
+fn test_console_line_splitter_multibyte_char_split_between_chunks() {
+    let mut console_line_splitter = ConsoleLineSplitter::default();
+
+    assert!(console_line_splitter.push(b"caf\xc3").is_empty());
+    assert_eq!(
+        console_line_splitter.push(b"\xa9\n"),
+        vec!["café".to_string()]
+    );
+}
+
+#[test]
+fn test_is_match() {
+    let filter_vec = vec![
+        Regex::new("Kernel panic").unwrap(),
+        Regex::new("(?i)call trace").unwrap(),
+    ];
+
+    assert!(is_match(
+        "[ 12.345] Kernel panic - not syncing: Fatal exception",
+        &filter_vec
+    ));
+    assert!(is_match("[ 12.346] Call Trace:", &filter_vec));
+    assert!(!is_match("[ 12.347] systemd[1]: Started sshd", &filter_vec));
+    assert!(is_match("[ 12.347] systemd[1]: Started sshd", &[]));
+}
+
+#[test]
+fn test_console_status_is_error() {
+    assert!(ConsoleStatus::ConnectionFailed.is_error());
+    assert!(ConsoleStatus::ReadFailed.is_error());
+    assert!(!ConsoleStatus::Closed.is_error());
+}
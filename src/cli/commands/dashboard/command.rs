@@ -0,0 +1,792 @@
+use std::{
+    io::{self, Stdout, Write},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+use mesa::{
+    cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, common::kubernetes,
+    node::r#struct::NodeDetails,
+};
+
+use crate::{cli::commands::console_node, common::vault::http_client::fetch_shasta_k8s_secrets};
+
+/// Number of CFS sessions shown in the dashboard
+const CFS_SESSION_LIMIT: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Xname,
+    Nid,
+    Power,
+    ConfigurationStatus,
+    DesiredConfiguration,
+    BootImage,
+}
+
+impl SortColumn {
+    /// Returns the next column to sort nodes by
+    pub fn next(self) -> Self {
+        match self {
+            SortColumn::Xname => SortColumn::Nid,
+            SortColumn::Nid => SortColumn::Power,
+            SortColumn::Power => SortColumn::ConfigurationStatus,
+            SortColumn::ConfigurationStatus => SortColumn::DesiredConfiguration,
+            SortColumn::DesiredConfiguration => SortColumn::BootImage,
+            SortColumn::BootImage => SortColumn::Xname,
+        }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            SortColumn::Xname => "XNAME",
+            SortColumn::Nid => "NID",
+            SortColumn::Power => "POWER",
+            SortColumn::ConfigurationStatus => "CFS STATUS",
+            SortColumn::DesiredConfiguration => "DESIRED CONFIGURATION",
+            SortColumn::BootImage => "BOOT IMAGE",
+        }
+    }
+
+    fn get_value<'a>(&self, node_details: &'a NodeDetails) -> &'a str {
+        match self {
+            SortColumn::Xname => &node_details.xname,
+            SortColumn::Nid => &node_details.nid,
+            SortColumn::Power => &node_details.power_status,
+            SortColumn::ConfigurationStatus => &node_details.configuration_status,
+            SortColumn::DesiredConfiguration => &node_details.desired_configuration,
+            SortColumn::BootImage => &node_details.boot_image_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    Normal,
+    Filter,
+}
+
+/// Actions requested by the user which can't be resolved by the dashboard state itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    Refresh,
+    Console(String),
+    Log(String),
+}
+
+pub struct Dashboard {
+    pub hsm_name_vec: Vec<String>,
+    pub node_details_vec: Vec<NodeDetails>,
+    pub cfs_session_vec: Vec<CfsSessionGetResponse>,
+    pub filter: String,
+    pub sort_column: SortColumn,
+    pub sort_desc: bool,
+    pub selected: usize,
+    pub input_mode: InputMode,
+    pub last_refresh_opt: Option<DateTime<Local>>,
+    pub message_opt: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(hsm_name_vec: Vec<String>) -> Self {
+        Self {
+            hsm_name_vec,
+            node_details_vec: Vec::new(),
+            cfs_session_vec: Vec::new(),
+            filter: String::new(),
+            sort_column: SortColumn::Xname,
+            sort_desc: false,
+            selected: 0,
+            input_mode: InputMode::Normal,
+            last_refresh_opt: None,
+            message_opt: None,
+        }
+    }
+
+    /// Returns the nodes matching the filter sorted by the sort column. The filter is case
+    /// insensitive and is matched against xname, nid, power status, configuration, configuration
+    /// status and boot image
+    pub fn get_node_details_visible_vec(&self) -> Vec<&NodeDetails> {
+        let filter = self.filter.to_lowercase();
+
+        let mut node_details_visible_vec: Vec<&NodeDetails> = self
+            .node_details_vec
+            .iter()
+            .filter(|node_details| {
+                filter.is_empty()
+                    || [
+                        &node_details.xname,
+                        &node_details.nid,
+                        &node_details.power_status,
+                        &node_details.desired_configuration,
+                        &node_details.configuration_status,
+                        &node_details.boot_configuration,
+                        &node_details.boot_image_id,
+                    ]
+                    .iter()
+                    .any(|value| value.to_lowercase().contains(&filter))
+            })
+            .collect();
+
+        node_details_visible_vec.sort_by(|a, b| {
+            self.sort_column
+                .get_value(a)
+                .cmp(self.sort_column.get_value(b))
+                .then(a.xname.cmp(&b.xname))
+        });
+
+        if self.sort_desc {
+            node_details_visible_vec.reverse();
+        }
+
+        node_details_visible_vec
+    }
+
+    pub fn get_selected_node_details(&self) -> Option<&NodeDetails> {
+        self.get_node_details_visible_vec()
+            .get(self.selected)
+            .copied()
+    }
+
+    /// Returns the most recent CFS session targeting a node, either by xname or by any of the HSM
+    /// groups the node belongs to
+    pub fn get_cfs_session_related_to_node(
+        &self,
+        node_details: &NodeDetails,
+    ) -> Option<&CfsSessionGetResponse> {
+        let node_hsm_group_vec: Vec<&str> = node_details
+            .hsm
+            .split(',')
+            .map(|hsm_group| hsm_group.trim())
+            .collect();
+
+        self.cfs_session_vec.iter().rev().find(|cfs_session| {
+            cfs_session
+                .get_target_xname()
+                .is_some_and(|xname_vec| xname_vec.contains(&node_details.xname))
+                || cfs_session.get_target_hsm().is_some_and(|hsm_vec| {
+                    hsm_vec
+                        .iter()
+                        .any(|hsm| node_hsm_group_vec.contains(&hsm.as_str()))
+                })
+        })
+    }
+
+    fn clamp_selection(&mut self) {
+        let node_visible_count = self.get_node_details_visible_vec().len();
+        self.selected = self.selected.min(node_visible_count.saturating_sub(1));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Action::Quit;
+        }
+
+        self.message_opt = None;
+
+        match self.input_mode {
+            InputMode::Filter => {
+                match key.code {
+                    KeyCode::Enter => self.input_mode = InputMode::Normal,
+                    KeyCode::Esc => {
+                        self.filter.clear();
+                        self.input_mode = InputMode::Normal;
+                    }
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    }
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => {}
+                }
+
+                self.clamp_selection();
+
+                Action::None
+            }
+            InputMode::Normal => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+                KeyCode::Char('r') => Action::Refresh,
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = self.selected.saturating_add(1);
+                    self.clamp_selection();
+                    Action::None
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected = self.selected.saturating_sub(1);
+                    Action::None
+                }
+                KeyCode::Home | KeyCode::Char('g') => {
+                    self.selected = 0;
+                    Action::None
+                }
+                KeyCode::End | KeyCode::Char('G') => {
+                    self.selected = usize::MAX;
+                    self.clamp_selection();
+                    Action::None
+                }
+                KeyCode::Char('/') => {
+                    self.input_mode = InputMode::Filter;
+                    Action::None
+                }
+                KeyCode::Char('s') => {
+                    self.sort_column = self.sort_column.next();
+                    Action::None
+                }
+                KeyCode::Char('o') => {
+                    self.sort_desc = !self.sort_desc;
+                    Action::None
+                }
+                KeyCode::Char('c') => match self.get_selected_node_details() {
+                    Some(node_details) => Action::Console(node_details.xname.clone()),
+                    None => Action::None,
+                },
+                KeyCode::Char('l') => match self.get_selected_node_details() {
+                    Some(node_details) => {
+                        match self
+                            .get_cfs_session_related_to_node(node_details)
+                            .and_then(|cfs_session| cfs_session.name.clone())
+                        {
+                            Some(cfs_session_name) => Action::Log(cfs_session_name),
+                            None => {
+                                self.message_opt = Some(format!(
+                                    "No recent CFS session found for node '{}'",
+                                    node_details.xname
+                                ));
+                                Action::None
+                            }
+                        }
+                    }
+                    None => Action::None,
+                },
+                _ => Action::None,
+            },
+        }
+    }
+}
+
+/// Pads or truncates a string so it fits in a column
+pub fn fit(value: &str, width: usize) -> String {
+    if value.chars().count() > width {
+        let mut value_truncated: String = value.chars().take(width.saturating_sub(1)).collect();
+        value_truncated.push('~');
+        value_truncated
+    } else {
+        format!("{:<width$}", value, width = width)
+    }
+}
+
+fn get_power_status_color(power_status: &str) -> Color {
+    match power_status {
+        "ON" | "READY" => Color::Green,
+        "OFF" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+fn get_configuration_status_color(configuration_status: &str) -> Color {
+    match configuration_status {
+        "configured" => Color::Green,
+        "failed" => Color::Red,
+        "pending" => Color::Yellow,
+        _ => Color::Reset,
+    }
+}
+
+/// Live view of nodes and CFS sessions of a cluster
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    hsm_name_vec: &[String],
+    refresh_interval: u64,
+) {
+    let mut dashboard = Dashboard::new(hsm_name_vec.to_vec());
+
+    let mut stdout = io::stdout();
+
+    if let Err(error) = enter_tui(&mut stdout) {
+        eprintln!("ERROR - Could not initialize terminal. Reason:\n{}", error);
+        std::process::exit(1);
+    }
+
+    let refresh_interval = Duration::from_secs(refresh_interval);
+    let mut last_refresh_opt: Option<Instant> = None;
+
+    let rslt: Result<(), io::Error> = async {
+        loop {
+            if last_refresh_opt.is_none()
+                || last_refresh_opt
+                    .is_some_and(|last_refresh| last_refresh.elapsed() >= refresh_interval)
+            {
+                dashboard.message_opt = Some("Refreshing ...".to_string());
+                draw(&mut stdout, &dashboard)?;
+
+                refresh(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &mut dashboard,
+                )
+                .await;
+
+                last_refresh_opt = Some(Instant::now());
+            }
+
+            draw(&mut stdout, &dashboard)?;
+
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+
+            match dashboard.handle_key(key) {
+                Action::None => {}
+                Action::Quit => break,
+                Action::Refresh => last_refresh_opt = None,
+                Action::Console(xname) => {
+                    leave_tui(&mut stdout)?;
+
+                    if let Err(error) = console_node::command::connect_to_console(
+                        &xname,
+                        vault_base_url,
+                        vault_secret_path,
+                        vault_role_id,
+                        k8s_api_url,
+                        None,
+                    )
+                    .await
+                    {
+                        log::error!("{:?}", error);
+                    }
+
+                    terminal::disable_raw_mode()?;
+                    enter_tui(&mut stdout)?;
+                }
+                Action::Log(cfs_session_name) => {
+                    leave_tui(&mut stdout)?;
+
+                    print_cfs_session_logs(
+                        vault_base_url,
+                        vault_secret_path,
+                        vault_role_id,
+                        k8s_api_url,
+                        &cfs_session_name,
+                    )
+                    .await;
+
+                    enter_tui(&mut stdout)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+    .await;
+
+    let _ = leave_tui(&mut stdout);
+
+    if let Err(error) = rslt {
+        eprintln!("ERROR - Dashboard failed. Reason:\n{}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn refresh(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    dashboard: &mut Dashboard,
+) {
+    let mut hsm_group_member_vec = mesa::hsm::group::utils::get_member_vec_from_hsm_name_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        dashboard.hsm_name_vec.clone(),
+    )
+    .await;
+
+    hsm_group_member_vec.sort();
+
+    let (node_details_vec, cfs_session_vec_rslt) = tokio::join!(
+        mesa::node::utils::get_node_details(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_group_member_vec,
+        ),
+        mesa::cfs::session::mesa::http_client::get(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    );
+
+    dashboard.node_details_vec = node_details_vec;
+    dashboard.message_opt = None;
+
+    match cfs_session_vec_rslt {
+        Ok(mut cfs_session_vec) => {
+            mesa::cfs::session::mesa::utils::filter_by_hsm(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                &mut cfs_session_vec,
+                &dashboard.hsm_name_vec,
+                Some(&CFS_SESSION_LIMIT),
+            )
+            .await;
+
+            dashboard.cfs_session_vec = cfs_session_vec;
+        }
+        Err(error) => {
+            dashboard.message_opt = Some(format!("Could not fetch CFS sessions: {}", error));
+        }
+    }
+
+    dashboard.last_refresh_opt = Some(Local::now());
+    dashboard.clamp_selection();
+}
+
+/// Streams the logs of a CFS session until it finishes or the user presses Ctrl-C
+async fn print_cfs_session_logs(
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    cfs_session_name: &str,
+) {
+    println!(
+        "Logs for CFS session '{}'. Press Ctrl-C to go back to the dashboard",
+        cfs_session_name
+    );
+
+    let shasta_k8s_secrets =
+        fetch_shasta_k8s_secrets(vault_base_url, vault_secret_path, vault_role_id).await;
+
+    let client =
+        match kubernetes::get_k8s_client_programmatically(k8s_api_url, shasta_k8s_secrets).await {
+            Ok(client) => client,
+            Err(error) => {
+                eprintln!("ERROR - Could not connect to k8s. Reason:\n{}", error);
+                return;
+            }
+        };
+
+    tokio::select! {
+        log_rslt = kubernetes::print_cfs_session_logs(client, cfs_session_name) => {
+            if let Err(error) = log_rslt {
+                eprintln!("ERROR - {}", error);
+            }
+
+            println!("Press Enter to go back to the dashboard");
+            let _ = io::stdin().read_line(&mut String::new());
+        }
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+fn enter_tui(stdout: &mut Stdout) -> Result<(), io::Error> {
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+}
+
+fn leave_tui(stdout: &mut Stdout) -> Result<(), io::Error> {
+    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()
+}
+
+fn draw(stdout: &mut Stdout, dashboard: &Dashboard) -> Result<(), io::Error> {
+    let (width, height) = terminal::size()?;
+    let width = width as usize;
+
+    let node_details_visible_vec = dashboard.get_node_details_visible_vec();
+
+    queue!(
+        stdout,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0)
+    )?;
+
+    // Title
+    let last_refresh = dashboard
+        .last_refresh_opt
+        .map(|last_refresh| last_refresh.format("%H:%M:%S").to_string())
+        .unwrap_or("-".to_string());
+
+    queue!(
+        stdout,
+        SetAttribute(Attribute::Bold),
+        Print(fit(
+            &format!(
+                "manta dashboard - HSM group: {} - nodes: {}/{} - last refresh: {}",
+                dashboard.hsm_name_vec.join(", "),
+                node_details_visible_vec.len(),
+                dashboard.node_details_vec.len(),
+                last_refresh
+            ),
+            width
+        )),
+        SetAttribute(Attribute::Reset),
+        cursor::MoveToNextLine(1),
+    )?;
+
+    // Filter and sort
+    let filter_line = if dashboard.input_mode == InputMode::Filter {
+        format!("Filter: {}_", dashboard.filter)
+    } else {
+        format!(
+            "Filter: {} - Sort: {} {}{}",
+            if dashboard.filter.is_empty() {
+                "-"
+            } else {
+                &dashboard.filter
+            },
+            dashboard.sort_column.header(),
+            if dashboard.sort_desc { "desc" } else { "asc" },
+            dashboard
+                .message_opt
+                .as_ref()
+                .map(|message| format!(" - {}", message))
+                .unwrap_or_default()
+        )
+    };
+
+    queue!(
+        stdout,
+        Print(fit(&filter_line, width)),
+        cursor::MoveToNextLine(1),
+        cursor::MoveToNextLine(1),
+    )?;
+
+    // Nodes
+    let node_column_width_vec = [16, 11, 8, 12, 7, 32, 32, 38];
+
+    print_row(
+        stdout,
+        &[
+            "XNAME",
+            "NID",
+            "POWER",
+            "CFS STATUS",
+            "ERRORS",
+            "DESIRED CONFIGURATION",
+            "BOOT CONFIGURATION",
+            "BOOT IMAGE",
+        ]
+        .iter()
+        .zip(node_column_width_vec)
+        .map(|(header, column_width)| (header.to_string(), column_width, Color::Reset))
+        .collect::<Vec<_>>(),
+        width,
+        true,
+    )?;
+
+    let cfs_session_panel_height = CFS_SESSION_LIMIT as usize + 3;
+    let node_panel_height = (height as usize)
+        .saturating_sub(4 + cfs_session_panel_height + 2)
+        .max(1);
+
+    let offset = dashboard
+        .selected
+        .saturating_sub(node_panel_height.saturating_sub(1));
+
+    for (index, node_details) in node_details_visible_vec
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(node_panel_height)
+    {
+        if index == dashboard.selected {
+            queue!(stdout, SetAttribute(Attribute::Reverse))?;
+        }
+
+        print_row(
+            stdout,
+            &[
+                (node_details.xname.clone(), 16, Color::Reset),
+                (node_details.nid.clone(), 11, Color::Reset),
+                (
+                    node_details.power_status.clone(),
+                    8,
+                    get_power_status_color(&node_details.power_status),
+                ),
+                (
+                    node_details.configuration_status.clone(),
+                    12,
+                    get_configuration_status_color(&node_details.configuration_status),
+                ),
+                (node_details.error_count.clone(), 7, Color::Reset),
+                (node_details.desired_configuration.clone(), 32, Color::Reset),
+                (node_details.boot_configuration.clone(), 32, Color::Reset),
+                (node_details.boot_image_id.clone(), 38, Color::Reset),
+            ],
+            width,
+            false,
+        )?;
+
+        queue!(stdout, SetAttribute(Attribute::Reset))?;
+    }
+
+    // CFS sessions
+    queue!(
+        stdout,
+        cursor::MoveTo(
+            0,
+            (height as usize).saturating_sub(cfs_session_panel_height + 1) as u16
+        ),
+        SetAttribute(Attribute::Bold),
+        Print("Recent CFS sessions"),
+        SetAttribute(Attribute::Reset),
+        cursor::MoveToNextLine(1),
+    )?;
+
+    let cfs_session_column_width_vec = [40, 32, 24, 20, 10, 10];
+
+    print_row(
+        stdout,
+        &[
+            "NAME",
+            "CONFIGURATION",
+            "TARGET",
+            "START",
+            "STATUS",
+            "SUCCEEDED",
+        ]
+        .iter()
+        .zip(cfs_session_column_width_vec)
+        .map(|(header, column_width)| (header.to_string(), column_width, Color::Reset))
+        .collect::<Vec<_>>(),
+        width,
+        true,
+    )?;
+
+    for cfs_session in dashboard.cfs_session_vec.iter().rev() {
+        let session_status_opt = cfs_session
+            .status
+            .as_ref()
+            .and_then(|status| status.session.as_ref());
+
+        let status = session_status_opt
+            .and_then(|session| session.status.clone())
+            .unwrap_or_default();
+
+        let succeeded = session_status_opt
+            .and_then(|session| session.succeeded.clone())
+            .unwrap_or_default();
+
+        let succeeded_color = match succeeded.as_str() {
+            "true" => Color::Green,
+            "false" => Color::Red,
+            _ => Color::Reset,
+        };
+
+        print_row(
+            stdout,
+            &[
+                (
+                    cfs_session.name.clone().unwrap_or_default(),
+                    40,
+                    Color::Reset,
+                ),
+                (
+                    cfs_session.get_configuration_name().unwrap_or_default(),
+                    32,
+                    Color::Reset,
+                ),
+                (
+                    cfs_session
+                        .get_target_hsm()
+                        .or(cfs_session.get_target_xname())
+                        .unwrap_or_default()
+                        .join(","),
+                    24,
+                    Color::Reset,
+                ),
+                (
+                    cfs_session.get_start_time().unwrap_or_default(),
+                    20,
+                    Color::Reset,
+                ),
+                (status, 10, Color::Reset),
+                (succeeded, 10, succeeded_color),
+            ],
+            width,
+            false,
+        )?;
+    }
+
+    // Key bindings
+    queue!(
+        stdout,
+        cursor::MoveTo(0, height.saturating_sub(1)),
+        SetAttribute(Attribute::Reverse),
+        Print(fit(
+            "q quit | r refresh | up/down select | / filter | s sort | o order | c console | l logs",
+            width
+        )),
+        SetAttribute(Attribute::Reset),
+    )?;
+
+    stdout.flush()
+}
+
+/// Prints a row of cells, each cell is a tuple (value, column width, color). The row is truncated
+/// to the terminal width
+fn print_row(
+    stdout: &mut Stdout,
+    cell_vec: &[(String, usize, Color)],
+    width: usize,
+    is_header: bool,
+) -> Result<(), io::Error> {
+    let mut remaining_width = width;
+
+    if is_header {
+        queue!(stdout, SetAttribute(Attribute::Bold))?;
+    }
+
+    for (value, column_width, color) in cell_vec {
+        if remaining_width == 0 {
+            break;
+        }
+
+        let cell_width = (*column_width).min(remaining_width);
+
+        queue!(
+            stdout,
+            SetForegroundColor(*color),
+            Print(fit(value, cell_width.saturating_sub(1))),
+            ResetColor,
+            Print(" "),
+        )?;
+
+        remaining_width -= cell_width;
+    }
+
+    if is_header {
+        queue!(stdout, SetAttribute(Attribute::NormalIntensity))?;
+    }
+
+    queue!(stdout, cursor::MoveToNextLine(1))
+}
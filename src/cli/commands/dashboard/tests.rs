@@ -0,0 +1,152 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use mesa::{cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, node::r#struct::NodeDetails};
+use serde_json::json;
+
+use crate::cli::commands::dashboard::command::{fit, Action, Dashboard, InputMode, SortColumn};
+
+fn node_details(xname: &str, nid: &str, power_status: &str, hsm: &str) -> NodeDetails {
+    NodeDetails {
+        xname: xname.to_string(),
+        nid: nid.to_string(),
+        hsm: hsm.to_string(),
+        power_status: power_status.to_string(),
+        desired_configuration: "cfg-tenant".to_string(),
+        configuration_status: "configured".to_string(),
+        enabled: "true".to_string(),
+        error_count: "0".to_string(),
+        boot_image_id: "image-id".to_string(),
+        boot_configuration: "cfg-tenant".to_string(),
+        kernel_params: "".to_string(),
+    }
+}
+
+fn dashboard() -> Dashboard {
+    let mut dashboard = Dashboard::new(vec!["tenant".to_string()]);
+
+    dashboard.node_details_vec = vec![
+        node_details("x1000c0s1b0n0", "nid000002", "OFF", "tenant"),
+        node_details("x1000c0s0b0n0", "nid000001", "ON", "tenant"),
+        node_details("x1000c0s2b0n0", "nid000003", "ON", "tenant, compute"),
+    ];
+
+    dashboard
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn visible_xname_vec(dashboard: &Dashboard) -> Vec<String> {
+    dashboard
+        .get_node_details_visible_vec()
+        .iter()
+        .map(|node_details| node_details.xname.clone())
+        .collect()
+}
+
+#[test]
+fn test_filter_and_sort_nodes() {
+    let mut dashboard = dashboard();
+
+    assert_eq!(
+        visible_xname_vec(&dashboard),
+        vec!["x1000c0s0b0n0", "x1000c0s1b0n0", "x1000c0s2b0n0"]
+    );
+
+    dashboard.sort_column = SortColumn::Power;
+    dashboard.sort_desc = true;
+
+    assert_eq!(
+        visible_xname_vec(&dashboard),
+        vec!["x1000c0s2b0n0", "x1000c0s0b0n0", "x1000c0s1b0n0"]
+    );
+
+    dashboard.handle_key(key(KeyCode::Char('/')));
+    assert_eq!(dashboard.input_mode, InputMode::Filter);
+
+    for c in "off".chars() {
+        dashboard.handle_key(key(KeyCode::Char(c)));
+    }
+    dashboard.handle_key(key(KeyCode::Enter));
+
+    assert_eq!(dashboard.input_mode, InputMode::Normal);
+    assert_eq!(visible_xname_vec(&dashboard), vec!["x1000c0s1b0n0"]);
+}
+
+#[test]
+fn test_key_bindings() {
+    let mut dashboard = dashboard();
+
+    dashboard.handle_key(key(KeyCode::Down));
+    dashboard.handle_key(key(KeyCode::Down));
+    dashboard.handle_key(key(KeyCode::Down));
+
+    // Selection does not go beyond last node
+    assert_eq!(dashboard.selected, 2);
+
+    assert_eq!(
+        dashboard.handle_key(key(KeyCode::Char('c'))),
+        Action::Console("x1000c0s2b0n0".to_string())
+    );
+
+    dashboard.handle_key(key(KeyCode::Char('s')));
+    assert_eq!(dashboard.sort_column, SortColumn::Nid);
+
+    assert_eq!(
+        dashboard.handle_key(key(KeyCode::Char('r'))),
+        Action::Refresh
+    );
+    assert_eq!(dashboard.handle_key(key(KeyCode::Char('q'))), Action::Quit);
+    assert_eq!(
+        dashboard.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        Action::Quit
+    );
+}
+
+#[test]
+fn test_log_action_uses_most_recent_cfs_session_related_to_node() {
+    let mut dashboard = dashboard();
+
+    let cfs_session_vec: Vec<CfsSessionGetResponse> = serde_json::from_value(json!([
+        {
+            "name": "session-compute",
+            "target": { "definition": "dynamic", "groups": [{ "name": "compute", "members": [] }] },
+            "debug_on_failure": false,
+            "logs": null
+        },
+        {
+            "name": "session-xname",
+            "ansible": { "limit": "x1000c0s0b0n0" },
+            "target": { "definition": "dynamic", "groups": null },
+            "debug_on_failure": false,
+            "logs": null
+        }
+    ]))
+    .unwrap();
+
+    dashboard.cfs_session_vec = cfs_session_vec;
+
+    // x1000c0s0b0n0
+    assert_eq!(
+        dashboard.handle_key(key(KeyCode::Char('l'))),
+        Action::Log("session-xname".to_string())
+    );
+
+    // x1000c0s1b0n0
+    dashboard.handle_key(key(KeyCode::Down));
+    assert_eq!(dashboard.handle_key(key(KeyCode::Char('l'))), Action::None);
+    assert!(dashboard.message_opt.is_some());
+
+    // x1000c0s2b0n0 is member of HSM group 'compute'
+    dashboard.handle_key(key(KeyCode::Down));
+    assert_eq!(
+        dashboard.handle_key(key(KeyCode::Char('l'))),
+        Action::Log("session-compute".to_string())
+    );
+}
+
+#[test]
+fn test_fit() {
+    assert_eq!(fit("abc", 5), "abc  ");
+    assert_eq!(fit("abcdef", 4), "abc~");
+}
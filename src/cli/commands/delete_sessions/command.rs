@@ -332,7 +332,7 @@ pub async fn exec(
     println!("Session '{cfs_session_name}' has been deleted.");
 }
 
-/* /// Validate CFS session type dynamic:
+/// Validate CFS session type dynamic:
 /// - check CFS configuration related to CFS session is not a desired configuration
 pub fn is_cfs_configuration_a_desired_configuration(
     cfs_component_vec: &Vec<ComponentResponse>,
@@ -343,10 +343,9 @@ pub fn is_cfs_configuration_a_desired_configuration(
         cfs_component
             .desired_config
             .as_ref()
-            .unwrap()
-            .eq(&cfs_configuration_name)
+            .is_some_and(|desired_config| desired_config.eq(&cfs_configuration_name))
     })
-} */
+}
 
 /// Validate CFS session type dynamic:
 /// - check CFS configuration related to CFS session is a desired configuration used by a node or
@@ -0,0 +1,386 @@
+use comfy_table::{Cell, Color, Table};
+use mesa::cfs::configuration::mesa::r#struct::cfs_configuration_response::v3::{
+    CfsConfigurationResponse, Layer,
+};
+use serde::Serialize;
+use serde_json::Value;
+use strum_macros::Display;
+
+use crate::common::gitea_ops;
+
+#[derive(Debug, Serialize, Clone, PartialEq, Display)]
+pub enum LayerChange {
+    Added,
+    Removed,
+    Changed,
+    Reordered,
+    Unchanged,
+}
+
+/// Layer in configuration A aligned with a layer in configuration B. Positions start at 0
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LayerDiff {
+    pub name: String,
+    pub repo: String,
+    pub change: LayerChange,
+    pub position_a: Option<usize>,
+    pub position_b: Option<usize>,
+    pub commit_a: Option<String>,
+    pub commit_b: Option<String>,
+    pub playbook_a: Option<String>,
+    pub playbook_b: Option<String>,
+    /// Commit messages between commit A and commit B
+    pub commit_vec: Vec<String>,
+}
+
+/// Compares two CFS configurations and prints the layers added, removed, reordered or pointing to
+/// a different commit. For changed layers, the commits between both layers are listed
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    gitea_base_url: &str,
+    gitea_token: &str,
+    configuration_name_a: &str,
+    configuration_name_b: &str,
+    hsm_group_name_vec: &[String],
+    output: &str,
+) {
+    let cfs_configuration_a = get_cfs_configuration(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        configuration_name_a,
+        hsm_group_name_vec,
+    )
+    .await;
+
+    let cfs_configuration_b = get_cfs_configuration(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        configuration_name_b,
+        hsm_group_name_vec,
+    )
+    .await;
+
+    let mut layer_diff_vec = diff(&cfs_configuration_a.layers, &cfs_configuration_b.layers);
+
+    // Get commits between both layers from Gitea
+    for layer_diff in layer_diff_vec
+        .iter_mut()
+        .filter(|layer_diff| layer_diff.commit_a != layer_diff.commit_b)
+    {
+        if let (Some(commit_a), Some(commit_b)) = (&layer_diff.commit_a, &layer_diff.commit_b) {
+            match gitea_ops::compare_commits(
+                gitea_base_url,
+                gitea_token,
+                shasta_root_cert,
+                &layer_diff.repo,
+                commit_a,
+                commit_b,
+            )
+            .await
+            {
+                Ok(commit_value_vec) => {
+                    layer_diff.commit_vec =
+                        commit_value_vec.iter().map(get_commit_summary).collect();
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Could not compare commits {} and {} in repo {}. Reason:\n{}",
+                        commit_a,
+                        commit_b,
+                        layer_diff.repo,
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    if output.eq("json") {
+        println!("{}", serde_json::to_string_pretty(&layer_diff_vec).unwrap());
+    } else if layer_diff_vec
+        .iter()
+        .all(|layer_diff| layer_diff.change == LayerChange::Unchanged)
+    {
+        println!(
+            "No differences found between CFS configurations '{}' and '{}'",
+            configuration_name_a, configuration_name_b
+        );
+    } else {
+        println!(
+            "Differences between CFS configurations '{}' ({}) and '{}' ({}):",
+            cfs_configuration_a.name,
+            cfs_configuration_a.last_updated,
+            cfs_configuration_b.name,
+            cfs_configuration_b.last_updated
+        );
+        print_table(&layer_diff_vec);
+    }
+}
+
+async fn get_cfs_configuration(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    configuration_name: &str,
+    hsm_group_name_vec: &[String],
+) -> CfsConfigurationResponse {
+    let cfs_configuration_vec = mesa::cfs::configuration::mesa::utils::get_and_filter(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        Some(configuration_name),
+        None,
+        hsm_group_name_vec,
+        None,
+    )
+    .await;
+
+    match cfs_configuration_vec.into_iter().next() {
+        Some(cfs_configuration) => cfs_configuration,
+        None => {
+            eprintln!(
+                "ERROR - CFS configuration '{}' not found. Exit",
+                configuration_name
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Returns '<short sha> <first line of commit message> (<author>)' for a commit returned by the
+/// Gitea API
+pub fn get_commit_summary(commit_value: &Value) -> String {
+    let sha = commit_value["sha"].as_str().unwrap_or_default();
+
+    format!(
+        "{} {} ({})",
+        sha.get(..8).unwrap_or(sha),
+        commit_value["commit"]["message"]
+            .as_str()
+            .unwrap_or_default()
+            .lines()
+            .next()
+            .unwrap_or_default(),
+        commit_value["commit"]["author"]["name"]
+            .as_str()
+            .unwrap_or_default()
+    )
+}
+
+/// Aligns the layers in configuration A with the layers in configuration B. Layers are matched by
+/// name first, remaining layers are matched by repo in order. Matched layers are:
+///  - Changed: commit or playbook changed
+///  - Reordered: same commit and playbook but its position relative to the other matched layers
+///  changed
+///  - Unchanged: otherwise
+/// Layers only in A are 'Removed' and layers only in B are 'Added'. The result follows the order
+/// of the layers in B, with removed layers placed after the layer which preceded them in A
+pub fn diff(layer_a_vec: &[Layer], layer_b_vec: &[Layer]) -> Vec<LayerDiff> {
+    // match_vec[i] = position in B of layer i in A
+    let mut match_vec: Vec<Option<usize>> = vec![None; layer_a_vec.len()];
+    let mut matched_b_vec = vec![false; layer_b_vec.len()];
+
+    // Match by name
+    for (position_a, layer_a) in layer_a_vec.iter().enumerate() {
+        if let Some(position_b) =
+            layer_b_vec
+                .iter()
+                .enumerate()
+                .position(|(position_b, layer_b)| {
+                    !matched_b_vec[position_b] && layer_b.name == layer_a.name
+                })
+        {
+            match_vec[position_a] = Some(position_b);
+            matched_b_vec[position_b] = true;
+        }
+    }
+
+    // Match by repo
+    for (position_a, layer_a) in layer_a_vec.iter().enumerate() {
+        if match_vec[position_a].is_some() {
+            continue;
+        }
+
+        if let Some(position_b) =
+            layer_b_vec
+                .iter()
+                .enumerate()
+                .position(|(position_b, layer_b)| {
+                    !matched_b_vec[position_b]
+                        && gitea_ops::get_repo_name_from_url(&layer_b.clone_url)
+                            == gitea_ops::get_repo_name_from_url(&layer_a.clone_url)
+                })
+        {
+            match_vec[position_a] = Some(position_b);
+            matched_b_vec[position_b] = true;
+        }
+    }
+
+    // Rank of each matched layer among the matched layers in A and in B
+    let mut matched_position_b_sorted_vec: Vec<usize> =
+        match_vec.iter().flatten().copied().collect();
+    matched_position_b_sorted_vec.sort();
+
+    // Sort key is the position in B, removed layers go right after the layer preceding them in A
+    let mut layer_diff_vec: Vec<((i64, u8), LayerDiff)> = Vec::new();
+
+    let mut rank_a = 0;
+
+    for (position_a, layer_a) in layer_a_vec.iter().enumerate() {
+        match match_vec[position_a] {
+            Some(position_b) => {
+                let layer_b = &layer_b_vec[position_b];
+
+                let rank_b = matched_position_b_sorted_vec
+                    .iter()
+                    .position(|matched_position_b| *matched_position_b == position_b)
+                    .unwrap();
+
+                let change =
+                    if layer_a.commit != layer_b.commit || layer_a.playbook != layer_b.playbook {
+                        LayerChange::Changed
+                    } else if rank_a != rank_b {
+                        LayerChange::Reordered
+                    } else {
+                        LayerChange::Unchanged
+                    };
+
+                rank_a += 1;
+
+                layer_diff_vec.push((
+                    (position_b as i64, 0),
+                    LayerDiff {
+                        name: layer_b.name.clone(),
+                        repo: gitea_ops::get_repo_name_from_url(&layer_b.clone_url).to_string(),
+                        change,
+                        position_a: Some(position_a),
+                        position_b: Some(position_b),
+                        commit_a: layer_a.commit.clone(),
+                        commit_b: layer_b.commit.clone(),
+                        playbook_a: Some(layer_a.playbook.clone()),
+                        playbook_b: Some(layer_b.playbook.clone()),
+                        commit_vec: Vec::new(),
+                    },
+                ));
+            }
+            None => {
+                let position_b_preceding = match_vec[..position_a]
+                    .iter()
+                    .rev()
+                    .flatten()
+                    .next()
+                    .map(|position_b| *position_b as i64)
+                    .unwrap_or(-1);
+
+                layer_diff_vec.push((
+                    (position_b_preceding, 1),
+                    LayerDiff {
+                        name: layer_a.name.clone(),
+                        repo: gitea_ops::get_repo_name_from_url(&layer_a.clone_url).to_string(),
+                        change: LayerChange::Removed,
+                        position_a: Some(position_a),
+                        position_b: None,
+                        commit_a: layer_a.commit.clone(),
+                        commit_b: None,
+                        playbook_a: Some(layer_a.playbook.clone()),
+                        playbook_b: None,
+                        commit_vec: Vec::new(),
+                    },
+                ));
+            }
+        }
+    }
+
+    for (position_b, layer_b) in layer_b_vec.iter().enumerate() {
+        if !matched_b_vec[position_b] {
+            layer_diff_vec.push((
+                (position_b as i64, 0),
+                LayerDiff {
+                    name: layer_b.name.clone(),
+                    repo: gitea_ops::get_repo_name_from_url(&layer_b.clone_url).to_string(),
+                    change: LayerChange::Added,
+                    position_a: None,
+                    position_b: Some(position_b),
+                    commit_a: None,
+                    commit_b: layer_b.commit.clone(),
+                    playbook_a: None,
+                    playbook_b: Some(layer_b.playbook.clone()),
+                    commit_vec: Vec::new(),
+                },
+            ));
+        }
+    }
+
+    layer_diff_vec.sort_by_key(|(sort_key, _)| *sort_key);
+
+    layer_diff_vec
+        .into_iter()
+        .map(|(_, layer_diff)| layer_diff)
+        .collect()
+}
+
+fn format_position(position_opt: Option<usize>) -> String {
+    position_opt
+        .map(|position| position.to_string())
+        .unwrap_or("-".to_string())
+}
+
+fn format_commit(commit_opt: &Option<String>) -> String {
+    commit_opt
+        .as_ref()
+        .map(|commit| commit.get(..8).unwrap_or(commit).to_string())
+        .unwrap_or("-".to_string())
+}
+
+pub fn print_table(layer_diff_vec: &[LayerDiff]) {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Layer", "Repo", "Change", "Position", "Commit A", "Commit B", "Commits",
+    ]);
+
+    for layer_diff in layer_diff_vec {
+        let color = match layer_diff.change {
+            LayerChange::Added => Color::Green,
+            LayerChange::Removed => Color::Red,
+            LayerChange::Changed | LayerChange::Reordered => Color::Yellow,
+            LayerChange::Unchanged => Color::Reset,
+        };
+
+        let mut commits = layer_diff.commit_vec.join("\n");
+
+        if layer_diff.playbook_a.is_some()
+            && layer_diff.playbook_b.is_some()
+            && layer_diff.playbook_a != layer_diff.playbook_b
+        {
+            commits = format!(
+                "Playbook: {} -> {}\n{}",
+                layer_diff.playbook_a.clone().unwrap_or_default(),
+                layer_diff.playbook_b.clone().unwrap_or_default(),
+                commits
+            )
+            .trim_end()
+            .to_string();
+        }
+
+        table.add_row(vec![
+            Cell::new(&layer_diff.name),
+            Cell::new(&layer_diff.repo),
+            Cell::new(&layer_diff.change).fg(color),
+            Cell::new(format!(
+                "{} -> {}",
+                format_position(layer_diff.position_a),
+                format_position(layer_diff.position_b)
+            )),
+            Cell::new(format_commit(&layer_diff.commit_a)),
+            Cell::new(format_commit(&layer_diff.commit_b)),
+            Cell::new(commits),
+        ]);
+    }
+
+    println!("{table}");
+}
@@ -0,0 +1,86 @@
+use mesa::cfs::configuration::mesa::r#struct::cfs_configuration_response::v3::Layer;
+use serde_json::json;
+
+use crate::cli::commands::diff_configuration::command::{diff, get_commit_summary, LayerChange};
+
+fn layer(name: &str, repo: &str, commit: &str) -> Layer {
+    Layer::new(
+        format!("https://api-gw-service-nmn.local/vcs/cray/{}.git", repo),
+        None,
+        Some(commit.to_string()),
+        name.to_string(),
+        "site.yml".to_string(),
+        None,
+    )
+}
+
+fn summary(layer_a_vec: &[Layer], layer_b_vec: &[Layer]) -> Vec<(String, LayerChange)> {
+    diff(layer_a_vec, layer_b_vec)
+        .into_iter()
+        .map(|layer_diff| (layer_diff.name, layer_diff.change))
+        .collect()
+}
+
+#[test]
+fn test_diff_added_removed_changed() {
+    let layer_a_vec = vec![
+        layer("base", "csm-config", "aaa"),
+        layer("legacy", "legacy-config", "bbb"),
+        layer("uan", "uan-config", "ccc"),
+    ];
+
+    let layer_b_vec = vec![
+        layer("base", "csm-config", "aaa"),
+        layer("uan", "uan-config", "ddd"),
+        layer("slurm", "slurm-config", "eee"),
+    ];
+
+    assert_eq!(
+        summary(&layer_a_vec, &layer_b_vec),
+        vec![
+            ("base".to_string(), LayerChange::Unchanged),
+            ("legacy".to_string(), LayerChange::Removed),
+            ("uan".to_string(), LayerChange::Changed),
+            ("slurm".to_string(), LayerChange::Added),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_reordered_and_matched_by_repo() {
+    let layer_a_vec = vec![
+        layer("base", "csm-config", "aaa"),
+        layer("uan-20240101", "uan-config", "bbb"),
+    ];
+
+    // 'uan' layer renamed, matched by repo, and moved before 'base'
+    let layer_b_vec = vec![
+        layer("uan-20240202", "uan-config", "bbb"),
+        layer("base", "csm-config", "aaa"),
+    ];
+
+    let layer_diff_vec = diff(&layer_a_vec, &layer_b_vec);
+
+    assert_eq!(layer_diff_vec.len(), 2);
+    assert_eq!(layer_diff_vec[0].name, "uan-20240202");
+    assert_eq!(layer_diff_vec[0].change, LayerChange::Reordered);
+    assert_eq!(layer_diff_vec[0].position_a, Some(1));
+    assert_eq!(layer_diff_vec[0].position_b, Some(0));
+    assert_eq!(layer_diff_vec[1].change, LayerChange::Reordered);
+}
+
+#[test]
+fn test_get_commit_summary() {
+    let commit_value = json!({
+        "sha": "0123456789abcdef",
+        "commit": {
+            "message": "Fix slurm config\n\nLong description",
+            "author": { "name": "John Doe" }
+        }
+    });
+
+    assert_eq!(
+        get_commit_summary(&commit_value),
+        "01234567 Fix slurm config (John Doe)"
+    );
+}
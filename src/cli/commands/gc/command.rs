@@ -0,0 +1,716 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use comfy_table::{Cell, Color, Table};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use mesa::{
+    bos::template::mesa::r#struct::v2::BosSessionTemplate,
+    bss::bootparameters::BootParameters,
+    cfs::{
+        component::shasta::r#struct::v2::ComponentResponse,
+        configuration::mesa::r#struct::cfs_configuration_response::v3::CfsConfigurationResponse,
+        session::mesa::r#struct::v3::CfsSessionGetResponse,
+    },
+    ims::image::r#struct::Image,
+};
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::{
+    cli::commands::{
+        delete_data_related_to_cfs_configuration,
+        delete_sessions::command::{
+            is_cfs_configuration_a_desired_configuration, is_cfs_configuration_used_to_build_image,
+        },
+    },
+    common::time_ops::parse_date,
+};
+
+use super::policy::GcPolicy;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ArtefactType {
+    #[strum(serialize = "CFS configuration")]
+    CfsConfiguration,
+    #[strum(serialize = "CFS session")]
+    CfsSession,
+    #[strum(serialize = "IMS image")]
+    ImsImage,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Display)]
+pub enum GcAction {
+    Keep,
+    Delete,
+}
+
+/// Decision taken by the garbage collector on a CFS configuration, CFS session or IMS image
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct GcItem {
+    pub artefact_type: ArtefactType,
+    /// CFS configuration name, CFS session name or IMS image id
+    pub id: String,
+    pub name: String,
+    /// HSM group or CFS configuration name prefix the CFS configuration belongs to
+    pub group: Option<String>,
+    pub date: Option<String>,
+    pub action: GcAction,
+    pub reason: String,
+}
+
+/// Data used to boot nodes, never garbage collected
+#[derive(Debug, Default)]
+pub struct GcReferences {
+    /// BOS sessiontemplate name, CFS configuration name and boot image ids
+    pub bos_sessiontemplate_vec: Vec<(String, Option<String>, Vec<String>)>,
+    /// Boot image id in BSS boot parameters and the nodes booting it
+    pub boot_image_map: HashMap<String, Vec<String>>,
+}
+
+impl GcReferences {
+    pub fn new(
+        bos_sessiontemplate_vec: &[BosSessionTemplate],
+        boot_param_vec: &[BootParameters],
+    ) -> Self {
+        let bos_sessiontemplate_vec = bos_sessiontemplate_vec
+            .iter()
+            .map(|bos_sessiontemplate| {
+                let image_id_vec = bos_sessiontemplate
+                    .boot_sets
+                    .as_ref()
+                    .map(|boot_set_map| {
+                        boot_set_map
+                            .values()
+                            .filter_map(|boot_set| boot_set.path.as_ref())
+                            .map(|path| {
+                                path.trim_start_matches("s3://boot-images/")
+                                    .trim_end_matches("/manifest.json")
+                                    .to_string()
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                (
+                    bos_sessiontemplate.name.clone().unwrap_or_default(),
+                    bos_sessiontemplate
+                        .cfs
+                        .as_ref()
+                        .and_then(|cfs| cfs.configuration.clone()),
+                    image_id_vec,
+                )
+            })
+            .collect();
+
+        let mut boot_image_map: HashMap<String, Vec<String>> = HashMap::new();
+
+        for boot_param in boot_param_vec {
+            boot_image_map
+                .entry(boot_param.get_boot_image())
+                .or_default()
+                .extend(boot_param.hosts.clone());
+        }
+
+        GcReferences {
+            bos_sessiontemplate_vec,
+            boot_image_map,
+        }
+    }
+
+    /// Returns the reason why an image can't be deleted, None if the image is not used to boot
+    /// nodes
+    pub fn get_image_reference(&self, image_id: &str) -> Option<String> {
+        if let Some(xname_vec) = self.boot_image_map.get(image_id) {
+            return Some(format!("Boot image of nodes {}", xname_vec.join(", ")));
+        }
+
+        self.bos_sessiontemplate_vec
+            .iter()
+            .find(|(_, _, image_id_vec)| image_id_vec.iter().any(|elem| elem == image_id))
+            .map(|(bos_sessiontemplate_name, _, _)| {
+                format!(
+                    "Boot image in BOS sessiontemplate '{}'",
+                    bos_sessiontemplate_name
+                )
+            })
+    }
+
+    pub fn get_bos_sessiontemplate_using_configuration(
+        &self,
+        cfs_configuration_name: &str,
+    ) -> Option<&str> {
+        self.bos_sessiontemplate_vec
+            .iter()
+            .find(|(_, configuration_opt, _)| {
+                configuration_opt.as_deref() == Some(cfs_configuration_name)
+            })
+            .map(|(bos_sessiontemplate_name, _, _)| bos_sessiontemplate_name.as_str())
+    }
+}
+
+/// Deletes CFS configurations, CFS sessions and IMS images not needed anymore according to a
+/// policy. CFS configurations used as desired configuration, used by BOS sessiontemplates or used
+/// to build images booting nodes are always kept
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_group_name_vec: &[String],
+    gc_policy: &GcPolicy,
+    output: &str,
+    dry_run: bool,
+    yes: bool,
+) {
+    if gc_policy.is_empty() {
+        eprintln!("ERROR - No garbage collection policy defined. Please set 'keep-last', 'failed-session-max-age' or 'orphan-image-max-age' either in the command line or in the '[gc]' section of the configuration file. Exit");
+        std::process::exit(1);
+    }
+
+    // COLLECT SITE WIDE DATA FOR VALIDATION
+    //
+    let (
+        cfs_configuration_vec_opt,
+        cfs_session_vec_opt,
+        bos_sessiontemplate_vec_opt,
+        image_vec_opt,
+        cfs_component_vec_opt,
+    ) = mesa::common::utils::get_configurations_sessions_bos_sessiontemplates_images_components(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        true,
+        true,
+        true,
+        true,
+        true,
+    )
+    .await;
+
+    let (
+        Some(cfs_configuration_vec),
+        Some(cfs_session_vec),
+        Some(bos_sessiontemplate_vec),
+        Some(image_vec),
+        Some(cfs_component_vec),
+    ) = (
+        cfs_configuration_vec_opt,
+        cfs_session_vec_opt,
+        bos_sessiontemplate_vec_opt,
+        image_vec_opt,
+        cfs_component_vec_opt,
+    )
+    else {
+        eprintln!("ERROR - Problem fetching CFS configurations, CFS sessions, BOS sessiontemplates, IMS images or CFS components. Exit");
+        std::process::exit(1);
+    };
+
+    let boot_param_vec: Vec<BootParameters> = match mesa::bss::bootparameters::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &vec![],
+    )
+    .await
+    {
+        Ok(boot_param_vec) => boot_param_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not fetch boot parameters. Reason:\n{}",
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let xname_vec = mesa::hsm::group::utils::get_member_vec_from_hsm_name_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_group_name_vec.to_vec(),
+    )
+    .await;
+
+    let gc_references = GcReferences::new(&bos_sessiontemplate_vec, &boot_param_vec);
+
+    let gc_item_vec = plan(
+        gc_policy,
+        hsm_group_name_vec,
+        &xname_vec,
+        &cfs_configuration_vec,
+        &cfs_session_vec,
+        &cfs_component_vec,
+        &image_vec,
+        &gc_references,
+        Utc::now(),
+    );
+
+    // PRINT REPORT
+    //
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&gc_item_vec).expect("ERROR - Could not serialize report")
+        );
+    } else {
+        print_report(&gc_item_vec);
+    }
+
+    let gc_item_to_delete_vec = |artefact_type: ArtefactType| -> Vec<&str> {
+        gc_item_vec
+            .iter()
+            .filter(|gc_item| {
+                gc_item.artefact_type == artefact_type && gc_item.action == GcAction::Delete
+            })
+            .map(|gc_item| gc_item.id.as_str())
+            .collect()
+    };
+
+    let cfs_configuration_name_vec = gc_item_to_delete_vec(ArtefactType::CfsConfiguration);
+    let cfs_session_name_vec = gc_item_to_delete_vec(ArtefactType::CfsSession);
+    let image_id_vec = gc_item_to_delete_vec(ArtefactType::ImsImage);
+
+    if cfs_configuration_name_vec.is_empty()
+        && cfs_session_name_vec.is_empty()
+        && image_id_vec.is_empty()
+    {
+        eprintln!("Nothing to delete.");
+        return;
+    }
+
+    if dry_run {
+        eprintln!("Dry-run enabled. No changes persisted into the system");
+        return;
+    }
+
+    // ASK USER FOR CONFIRMATION
+    //
+    if !yes {
+        if Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Please revew the data above and confirm to delete:")
+            .interact()
+            .unwrap()
+        {
+            println!("Continue");
+        } else {
+            println!("Cancelled by user. Aborting.");
+            std::process::exit(0);
+        }
+    }
+
+    // DELETE DATA
+    //
+    delete_data_related_to_cfs_configuration::delete(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        cfs_configuration_name_vec,
+        image_id_vec,
+        cfs_session_name_vec,
+        Vec::new(),
+    )
+    .await;
+}
+
+/// Applies the policy and returns the CFS configurations, CFS sessions and IMS images to keep or
+/// delete. Only CFS configurations belonging to a prefix in the policy or related to the HSM
+/// groups are considered
+pub fn plan(
+    gc_policy: &GcPolicy,
+    hsm_group_name_vec: &[String],
+    xname_vec: &[String],
+    cfs_configuration_vec: &[CfsConfigurationResponse],
+    cfs_session_vec: &Vec<CfsSessionGetResponse>,
+    cfs_component_vec: &Vec<ComponentResponse>,
+    image_vec: &[Image],
+    gc_references: &GcReferences,
+    now: DateTime<Utc>,
+) -> Vec<GcItem> {
+    let mut gc_item_vec: Vec<GcItem> = Vec::new();
+
+    // Avoid deleting the same CFS session or IMS image twice
+    let mut deleted_id_set: HashSet<String> = HashSet::new();
+
+    // KEEP LAST N CFS CONFIGURATIONS PER HSM GROUP OR PREFIX
+    //
+    if let Some(keep_last) = gc_policy.keep_last_opt {
+        let mut cfs_configuration_group_map: BTreeMap<String, Vec<&CfsConfigurationResponse>> =
+            BTreeMap::new();
+
+        for cfs_configuration in cfs_configuration_vec {
+            if let Some(group) = get_cfs_configuration_group(
+                &cfs_configuration.name,
+                &gc_policy.prefix_vec,
+                hsm_group_name_vec,
+                cfs_session_vec,
+            ) {
+                cfs_configuration_group_map
+                    .entry(group)
+                    .or_default()
+                    .push(cfs_configuration);
+            }
+        }
+
+        for (group, mut cfs_configuration_group_vec) in cfs_configuration_group_map {
+            // Most recent first
+            cfs_configuration_group_vec.sort_by(|cfs_configuration_1, cfs_configuration_2| {
+                parse_date(&cfs_configuration_2.last_updated)
+                    .cmp(&parse_date(&cfs_configuration_1.last_updated))
+            });
+
+            for (index, cfs_configuration) in cfs_configuration_group_vec.into_iter().enumerate() {
+                let (action, reason) = if index < keep_last {
+                    (
+                        GcAction::Keep,
+                        format!("One of the last {} in group", keep_last),
+                    )
+                } else if let Some(reason) = get_cfs_configuration_reference(
+                    &cfs_configuration.name,
+                    cfs_session_vec,
+                    cfs_component_vec,
+                    gc_references,
+                ) {
+                    (GcAction::Keep, reason)
+                } else {
+                    (
+                        GcAction::Delete,
+                        format!("Older than the last {} in group", keep_last),
+                    )
+                };
+
+                gc_item_vec.push(GcItem {
+                    artefact_type: ArtefactType::CfsConfiguration,
+                    id: cfs_configuration.name.clone(),
+                    name: cfs_configuration.name.clone(),
+                    group: Some(group.clone()),
+                    date: Some(cfs_configuration.last_updated.clone()),
+                    action,
+                    reason,
+                });
+
+                if action == GcAction::Keep {
+                    continue;
+                }
+
+                // Delete CFS sessions and images related to the CFS configuration deleted
+                for image_id in get_image_id_built_vec(cfs_session_vec, &cfs_configuration.name) {
+                    if let Some(image) = image_vec
+                        .iter()
+                        .find(|image| image.id.as_ref() == Some(&image_id))
+                    {
+                        if deleted_id_set.insert(image_id.clone()) {
+                            gc_item_vec.push(GcItem {
+                                artefact_type: ArtefactType::ImsImage,
+                                id: image_id,
+                                name: image.name.clone(),
+                                group: Some(group.clone()),
+                                date: image.created.clone(),
+                                action: GcAction::Delete,
+                                reason: format!(
+                                    "Built with CFS configuration '{}'",
+                                    cfs_configuration.name
+                                ),
+                            });
+                        }
+                    }
+                }
+
+                for cfs_session in cfs_session_vec.iter().filter(|cfs_session| {
+                    cfs_session.get_configuration_name().as_ref() == Some(&cfs_configuration.name)
+                }) {
+                    let Some(cfs_session_name) = cfs_session.name.clone() else {
+                        continue;
+                    };
+
+                    if deleted_id_set.insert(cfs_session_name.clone()) {
+                        gc_item_vec.push(GcItem {
+                            artefact_type: ArtefactType::CfsSession,
+                            id: cfs_session_name.clone(),
+                            name: cfs_session_name,
+                            group: Some(group.clone()),
+                            date: cfs_session.get_start_time(),
+                            action: GcAction::Delete,
+                            reason: format!("Uses CFS configuration '{}'", cfs_configuration.name),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // FAILED CFS SESSIONS
+    //
+    if let Some(failed_session_max_age) = gc_policy.failed_session_max_age_opt {
+        for cfs_session in cfs_session_vec.iter().filter(|cfs_session| {
+            is_cfs_session_failed(cfs_session)
+                && is_cfs_session_in_scope(cfs_session, hsm_group_name_vec, xname_vec)
+        }) {
+            let Some(cfs_session_name) = cfs_session.name.clone() else {
+                continue;
+            };
+
+            let Some(start_time) = cfs_session.get_start_time().as_deref().and_then(parse_date)
+            else {
+                continue;
+            };
+
+            if now - start_time < failed_session_max_age
+                || deleted_id_set.contains(&cfs_session_name)
+            {
+                continue;
+            }
+
+            deleted_id_set.insert(cfs_session_name.clone());
+
+            gc_item_vec.push(GcItem {
+                artefact_type: ArtefactType::CfsSession,
+                id: cfs_session_name.clone(),
+                name: cfs_session_name,
+                group: cfs_session
+                    .get_target_hsm()
+                    .map(|target_hsm_vec| target_hsm_vec.join(", ")),
+                date: cfs_session.get_start_time(),
+                action: GcAction::Delete,
+                reason: format!(
+                    "Failed session started {} days ago",
+                    (now - start_time).num_days()
+                ),
+            });
+        }
+    }
+
+    // ORPHANED IMS IMAGES
+    //
+    if let Some(orphan_image_max_age) = gc_policy.orphan_image_max_age_opt {
+        let image_id_in_cfs_session_set: HashSet<String> = cfs_session_vec
+            .iter()
+            .flat_map(|cfs_session| cfs_session.get_result_id_vec())
+            .collect();
+
+        for image in image_vec {
+            let Some(image_id) = image.id.clone() else {
+                continue;
+            };
+
+            // Images are not linked to HSM groups, we rely on the HSM group name being part of
+            // the image name
+            let Some(hsm_group_name) = hsm_group_name_vec
+                .iter()
+                .find(|hsm_group_name| is_hsm_group_in_name(&image.name, hsm_group_name))
+            else {
+                continue;
+            };
+
+            let Some(created) = image.created.as_deref().and_then(parse_date) else {
+                continue;
+            };
+
+            if now - created < orphan_image_max_age
+                || deleted_id_set.contains(&image_id)
+                || image_id_in_cfs_session_set.contains(&image_id)
+                || gc_references.get_image_reference(&image_id).is_some()
+            {
+                continue;
+            }
+
+            deleted_id_set.insert(image_id.clone());
+
+            gc_item_vec.push(GcItem {
+                artefact_type: ArtefactType::ImsImage,
+                id: image_id,
+                name: image.name.clone(),
+                group: Some(hsm_group_name.clone()),
+                date: image.created.clone(),
+                action: GcAction::Delete,
+                reason: "Not referenced by CFS sessions, BOS sessiontemplates or boot parameters"
+                    .to_string(),
+            });
+        }
+    }
+
+    gc_item_vec
+}
+
+/// Returns true if the HSM group name is part of an artefact name, delimited by non alphanumeric
+/// characters or the ends of the name, eg 'zinal' in 'zinal-cos-v1' but not in 'zinalx-cos-v1'
+pub fn is_hsm_group_in_name(name: &str, hsm_group_name: &str) -> bool {
+    !hsm_group_name.is_empty()
+        && name.match_indices(hsm_group_name).any(|(index, _)| {
+            let before_opt = name[..index].chars().next_back();
+            let after_opt = name[index + hsm_group_name.len()..].chars().next();
+
+            before_opt.is_none_or(|c| !c.is_ascii_alphanumeric())
+                && after_opt.is_none_or(|c| !c.is_ascii_alphanumeric())
+        })
+}
+
+/// Returns the group a CFS configuration belongs to. Prefixes in the policy take precedence
+/// (longest match), then HSM groups either in the CFS configuration name or targeted by a CFS
+/// session using the CFS configuration. None if the CFS configuration is out of scope, that is, not
+/// related to any of the HSM groups, even if it matches a prefix
+pub fn get_cfs_configuration_group(
+    cfs_configuration_name: &str,
+    prefix_vec: &[String],
+    hsm_group_name_vec: &[String],
+    cfs_session_vec: &[CfsSessionGetResponse],
+) -> Option<String> {
+    let hsm_group_name = hsm_group_name_vec
+        .iter()
+        .find(|hsm_group_name| is_hsm_group_in_name(cfs_configuration_name, hsm_group_name))
+        .cloned()
+        .or_else(|| {
+            cfs_session_vec
+                .iter()
+                .filter(|cfs_session| {
+                    cfs_session.get_configuration_name().as_deref() == Some(cfs_configuration_name)
+                })
+                .flat_map(|cfs_session| cfs_session.get_target_hsm().unwrap_or_default())
+                .find(|target_hsm| hsm_group_name_vec.contains(target_hsm))
+        })?;
+
+    match prefix_vec
+        .iter()
+        .filter(|prefix| cfs_configuration_name.starts_with(prefix.as_str()))
+        .max_by_key(|prefix| prefix.len())
+    {
+        Some(prefix) => Some(format!("{}*", prefix)),
+        None => Some(hsm_group_name),
+    }
+}
+
+/// Returns the reason why a CFS configuration can't be deleted, None if it is not used
+fn get_cfs_configuration_reference(
+    cfs_configuration_name: &str,
+    cfs_session_vec: &Vec<CfsSessionGetResponse>,
+    cfs_component_vec: &Vec<ComponentResponse>,
+    gc_references: &GcReferences,
+) -> Option<String> {
+    if is_cfs_configuration_a_desired_configuration(cfs_component_vec, cfs_configuration_name) {
+        return Some("Desired configuration of CFS components".to_string());
+    }
+
+    if let Some(bos_sessiontemplate_name) =
+        gc_references.get_bos_sessiontemplate_using_configuration(cfs_configuration_name)
+    {
+        return Some(format!(
+            "Used by BOS sessiontemplate '{}'",
+            bos_sessiontemplate_name
+        ));
+    }
+
+    get_image_id_built_vec(cfs_session_vec, cfs_configuration_name)
+        .into_iter()
+        .find_map(|image_id| {
+            gc_references
+                .get_image_reference(&image_id)
+                .map(|reason| format!("Image '{}' built with it. {}", image_id, reason))
+        })
+}
+
+/// Returns the images built by successful CFS sessions using a CFS configuration
+fn get_image_id_built_vec(
+    cfs_session_vec: &[CfsSessionGetResponse],
+    cfs_configuration_name: &str,
+) -> Vec<String> {
+    // Only CFS sessions finished, otherwise we can't tell if they succeeded
+    let cfs_session_related_vec: Vec<CfsSessionGetResponse> = cfs_session_vec
+        .iter()
+        .filter(|cfs_session| {
+            cfs_session.name.is_some()
+                && cfs_session.get_configuration_name().as_deref() == Some(cfs_configuration_name)
+                && cfs_session
+                    .status
+                    .as_ref()
+                    .and_then(|status| status.session.as_ref())
+                    .is_some_and(|session| session.succeeded.is_some())
+        })
+        .cloned()
+        .collect();
+
+    cfs_session_related_vec
+        .iter()
+        .flat_map(|cfs_session| {
+            is_cfs_configuration_used_to_build_image(
+                &cfs_session_related_vec,
+                cfs_session.name.as_ref().unwrap(),
+                cfs_configuration_name,
+            )
+        })
+        .collect()
+}
+
+fn is_cfs_session_failed(cfs_session: &CfsSessionGetResponse) -> bool {
+    cfs_session
+        .status
+        .as_ref()
+        .and_then(|status| status.session.as_ref())
+        .is_some_and(|session| {
+            session.status.as_deref() == Some("complete")
+                && session.succeeded.as_deref() == Some("false")
+        })
+}
+
+/// Returns true if the CFS session targets any of the HSM groups or only nodes in them
+fn is_cfs_session_in_scope(
+    cfs_session: &CfsSessionGetResponse,
+    hsm_group_name_vec: &[String],
+    xname_vec: &[String],
+) -> bool {
+    if let Some(target_hsm_vec) = cfs_session.get_target_hsm() {
+        return target_hsm_vec
+            .iter()
+            .any(|target_hsm| hsm_group_name_vec.contains(target_hsm));
+    }
+
+    cfs_session
+        .get_target_xname()
+        .is_some_and(|target_xname_vec| {
+            !target_xname_vec.is_empty()
+                && target_xname_vec
+                    .iter()
+                    .all(|target_xname| xname_vec.contains(target_xname))
+        })
+}
+
+pub fn print_report(gc_item_vec: &[GcItem]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["Type", "Name", "Group", "Date", "Action", "Reason"]);
+
+    for gc_item in gc_item_vec {
+        let name = if gc_item.artefact_type == ArtefactType::ImsImage {
+            format!("{}\n({})", gc_item.name, gc_item.id)
+        } else {
+            gc_item.name.clone()
+        };
+
+        table.add_row(vec![
+            Cell::new(gc_item.artefact_type),
+            Cell::new(name),
+            Cell::new(gc_item.group.clone().unwrap_or_default()),
+            Cell::new(gc_item.date.clone().unwrap_or_default()),
+            Cell::new(gc_item.action).fg(match gc_item.action {
+                GcAction::Keep => Color::Green,
+                GcAction::Delete => Color::Red,
+            }),
+            Cell::new(&gc_item.reason),
+        ]);
+    }
+
+    println!("{table}");
+
+    println!(
+        "{} CFS configurations, {} CFS sessions and {} IMS images to delete",
+        count(gc_item_vec, ArtefactType::CfsConfiguration),
+        count(gc_item_vec, ArtefactType::CfsSession),
+        count(gc_item_vec, ArtefactType::ImsImage)
+    );
+}
+
+fn count(gc_item_vec: &[GcItem], artefact_type: ArtefactType) -> usize {
+    gc_item_vec
+        .iter()
+        .filter(|gc_item| {
+            gc_item.artefact_type == artefact_type && gc_item.action == GcAction::Delete
+        })
+        .count()
+}
@@ -0,0 +1,4 @@
+pub mod command;
+pub mod policy;
+#[cfg(test)]
+mod tests;
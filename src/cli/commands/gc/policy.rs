@@ -0,0 +1,105 @@
+use chrono::Duration;
+use config::Config;
+
+use crate::common::time_ops;
+
+/// Rules used to decide which CFS configurations, CFS sessions and IMS images can be deleted.
+/// Values come from the '[gc]' section in the configuration file and can be overridden from the
+/// command line. Rules not defined are not applied
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GcPolicy {
+    /// Number of most recent CFS configurations kept per HSM group or prefix
+    pub keep_last_opt: Option<usize>,
+    /// CFS configuration name prefixes. CFS configurations matching a prefix are grouped by
+    /// prefix instead of HSM group
+    pub prefix_vec: Vec<String>,
+    /// Failed CFS sessions older than this are deleted
+    pub failed_session_max_age_opt: Option<Duration>,
+    /// IMS images not referenced by CFS sessions, BOS sessiontemplates or boot parameters older
+    /// than this are deleted
+    pub orphan_image_max_age_opt: Option<Duration>,
+}
+
+impl GcPolicy {
+    pub fn from_settings(settings: &Config) -> Result<Self, String> {
+        let keep_last_opt = match settings.get_int("gc.keep_last") {
+            Ok(keep_last) if keep_last >= 0 => Some(keep_last as usize),
+            Ok(keep_last) => {
+                return Err(format!(
+                    "Invalid 'gc.keep_last' value '{}' in configuration file",
+                    keep_last
+                ))
+            }
+            Err(_) => None,
+        };
+
+        let prefix_vec = settings
+            .get_array("gc.prefixes")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|prefix| prefix.into_string().ok())
+            .collect();
+
+        Ok(GcPolicy {
+            keep_last_opt,
+            prefix_vec,
+            failed_session_max_age_opt: get_duration_setting(
+                settings,
+                "gc.failed_session_max_age",
+            )?,
+            orphan_image_max_age_opt: get_duration_setting(settings, "gc.orphan_image_max_age")?,
+        })
+    }
+
+    /// Overrides the policy with the values provided in the command line
+    pub fn merge(
+        mut self,
+        keep_last_opt: Option<usize>,
+        prefix_vec_opt: Option<Vec<String>>,
+        failed_session_max_age_opt: Option<&String>,
+        orphan_image_max_age_opt: Option<&String>,
+    ) -> Result<Self, String> {
+        if keep_last_opt.is_some() {
+            self.keep_last_opt = keep_last_opt;
+        }
+
+        if let Some(prefix_vec) = prefix_vec_opt {
+            self.prefix_vec = prefix_vec;
+        }
+
+        if let Some(failed_session_max_age) = failed_session_max_age_opt {
+            self.failed_session_max_age_opt = Some(parse_max_age(failed_session_max_age)?);
+        }
+
+        if let Some(orphan_image_max_age) = orphan_image_max_age_opt {
+            self.orphan_image_max_age_opt = Some(parse_max_age(orphan_image_max_age)?);
+        }
+
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keep_last_opt.is_none()
+            && self.failed_session_max_age_opt.is_none()
+            && self.orphan_image_max_age_opt.is_none()
+    }
+}
+
+/// Parses a max age, zero or negative values are rejected since they would select every artefact
+fn parse_max_age(max_age: &str) -> Result<Duration, String> {
+    time_ops::parse_duration(max_age)
+        .filter(|max_age| *max_age > Duration::zero())
+        .ok_or(format!(
+            "Invalid max age '{}'. Use a positive duration eg '12h', '7d', '4w'",
+            max_age
+        ))
+}
+
+fn get_duration_setting(settings: &Config, key: &str) -> Result<Option<Duration>, String> {
+    match settings.get_string(key) {
+        Ok(max_age) => parse_max_age(&max_age)
+            .map(Some)
+            .map_err(|error| format!("{} ('{}' in configuration file)", error, key)),
+        Err(_) => Ok(None),
+    }
+}
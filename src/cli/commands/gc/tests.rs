@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use crate::cli::commands::test_fixtures::{
+    get_cfs_component, get_cfs_configuration, get_cfs_session, get_image,
+};
+use chrono::{DateTime, Duration, Utc};
+
+use super::{
+    command::{
+        get_cfs_configuration_group, is_hsm_group_in_name, plan, ArtefactType, GcAction, GcItem,
+        GcReferences,
+    },
+    policy::GcPolicy,
+};
+
+fn get_now() -> DateTime<Utc> {
+    "2024-06-01T00:00:00Z".parse().unwrap()
+}
+
+fn get_action(gc_item_vec: &[GcItem], id: &str) -> Option<GcAction> {
+    gc_item_vec
+        .iter()
+        .find(|gc_item| gc_item.id == id)
+        .map(|gc_item| gc_item.action)
+}
+
+#[test]
+fn test_get_cfs_configuration_group() {
+    let hsm_group_name_vec = vec!["zinal".to_string(), "psi".to_string()];
+    let prefix_vec = vec!["compute-".to_string(), "compute-uan-".to_string()];
+
+    let cfs_session_vec = vec![
+        get_cfs_session(
+            "session-1",
+            "my-config",
+            "psi",
+            "dynamic",
+            "true",
+            "2024-05-01T00:00:00",
+            None,
+        ),
+        get_cfs_session(
+            "session-2",
+            "compute-v3",
+            "psi",
+            "dynamic",
+            "true",
+            "2024-05-01T00:00:00",
+            None,
+        ),
+    ];
+
+    // Longest prefix wins
+    assert_eq!(
+        get_cfs_configuration_group(
+            "compute-uan-zinal-v1",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        Some("compute-uan-*".to_string())
+    );
+
+    // Prefix match of a CFS configuration related to an HSM group through a CFS session
+    assert_eq!(
+        get_cfs_configuration_group(
+            "compute-v3",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        Some("compute-*".to_string())
+    );
+
+    // Prefix match of a CFS configuration not related to any HSM group is out of scope
+    assert_eq!(
+        get_cfs_configuration_group(
+            "compute-uan-v1",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        None
+    );
+
+    assert_eq!(
+        get_cfs_configuration_group(
+            "zinal-cos-v1",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        Some("zinal".to_string())
+    );
+
+    // HSM group targeted by a CFS session using the CFS configuration
+    assert_eq!(
+        get_cfs_configuration_group(
+            "my-config",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        Some("psi".to_string())
+    );
+
+    assert_eq!(
+        get_cfs_configuration_group(
+            "other-config",
+            &prefix_vec,
+            &hsm_group_name_vec,
+            &cfs_session_vec
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_gc_plan_keep_last_and_references() {
+    let hsm_group_name_vec = vec!["zinal".to_string()];
+
+    let cfs_configuration_vec = vec![
+        get_cfs_configuration("zinal-v1", "2024-01-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v2", "2024-02-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v3", "2024-03-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v4", "2024-04-01T00:00:00Z"),
+        get_cfs_configuration("zinal-v5", "2024-05-01T00:00:00Z"),
+        // Out of scope
+        get_cfs_configuration("psi-v1", "2023-01-01T00:00:00Z"),
+    ];
+
+    let cfs_session_vec = vec![
+        get_cfs_session(
+            "session-v1",
+            "zinal-v1",
+            "zinal",
+            "image",
+            "true",
+            "2024-01-01T00:00:00",
+            Some("image-v1"),
+        ),
+        get_cfs_session(
+            "session-v2",
+            "zinal-v2",
+            "zinal",
+            "image",
+            "true",
+            "2024-02-01T00:00:00",
+            Some("image-v2"),
+        ),
+    ];
+
+    // 'zinal-v3' is a desired configuration
+    let cfs_component_vec = vec![get_cfs_component("x1000c0s0b0n0", "zinal-v3")];
+
+    let image_vec = vec![
+        get_image("image-v1", "zinal-image-v1", "2024-01-01T00:00:00Z"),
+        get_image("image-v2", "zinal-image-v2", "2024-02-01T00:00:00Z"),
+    ];
+
+    // Image built with 'zinal-v2' boots nodes
+    let gc_references = GcReferences {
+        bos_sessiontemplate_vec: Vec::new(),
+        boot_image_map: HashMap::from([(
+            "image-v2".to_string(),
+            vec!["x1000c0s0b0n0".to_string()],
+        )]),
+    };
+
+    let gc_policy = GcPolicy {
+        keep_last_opt: Some(2),
+        ..Default::default()
+    };
+
+    let gc_item_vec = plan(
+        &gc_policy,
+        &hsm_group_name_vec,
+        &[],
+        &cfs_configuration_vec,
+        &cfs_session_vec,
+        &cfs_component_vec,
+        &image_vec,
+        &gc_references,
+        get_now(),
+    );
+
+    assert_eq!(get_action(&gc_item_vec, "zinal-v5"), Some(GcAction::Keep));
+    assert_eq!(get_action(&gc_item_vec, "zinal-v4"), Some(GcAction::Keep));
+    assert_eq!(get_action(&gc_item_vec, "zinal-v3"), Some(GcAction::Keep));
+    assert_eq!(get_action(&gc_item_vec, "zinal-v2"), Some(GcAction::Keep));
+    assert_eq!(get_action(&gc_item_vec, "zinal-v1"), Some(GcAction::Delete));
+    assert_eq!(get_action(&gc_item_vec, "psi-v1"), None);
+
+    // Data related to the CFS configuration deleted
+    assert_eq!(
+        get_action(&gc_item_vec, "session-v1"),
+        Some(GcAction::Delete)
+    );
+    assert_eq!(get_action(&gc_item_vec, "image-v1"), Some(GcAction::Delete));
+    assert_eq!(get_action(&gc_item_vec, "session-v2"), None);
+    assert_eq!(get_action(&gc_item_vec, "image-v2"), None);
+}
+
+#[test]
+fn test_gc_plan_failed_sessions_and_orphan_images() {
+    let hsm_group_name_vec = vec!["zinal".to_string()];
+
+    let cfs_session_vec = vec![
+        get_cfs_session(
+            "failed-old",
+            "zinal-v1",
+            "zinal",
+            "dynamic",
+            "false",
+            "2024-05-01T00:00:00",
+            None,
+        ),
+        get_cfs_session(
+            "failed-recent",
+            "zinal-v1",
+            "zinal",
+            "dynamic",
+            "false",
+            "2024-05-30T00:00:00",
+            None,
+        ),
+        get_cfs_session(
+            "failed-other-hsm",
+            "psi-v1",
+            "psi",
+            "dynamic",
+            "false",
+            "2024-05-01T00:00:00",
+            None,
+        ),
+        get_cfs_session(
+            "succeeded-old",
+            "zinal-v1",
+            "zinal",
+            "image",
+            "true",
+            "2024-01-01T00:00:00",
+            Some("image-built"),
+        ),
+    ];
+
+    let image_vec = vec![
+        get_image("image-orphan", "zinal-orphan", "2024-01-01T00:00:00Z"),
+        get_image(
+            "image-orphan-recent",
+            "zinal-recent",
+            "2024-05-30T00:00:00Z",
+        ),
+        get_image("image-built", "zinal-built", "2024-01-01T00:00:00Z"),
+        get_image("image-bos", "zinal-bos", "2024-01-01T00:00:00Z"),
+        get_image("image-other-hsm", "psi-orphan", "2024-01-01T00:00:00Z"),
+        get_image(
+            "image-other-hsm-sharing-name",
+            "zinalx-orphan",
+            "2024-01-01T00:00:00Z",
+        ),
+    ];
+
+    let gc_references = GcReferences {
+        bos_sessiontemplate_vec: vec![(
+            "zinal-template".to_string(),
+            Some("zinal-v1".to_string()),
+            vec!["image-bos".to_string()],
+        )],
+        boot_image_map: HashMap::new(),
+    };
+
+    let gc_policy = GcPolicy {
+        failed_session_max_age_opt: Some(Duration::days(7)),
+        orphan_image_max_age_opt: Some(Duration::days(30)),
+        ..Default::default()
+    };
+
+    let gc_item_vec = plan(
+        &gc_policy,
+        &hsm_group_name_vec,
+        &[],
+        &[],
+        &cfs_session_vec,
+        &Vec::new(),
+        &image_vec,
+        &gc_references,
+        get_now(),
+    );
+
+    assert_eq!(
+        get_action(&gc_item_vec, "failed-old"),
+        Some(GcAction::Delete)
+    );
+    assert_eq!(get_action(&gc_item_vec, "failed-recent"), None);
+    assert_eq!(get_action(&gc_item_vec, "failed-other-hsm"), None);
+    assert_eq!(get_action(&gc_item_vec, "succeeded-old"), None);
+
+    assert_eq!(
+        get_action(&gc_item_vec, "image-orphan"),
+        Some(GcAction::Delete)
+    );
+    assert_eq!(get_action(&gc_item_vec, "image-orphan-recent"), None);
+    assert_eq!(get_action(&gc_item_vec, "image-built"), None);
+    assert_eq!(get_action(&gc_item_vec, "image-bos"), None);
+    assert_eq!(get_action(&gc_item_vec, "image-other-hsm"), None);
+    assert_eq!(
+        get_action(&gc_item_vec, "image-other-hsm-sharing-name"),
+        None
+    );
+
+    assert!(gc_item_vec
+        .iter()
+        .all(|gc_item| gc_item.artefact_type != ArtefactType::CfsConfiguration));
+}
+
+#[test]
+fn test_gc_policy_merge() {
+    let gc_policy = GcPolicy {
+        keep_last_opt: Some(5),
+        prefix_vec: vec!["compute-".to_string()],
+        ..Default::default()
+    }
+    .merge(Some(2), None, Some(&"7d".to_string()), None)
+    .unwrap();
+
+    assert_eq!(gc_policy.keep_last_opt, Some(2));
+    assert_eq!(gc_policy.prefix_vec, vec!["compute-"]);
+    assert_eq!(
+        gc_policy.failed_session_max_age_opt,
+        Some(Duration::days(7))
+    );
+    assert_eq!(gc_policy.orphan_image_max_age_opt, None);
+
+    assert!(GcPolicy::default()
+        .merge(None, None, None, Some(&"30 days".to_string()))
+        .is_err());
+
+    // Zero or negative max ages would select every artefact
+    for max_age in ["-1d", "0d"] {
+        assert!(GcPolicy::default()
+            .merge(None, None, Some(&max_age.to_string()), None)
+            .is_err());
+        assert!(GcPolicy::default()
+            .merge(None, None, None, Some(&max_age.to_string()))
+            .is_err());
+    }
+
+    assert!(GcPolicy::default().is_empty());
+}
+
+#[test]
+fn test_gc_policy_from_settings_rejects_negative_max_age() {
+    let settings = config::Config::builder()
+        .set_override("gc.orphan_image_max_age", "-1d")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert!(GcPolicy::from_settings(&settings).is_err());
+}
+
+#[test]
+fn test_is_hsm_group_in_name() {
+    assert!(is_hsm_group_in_name("zinal", "zinal"));
+    assert!(is_hsm_group_in_name("zinal-cos-v1", "zinal"));
+    assert!(is_hsm_group_in_name("compute-zinal_v2", "zinal"));
+    assert!(is_hsm_group_in_name("zinalx-zinal", "zinal"));
+    assert!(!is_hsm_group_in_name("zinalx-cos-v1", "zinal"));
+    assert!(!is_hsm_group_in_name("compute-xzinal", "zinal"));
+    assert!(!is_hsm_group_in_name("zinal-cos-v1", ""));
+}
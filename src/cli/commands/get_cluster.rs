@@ -1,6 +1,9 @@
-use mesa::hsm;
+use mesa::{hsm, node::r#struct::NodeDetails};
 
-use crate::common::node_ops;
+use crate::common::{
+    node_ops,
+    watch_ops::{self, WatchCondition, WatchSnapshot},
+};
 
 /// Get nodes status/configuration for some nodes filtered by a HSM group.
 pub async fn exec(
@@ -12,58 +15,22 @@ pub async fn exec(
     silent_xname: bool,
     output_opt: Option<&String>,
     status: bool,
+    watch_interval_opt: Option<&u64>,
+    until_opt: Option<&WatchCondition>,
 ) {
-    // Take all nodes for all hsm_groups found and put them in a Vec
-    let mut hsm_groups_node_list: Vec<String> =
-        hsm::group::utils::get_member_vec_from_hsm_name_vec(
-            shasta_token,
-            shasta_base_url,
-            shasta_root_cert,
-            hsm_name_vec.to_vec(),
-        )
-        .await;
-
-    hsm_groups_node_list.sort();
-
-    let node_details_list = mesa::node::utils::get_node_details(
+    let node_details_list = get_node_details_list(
         shasta_token,
         shasta_base_url,
         shasta_root_cert,
-        hsm_groups_node_list,
+        hsm_name_vec,
     )
     .await;
 
+    let watch_snapshot_opt =
+        watch_interval_opt.map(|_| WatchSnapshot::from_node_details_vec(&node_details_list));
+
     if status {
-        let status_output = if node_details_list.iter().any(|node_details| {
-            node_details
-                .configuration_status
-                .eq_ignore_ascii_case("failed")
-        }) {
-            "FAILED"
-        } else if node_details_list
-            .iter()
-            .any(|node_detail| node_detail.power_status.eq_ignore_ascii_case("OFF"))
-        {
-            "OFF"
-        } else if node_details_list
-            .iter()
-            .any(|node_details| node_details.power_status.eq_ignore_ascii_case("on"))
-        {
-            "ON"
-        } else if node_details_list
-            .iter()
-            .any(|node_details| node_details.power_status.eq_ignore_ascii_case("standby"))
-        {
-            "STANDBY"
-        } else if node_details_list.iter().any(|node_details| {
-            !node_details
-                .configuration_status
-                .eq_ignore_ascii_case("configured")
-        }) {
-            "UNCONFIGURED"
-        } else {
-            "OK"
-        };
+        let status_output = node_ops::get_cluster_status(&node_details_list);
 
         println!("{}", status_output);
     } else if silent {
@@ -103,4 +70,48 @@ pub async fn exec(
         eprintln!("ERROR - output value not recognized or missing. Exit");
         std::process::exit(1);
     }
+
+    // Keep polling and print changes
+    if let (Some(watch_interval), Some(watch_snapshot)) = (watch_interval_opt, watch_snapshot_opt) {
+        watch_ops::watch(*watch_interval, until_opt, watch_snapshot, || async {
+            WatchSnapshot::from_node_details_vec(
+                &get_node_details_list(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    hsm_name_vec,
+                )
+                .await,
+            )
+        })
+        .await;
+    }
+}
+
+/// Get nodes status/configuration for all nodes in a list of HSM groups
+async fn get_node_details_list(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_name_vec: &[String],
+) -> Vec<NodeDetails> {
+    // Take all nodes for all hsm_groups found and put them in a Vec
+    let mut hsm_groups_node_list: Vec<String> =
+        hsm::group::utils::get_member_vec_from_hsm_name_vec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_name_vec.to_vec(),
+        )
+        .await;
+
+    hsm_groups_node_list.sort();
+
+    mesa::node::utils::get_node_details(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_groups_node_list,
+    )
+    .await
 }
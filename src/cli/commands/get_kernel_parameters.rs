@@ -1,6 +1,6 @@
 use mesa::{bss, error::Error};
 
-use crate::common;
+use crate::{cli::commands::set_kernel_parameters::profile, common};
 
 pub async fn exec(
     shasta_token: &str,
@@ -8,6 +8,8 @@ pub async fn exec(
     shasta_root_cert: &[u8],
     xname_vec: Vec<String>,
     filter: Option<&String>,
+    profile_opt: Option<(&str, &str)>,
+    strict: bool,
     output: &str,
 ) -> Result<(), Error> {
     // Get BSS boot parameters
@@ -20,6 +22,27 @@ pub async fn exec(
     .await
     .unwrap();
 
+    // Compare kernel parameters against a profile
+    if let Some((profile_name, profile_kernel_params)) = profile_opt {
+        let kernel_params_drift_vec = profile::get_drift_vec(
+            &boot_parameter_vec,
+            &xname_vec,
+            profile_kernel_params,
+            strict,
+        );
+
+        match output {
+            "json" => println!(
+                "{}",
+                serde_json::to_string_pretty(&kernel_params_drift_vec).unwrap()
+            ),
+            "table" => profile::print_drift_table(profile_name, &kernel_params_drift_vec),
+            _ => panic!("ERROR - 'output' argument value missing or not supported"),
+        }
+
+        return Ok(());
+    }
+
     match output {
         "json" => println!(
             "{}",
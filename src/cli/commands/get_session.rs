@@ -1,6 +1,9 @@
-use mesa::ims::image::r#struct::Image;
+use mesa::{cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, ims::image::r#struct::Image};
 
-use crate::common;
+use crate::common::{
+    self,
+    watch_ops::{self, WatchCondition, WatchSnapshot},
+};
 
 pub async fn exec(
     shasta_token: &str,
@@ -14,50 +17,27 @@ pub async fn exec(
     cfs_session_name_opt: Option<&String>,
     limit_number_opt: Option<&u8>,
     output_opt: Option<&String>,
+    watch_interval_opt: Option<&u64>,
+    until_opt: Option<&WatchCondition>,
 ) {
     log::info!(
         "Get CFS sessions for HSM groups: {:?}",
         hsm_group_name_vec_opt
     );
 
-    let mut cfs_session_vec = mesa::cfs::session::mesa::http_client::get(
+    let mut cfs_session_vec = get_cfs_session_vec(
         shasta_token,
         shasta_base_url,
         shasta_root_cert,
+        hsm_group_name_vec_opt.as_ref(),
+        xname_vec_opt.as_ref(),
         min_age_opt,
         max_age_opt,
         status_opt,
         cfs_session_name_opt,
-        None,
+        limit_number_opt,
     )
-    .await
-    .unwrap();
-
-    if let Some(hsm_group_name_vec) = hsm_group_name_vec_opt {
-        if !hsm_group_name_vec.is_empty() {
-            mesa::cfs::session::mesa::utils::filter_by_hsm(
-                shasta_token,
-                shasta_base_url,
-                shasta_root_cert,
-                &mut cfs_session_vec,
-                &hsm_group_name_vec,
-                limit_number_opt,
-            )
-            .await;
-        }
-    }
-
-    if let Some(xname_vec) = xname_vec_opt {
-        mesa::cfs::session::mesa::utils::filter_by_xname(
-            shasta_token,
-            shasta_base_url,
-            shasta_root_cert,
-            &mut cfs_session_vec,
-            xname_vec.as_slice(),
-            limit_number_opt,
-        )
-        .await;
-    }
+    .await;
 
     if cfs_session_vec.is_empty() {
         println!("CFS session not found!");
@@ -169,4 +149,86 @@ pub async fn exec(
     } else {
         common::cfs_session_utils::print_table_struct(&cfs_session_vec);
     }
+
+    // Keep polling and print changes
+    if let Some(watch_interval) = watch_interval_opt {
+        watch_ops::watch(
+            *watch_interval,
+            until_opt,
+            WatchSnapshot::from_cfs_session_vec(&cfs_session_vec),
+            || async {
+                WatchSnapshot::from_cfs_session_vec(
+                    &get_cfs_session_vec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        hsm_group_name_vec_opt.as_ref(),
+                        xname_vec_opt.as_ref(),
+                        min_age_opt,
+                        max_age_opt,
+                        status_opt,
+                        cfs_session_name_opt,
+                        limit_number_opt,
+                    )
+                    .await,
+                )
+            },
+        )
+        .await;
+    }
+}
+
+/// Get CFS sessions filtered by HSM groups or xnames
+async fn get_cfs_session_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_group_name_vec_opt: Option<&Vec<String>>,
+    xname_vec_opt: Option<&Vec<&str>>,
+    min_age_opt: Option<&String>,
+    max_age_opt: Option<&String>,
+    status_opt: Option<&String>,
+    cfs_session_name_opt: Option<&String>,
+    limit_number_opt: Option<&u8>,
+) -> Vec<CfsSessionGetResponse> {
+    let mut cfs_session_vec = mesa::cfs::session::mesa::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        min_age_opt,
+        max_age_opt,
+        status_opt,
+        cfs_session_name_opt,
+        None,
+    )
+    .await
+    .unwrap();
+
+    if let Some(hsm_group_name_vec) = hsm_group_name_vec_opt {
+        if !hsm_group_name_vec.is_empty() {
+            mesa::cfs::session::mesa::utils::filter_by_hsm(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                &mut cfs_session_vec,
+                hsm_group_name_vec,
+                limit_number_opt,
+            )
+            .await;
+        }
+    }
+
+    if let Some(xname_vec) = xname_vec_opt {
+        mesa::cfs::session::mesa::utils::filter_by_xname(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &mut cfs_session_vec,
+            xname_vec.as_slice(),
+            limit_number_opt,
+        )
+        .await;
+    }
+
+    cfs_session_vec
 }
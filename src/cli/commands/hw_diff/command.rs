@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+
+use comfy_table::{Cell, Color, Table};
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::cli::commands::hw_snapshot::command::{
+    read_snapshot, HwComponentSnapshot, HwSnapshot, NodeHwSnapshot,
+};
+
+#[derive(Debug, Serialize, Clone, PartialEq, Display)]
+pub enum HwChange {
+    Replaced,
+    Missing,
+    Degraded,
+    Changed,
+    Added,
+    #[strum(serialize = "Node missing")]
+    NodeMissing,
+    #[strum(serialize = "Node added")]
+    NodeAdded,
+    /// Node hw inventory could not be fetched in one of the snapshots
+    #[strum(serialize = "Node unknown")]
+    NodeUnknown,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct HwComponentDiff {
+    pub node_xname: String,
+    pub component_xname: String,
+    pub r#type: String,
+    pub change: HwChange,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Compares two hw snapshots and prints the hw components replaced, missing, degraded, changed or
+/// added per node
+pub async fn exec(snapshot_a: &str, snapshot_b: &str, output: &str) {
+    let hw_snapshot_a = match read_snapshot(snapshot_a) {
+        Ok(hw_snapshot) => hw_snapshot,
+        Err(error) => {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        }
+    };
+
+    let hw_snapshot_b = match read_snapshot(snapshot_b) {
+        Ok(hw_snapshot) => hw_snapshot,
+        Err(error) => {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        }
+    };
+
+    let hw_component_diff_vec = diff(&hw_snapshot_a, &hw_snapshot_b);
+
+    if output.eq("json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&hw_component_diff_vec).unwrap()
+        );
+    } else if hw_component_diff_vec.is_empty() {
+        println!(
+            "No hw component changes found between snapshots '{}' ({}) and '{}' ({})",
+            hw_snapshot_a.name, hw_snapshot_a.created, hw_snapshot_b.name, hw_snapshot_b.created
+        );
+    } else {
+        println!(
+            "Hw component changes between snapshots '{}' ({}) and '{}' ({}):",
+            hw_snapshot_a.name, hw_snapshot_a.created, hw_snapshot_b.name, hw_snapshot_b.created
+        );
+        print_table(&hw_component_diff_vec);
+    }
+}
+
+/// Returns the list of hw components which changed between snapshot A and snapshot B sorted by
+/// node and component xname:
+///  - Replaced: component serial number changed
+///  - Missing: component in snapshot A is not in snapshot B
+///  - Degraded: component is no longer 'Populated' or its memory capacity decreased
+///  - Changed: any other change in the component status or info (eg new model)
+///  - Added: component in snapshot B is not in snapshot A
+/// Nodes in only one of the snapshots are reported once instead of per component:
+///  - Node missing: node in snapshot A is not in snapshot B
+///  - Node added: node in snapshot B is not in snapshot A
+///  - Node unknown: node hw inventory could not be fetched in the other snapshot
+pub fn diff(hw_snapshot_a: &HwSnapshot, hw_snapshot_b: &HwSnapshot) -> Vec<HwComponentDiff> {
+    let node_a_map = get_node_map(hw_snapshot_a);
+    let node_b_map = get_node_map(hw_snapshot_b);
+
+    let component_a_map = get_component_map(hw_snapshot_a, &node_b_map);
+    let component_b_map = get_component_map(hw_snapshot_b, &node_a_map);
+
+    let mut hw_component_diff_vec = Vec::new();
+
+    for (node_xname, node_a) in &node_a_map {
+        if !node_b_map.contains_key(node_xname) {
+            let failed_in_b = hw_snapshot_b.failed_nodes.contains(&node_a.xname);
+
+            hw_component_diff_vec.push(HwComponentDiff {
+                node_xname: node_xname.to_string(),
+                component_xname: "-".to_string(),
+                r#type: "Node".to_string(),
+                change: if failed_in_b {
+                    HwChange::NodeUnknown
+                } else {
+                    HwChange::NodeMissing
+                },
+                before: Some(node_summary(node_a)),
+                after: failed_in_b.then(|| "hw inventory not fetched".to_string()),
+            });
+        }
+    }
+
+    for (node_xname, node_b) in &node_b_map {
+        if !node_a_map.contains_key(node_xname) {
+            let failed_in_a = hw_snapshot_a.failed_nodes.contains(&node_b.xname);
+
+            hw_component_diff_vec.push(HwComponentDiff {
+                node_xname: node_xname.to_string(),
+                component_xname: "-".to_string(),
+                r#type: "Node".to_string(),
+                change: if failed_in_a {
+                    HwChange::NodeUnknown
+                } else {
+                    HwChange::NodeAdded
+                },
+                before: failed_in_a.then(|| "hw inventory not fetched".to_string()),
+                after: Some(node_summary(node_b)),
+            });
+        }
+    }
+
+    for ((node_xname, component_xname), component_a) in &component_a_map {
+        let change_opt = match component_b_map.get(&(node_xname, component_xname)) {
+            None => Some(HwChange::Missing),
+            Some(component_b) => compare_component(component_a, component_b),
+        };
+
+        if let Some(change) = change_opt {
+            hw_component_diff_vec.push(HwComponentDiff {
+                node_xname: node_xname.to_string(),
+                component_xname: component_xname.to_string(),
+                r#type: component_a.r#type.clone(),
+                change,
+                before: Some(component_summary(component_a)),
+                after: component_b_map
+                    .get(&(node_xname, component_xname))
+                    .map(|component_b| component_summary(component_b)),
+            });
+        }
+    }
+
+    for ((node_xname, component_xname), component_b) in &component_b_map {
+        if !component_a_map.contains_key(&(node_xname, component_xname)) {
+            hw_component_diff_vec.push(HwComponentDiff {
+                node_xname: node_xname.to_string(),
+                component_xname: component_xname.to_string(),
+                r#type: component_b.r#type.clone(),
+                change: HwChange::Added,
+                before: None,
+                after: Some(component_summary(component_b)),
+            });
+        }
+    }
+
+    hw_component_diff_vec.sort_by(|a, b| {
+        (&a.node_xname, &a.component_xname).cmp(&(&b.node_xname, &b.component_xname))
+    });
+
+    hw_component_diff_vec
+}
+
+fn get_node_map(hw_snapshot: &HwSnapshot) -> BTreeMap<&str, &NodeHwSnapshot> {
+    hw_snapshot
+        .nodes
+        .iter()
+        .map(|node| (node.xname.as_str(), node))
+        .collect()
+}
+
+/// Returns the components of the nodes in both snapshots
+fn get_component_map<'a>(
+    hw_snapshot: &'a HwSnapshot,
+    other_node_map: &BTreeMap<&str, &NodeHwSnapshot>,
+) -> BTreeMap<(&'a str, &'a str), &'a HwComponentSnapshot> {
+    hw_snapshot
+        .nodes
+        .iter()
+        .filter(|node| other_node_map.contains_key(node.xname.as_str()))
+        .flat_map(|node| {
+            node.components
+                .iter()
+                .map(move |component| ((node.xname.as_str(), component.xname.as_str()), component))
+        })
+        .collect()
+}
+
+fn compare_component(
+    component_a: &HwComponentSnapshot,
+    component_b: &HwComponentSnapshot,
+) -> Option<HwChange> {
+    if component_a.serial_number.is_some()
+        && component_b.serial_number.is_some()
+        && component_a.serial_number != component_b.serial_number
+    {
+        Some(HwChange::Replaced)
+    } else if is_degraded(component_a, component_b) {
+        Some(HwChange::Degraded)
+    } else if component_a.status != component_b.status || component_a.info != component_b.info {
+        Some(HwChange::Changed)
+    } else {
+        None
+    }
+}
+
+/// A component is degraded if it was 'Populated' and it is no longer or if its memory capacity
+/// decreased
+fn is_degraded(component_a: &HwComponentSnapshot, component_b: &HwComponentSnapshot) -> bool {
+    let is_populated =
+        |component: &HwComponentSnapshot| component.status.as_deref() == Some("Populated");
+
+    let get_capacity_mib = |component: &HwComponentSnapshot| {
+        component
+            .info
+            .as_deref()
+            .and_then(|info| info.strip_suffix(" MiB"))
+            .and_then(|capacity_mib| capacity_mib.parse::<u64>().ok())
+    };
+
+    (is_populated(component_a) && !is_populated(component_b))
+        || get_capacity_mib(component_a)
+            .zip(get_capacity_mib(component_b))
+            .is_some_and(|(capacity_a, capacity_b)| capacity_b < capacity_a)
+}
+
+fn node_summary(node: &NodeHwSnapshot) -> String {
+    format!("{} components", node.components.len())
+}
+
+fn component_summary(component: &HwComponentSnapshot) -> String {
+    format!(
+        "{} (status: {}, serial: {})",
+        component.info.as_deref().unwrap_or("*** Missing info"),
+        component.status.as_deref().unwrap_or("unknown"),
+        component.serial_number.as_deref().unwrap_or("unknown")
+    )
+}
+
+pub fn print_table(hw_component_diff_vec: &[HwComponentDiff]) {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Node XName",
+        "Component XName",
+        "Component Type",
+        "Change",
+        "Snapshot A",
+        "Snapshot B",
+    ]);
+
+    for hw_component_diff in hw_component_diff_vec {
+        let color = match hw_component_diff.change {
+            HwChange::Added | HwChange::NodeAdded => Color::Green,
+            HwChange::Replaced | HwChange::Changed | HwChange::NodeUnknown => Color::Yellow,
+            HwChange::Missing | HwChange::Degraded | HwChange::NodeMissing => Color::Red,
+        };
+
+        table.add_row(vec![
+            Cell::new(hw_component_diff.node_xname.clone()),
+            Cell::new(hw_component_diff.component_xname.clone()),
+            Cell::new(hw_component_diff.r#type.clone()),
+            Cell::new(hw_component_diff.change.to_string()).fg(color),
+            Cell::new(hw_component_diff.before.clone().unwrap_or_default()),
+            Cell::new(hw_component_diff.after.clone().unwrap_or_default()),
+        ]);
+    }
+
+    println!("{table}");
+}
@@ -0,0 +1,3 @@
+pub mod command;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,238 @@
+use crate::cli::commands::{
+    hw_diff::command::{diff, HwChange},
+    hw_snapshot::command::{HwComponentSnapshot, HwSnapshot, NodeHwSnapshot},
+};
+
+fn component(
+    xname: &str,
+    r#type: &str,
+    status: &str,
+    info: &str,
+    serial_number: Option<&str>,
+) -> HwComponentSnapshot {
+    HwComponentSnapshot {
+        xname: xname.to_string(),
+        r#type: r#type.to_string(),
+        status: Some(status.to_string()),
+        info: Some(info.to_string()),
+        serial_number: serial_number.map(|serial_number| serial_number.to_string()),
+    }
+}
+
+fn snapshot(name: &str, components: Vec<HwComponentSnapshot>) -> HwSnapshot {
+    HwSnapshot {
+        name: name.to_string(),
+        created: "2024-01-01T00:00:00+00:00".to_string(),
+        nodes: vec![NodeHwSnapshot {
+            xname: "x1000c0s0b0n0".to_string(),
+            components,
+        }],
+        failed_nodes: Vec::new(),
+    }
+}
+
+/// Test diff returns no changes if both snapshots are the same
+#[test]
+fn test_diff_no_changes() {
+    let components = vec![
+        component(
+            "x1000c0s0b0n0p0",
+            "Processor",
+            "Populated",
+            "EPYC",
+            Some("CPU001"),
+        ),
+        component(
+            "x1000c0s0b0n0d0",
+            "Memory",
+            "Populated",
+            "16384 MiB",
+            Some("DIMM001"),
+        ),
+    ];
+
+    let hw_snapshot_a = snapshot("a", components.clone());
+    let hw_snapshot_b = snapshot("b", components);
+
+    assert!(diff(&hw_snapshot_a, &hw_snapshot_b).is_empty());
+}
+
+/// Test diff detects replaced, missing, degraded and added hw components
+#[test]
+fn test_diff_changes() {
+    let hw_snapshot_a = snapshot(
+        "a",
+        vec![
+            component(
+                "x1000c0s0b0n0a0",
+                "NodeAccel",
+                "Populated",
+                "A100",
+                Some("GPU001"),
+            ),
+            component(
+                "x1000c0s0b0n0a1",
+                "NodeAccel",
+                "Populated",
+                "A100",
+                Some("GPU002"),
+            ),
+            component(
+                "x1000c0s0b0n0d0",
+                "Memory",
+                "Populated",
+                "16384 MiB",
+                Some("DIMM001"),
+            ),
+        ],
+    );
+
+    let hw_snapshot_b = snapshot(
+        "b",
+        vec![
+            component(
+                "x1000c0s0b0n0a0",
+                "NodeAccel",
+                "Populated",
+                "A100",
+                Some("GPU003"),
+            ),
+            component(
+                "x1000c0s0b0n0d0",
+                "Memory",
+                "Empty",
+                "16384 MiB",
+                Some("DIMM001"),
+            ),
+            component(
+                "x1000c0s0b0n0d1",
+                "Memory",
+                "Populated",
+                "16384 MiB",
+                Some("DIMM002"),
+            ),
+        ],
+    );
+
+    let change_vec: Vec<(String, HwChange)> = diff(&hw_snapshot_a, &hw_snapshot_b)
+        .into_iter()
+        .map(|hw_component_diff| (hw_component_diff.component_xname, hw_component_diff.change))
+        .collect();
+
+    assert_eq!(
+        change_vec,
+        vec![
+            ("x1000c0s0b0n0a0".to_string(), HwChange::Replaced),
+            ("x1000c0s0b0n0a1".to_string(), HwChange::Missing),
+            ("x1000c0s0b0n0d0".to_string(), HwChange::Degraded),
+            ("x1000c0s0b0n0d1".to_string(), HwChange::Added),
+        ]
+    );
+}
+
+/// Test only status changes from 'Populated' and memory capacity decreases are reported as
+/// degraded, any other status or info change is reported as changed
+#[test]
+fn test_diff_degraded_vs_changed() {
+    let hw_snapshot_a = snapshot(
+        "a",
+        vec![
+            component("x1000c0s0b0n0a0", "NodeAccel", "Empty", "A100", None),
+            component("x1000c0s0b0n0a1", "NodeAccel", "Populated", "A100", None),
+            component("x1000c0s0b0n0d0", "Memory", "Populated", "32768 MiB", None),
+            component("x1000c0s0b0n0d1", "Memory", "Populated", "16384 MiB", None),
+        ],
+    );
+
+    let hw_snapshot_b = snapshot(
+        "b",
+        vec![
+            component("x1000c0s0b0n0a0", "NodeAccel", "Populated", "A100", None),
+            component("x1000c0s0b0n0a1", "NodeAccel", "Populated", "H100", None),
+            component("x1000c0s0b0n0d0", "Memory", "Populated", "16384 MiB", None),
+            component("x1000c0s0b0n0d1", "Memory", "Populated", "32768 MiB", None),
+        ],
+    );
+
+    let change_vec: Vec<(String, HwChange)> = diff(&hw_snapshot_a, &hw_snapshot_b)
+        .into_iter()
+        .map(|hw_component_diff| (hw_component_diff.component_xname, hw_component_diff.change))
+        .collect();
+
+    assert_eq!(
+        change_vec,
+        vec![
+            ("x1000c0s0b0n0a0".to_string(), HwChange::Changed),
+            ("x1000c0s0b0n0a1".to_string(), HwChange::Changed),
+            ("x1000c0s0b0n0d0".to_string(), HwChange::Degraded),
+            ("x1000c0s0b0n0d1".to_string(), HwChange::Changed),
+        ]
+    );
+}
+
+/// Test nodes in only one snapshot are reported once, as unknown if their hw inventory could not
+/// be fetched in the other snapshot
+#[test]
+fn test_diff_node_level_changes() {
+    let node = |xname: &str| NodeHwSnapshot {
+        xname: xname.to_string(),
+        components: vec![component(
+            &format!("{}p0", xname),
+            "Processor",
+            "Populated",
+            "EPYC",
+            Some("CPU001"),
+        )],
+    };
+
+    let hw_snapshot_a = HwSnapshot {
+        name: "a".to_string(),
+        created: "2024-01-01T00:00:00+00:00".to_string(),
+        nodes: vec![node("x1000c0s0b0n0"), node("x1000c0s1b0n0")],
+        failed_nodes: vec!["x1000c0s3b0n0".to_string()],
+    };
+
+    let hw_snapshot_b = HwSnapshot {
+        name: "b".to_string(),
+        created: "2024-02-01T00:00:00+00:00".to_string(),
+        nodes: vec![node("x1000c0s2b0n0"), node("x1000c0s3b0n0")],
+        failed_nodes: vec!["x1000c0s1b0n0".to_string()],
+    };
+
+    let change_vec: Vec<(String, String, HwChange)> = diff(&hw_snapshot_a, &hw_snapshot_b)
+        .into_iter()
+        .map(|hw_component_diff| {
+            (
+                hw_component_diff.node_xname,
+                hw_component_diff.component_xname,
+                hw_component_diff.change,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        change_vec,
+        vec![
+            (
+                "x1000c0s0b0n0".to_string(),
+                "-".to_string(),
+                HwChange::NodeMissing
+            ),
+            (
+                "x1000c0s1b0n0".to_string(),
+                "-".to_string(),
+                HwChange::NodeUnknown
+            ),
+            (
+                "x1000c0s2b0n0".to_string(),
+                "-".to_string(),
+                HwChange::NodeAdded
+            ),
+            (
+                "x1000c0s3b0n0".to_string(),
+                "-".to_string(),
+                HwChange::NodeUnknown
+            ),
+        ]
+    );
+}
@@ -0,0 +1,255 @@
+use std::{fs::File, path::PathBuf, sync::Arc, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use crate::common::{config_ops, snapshot_ops};
+
+/// Hardware inventory of a list of nodes at a point in time
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HwSnapshot {
+    pub name: String,
+    pub created: String,
+    pub nodes: Vec<NodeHwSnapshot>,
+    /// Nodes whose hw inventory could not be fetched
+    #[serde(default)]
+    pub failed_nodes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeHwSnapshot {
+    pub xname: String,
+    pub components: Vec<HwComponentSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HwComponentSnapshot {
+    pub xname: String,
+    pub r#type: String,
+    pub status: Option<String>,
+    pub info: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+impl NodeHwSnapshot {
+    /// Creates a node hw snapshot from a node in the HSM hardware inventory API json response
+    /// (eg '/Nodes/0')
+    pub fn from_csm_value(node_hw_inventory_value: &Value) -> Self {
+        let mut components = Vec::new();
+
+        for (component_list, fru_info, info_field) in [
+            ("Processors", "ProcessorFRUInfo", "Model"),
+            ("NodeAccels", "NodeAccelFRUInfo", "Model"),
+            ("Memory", "MemoryFRUInfo", "CapacityMiB"),
+            ("NodeHsnNics", "NodeHsnNicFRUInfo", "Model"),
+        ] {
+            for component_value in node_hw_inventory_value[component_list]
+                .as_array()
+                .unwrap_or(&Vec::new())
+            {
+                components.push(HwComponentSnapshot::from_csm_value(
+                    component_value,
+                    fru_info,
+                    info_field,
+                ));
+            }
+        }
+
+        Self {
+            xname: node_hw_inventory_value["ID"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            components,
+        }
+    }
+}
+
+impl HwComponentSnapshot {
+    fn from_csm_value(component_value: &Value, fru_info: &str, info_field: &str) -> Self {
+        let info = component_value
+            .pointer(&format!("/PopulatedFRU/{}/{}", fru_info, info_field))
+            .and_then(|info_value| match info_value {
+                Value::String(info) => Some(info.to_string()),
+                Value::Number(capacity_mib) => Some(capacity_mib.to_string() + " MiB"),
+                _ => None,
+            });
+
+        let serial_number = component_value
+            .pointer(&format!("/PopulatedFRU/{}/SerialNumber", fru_info))
+            .and_then(|serial_number| serial_number.as_str())
+            .map(|serial_number| serial_number.trim().to_string())
+            .filter(|serial_number| !serial_number.is_empty());
+
+        Self {
+            xname: component_value["ID"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            r#type: component_value["Type"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            status: component_value["Status"]
+                .as_str()
+                .map(|status| status.to_string()),
+            info,
+            serial_number,
+        }
+    }
+}
+
+/// Fetches the hw inventory of a list of nodes and stores it in a local file. If the user does not
+/// provide a file path, the snapshot is stored in manta's data folder
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    snapshot_name: &str,
+    xname_vec: &[String],
+    file_path_opt: Option<&PathBuf>,
+) {
+    let start = Instant::now();
+
+    let mut tasks = tokio::task::JoinSet::new();
+
+    let sem = Arc::new(Semaphore::new(5)); // CSM 1.3.1 higher number of concurrent tasks won't
+                                           // make it faster
+
+    for xname in xname_vec {
+        let shasta_token_string = shasta_token.to_string();
+        let shasta_base_url_string = shasta_base_url.to_string();
+        let shasta_root_cert_vec = shasta_root_cert.to_vec();
+        let xname_string = xname.to_string();
+
+        let permit = Arc::clone(&sem).acquire_owned().await;
+
+        tasks.spawn(async move {
+            let _permit = permit; // Wait semaphore to allow new tasks https://github.com/tokio-rs/tokio/discussions/2648#discussioncomment-34885
+            let node_hw_inventory_rslt =
+                mesa::hsm::hw_inventory::hw_component::http_client::get_hw_inventory(
+                    &shasta_token_string,
+                    &shasta_base_url_string,
+                    &shasta_root_cert_vec,
+                    &xname_string,
+                )
+                .await;
+
+            (xname_string, node_hw_inventory_rslt)
+        });
+    }
+
+    let mut node_hw_snapshot_vec = Vec::new();
+
+    while let Some(message) = tasks.join_next().await {
+        match message {
+            Ok((xname, Ok(node_hw_inventory))) => {
+                match node_hw_inventory.pointer("/Nodes/0") {
+                    Some(node_value) => {
+                        node_hw_snapshot_vec.push(NodeHwSnapshot::from_csm_value(node_value))
+                    }
+                    None => eprintln!(
+                        "WARNING - json section '/Node' missing in json response API for node '{}'. Skipping",
+                        xname
+                    ),
+                }
+            }
+            Ok((xname, Err(error))) => {
+                eprintln!(
+                    "WARNING - Could not fetch hw inventory for node '{}'. Reason:\n{}\nSkipping",
+                    xname, error
+                );
+            }
+            Err(_) => log::error!("Failed procesing/fetching node hw information"),
+        }
+    }
+
+    // Nodes not in the snapshot are recorded so they are not reported as missing when comparing
+    // snapshots
+    let mut failed_node_vec: Vec<String> = xname_vec
+        .iter()
+        .filter(|xname| {
+            !node_hw_snapshot_vec
+                .iter()
+                .any(|node_hw_snapshot| node_hw_snapshot.xname.eq(*xname))
+        })
+        .cloned()
+        .collect();
+
+    failed_node_vec.sort();
+
+    log::info!(
+        "Time elapsed to fetch hw inventory is: {:?}",
+        start.elapsed()
+    );
+
+    node_hw_snapshot_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    let timestamp = chrono::Utc::now();
+
+    let hw_snapshot = HwSnapshot {
+        name: snapshot_name.to_string(),
+        created: timestamp.to_rfc3339(),
+        nodes: node_hw_snapshot_vec,
+        failed_nodes: failed_node_vec,
+    };
+
+    let file_path = if let Some(file_path) = file_path_opt {
+        file_path.clone()
+    } else {
+        snapshot_ops::get_new_snapshot_file_path(
+            &config_ops::get_default_hw_snapshot_dir_path(),
+            snapshot_name,
+            &timestamp,
+        )
+    };
+
+    if let Some(parent_dir) = file_path.parent() {
+        let _ = std::fs::create_dir_all(parent_dir);
+    }
+
+    let file = match File::create(&file_path) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not create hw snapshot file '{}'. Reason:\n{}\nExit",
+                file_path.to_string_lossy(),
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(error) = serde_json::to_writer_pretty(&file, &hw_snapshot) {
+        eprintln!(
+            "ERROR - Could not write hw snapshot file '{}'. Reason:\n{}\nExit",
+            file_path.to_string_lossy(),
+            error
+        );
+        std::process::exit(1);
+    }
+
+    println!(
+        "Hw snapshot for {} nodes stored in '{}'",
+        hw_snapshot.nodes.len(),
+        file_path.to_string_lossy()
+    );
+
+    if !hw_snapshot.failed_nodes.is_empty() {
+        eprintln!(
+            "WARNING - Hw inventory of nodes {:?} could not be fetched, they are not in the snapshot",
+            hw_snapshot.failed_nodes
+        );
+    }
+}
+
+/// Reads a hw snapshot. The snapshot can be either a path to a file or the name of a snapshot
+/// stored in manta's hw snapshot folder, in which case the latest one is read
+pub fn read_snapshot(snapshot: &str) -> Result<HwSnapshot, String> {
+    snapshot_ops::read_snapshot(
+        snapshot,
+        &config_ops::get_default_hw_snapshot_dir_path(),
+        "hw",
+    )
+}
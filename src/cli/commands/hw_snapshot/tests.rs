@@ -0,0 +1,58 @@
+use serde_json::json;
+
+use crate::cli::commands::hw_snapshot::command::{HwComponentSnapshot, NodeHwSnapshot};
+
+/// Test NodeHwSnapshot is created from the HSM hw inventory API response including serial numbers
+/// and memory capacity
+#[test]
+fn test_node_hw_snapshot_from_csm_value() {
+    let node_hw_inventory_value = json!({
+        "ID": "x1000c0s0b0n0",
+        "Type": "Node",
+        "Processors": [{
+            "ID": "x1000c0s0b0n0p0",
+            "Type": "Processor",
+            "Status": "Populated",
+            "PopulatedFRU": {
+                "ProcessorFRUInfo": {
+                    "Model": "AMD EPYC 7742 64-Core Processor",
+                    "SerialNumber": "CPU001"
+                }
+            }
+        }],
+        "Memory": [{
+            "ID": "x1000c0s0b0n0d0",
+            "Type": "Memory",
+            "Status": "Populated",
+            "PopulatedFRU": {
+                "MemoryFRUInfo": {
+                    "CapacityMiB": 16384,
+                    "SerialNumber": "  "
+                }
+            }
+        }]
+    });
+
+    let node_hw_snapshot = NodeHwSnapshot::from_csm_value(&node_hw_inventory_value);
+
+    assert_eq!(node_hw_snapshot.xname, "x1000c0s0b0n0");
+    assert_eq!(
+        node_hw_snapshot.components,
+        vec![
+            HwComponentSnapshot {
+                xname: "x1000c0s0b0n0p0".to_string(),
+                r#type: "Processor".to_string(),
+                status: Some("Populated".to_string()),
+                info: Some("AMD EPYC 7742 64-Core Processor".to_string()),
+                serial_number: Some("CPU001".to_string()),
+            },
+            HwComponentSnapshot {
+                xname: "x1000c0s0b0n0d0".to_string(),
+                r#type: "Memory".to_string(),
+                status: Some("Populated".to_string()),
+                info: Some("16384 MiB".to_string()),
+                serial_number: None,
+            },
+        ]
+    );
+}
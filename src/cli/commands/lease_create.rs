@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+
+use crate::common::lease_ops::{self, Lease, LEASE_EXCLUSIVE_GROUP, LEASE_TAG};
+
+/// Reserves nodes from the parent HSM group and moves them to the target HSM group until the
+/// lease expires. Expired leases of the HSM groups available are returned to their parent HSM
+/// group before reserving new nodes
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_group_available_vec: &[String],
+    owner: &str,
+    target_hsm_name: &str,
+    parent_hsm_name: &str,
+    duration: Duration,
+    xname_requested_hostlist: &str,
+    dry_run: bool,
+) {
+    // Return expired leases to the parent HSM groups so nodes are available again
+    lease_ops::reclaim_expired_leases(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_group_available_vec,
+        dry_run,
+    )
+    .await;
+
+    // Get list of nodes requested which are members of the parent HSM group
+    let hsm_group_summary: HashMap<String, Vec<String>> =
+        crate::common::node_ops::get_curated_hsm_group_from_hostlist(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            xname_requested_hostlist,
+        )
+        .await;
+
+    let mut xname_to_lease_vec: Vec<String> = hsm_group_summary
+        .get(parent_hsm_name)
+        .cloned()
+        .unwrap_or_default();
+
+    xname_to_lease_vec.sort();
+    xname_to_lease_vec.dedup();
+
+    if xname_to_lease_vec.is_empty() {
+        println!(
+            "No nodes requested are available in parent HSM group '{}'. Exit",
+            parent_hsm_name
+        );
+        std::process::exit(0);
+    }
+
+    // Check nodes are not already leased
+    let lease_vec = lease_ops::get_lease_vec(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+        .unwrap_or_else(|error| {
+            eprintln!("ERROR - Could not fetch leases. Reason:\n{}\nExit", error);
+            std::process::exit(1);
+        });
+
+    let xname_already_leased_vec: Vec<&String> = xname_to_lease_vec
+        .iter()
+        .filter(|xname| lease_vec.iter().any(|lease| lease.members.contains(xname)))
+        .collect();
+
+    if !xname_already_leased_vec.is_empty() {
+        eprintln!(
+            "ERROR - Nodes {:?} are already leased. Exit",
+            xname_already_leased_vec
+        );
+        std::process::exit(1);
+    }
+
+    let now = chrono::Utc::now();
+
+    let lease = Lease {
+        name: Lease::get_name(owner, &now),
+        owner: owner.to_string(),
+        target_hsm_group: target_hsm_name.to_string(),
+        parent_hsm_group: parent_hsm_name.to_string(),
+        created: now.to_rfc3339(),
+        expires: (now + duration).to_rfc3339(),
+        members: xname_to_lease_vec.clone(),
+    };
+
+    if dry_run {
+        println!("dry-run enabled, lease not created:");
+        lease_ops::print_table(&[lease]);
+        return;
+    }
+
+    // Create the HSM group storing the lease. HSM won't allow a node to be member of 2 groups
+    // with the same exclusive group, this prevents 2 operators leasing the same node
+    let lease_description = serde_json::to_string(&Lease {
+        members: Vec::new(),
+        ..lease.clone()
+    })
+    .unwrap();
+
+    if let Err(error) = mesa::hsm::group::http_client::create_new_hsm_group(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &lease.name,
+        &xname_to_lease_vec,
+        LEASE_EXCLUSIVE_GROUP,
+        &lease_description,
+        &[LEASE_TAG.to_string()],
+    )
+    .await
+    {
+        eprintln!(
+            "ERROR - Could not create lease '{}'. Nodes may have been leased by someone else. Reason:\n{}\nExit",
+            lease.name, error
+        );
+        std::process::exit(1);
+    }
+
+    // Move nodes from parent to target HSM group
+    let node_migration_rslt = mesa::hsm::group::utils::migrate_hsm_members(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        target_hsm_name,
+        parent_hsm_name,
+        xname_to_lease_vec
+            .iter()
+            .map(|xname| xname.as_str())
+            .collect(),
+        true,
+    )
+    .await;
+
+    if let Err(error) = node_migration_rslt {
+        eprintln!(
+            "ERROR - Could not move nodes from HSM group '{}' to '{}'. Reason:\n{}\nDeleting lease '{}'",
+            parent_hsm_name, target_hsm_name, error, lease.name
+        );
+
+        let _ = mesa::hsm::group::http_client::delete_hsm_group(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &lease.name,
+        )
+        .await;
+
+        std::process::exit(1);
+    }
+
+    println!("Lease '{}' created", lease.name);
+    lease_ops::print_table(&[lease]);
+}
@@ -0,0 +1,27 @@
+use crate::common::lease_ops;
+
+/// Prints the leases targeting the HSM groups the user has access to
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    target_hsm_group_vec: &[String],
+    output: &str,
+) {
+    let mut lease_vec = lease_ops::get_lease_vec(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+        .unwrap_or_else(|error| {
+            eprintln!("ERROR - Could not fetch leases. Reason:\n{}\nExit", error);
+            std::process::exit(1);
+        });
+
+    lease_vec.retain(|lease| target_hsm_group_vec.contains(&lease.target_hsm_group));
+
+    if output.eq("json") {
+        println!("{}", serde_json::to_string_pretty(&lease_vec).unwrap());
+    } else if lease_vec.is_empty() {
+        println!("No leases found");
+    } else {
+        lease_ops::print_table(&lease_vec);
+    }
+}
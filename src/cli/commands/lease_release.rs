@@ -0,0 +1,63 @@
+use crate::common::lease_ops;
+
+/// Returns the nodes of a lease to its parent HSM group. If no lease name is provided, then all
+/// expired leases of the HSM groups the user has access to are released
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    target_hsm_group_vec: &[String],
+    lease_name_opt: Option<&String>,
+    dry_run: bool,
+) {
+    let Some(lease_name) = lease_name_opt else {
+        lease_ops::reclaim_expired_leases(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            target_hsm_group_vec,
+            dry_run,
+        )
+        .await;
+        return;
+    };
+
+    let lease_vec = lease_ops::get_lease_vec(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+        .unwrap_or_else(|error| {
+            eprintln!("ERROR - Could not fetch leases. Reason:\n{}\nExit", error);
+            std::process::exit(1);
+        });
+
+    let lease = match lease_vec.iter().find(|lease| lease.name.eq(lease_name)) {
+        Some(lease) => lease,
+        None => {
+            eprintln!("ERROR - Lease '{}' not found. Exit", lease_name);
+            std::process::exit(1);
+        }
+    };
+
+    if !target_hsm_group_vec.contains(&lease.target_hsm_group) {
+        eprintln!(
+            "ERROR - Can't access HSM group '{}' related to lease '{}'. Exit",
+            lease.target_hsm_group, lease_name
+        );
+        std::process::exit(1);
+    }
+
+    if let Err(error) = lease_ops::release_lease(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        lease,
+        dry_run,
+    )
+    .await
+    {
+        eprintln!(
+            "ERROR - Could not release lease '{}'. Reason:\n{}\nExit",
+            lease_name, error
+        );
+        std::process::exit(1);
+    }
+}
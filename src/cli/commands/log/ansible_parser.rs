@@ -0,0 +1,411 @@
+use std::{
+    collections::BTreeMap,
+    io::{IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+use comfy_table::{Cell, Color, Table};
+use crossterm::{cursor, terminal, QueueableCommand};
+use serde_json::Value;
+
+/// Minimum time between two redraws of the live status table
+const REDRAW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Max length of the error messages in the summary
+const ERROR_MESSAGE_MAX_LEN: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedTask {
+    pub task: String,
+    pub message: String,
+    pub unreachable: bool,
+}
+
+/// Ansible results for a host (xname)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostStatus {
+    pub ok: u32,
+    pub changed: u32,
+    pub failed: u32,
+    pub unreachable: u32,
+    pub skipped: u32,
+    pub rescued: u32,
+    pub ignored: u32,
+    pub last_task: String,
+    pub failed_task_vec: Vec<FailedTask>,
+}
+
+impl HostStatus {
+    pub fn get_status(&self) -> &'static str {
+        if self.unreachable > 0 {
+            "unreachable"
+        } else if self.failed > 0 {
+            "failed"
+        } else if self.changed > 0 {
+            "changed"
+        } else {
+            "ok"
+        }
+    }
+}
+
+/// Something relevant found in the ansible output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnsibleEvent {
+    Play(String),
+    Task(String),
+    HostResult(String),
+    HostFailed(String, FailedTask),
+    Recap,
+}
+
+/// Parses the output of the ansible default callback line by line and keeps the results per host
+#[derive(Debug, Default)]
+pub struct AnsibleLogParser {
+    pub play: String,
+    pub task: String,
+    pub host_status_map: BTreeMap<String, HostStatus>,
+    in_recap: bool,
+    /// Task results (eg 'fatal: [x1000c0s0b0n0]: FAILED!') followed by '...ignoring'
+    last_failed_host_opt: Option<String>,
+}
+
+impl AnsibleLogParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse_line(&mut self, line: &str) -> Option<AnsibleEvent> {
+        let line = line.trim_end();
+
+        if let Some(play) = get_header(line, "PLAY [") {
+            self.in_recap = false;
+            self.play = play.clone();
+            return Some(AnsibleEvent::Play(play));
+        }
+
+        if line.starts_with("PLAY RECAP") {
+            self.in_recap = true;
+            return Some(AnsibleEvent::Recap);
+        }
+
+        if let Some(task) =
+            get_header(line, "TASK [").or_else(|| get_header(line, "RUNNING HANDLER ["))
+        {
+            self.in_recap = false;
+            self.task = task.clone();
+            return Some(AnsibleEvent::Task(task));
+        }
+
+        if line.trim_start().starts_with("...ignoring") {
+            // Previous failure ignored by 'ignore_errors'
+            if let Some(host) = self.last_failed_host_opt.take() {
+                let host_status = self.host_status_map.entry(host.clone()).or_default();
+                host_status.failed = host_status.failed.saturating_sub(1);
+                host_status.ignored += 1;
+                host_status.failed_task_vec.pop();
+                return Some(AnsibleEvent::HostResult(host));
+            }
+            return None;
+        }
+
+        if self.in_recap {
+            return self.parse_recap_line(line);
+        }
+
+        self.parse_result_line(line)
+    }
+
+    /// Parses lines like 'ok: [x1000c0s0b0n0]', 'changed: [x1000c0s0b0n0] => (item=foo)' or
+    /// 'fatal: [x1000c0s0b0n0]: FAILED! => {"msg": "error"}'
+    fn parse_result_line(&mut self, line: &str) -> Option<AnsibleEvent> {
+        let (result, rest) = line.split_once(": [")?;
+
+        if !["ok", "changed", "skipping", "failed", "fatal", "rescued"].contains(&result) {
+            return None;
+        }
+
+        let (host, rest) = rest.split_once(']')?;
+        let host = host.split(" -> ").next().unwrap_or(host).to_string();
+
+        // Loop items are counted once per task by the PLAY RECAP, we count them once per item
+        let host_status = self.host_status_map.entry(host.clone()).or_default();
+        host_status.last_task = self.task.clone();
+
+        match result {
+            "ok" => host_status.ok += 1,
+            "changed" => host_status.changed += 1,
+            "skipping" => host_status.skipped += 1,
+            "rescued" => host_status.rescued += 1,
+            _ => {
+                let unreachable = rest.contains("UNREACHABLE!");
+
+                if unreachable {
+                    host_status.unreachable += 1;
+                } else {
+                    host_status.failed += 1;
+                }
+
+                let failed_task = FailedTask {
+                    task: self.task.clone(),
+                    message: get_error_message(rest),
+                    unreachable,
+                };
+
+                host_status.failed_task_vec.push(failed_task.clone());
+
+                self.last_failed_host_opt = Some(host.clone());
+
+                return Some(AnsibleEvent::HostFailed(host, failed_task));
+            }
+        }
+
+        self.last_failed_host_opt = None;
+
+        Some(AnsibleEvent::HostResult(host))
+    }
+
+    /// Parses lines like 'x1000c0s0b0n0 : ok=3 changed=1 unreachable=0 failed=0 skipped=2
+    /// rescued=0 ignored=0'. PLAY RECAP counters replace the ones counted while parsing tasks
+    fn parse_recap_line(&mut self, line: &str) -> Option<AnsibleEvent> {
+        let (host, counters) = line.split_once(" : ")?;
+        let host = host.trim().to_string();
+
+        let host_status = self.host_status_map.entry(host.clone()).or_default();
+
+        for counter in counters.split_whitespace() {
+            let Some((name, value)) = counter.split_once('=') else {
+                continue;
+            };
+
+            let Ok(value) = value.parse::<u32>() else {
+                continue;
+            };
+
+            match name {
+                "ok" => host_status.ok = value,
+                "changed" => host_status.changed = value,
+                "unreachable" => host_status.unreachable = value,
+                "failed" => host_status.failed = value,
+                "skipped" => host_status.skipped = value,
+                "rescued" => host_status.rescued = value,
+                "ignored" => host_status.ignored = value,
+                _ => {}
+            }
+        }
+
+        Some(AnsibleEvent::HostResult(host))
+    }
+}
+
+/// Returns the name in headers like 'TASK [common : install packages] *****'
+fn get_header(line: &str, prefix: &str) -> Option<String> {
+    let header = line.strip_prefix(prefix)?;
+    let end = header.rfind(']')?;
+
+    Some(header[..end].to_string())
+}
+
+/// Returns the error message in a failed task result, eg 'FAILED! => {"msg": "error"}'
+pub fn get_error_message(result: &str) -> String {
+    let message = match result.split_once("=> ") {
+        Some((_, result_json)) => match serde_json::from_str::<Value>(result_json) {
+            Ok(result_value) => ["msg", "stderr", "module_stderr", "reason"]
+                .iter()
+                .filter_map(|field| result_value[field].as_str())
+                .find(|message| !message.trim().is_empty())
+                .map(|message| message.trim().to_string())
+                .unwrap_or(result_json.to_string()),
+            Err(_) => result_json.to_string(),
+        },
+        None => result.trim_start_matches(':').trim().to_string(),
+    };
+
+    if message.chars().count() > ERROR_MESSAGE_MAX_LEN {
+        format!(
+            "{}...",
+            message
+                .chars()
+                .take(ERROR_MESSAGE_MAX_LEN)
+                .collect::<String>()
+        )
+    } else {
+        message
+    }
+}
+
+fn get_status_color(status: &str) -> Color {
+    match status {
+        "ok" => Color::Green,
+        "changed" => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+pub fn get_status_table(host_status_map: &BTreeMap<String, HostStatus>) -> Table {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Xname",
+        "Status",
+        "Ok",
+        "Changed",
+        "Failed",
+        "Unreachable",
+        "Skipped",
+        "Last task",
+    ]);
+
+    for (host, host_status) in host_status_map {
+        let status = host_status.get_status();
+
+        table.add_row(vec![
+            Cell::new(host),
+            Cell::new(status).fg(get_status_color(status)),
+            Cell::new(host_status.ok),
+            Cell::new(host_status.changed),
+            Cell::new(host_status.failed),
+            Cell::new(host_status.unreachable),
+            Cell::new(host_status.skipped),
+            Cell::new(&host_status.last_task),
+        ]);
+    }
+
+    table
+}
+
+/// Prints the PLAY RECAP with the tasks failed and their error message per node
+pub fn print_summary(host_status_map: &BTreeMap<String, HostStatus>) {
+    println!("\nPLAY RECAP");
+
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Xname",
+        "Status",
+        "Ok",
+        "Changed",
+        "Failed",
+        "Unreachable",
+        "Skipped",
+        "Rescued",
+        "Ignored",
+        "Failed tasks",
+    ]);
+
+    for (host, host_status) in host_status_map {
+        let status = host_status.get_status();
+
+        let failed_tasks = host_status
+            .failed_task_vec
+            .iter()
+            .map(|failed_task| format!("{}:\n  {}", failed_task.task, failed_task.message))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        table.add_row(vec![
+            Cell::new(host),
+            Cell::new(status).fg(get_status_color(status)),
+            Cell::new(host_status.ok),
+            Cell::new(host_status.changed),
+            Cell::new(host_status.failed),
+            Cell::new(host_status.unreachable),
+            Cell::new(host_status.skipped),
+            Cell::new(host_status.rescued),
+            Cell::new(host_status.ignored),
+            Cell::new(failed_tasks),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+/// Prints ansible output as a live status table per host. Only plays, tasks and failures are
+/// printed above the table. If stdout is not a terminal, the table is not printed
+#[derive(Default)]
+pub struct AnsibleLogPrinter {
+    pub parser: AnsibleLogParser,
+    table_line_count: u16,
+    last_redraw_opt: Option<Instant>,
+}
+
+impl AnsibleLogPrinter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn process_line(&mut self, line: &str) {
+        let Some(ansible_event) = self.parser.parse_line(line) else {
+            return;
+        };
+
+        match ansible_event {
+            AnsibleEvent::Play(play) => {
+                self.clear_table();
+                println!("PLAY [{}]", play);
+                self.draw_table();
+            }
+            AnsibleEvent::Task(task) => {
+                self.clear_table();
+                println!("  TASK [{}]", task);
+                self.draw_table();
+            }
+            AnsibleEvent::HostFailed(host, failed_task) => {
+                self.clear_table();
+                eprintln!(
+                    "    {} {}: {}",
+                    if failed_task.unreachable {
+                        "UNREACHABLE"
+                    } else {
+                        "FAILED"
+                    },
+                    host,
+                    failed_task.message
+                );
+                self.draw_table();
+            }
+            AnsibleEvent::HostResult(_) => {
+                if self
+                    .last_redraw_opt
+                    .is_none_or(|last_redraw| last_redraw.elapsed() > REDRAW_INTERVAL)
+                {
+                    self.clear_table();
+                    self.draw_table();
+                }
+            }
+            AnsibleEvent::Recap => {}
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.clear_table();
+        print_summary(&self.parser.host_status_map);
+    }
+
+    fn clear_table(&mut self) {
+        if self.table_line_count == 0 {
+            return;
+        }
+
+        let mut stdout = std::io::stdout();
+
+        let _ = stdout.queue(cursor::MoveUp(self.table_line_count));
+        let _ = stdout.queue(terminal::Clear(terminal::ClearType::FromCursorDown));
+        let _ = stdout.flush();
+
+        self.table_line_count = 0;
+    }
+
+    fn draw_table(&mut self) {
+        if !std::io::stdout().is_terminal() || self.parser.host_status_map.is_empty() {
+            return;
+        }
+
+        let table = get_status_table(&self.parser.host_status_map).to_string();
+
+        println!("{}", table);
+
+        self.table_line_count = table.lines().count() as u16;
+        self.last_redraw_opt = Some(Instant::now());
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use config::Config;
+use mesa::{cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, ims::s3};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::common::config_ops;
+
+use super::ansible_parser::AnsibleLogParser;
+
+/// Name of the file with the details of the sessions archived
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// Prefix for the objects in the S3 bucket
+const S3_PREFIX: &str = "manta/cfs-session-logs";
+
+/// CFS session logs archived
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedSession {
+    pub cfs_session_name: String,
+    pub configuration_name: Option<String>,
+    pub hsm_group_vec: Vec<String>,
+    /// Nodes in the ansible limit and nodes found in the logs
+    pub xname_vec: Vec<String>,
+    pub start_time: DateTime<Utc>,
+    pub archived_at: DateTime<Utc>,
+    pub log_file_name: String,
+}
+
+/// Line matching a search in the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub cfs_session_name: String,
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// Local directory (and optionally S3 bucket) where CFS session logs are archived while
+/// streaming. Configured in the 'log_archive' section of the configuration file, eg:
+///
+/// [log_archive]
+/// enabled = true
+/// dir = "/home/user/cfs_session_logs"
+/// s3_bucket = "manta"
+#[derive(Debug, Clone)]
+pub struct LogArchive {
+    pub enabled: bool,
+    pub dir: PathBuf,
+    pub s3_bucket_opt: Option<String>,
+}
+
+impl LogArchive {
+    pub fn from_settings(settings: &Config) -> Self {
+        LogArchive {
+            enabled: settings.get_bool("log_archive.enabled").unwrap_or(false),
+            dir: settings
+                .get_string("log_archive.dir")
+                .map(PathBuf::from)
+                .unwrap_or(config_ops::get_default_cfs_session_log_archive_dir_path()),
+            s3_bucket_opt: settings.get_string("log_archive.s3_bucket").ok(),
+        }
+    }
+
+    fn get_index_file_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE_NAME)
+    }
+
+    /// Returns the sessions archived, empty if the archive does not exists
+    pub fn read_index(&self) -> Result<Vec<ArchivedSession>, String> {
+        read_index_file(&self.get_index_file_path())
+    }
+
+    /// Adds or replaces a session in the index
+    pub fn update_index(&self, archived_session: &ArchivedSession) -> Result<(), String> {
+        let mut archived_session_vec = self.read_index()?;
+
+        merge_index(&mut archived_session_vec, &[archived_session.clone()]);
+
+        self.write_index(&archived_session_vec)
+    }
+
+    fn write_index(&self, archived_session_vec: &[ArchivedSession]) -> Result<(), String> {
+        let index_content = serde_json::to_string_pretty(archived_session_vec)
+            .map_err(|error| format!("Could not serialize log archive index: {}", error))?;
+
+        std::fs::write(self.get_index_file_path(), index_content)
+            .map_err(|error| format!("Could not write log archive index: {}", error))
+    }
+
+    /// Creates the file to archive the logs of a CFS session. Returns None if the log archive is
+    /// not enabled
+    pub fn create_writer(
+        &self,
+        cfs_session: &CfsSessionGetResponse,
+    ) -> Result<Option<LogArchiveWriter>, String> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|error| format!("Could not create log archive directory: {}", error))?;
+
+        let cfs_session_name = cfs_session.name.clone().unwrap_or_default();
+
+        let log_file_name = format!("{}.log", cfs_session_name);
+
+        // Logs are streamed to a temporary file so an existing archive is only replaced once
+        // new logs are received
+        let partial_file_path = self.dir.join(format!("{}.partial", log_file_name));
+
+        let file = File::create(&partial_file_path)
+            .map_err(|error| format!("Could not create log archive file: {}", error))?;
+
+        let xname_vec = cfs_session
+            .ansible
+            .as_ref()
+            .and_then(|ansible| ansible.limit.clone())
+            .map(|limit| {
+                limit
+                    .split(',')
+                    .map(|xname| xname.trim().to_string())
+                    .filter(|xname| !xname.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let now = Utc::now();
+
+        let archived_session = ArchivedSession {
+            cfs_session_name,
+            configuration_name: cfs_session.get_configuration_name(),
+            hsm_group_vec: cfs_session.get_target_hsm().unwrap_or_default(),
+            xname_vec,
+            start_time: cfs_session
+                .get_start_time()
+                .and_then(|start_time| start_time.parse::<DateTime<Utc>>().ok())
+                .unwrap_or(now),
+            archived_at: now,
+            log_file_name,
+        };
+
+        Ok(Some(LogArchiveWriter {
+            file: BufWriter::new(file),
+            partial_file_path,
+            line_count: 0,
+            archived_session,
+            parser: AnsibleLogParser::new(),
+        }))
+    }
+
+    /// Uploads the logs of a session and the index to the S3 bucket. The index in the bucket
+    /// is merged with the local one so sessions archived by other users are not lost
+    pub async fn upload(
+        &self,
+        shasta_token: &str,
+        shasta_base_url: &str,
+        shasta_root_cert: &[u8],
+        archived_session: &ArchivedSession,
+    ) -> Result<(), String> {
+        let Some(s3_bucket) = &self.s3_bucket_opt else {
+            return Ok(());
+        };
+
+        let sts_value = s3::s3_auth(shasta_token, shasta_base_url, shasta_root_cert)
+            .await
+            .map_err(|error| format!("Could not authenticate against S3: {}", error))?;
+
+        let mut archived_session_vec = self.download_index(&sts_value, s3_bucket).await;
+
+        merge_index(&mut archived_session_vec, &[archived_session.clone()]);
+
+        let upload_dir = tempfile_dir()?;
+        let index_file_path = upload_dir.join(INDEX_FILE_NAME);
+
+        std::fs::write(
+            &index_file_path,
+            serde_json::to_string_pretty(&archived_session_vec)
+                .map_err(|error| format!("Could not serialize log archive index: {}", error))?,
+        )
+        .map_err(|error| format!("Could not write log archive index: {}", error))?;
+
+        s3::s3_upload_object(
+            &sts_value,
+            &format!("{}/{}", S3_PREFIX, archived_session.log_file_name),
+            s3_bucket,
+            &self
+                .dir
+                .join(&archived_session.log_file_name)
+                .to_string_lossy(),
+        )
+        .await
+        .map_err(|error| format!("Could not upload logs to S3: {}", error))?;
+
+        s3::s3_upload_object(
+            &sts_value,
+            &format!("{}/{}", S3_PREFIX, INDEX_FILE_NAME),
+            s3_bucket,
+            &index_file_path.to_string_lossy(),
+        )
+        .await
+        .map_err(|error| format!("Could not upload log archive index to S3: {}", error))?;
+
+        let _ = std::fs::remove_dir_all(upload_dir);
+
+        Ok(())
+    }
+
+    /// Downloads the sessions in the S3 bucket missing in the local archive
+    pub async fn sync(
+        &self,
+        shasta_token: &str,
+        shasta_base_url: &str,
+        shasta_root_cert: &[u8],
+    ) -> Result<(), String> {
+        let Some(s3_bucket) = &self.s3_bucket_opt else {
+            return Ok(());
+        };
+
+        let sts_value = s3::s3_auth(shasta_token, shasta_base_url, shasta_root_cert)
+            .await
+            .map_err(|error| format!("Could not authenticate against S3: {}", error))?;
+
+        let s3_archived_session_vec = self.download_index(&sts_value, s3_bucket).await;
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|error| format!("Could not create log archive directory: {}", error))?;
+
+        let mut archived_session_vec = self.read_index()?;
+
+        for s3_archived_session in s3_archived_session_vec {
+            if self.dir.join(&s3_archived_session.log_file_name).exists() {
+                continue;
+            }
+
+            log::info!(
+                "Downloading logs for CFS session '{}'",
+                s3_archived_session.cfs_session_name
+            );
+
+            if let Err(error) = s3::s3_download_object(
+                &sts_value,
+                &format!("{}/{}", S3_PREFIX, s3_archived_session.log_file_name),
+                s3_bucket,
+                &self.dir.to_string_lossy(),
+            )
+            .await
+            {
+                log::warn!(
+                    "Could not download logs for CFS session '{}': {}",
+                    s3_archived_session.cfs_session_name,
+                    error
+                );
+                continue;
+            }
+
+            merge_index(&mut archived_session_vec, &[s3_archived_session]);
+        }
+
+        self.write_index(&archived_session_vec)
+    }
+
+    /// Returns the index in the S3 bucket, empty if it does not exists
+    async fn download_index(
+        &self,
+        sts_value: &serde_json::Value,
+        s3_bucket: &str,
+    ) -> Vec<ArchivedSession> {
+        let Ok(download_dir) = tempfile_dir() else {
+            return Vec::new();
+        };
+
+        let archived_session_vec = match s3::s3_download_object(
+            sts_value,
+            &format!("{}/{}", S3_PREFIX, INDEX_FILE_NAME),
+            s3_bucket,
+            &download_dir.to_string_lossy(),
+        )
+        .await
+        {
+            Ok(index_file_path) => read_index_file(Path::new(&index_file_path)).unwrap_or_default(),
+            Err(error) => {
+                log::info!("Log archive index not found in S3: {}", error);
+                Vec::new()
+            }
+        };
+
+        let _ = std::fs::remove_dir_all(download_dir);
+
+        archived_session_vec
+    }
+}
+
+/// Archives the logs of a CFS session line by line
+pub struct LogArchiveWriter {
+    file: BufWriter<File>,
+    partial_file_path: PathBuf,
+    line_count: usize,
+    archived_session: ArchivedSession,
+    parser: AnsibleLogParser,
+}
+
+impl LogArchiveWriter {
+    pub fn write_line(&mut self, line: &str) {
+        if let Err(error) = writeln!(self.file, "{}", line) {
+            log::warn!("Could not write logs to archive: {}", error);
+        }
+
+        self.line_count += 1;
+
+        self.parser.parse_line(line);
+    }
+
+    /// Flushes the logs and adds the session to the index. Returns None if no logs were
+    /// received, the archive of the session, if any, is left untouched
+    pub fn finish(mut self, log_archive: &LogArchive) -> Result<Option<ArchivedSession>, String> {
+        self.file
+            .flush()
+            .map_err(|error| format!("Could not write logs to archive: {}", error))?;
+
+        drop(self.file);
+
+        if self.line_count == 0 {
+            let _ = std::fs::remove_file(&self.partial_file_path);
+
+            return Ok(None);
+        }
+
+        std::fs::rename(
+            &self.partial_file_path,
+            log_archive.dir.join(&self.archived_session.log_file_name),
+        )
+        .map_err(|error| format!("Could not write logs to archive: {}", error))?;
+
+        let xname_set: BTreeSet<String> = self
+            .archived_session
+            .xname_vec
+            .drain(..)
+            .chain(self.parser.host_status_map.into_keys())
+            .collect();
+
+        self.archived_session.xname_vec = xname_set.into_iter().collect();
+
+        log_archive.update_index(&self.archived_session)?;
+
+        Ok(Some(self.archived_session))
+    }
+}
+
+fn read_index_file(index_file_path: &Path) -> Result<Vec<ArchivedSession>, String> {
+    if !index_file_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let index_content = std::fs::read_to_string(index_file_path)
+        .map_err(|error| format!("Could not read log archive index: {}", error))?;
+
+    serde_json::from_str(&index_content)
+        .map_err(|error| format!("Could not parse log archive index: {}", error))
+}
+
+fn tempfile_dir() -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("manta-log-archive-{}", uuid::Uuid::new_v4()));
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|error| format!("Could not create temporary directory: {}", error))?;
+
+    Ok(dir)
+}
+
+/// Adds new sessions to the index and replaces the ones already there. Sessions are sorted by
+/// start time
+pub fn merge_index(
+    archived_session_vec: &mut Vec<ArchivedSession>,
+    new_archived_session_vec: &[ArchivedSession],
+) {
+    for new_archived_session in new_archived_session_vec {
+        archived_session_vec.retain(|archived_session| {
+            archived_session.cfs_session_name != new_archived_session.cfs_session_name
+        });
+
+        archived_session_vec.push(new_archived_session.clone());
+    }
+
+    archived_session_vec.sort_by_key(|archived_session| archived_session.start_time);
+}
+
+/// Returns the sessions matching the filters. Sessions not targeting any HSM group (eg
+/// ansible limit only) are only visible if all their nodes are members of the HSM groups
+/// available
+pub fn filter_index<'a>(
+    archived_session_vec: &'a [ArchivedSession],
+    hsm_group_available_vec: &[String],
+    hsm_member_available_vec: &[String],
+    cfs_session_name_opt: Option<&String>,
+    configuration_name_opt: Option<&String>,
+    xname_opt: Option<&String>,
+    since_opt: Option<DateTime<Utc>>,
+) -> Vec<&'a ArchivedSession> {
+    archived_session_vec
+        .iter()
+        .filter(|archived_session| {
+            if archived_session.hsm_group_vec.is_empty() {
+                !archived_session.xname_vec.is_empty()
+                    && archived_session
+                        .xname_vec
+                        .iter()
+                        .all(|xname| hsm_member_available_vec.contains(xname))
+            } else {
+                archived_session
+                    .hsm_group_vec
+                    .iter()
+                    .any(|hsm_group| hsm_group_available_vec.contains(hsm_group))
+            }
+        })
+        .filter(|archived_session| {
+            cfs_session_name_opt.is_none_or(|cfs_session_name| {
+                &archived_session.cfs_session_name == cfs_session_name
+            })
+        })
+        .filter(|archived_session| {
+            configuration_name_opt.is_none_or(|configuration_name| {
+                archived_session.configuration_name.as_ref() == Some(configuration_name)
+            })
+        })
+        .filter(|archived_session| {
+            xname_opt.is_none_or(|xname| archived_session.xname_vec.contains(xname))
+        })
+        .filter(|archived_session| {
+            since_opt.is_none_or(|since| archived_session.start_time >= since)
+        })
+        .collect()
+}
+
+/// Returns the lines matching the regex in the logs of an archived session. If an xname is
+/// provided, only lines mentioning it are returned
+pub fn search_log_file(
+    log_file_path: &Path,
+    cfs_session_name: &str,
+    regex: &Regex,
+    xname_opt: Option<&String>,
+) -> Result<Vec<SearchMatch>, String> {
+    let file = File::open(log_file_path)
+        .map_err(|error| format!("Could not open {}: {}", log_file_path.display(), error))?;
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .filter(|(_, line)| xname_opt.is_none_or(|xname| line.contains(xname.as_str())))
+        .map(|(index, line)| SearchMatch {
+            cfs_session_name: cfs_session_name.to_string(),
+            line_number: index + 1,
+            line,
+        })
+        .collect())
+}
@@ -0,0 +1,471 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use chrono::Utc;
+use futures::TryStreamExt;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{api::ListParams, Api};
+use mesa::{cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, common::kubernetes, hsm};
+use regex::Regex;
+
+use crate::common::{self, time_ops, vault::http_client::fetch_shasta_k8s_secrets};
+
+use super::{
+    ansible_parser::AnsibleLogPrinter,
+    archive::{self, LogArchive, LogArchiveWriter},
+    containers::{self, ContainerLogOptions},
+};
+
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    hsm_name_vec: &[String],
+    session_name_opt: Option<&String>,
+    hsm_group_config: Option<&String>,
+    structured: bool,
+    log_archive: &LogArchive,
+    container_log_options_opt: Option<ContainerLogOptions>,
+) {
+    // Get CFS sessions
+    let cfs_sessions_vec_opt = mesa::cfs::session::mesa::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        None,
+        None,
+        None,
+        session_name_opt,
+        None,
+    )
+    .await;
+
+    let mut cfs_sessions_vec = match cfs_sessions_vec_opt {
+        Ok(cfs_sessions_resp) => cfs_sessions_resp,
+        Err(_)
+            if print_archived_cfs_session_logs(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                hsm_name_vec,
+                session_name_opt,
+                structured,
+                log_archive,
+            )
+            .await =>
+        {
+            return;
+        }
+        Err(error) => {
+            eprintln!(
+                "ERROR: CFS session '{}' not found.\nReason: {:#?}\nExit",
+                session_name_opt.unwrap(),
+                error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    mesa::cfs::session::mesa::utils::filter_by_hsm(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &mut cfs_sessions_vec,
+        hsm_name_vec,
+        Some(&1),
+    )
+    .await;
+
+    if cfs_sessions_vec.is_empty() {
+        println!("No CFS session found");
+        std::process::exit(0);
+    }
+
+    // Check HSM group in configurarion file can access CFS session
+    hsm::group::utils::validate_config_hsm_group_and_hsm_group_accessed(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_group_config,
+        session_name_opt,
+        &cfs_sessions_vec,
+    )
+    .await;
+
+    let shasta_k8s_secrets =
+        fetch_shasta_k8s_secrets(vault_base_url, vault_secret_path, vault_role_id).await;
+
+    log::info!(
+        "Get logs for CFS session:\n{}",
+        common::cfs_session_utils::get_table_struct(&cfs_sessions_vec)
+    );
+
+    let client = kubernetes::get_k8s_client_programmatically(k8s_api_url, shasta_k8s_secrets)
+        .await
+        .unwrap();
+
+    let cfs_session = cfs_sessions_vec.first().unwrap();
+
+    // Pod of finished sessions may have been garbage collected, use the log archive instead
+    if container_log_options_opt.is_none()
+        && is_cfs_session_complete(cfs_session)
+        && !is_cfs_session_pod_available(client.clone(), cfs_session.name.as_ref().unwrap()).await
+        && print_archived_cfs_session_logs(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_name_vec,
+            cfs_session.name.as_ref(),
+            structured,
+            log_archive,
+        )
+        .await
+    {
+        return;
+    }
+
+    let log_rslt = if let Some(container_log_options) = container_log_options_opt {
+        containers::print_cfs_session_container_logs(
+            client,
+            cfs_sessions_vec.first().unwrap().name.as_ref().unwrap(),
+            &container_log_options,
+        )
+        .await
+    } else {
+        print_cfs_session_logs(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            client,
+            cfs_sessions_vec.first().unwrap(),
+            structured,
+            log_archive,
+        )
+        .await
+    };
+
+    if let Err(e) = log_rslt {
+        eprintln!("ERROR - {e}. Exit");
+        std::process::exit(1);
+    }
+}
+
+/// Prints the logs of a CFS session, ansible logs are printed through AnsibleLogPrinter if
+/// structured. Logs are archived while streaming if the log archive is enabled
+pub async fn print_cfs_session_logs(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    client: kube::Client,
+    cfs_session: &CfsSessionGetResponse,
+    structured: bool,
+    log_archive: &LogArchive,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let cfs_session_name = cfs_session.name.as_ref().unwrap();
+
+    let mut log_archive_writer_opt = match log_archive.create_writer(cfs_session) {
+        Ok(log_archive_writer_opt) => log_archive_writer_opt,
+        Err(error) => {
+            log::warn!("{}. Logs won't be archived", error);
+            None
+        }
+    };
+
+    if !structured && log_archive_writer_opt.is_none() {
+        return kubernetes::print_cfs_session_logs(client, cfs_session_name).await;
+    }
+
+    let log_rslt = stream_cfs_session_logs(
+        client,
+        cfs_session_name,
+        structured,
+        log_archive_writer_opt.as_mut(),
+    )
+    .await;
+
+    // Archive whatever we got even if the stream broke
+    if let Some(log_archive_writer) = log_archive_writer_opt {
+        match log_archive_writer.finish(log_archive) {
+            Ok(None) => log::info!(
+                "No logs received for CFS session '{}', log archive not updated",
+                cfs_session_name
+            ),
+            Ok(Some(archived_session)) => {
+                log::info!(
+                    "Logs for CFS session '{}' archived in {}",
+                    cfs_session_name,
+                    log_archive.dir.display()
+                );
+
+                if let Err(error) = log_archive
+                    .upload(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        &archived_session,
+                    )
+                    .await
+                {
+                    log::warn!("{}", error);
+                }
+            }
+            Err(error) => log::warn!("{}", error),
+        }
+    }
+
+    log_rslt
+}
+
+fn is_cfs_session_complete(cfs_session: &CfsSessionGetResponse) -> bool {
+    cfs_session
+        .status
+        .as_ref()
+        .and_then(|status| status.session.as_ref())
+        .and_then(|session| session.status.as_deref())
+        == Some("complete")
+}
+
+async fn is_cfs_session_pod_available(client: kube::Client, cfs_session_name: &str) -> bool {
+    let pods_api: Api<Pod> = Api::namespaced(client, "services");
+
+    let params = ListParams::default()
+        .limit(1)
+        .labels(&format!("cfsession={}", cfs_session_name));
+
+    // Assume the pod is there if we can't check it
+    match pods_api.list(&params).await {
+        Ok(pod_list) => !pod_list.items.is_empty(),
+        Err(_) => true,
+    }
+}
+
+/// Prints the logs of a CFS session from the log archive. Returns false if the session is not
+/// in the archive or the user has no access to it
+async fn print_archived_cfs_session_logs(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_name_vec: &[String],
+    cfs_session_name_opt: Option<&String>,
+    structured: bool,
+    log_archive: &LogArchive,
+) -> bool {
+    if cfs_session_name_opt.is_none() {
+        return false;
+    }
+
+    // Get sessions archived by other users
+    if let Err(error) = log_archive
+        .sync(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+    {
+        log::warn!("{}", error);
+    }
+
+    let Ok(archived_session_vec) = log_archive.read_index() else {
+        return false;
+    };
+
+    let hsm_member_vec = hsm::group::utils::get_member_vec_from_hsm_name_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_name_vec.to_vec(),
+    )
+    .await;
+
+    let Some(archived_session) = archive::filter_index(
+        &archived_session_vec,
+        hsm_name_vec,
+        &hsm_member_vec,
+        cfs_session_name_opt,
+        None,
+        None,
+        None,
+    )
+    .into_iter()
+    .next() else {
+        return false;
+    };
+
+    let log_file_path = log_archive.dir.join(&archived_session.log_file_name);
+
+    let Ok(file) = File::open(&log_file_path) else {
+        return false;
+    };
+
+    eprintln!(
+        "Pod for CFS session '{}' not available. Showing logs from log archive '{}'",
+        archived_session.cfs_session_name,
+        log_file_path.display()
+    );
+
+    let mut ansible_log_printer_opt = structured.then(AnsibleLogPrinter::new);
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        match ansible_log_printer_opt.as_mut() {
+            Some(ansible_log_printer) => ansible_log_printer.process_line(&line),
+            None => println!("{}", line),
+        }
+    }
+
+    if let Some(mut ansible_log_printer) = ansible_log_printer_opt {
+        ansible_log_printer.finish();
+    }
+
+    true
+}
+
+async fn stream_cfs_session_logs(
+    client: kube::Client,
+    cfs_session_name: &str,
+    structured: bool,
+    mut log_archive_writer_opt: Option<&mut LogArchiveWriter>,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let mut git_clone_logs_stream = kubernetes::get_cfs_session_container_git_clone_logs_stream(
+        client.clone(),
+        cfs_session_name,
+    )
+    .await?;
+
+    while let Some(line) = git_clone_logs_stream.try_next().await? {
+        println!("{}", line);
+
+        if let Some(log_archive_writer) = log_archive_writer_opt.as_mut() {
+            log_archive_writer.write_line(&line);
+        }
+    }
+
+    let mut ansible_logs_stream =
+        kubernetes::get_cfs_session_container_ansible_logs_stream(client, cfs_session_name).await?;
+
+    let mut ansible_log_printer_opt = structured.then(AnsibleLogPrinter::new);
+
+    while let Some(line) = ansible_logs_stream.try_next().await? {
+        match ansible_log_printer_opt.as_mut() {
+            Some(ansible_log_printer) => ansible_log_printer.process_line(&line),
+            None => println!("{}", line),
+        }
+
+        if let Some(log_archive_writer) = log_archive_writer_opt.as_mut() {
+            log_archive_writer.write_line(&line);
+        }
+    }
+
+    if let Some(mut ansible_log_printer) = ansible_log_printer_opt {
+        ansible_log_printer.finish();
+    }
+
+    Ok(())
+}
+
+/// Searches the logs of the CFS sessions in the log archive
+pub async fn exec_search(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    hsm_group_available_vec: &[String],
+    log_archive: &LogArchive,
+    pattern: &str,
+    cfs_session_name_opt: Option<&String>,
+    configuration_name_opt: Option<&String>,
+    xname_opt: Option<&String>,
+    since_opt: Option<&String>,
+) {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Invalid search pattern '{}': {}. Exit",
+                pattern, error
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let since_opt = since_opt.map(|since| match time_ops::parse_duration(since) {
+        Some(duration) => Utc::now() - duration,
+        None => {
+            eprintln!(
+                "ERROR - Invalid duration '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                since
+            );
+            std::process::exit(1);
+        }
+    });
+
+    // Get sessions archived by other users
+    if let Err(error) = log_archive
+        .sync(shasta_token, shasta_base_url, shasta_root_cert)
+        .await
+    {
+        log::warn!("{}", error);
+    }
+
+    let archived_session_vec = match log_archive.read_index() {
+        Ok(archived_session_vec) => archived_session_vec,
+        Err(error) => {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        }
+    };
+
+    let hsm_member_available_vec = hsm::group::utils::get_member_vec_from_hsm_name_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        hsm_group_available_vec.to_vec(),
+    )
+    .await;
+
+    let archived_session_filtered_vec = archive::filter_index(
+        &archived_session_vec,
+        hsm_group_available_vec,
+        &hsm_member_available_vec,
+        cfs_session_name_opt,
+        configuration_name_opt,
+        xname_opt,
+        since_opt,
+    );
+
+    if archived_session_filtered_vec.is_empty() {
+        println!("No CFS session found in log archive");
+        std::process::exit(0);
+    }
+
+    let mut match_count = 0;
+
+    for archived_session in archived_session_filtered_vec {
+        let search_match_vec = match archive::search_log_file(
+            &log_archive.dir.join(&archived_session.log_file_name),
+            &archived_session.cfs_session_name,
+            &regex,
+            xname_opt,
+        ) {
+            Ok(search_match_vec) => search_match_vec,
+            Err(error) => {
+                log::warn!("{}", error);
+                continue;
+            }
+        };
+
+        for search_match in &search_match_vec {
+            println!(
+                "{}:{}: {}",
+                search_match.cfs_session_name, search_match.line_number, search_match.line
+            );
+        }
+
+        match_count += search_match_vec.len();
+    }
+
+    if match_count == 0 {
+        println!("No match found");
+    }
+}
@@ -0,0 +1,419 @@
+use std::{io::IsTerminal, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures::{AsyncBufReadExt, TryStreamExt};
+use k8s_openapi::api::core::v1::{ContainerState, Pod};
+use kube::{
+    api::{ListParams, LogParams},
+    Api,
+};
+use termion::color;
+use tokio::sync::mpsc;
+
+/// Containers followed when no container is selected
+pub const DEFAULT_CONTAINER_VEC: [&str; 2] = ["git-clone", "ansible"];
+
+/// Number of times we try to reconnect to a container log stream before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Colors used for the container prefixes
+const PREFIX_COLOR_VEC: [u8; 6] = [6, 2, 3, 5, 4, 1];
+
+pub struct ContainerLogOptions {
+    /// Containers to follow, all containers in the pod if empty
+    pub container_vec: Vec<String>,
+    pub since_seconds_opt: Option<i64>,
+    pub tail_lines_opt: Option<i64>,
+    pub timestamps: bool,
+}
+
+/// Log line coming from a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerLogLine {
+    pub container: String,
+    pub timestamp_opt: Option<DateTime<Utc>>,
+    pub line: String,
+}
+
+/// Tracks the lines sent so far to skip the ones sent again by the log stream after
+/// reconnecting. Lines sharing the last timestamp are counted since 'since_time' returns all of
+/// them again, including the ones not sent yet
+#[derive(Debug, Default)]
+pub struct LogResume {
+    last_timestamp_opt: Option<DateTime<Utc>>,
+    /// Lines sent with the last timestamp
+    last_timestamp_line_count: usize,
+    /// Lines with the last timestamp received since the log stream was (re)opened
+    last_timestamp_line_seen: usize,
+}
+
+impl LogResume {
+    /// Resets the lines seen when the log stream is reopened
+    pub fn reconnect(&mut self) {
+        self.last_timestamp_line_seen = 0;
+    }
+
+    /// Returns true if the line was not sent before. Lines without timestamp are always sent
+    pub fn is_new_line(&mut self, timestamp_opt: Option<DateTime<Utc>>) -> bool {
+        let Some(timestamp) = timestamp_opt else {
+            return true;
+        };
+
+        match self.last_timestamp_opt {
+            Some(last_timestamp) if timestamp < last_timestamp => false,
+            Some(last_timestamp) if timestamp == last_timestamp => {
+                self.last_timestamp_line_seen += 1;
+
+                if self.last_timestamp_line_seen <= self.last_timestamp_line_count {
+                    false
+                } else {
+                    self.last_timestamp_line_count += 1;
+                    true
+                }
+            }
+            _ => {
+                self.last_timestamp_opt = Some(timestamp);
+                self.last_timestamp_line_count = 1;
+                self.last_timestamp_line_seen = 1;
+                true
+            }
+        }
+    }
+
+    pub fn last_timestamp_opt(&self) -> Option<DateTime<Utc>> {
+        self.last_timestamp_opt
+    }
+}
+
+/// Splits a log line requested with 'timestamps' in timestamp and message, eg
+/// '2024-05-01T10:00:00.123456789Z TASK [common : install packages]'
+pub fn split_timestamp(line: &str) -> (Option<DateTime<Utc>>, &str) {
+    match line.split_once(' ') {
+        Some((timestamp, message)) => match timestamp.parse::<DateTime<Utc>>() {
+            Ok(timestamp) => (Some(timestamp), message),
+            Err(_) => (None, line),
+        },
+        None => match line.parse::<DateTime<Utc>>() {
+            Ok(timestamp) => (Some(timestamp), ""),
+            Err(_) => (None, line),
+        },
+    }
+}
+
+/// Returns the state of a container or init container in a pod
+fn get_container_state(pod: &Pod, container_name: &str) -> Option<ContainerState> {
+    let pod_status = pod.status.as_ref()?;
+
+    pod_status
+        .init_container_statuses
+        .iter()
+        .flatten()
+        .chain(pod_status.container_statuses.iter().flatten())
+        .find(|container_status| container_status.name == container_name)
+        .and_then(|container_status| container_status.state.clone())
+}
+
+/// Returns true if the pod is gone or finished
+fn is_pod_finished(pod_opt: Option<&Pod>) -> bool {
+    pod_opt
+        .and_then(|pod| pod.status.as_ref())
+        .and_then(|pod_status| pod_status.phase.as_deref())
+        .is_none_or(|phase| phase == "Succeeded" || phase == "Failed")
+}
+
+/// Returns the names of the init containers and containers in a pod, in the order they run
+pub fn get_container_name_vec(pod: &Pod) -> Vec<String> {
+    pod.spec
+        .as_ref()
+        .map(|pod_spec| {
+            pod_spec
+                .init_containers
+                .iter()
+                .flatten()
+                .chain(pod_spec.containers.iter())
+                .map(|container| container.name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the pod running the CFS session, waits for k8s to create it
+async fn get_cfs_session_pod(
+    pods_api: &Api<Pod>,
+    cfs_session_name: &str,
+) -> Result<Pod, Box<dyn std::error::Error + Sync + Send>> {
+    let params = ListParams::default()
+        .limit(1)
+        .labels(&format!("cfsession={}", cfs_session_name));
+
+    let max = 30;
+
+    for i in 0..=max {
+        if let Some(pod) = pods_api.list(&params).await?.items.into_iter().next() {
+            return Ok(pod);
+        }
+
+        eprintln!(
+            "Waiting k8s to create pod for CFS session '{}'. Trying again in {} secs. Attempt {} of {}",
+            cfs_session_name,
+            RECONNECT_DELAY.as_secs(),
+            i + 1,
+            max
+        );
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+
+    Err(format!(
+        "Pod for CFS session '{}' not created. Aborting operation.",
+        cfs_session_name
+    )
+    .into())
+}
+
+/// Follows the logs of the containers in the pod of a CFS session. Logs from different
+/// containers are interleaved and prefixed with the container name
+pub async fn print_cfs_session_container_logs(
+    client: kube::Client,
+    cfs_session_name: &str,
+    container_log_options: &ContainerLogOptions,
+) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+    let pods_api: Api<Pod> = Api::namespaced(client, "services");
+
+    let pod = get_cfs_session_pod(&pods_api, cfs_session_name).await?;
+
+    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+
+    let available_container_name_vec = get_container_name_vec(&pod);
+
+    let container_name_vec: Vec<String> = if container_log_options.container_vec.is_empty() {
+        available_container_name_vec.clone()
+    } else {
+        container_log_options.container_vec.clone()
+    };
+
+    if let Some(container_name) = container_name_vec
+        .iter()
+        .find(|container_name| !available_container_name_vec.contains(container_name))
+    {
+        return Err(format!(
+            "Container '{}' not found in pod '{}'. Containers available: {}",
+            container_name,
+            pod_name,
+            available_container_name_vec.join(", ")
+        )
+        .into());
+    }
+
+    log::info!(
+        "Following containers {:?} in pod {}",
+        container_name_vec,
+        pod_name
+    );
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<ContainerLogLine>();
+
+    for container_name in &container_name_vec {
+        let log_params = LogParams {
+            container: Some(container_name.clone()),
+            follow: true,
+            since_seconds: container_log_options.since_seconds_opt,
+            tail_lines: container_log_options.tail_lines_opt,
+            // Needed to resume the stream after a reconnection
+            timestamps: true,
+            ..Default::default()
+        };
+
+        tokio::spawn(follow_container_logs(
+            pods_api.clone(),
+            pod_name.clone(),
+            container_name.clone(),
+            log_params,
+            tx.clone(),
+        ));
+    }
+
+    // Channel closes once all containers are done
+    drop(tx);
+
+    let prefix_width = container_name_vec
+        .iter()
+        .map(|container_name| container_name.len())
+        .max()
+        .unwrap_or_default();
+
+    let is_terminal = std::io::stdout().is_terminal();
+
+    while let Some(container_log_line) = rx.recv().await {
+        let container_index = container_name_vec
+            .iter()
+            .position(|container_name| container_name == &container_log_line.container)
+            .unwrap_or_default();
+
+        let prefix = format!(
+            "[{:width$}]",
+            container_log_line.container,
+            width = prefix_width
+        );
+
+        let prefix = if is_terminal {
+            format!(
+                "{}{}{}",
+                color::Fg(color::AnsiValue(
+                    PREFIX_COLOR_VEC[container_index % PREFIX_COLOR_VEC.len()]
+                )),
+                prefix,
+                color::Fg(color::Reset)
+            )
+        } else {
+            prefix
+        };
+
+        match container_log_line.timestamp_opt {
+            Some(timestamp) if container_log_options.timestamps => println!(
+                "{} {} {}",
+                prefix,
+                timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                container_log_line.line
+            ),
+            _ => println!("{} {}", prefix, container_log_line.line),
+        }
+    }
+
+    Ok(())
+}
+
+/// Streams the logs of a container to the channel. Waits for the container to start and
+/// reconnects if the stream drops before the container finishes
+async fn follow_container_logs(
+    pods_api: Api<Pod>,
+    pod_name: String,
+    container_name: String,
+    mut log_params: LogParams,
+    tx: mpsc::UnboundedSender<ContainerLogLine>,
+) {
+    let send_message = |message: String| {
+        let _ = tx.send(ContainerLogLine {
+            container: container_name.clone(),
+            timestamp_opt: None,
+            line: message,
+        });
+    };
+
+    // Wait for container to start
+    loop {
+        let pod_opt = pods_api.get_opt(&pod_name).await.ok().flatten();
+
+        let container_state_opt = pod_opt
+            .as_ref()
+            .and_then(|pod| get_container_state(pod, &container_name));
+
+        match container_state_opt {
+            Some(container_state)
+                if container_state.running.is_some() || container_state.terminated.is_some() =>
+            {
+                break
+            }
+            _ if is_pod_finished(pod_opt.as_ref()) => {
+                send_message("Container did not run".to_string());
+                return;
+            }
+            _ => tokio::time::sleep(RECONNECT_DELAY).await,
+        }
+    }
+
+    let mut log_resume = LogResume::default();
+    let mut attempt = 0;
+
+    loop {
+        match pods_api.log_stream(&pod_name, &log_params).await {
+            Ok(log_stream) => {
+                log_resume.reconnect();
+
+                let mut lines = log_stream.lines();
+
+                loop {
+                    match lines.try_next().await {
+                        Ok(Some(line)) => {
+                            attempt = 0;
+
+                            let (timestamp_opt, message) = split_timestamp(&line);
+
+                            // Lines already printed before reconnecting
+                            if !log_resume.is_new_line(timestamp_opt) {
+                                continue;
+                            }
+
+                            if tx
+                                .send(ContainerLogLine {
+                                    container: container_name.clone(),
+                                    timestamp_opt,
+                                    line: message.to_string(),
+                                })
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(error) => {
+                            log::warn!(
+                                "Log stream for container '{}' dropped: {}",
+                                container_name,
+                                error
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(error) => {
+                log::warn!(
+                    "Could not get log stream for container '{}': {}",
+                    container_name,
+                    error
+                );
+            }
+        }
+
+        // Stream ended, check if the container finished or the connection dropped
+        let pod_opt = pods_api.get_opt(&pod_name).await.ok().flatten();
+
+        let container_terminated = pod_opt
+            .as_ref()
+            .and_then(|pod| get_container_state(pod, &container_name))
+            .is_none_or(|container_state| container_state.terminated.is_some());
+
+        if container_terminated || is_pod_finished(pod_opt.as_ref()) {
+            return;
+        }
+
+        attempt += 1;
+
+        if attempt > MAX_RECONNECT_ATTEMPTS {
+            send_message(format!(
+                "Could not reconnect to log stream after {} attempts",
+                MAX_RECONNECT_ATTEMPTS
+            ));
+            return;
+        }
+
+        log::info!(
+            "Reconnecting to log stream for container '{}'. Attempt {} of {}",
+            container_name,
+            attempt,
+            MAX_RECONNECT_ATTEMPTS
+        );
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+
+        // Resume from the last line received
+        if let Some(last_timestamp) = log_resume.last_timestamp_opt() {
+            log_params.since_seconds = None;
+            log_params.since_time = Some(last_timestamp);
+            log_params.tail_lines = None;
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod ansible_parser;
+pub mod archive;
+pub mod command;
+pub mod containers;
+#[cfg(test)]
+mod tests;
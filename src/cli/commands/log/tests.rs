@@ -0,0 +1,372 @@
+use mesa::cfs::session::mesa::r#struct::v3::CfsSessionGetResponse;
+use regex::Regex;
+
+use super::{
+    ansible_parser::{get_error_message, AnsibleEvent, AnsibleLogParser},
+    archive::{filter_index, merge_index, search_log_file, ArchivedSession, LogArchive},
+    containers::{split_timestamp, LogResume},
+};
+
+#[test]
+fn test_parse_ansible_task_results() {
+    let log = r#"PLAY [Compute] *****************************************************************
+
+TASK [Gathering Facts] *********************************************************
+ok: [x1000c0s0b0n0]
+ok: [x1000c0s0b0n1]
+
+TASK [common : install packages] ***********************************************
+changed: [x1000c0s0b0n0] => (item=vim)
+skipping: [x1000c0s0b0n1]
+"#;
+
+    let mut parser = AnsibleLogParser::new();
+
+    for line in log.lines() {
+        parser.parse_line(line);
+    }
+
+    assert_eq!(parser.play, "Compute");
+    assert_eq!(parser.task, "common : install packages");
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n0"];
+    assert_eq!(host_status.ok, 1);
+    assert_eq!(host_status.changed, 1);
+    assert_eq!(host_status.last_task, "common : install packages");
+    assert_eq!(host_status.get_status(), "changed");
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n1"];
+    assert_eq!(host_status.ok, 1);
+    assert_eq!(host_status.skipped, 1);
+    assert_eq!(host_status.get_status(), "ok");
+}
+
+#[test]
+fn test_parse_ansible_failed_and_unreachable_hosts() {
+    let log = r#"TASK [common : configure ntp] **************************************************
+fatal: [x1000c0s0b0n0]: FAILED! => {"changed": false, "msg": "Could not find the requested service chronyd"}
+fatal: [x1000c0s0b0n1]: UNREACHABLE! => {"changed": false, "msg": "Failed to connect to the host via ssh", "unreachable": true}
+fatal: [x1000c0s0b0n2]: FAILED! => {"changed": false, "msg": "ignored error"}
+...ignoring
+"#;
+
+    let mut parser = AnsibleLogParser::new();
+
+    let event_vec: Vec<AnsibleEvent> = log
+        .lines()
+        .filter_map(|line| parser.parse_line(line))
+        .collect();
+
+    assert!(
+        matches!(event_vec[1], AnsibleEvent::HostFailed(ref host, _) if host == "x1000c0s0b0n0")
+    );
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n0"];
+    assert_eq!(host_status.failed, 1);
+    assert_eq!(host_status.get_status(), "failed");
+    assert_eq!(
+        host_status.failed_task_vec[0].task,
+        "common : configure ntp"
+    );
+    assert_eq!(
+        host_status.failed_task_vec[0].message,
+        "Could not find the requested service chronyd"
+    );
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n1"];
+    assert_eq!(host_status.unreachable, 1);
+    assert_eq!(host_status.failed, 0);
+    assert_eq!(host_status.get_status(), "unreachable");
+    assert!(host_status.failed_task_vec[0].unreachable);
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n2"];
+    assert_eq!(host_status.failed, 0);
+    assert_eq!(host_status.ignored, 1);
+    assert!(host_status.failed_task_vec.is_empty());
+}
+
+#[test]
+fn test_parse_ansible_play_recap() {
+    let log = r#"TASK [common : install packages] ***********************************************
+changed: [x1000c0s0b0n0] => (item=vim)
+changed: [x1000c0s0b0n0] => (item=git)
+
+PLAY RECAP *********************************************************************
+x1000c0s0b0n0              : ok=5    changed=1    unreachable=0    failed=0    skipped=2    rescued=0    ignored=1
+x1000c0s0b0n1              : ok=3    changed=0    unreachable=0    failed=1    skipped=0    rescued=0    ignored=0
+"#;
+
+    let mut parser = AnsibleLogParser::new();
+
+    for line in log.lines() {
+        parser.parse_line(line);
+    }
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n0"];
+    assert_eq!(host_status.ok, 5);
+    assert_eq!(host_status.changed, 1);
+    assert_eq!(host_status.skipped, 2);
+    assert_eq!(host_status.ignored, 1);
+
+    let host_status = &parser.host_status_map["x1000c0s0b0n1"];
+    assert_eq!(host_status.failed, 1);
+    assert_eq!(host_status.get_status(), "failed");
+}
+
+#[test]
+fn test_get_error_message() {
+    assert_eq!(
+        get_error_message(
+            r#": FAILED! => {"changed": false, "msg": "", "stderr": "  permission denied\n"}"#
+        ),
+        "permission denied"
+    );
+
+    assert_eq!(
+        get_error_message(r#": FAILED! => not a json"#),
+        "not a json"
+    );
+}
+
+fn get_archived_session(
+    cfs_session_name: &str,
+    hsm_group: Option<&str>,
+    xname: &str,
+    start_time: &str,
+) -> ArchivedSession {
+    ArchivedSession {
+        cfs_session_name: cfs_session_name.to_string(),
+        configuration_name: Some(format!("{}-config", cfs_session_name)),
+        hsm_group_vec: hsm_group.into_iter().map(str::to_string).collect(),
+        xname_vec: vec![xname.to_string()],
+        start_time: start_time.parse().unwrap(),
+        archived_at: start_time.parse().unwrap(),
+        log_file_name: format!("{}.log", cfs_session_name),
+    }
+}
+
+#[test]
+fn test_log_archive_merge_index() {
+    let mut archived_session_vec = vec![
+        get_archived_session("b", None, "x1000c0s0b0n0", "2024-05-02T00:00:00Z"),
+        get_archived_session("a", None, "x1000c0s0b0n0", "2024-05-01T00:00:00Z"),
+    ];
+
+    merge_index(
+        &mut archived_session_vec,
+        &[get_archived_session(
+            "b",
+            None,
+            "x1000c0s0b0n1",
+            "2024-04-30T00:00:00Z",
+        )],
+    );
+
+    assert_eq!(archived_session_vec.len(), 2);
+    assert_eq!(archived_session_vec[0].cfs_session_name, "b");
+    assert_eq!(archived_session_vec[0].xname_vec, vec!["x1000c0s0b0n1"]);
+    assert_eq!(archived_session_vec[1].cfs_session_name, "a");
+}
+
+#[test]
+fn test_log_archive_filter_index() {
+    let archived_session_vec = vec![
+        get_archived_session("a", Some("zinal"), "x1000c0s0b0n0", "2024-05-01T00:00:00Z"),
+        get_archived_session("b", Some("psi"), "x1000c0s0b0n0", "2024-05-02T00:00:00Z"),
+        get_archived_session("c", None, "x1000c0s0b0n1", "2024-05-03T00:00:00Z"),
+    ];
+
+    let hsm_group_available_vec = vec!["zinal".to_string()];
+    let hsm_member_available_vec = vec!["x1000c0s0b0n0".to_string(), "x1000c0s0b0n1".to_string()];
+
+    let cfs_session_name_vec = |archived_session_vec: Vec<&ArchivedSession>| {
+        archived_session_vec
+            .iter()
+            .map(|archived_session| archived_session.cfs_session_name.clone())
+            .collect::<Vec<String>>()
+    };
+
+    // Session 'b' targets an HSM group not available
+    assert_eq!(
+        cfs_session_name_vec(filter_index(
+            &archived_session_vec,
+            &hsm_group_available_vec,
+            &hsm_member_available_vec,
+            None,
+            None,
+            None,
+            None
+        )),
+        vec!["a", "c"]
+    );
+
+    assert_eq!(
+        cfs_session_name_vec(filter_index(
+            &archived_session_vec,
+            &hsm_group_available_vec,
+            &hsm_member_available_vec,
+            None,
+            None,
+            Some(&"x1000c0s0b0n1".to_string()),
+            None
+        )),
+        vec!["c"]
+    );
+
+    assert_eq!(
+        cfs_session_name_vec(filter_index(
+            &archived_session_vec,
+            &hsm_group_available_vec,
+            &hsm_member_available_vec,
+            None,
+            Some(&"a-config".to_string()),
+            None,
+            Some("2024-04-30T00:00:00Z".parse().unwrap())
+        )),
+        vec!["a"]
+    );
+
+    // Session 'c' targets a node not available
+    assert_eq!(
+        cfs_session_name_vec(filter_index(
+            &archived_session_vec,
+            &hsm_group_available_vec,
+            &["x1000c0s0b0n0".to_string()],
+            None,
+            None,
+            None,
+            None
+        )),
+        vec!["a"]
+    );
+
+    assert!(filter_index(
+        &archived_session_vec,
+        &hsm_group_available_vec,
+        &hsm_member_available_vec,
+        None,
+        None,
+        None,
+        Some("2024-05-04T00:00:00Z".parse().unwrap())
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_log_archive_search_log_file() {
+    let log_file_path = std::env::temp_dir().join(format!(
+        "manta-test-log-archive-{}.log",
+        uuid::Uuid::new_v4()
+    ));
+
+    std::fs::write(
+        &log_file_path,
+        r#"TASK [common : configure ntp] **************************************************
+fatal: [x1000c0s0b0n0]: FAILED! => {"msg": "Could not find the requested service chronyd"}
+fatal: [x1000c0s0b0n1]: FAILED! => {"msg": "Could not find the requested service chronyd"}
+ok: [x1000c0s0b0n2]
+"#,
+    )
+    .unwrap();
+
+    let regex = Regex::new("FAILED!.*chronyd").unwrap();
+
+    let search_match_vec = search_log_file(&log_file_path, "a", &regex, None).unwrap();
+
+    assert_eq!(search_match_vec.len(), 2);
+    assert_eq!(search_match_vec[0].line_number, 2);
+
+    let search_match_vec = search_log_file(
+        &log_file_path,
+        "a",
+        &regex,
+        Some(&"x1000c0s0b0n1".to_string()),
+    )
+    .unwrap();
+
+    std::fs::remove_file(&log_file_path).unwrap();
+
+    assert_eq!(search_match_vec.len(), 1);
+    assert_eq!(search_match_vec[0].line_number, 3);
+    assert_eq!(search_match_vec[0].cfs_session_name, "a");
+}
+
+#[test]
+fn test_split_container_log_timestamp() {
+    let (timestamp_opt, message) =
+        split_timestamp("2024-05-01T10:00:00.123456789Z TASK [common : install packages]");
+
+    assert_eq!(
+        timestamp_opt,
+        Some("2024-05-01T10:00:00.123456789Z".parse().unwrap())
+    );
+    assert_eq!(message, "TASK [common : install packages]");
+
+    assert_eq!(
+        split_timestamp("no timestamp here"),
+        (None, "no timestamp here")
+    );
+}
+
+#[test]
+fn test_log_archive_writer_keeps_archive_if_no_logs_received() {
+    let log_archive = LogArchive {
+        enabled: true,
+        dir: std::env::temp_dir().join(format!("manta-test-log-archive-{}", uuid::Uuid::new_v4())),
+        s3_bucket_opt: None,
+    };
+
+    let cfs_session: CfsSessionGetResponse =
+        serde_json::from_value(serde_json::json!({ "name": "batcher-123" })).unwrap();
+
+    let log_file_path = log_archive.dir.join("batcher-123.log");
+
+    let mut log_archive_writer = log_archive.create_writer(&cfs_session).unwrap().unwrap();
+    log_archive_writer.write_line("PLAY [Compute] ****");
+
+    assert!(log_archive_writer.finish(&log_archive).unwrap().is_some());
+    assert_eq!(
+        std::fs::read_to_string(&log_file_path).unwrap(),
+        "PLAY [Compute] ****\n"
+    );
+
+    // Pod is gone, nothing is streamed
+    let log_archive_writer = log_archive.create_writer(&cfs_session).unwrap().unwrap();
+
+    assert!(log_archive_writer.finish(&log_archive).unwrap().is_none());
+    assert_eq!(
+        std::fs::read_to_string(&log_file_path).unwrap(),
+        "PLAY [Compute] ****\n"
+    );
+    assert_eq!(log_archive.read_index().unwrap().len(), 1);
+
+    std::fs::remove_dir_all(&log_archive.dir).unwrap();
+}
+
+#[test]
+fn test_log_resume_skips_lines_sent_before_reconnecting() {
+    let timestamp_1 = "2024-05-01T10:00:00Z".parse().ok();
+    let timestamp_2 = "2024-05-01T10:00:01Z".parse().ok();
+
+    let mut log_resume = LogResume::default();
+
+    log_resume.reconnect();
+    assert!(log_resume.is_new_line(timestamp_1));
+    assert!(log_resume.is_new_line(timestamp_2));
+    assert!(log_resume.is_new_line(timestamp_2));
+    assert!(log_resume.is_new_line(None));
+
+    // Stream resumed from the last timestamp returns the lines sent already and a new line
+    // sharing the same timestamp
+    log_resume.reconnect();
+    assert!(!log_resume.is_new_line(timestamp_1));
+    assert!(!log_resume.is_new_line(timestamp_2));
+    assert!(!log_resume.is_new_line(timestamp_2));
+    assert!(log_resume.is_new_line(timestamp_2));
+    assert!(log_resume.is_new_line("2024-05-01T10:00:02Z".parse().ok()));
+
+    assert_eq!(
+        log_resume.last_timestamp_opt(),
+        "2024-05-01T10:00:02Z".parse().ok()
+    );
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::common::node_health_ops::{self, HealthPolicy};
+
 pub async fn exec(
     shasta_token: &str,
     shasta_base_url: &str,
@@ -9,6 +11,7 @@ pub async fn exec(
     xname_requested_hostlist: &str,
     nodryrun: bool,
     create_hsm_group: bool,
+    health_policy_opt: Option<HealthPolicy>,
 ) {
     // Filter xnames to the ones members to HSM groups the user has access to
     //
@@ -37,6 +40,35 @@ pub async fn exec(
     xname_to_move_vec.sort();
     xname_to_move_vec.dedup();
 
+    // Check nodes are healthy before handing them over to the target HSM group
+    if let Some(health_policy) = health_policy_opt {
+        let xname_unhealthy_vec = node_health_ops::gate_nodes(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_to_move_vec
+                .into_iter()
+                .cloned()
+                .collect::<Vec<String>>(),
+            health_policy,
+        )
+        .await;
+
+        for hsm_group_members in hsm_group_summary.values_mut() {
+            hsm_group_members.retain(|xname| !xname_unhealthy_vec.contains(xname));
+        }
+
+        hsm_group_summary.retain(|_hsm_name, hsm_group_members| !hsm_group_members.is_empty());
+
+        xname_to_move_vec = hsm_group_summary
+            .iter()
+            .flat_map(|(_hsm_group_name, hsm_group_members)| hsm_group_members)
+            .collect();
+
+        xname_to_move_vec.sort();
+        xname_to_move_vec.dedup();
+    }
+
     // Check if there are any xname to migrate/move and exit otherwise
     if xname_to_move_vec.is_empty() {
         println!("No hosts to move. Exit");
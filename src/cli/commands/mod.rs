@@ -10,6 +10,8 @@ pub mod apply_image;
 pub mod apply_sat_file;
 pub mod apply_session;
 pub mod apply_template;
+pub mod audit_artefacts;
+pub mod bootparams;
 pub mod config_set_hsm;
 pub mod config_set_log;
 pub mod config_set_parent_hsm;
@@ -20,8 +22,13 @@ pub mod config_unset_hsm;
 pub mod config_unset_parent_hsm;
 pub mod console_cfs_session_image_target_ansible;
 pub mod console_node;
+pub mod console_recording;
+pub mod console_tail;
+pub mod dashboard;
 pub mod delete_data_related_to_cfs_configuration;
 pub mod delete_sessions;
+pub mod diff_configuration;
+pub mod gc;
 pub mod get_cluster;
 pub mod get_configuration;
 pub mod get_hsm;
@@ -32,21 +39,33 @@ pub mod get_kernel_parameters;
 pub mod get_nodes;
 pub mod get_session;
 pub mod get_template;
+pub mod hw_diff;
+pub mod hw_snapshot;
+pub mod lease_create;
+pub mod lease_list;
+pub mod lease_release;
 pub mod log;
 pub mod migrate_backup;
 pub mod migrate_nodes_between_hsm_groups;
 pub mod migrate_restore;
+pub mod power_cap_get;
+pub mod power_cap_set;
 pub mod power_off_cluster;
 pub mod power_off_nodes;
 pub mod power_on_cluster;
 pub mod power_on_nodes;
 pub mod power_reset_cluster;
 pub mod power_reset_nodes;
+pub mod power_status;
 pub mod remove_hw_component_cluster;
 pub mod remove_nodes_from_hsm_groups;
 pub mod set_boot_configuration;
 pub mod set_boot_image;
 pub mod set_kernel_parameters;
 pub mod set_runtime_configuration;
+/// Factories of CSM structs shared by the tests of the commands
+#[cfg(test)]
+pub mod test_fixtures;
 pub mod update_hsm_group;
+pub mod update_sat_file_lock;
 pub mod validate_local_repo;
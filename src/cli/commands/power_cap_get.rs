@@ -0,0 +1,90 @@
+use comfy_table::{Cell, Color, Table};
+
+use crate::common::pcs_utils::{self, PowerCapComponent};
+
+/// Prints the power cap controls (current, min and max watts) of a list of nodes
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+    output: &str,
+) {
+    let mut power_cap_component_vec = match pcs_utils::get_power_cap_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        xname_vec,
+    )
+    .await
+    {
+        Ok(power_cap_component_vec) => power_cap_component_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not get power cap of node/s '{:?}'. Reason:\n{}",
+                xname_vec,
+                pcs_utils::get_error_message(error)
+            );
+            std::process::exit(1);
+        }
+    };
+
+    power_cap_component_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&power_cap_component_vec).unwrap()
+        );
+    } else {
+        print_table(&power_cap_component_vec);
+    }
+}
+
+/// Prints a row per node and power cap control
+pub fn print_table(power_cap_component_vec: &[PowerCapComponent]) {
+    let value_to_string =
+        |value_opt: Option<i64>| value_opt.map(|value| value.to_string()).unwrap_or_default();
+
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "XNAME",
+        "Control",
+        "Current (W)",
+        "Min (W)",
+        "Max (W)",
+        "Error",
+    ]);
+
+    for power_cap_component in power_cap_component_vec {
+        let error_cell =
+            Cell::new(power_cap_component.error.as_deref().unwrap_or_default()).fg(Color::Red);
+
+        if power_cap_component.power_cap_limits.is_empty() {
+            table.add_row(vec![
+                Cell::new(&power_cap_component.xname),
+                Cell::new(""),
+                Cell::new(""),
+                Cell::new(""),
+                Cell::new(""),
+                error_cell,
+            ]);
+
+            continue;
+        }
+
+        for power_cap_control in &power_cap_component.power_cap_limits {
+            table.add_row(vec![
+                Cell::new(&power_cap_component.xname),
+                Cell::new(&power_cap_control.name),
+                Cell::new(value_to_string(power_cap_control.current_value)),
+                Cell::new(value_to_string(power_cap_control.minimum_value)),
+                Cell::new(value_to_string(power_cap_control.maximum_value)),
+                error_cell.clone(),
+            ]);
+        }
+    }
+
+    println!("{table}");
+}
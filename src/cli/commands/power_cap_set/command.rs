@@ -0,0 +1,205 @@
+use comfy_table::Table;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use mesa::common::jwt_ops::get_claims_from_jwt_token;
+
+use crate::{
+    cli::commands::power_cap_get,
+    common::pcs_utils::{self, PowerCapComponent},
+};
+
+/// Checks the power cap value is within the limits of the control for each node. Returns the
+/// current value per node and the list of errors
+pub fn validate(
+    power_cap_component_vec: &[PowerCapComponent],
+    control_name: &str,
+    value: i64,
+) -> (Vec<(String, Option<i64>)>, Vec<String>) {
+    let mut current_value_vec: Vec<(String, Option<i64>)> = Vec::new();
+    let mut error_vec: Vec<String> = Vec::new();
+
+    for power_cap_component in power_cap_component_vec {
+        if let Some(error) = power_cap_component
+            .error
+            .as_ref()
+            .filter(|error| !error.is_empty())
+        {
+            error_vec.push(format!("{}: {}", power_cap_component.xname, error));
+            continue;
+        }
+
+        let Some(power_cap_control) = power_cap_component
+            .power_cap_limits
+            .iter()
+            .find(|power_cap_control| power_cap_control.name == control_name)
+        else {
+            error_vec.push(format!(
+                "{}: power cap control '{}' not found. Controls available: {:?}",
+                power_cap_component.xname,
+                control_name,
+                power_cap_component
+                    .power_cap_limits
+                    .iter()
+                    .map(|power_cap_control| &power_cap_control.name)
+                    .collect::<Vec<&String>>()
+            ));
+            continue;
+        };
+
+        if power_cap_control
+            .minimum_value
+            .is_some_and(|minimum_value| value < minimum_value)
+            || power_cap_control
+                .maximum_value
+                .is_some_and(|maximum_value| value > maximum_value)
+        {
+            error_vec.push(format!(
+                "{}: value {}W out of range [{}W, {}W]",
+                power_cap_component.xname,
+                value,
+                power_cap_control.minimum_value.unwrap_or_default(),
+                power_cap_control.maximum_value.unwrap_or_default()
+            ));
+            continue;
+        }
+
+        current_value_vec.push((
+            power_cap_component.xname.clone(),
+            power_cap_control.current_value,
+        ));
+    }
+
+    (current_value_vec, error_vec)
+}
+
+/// Sets a power cap control to a list of nodes. The value is validated against the limits
+/// reported by each node and the changes are shown before applying them
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+    control_name: &str,
+    value: i64,
+    yes: bool,
+    output: &str,
+) {
+    let power_cap_component_vec = match pcs_utils::get_power_cap_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        xname_vec,
+    )
+    .await
+    {
+        Ok(power_cap_component_vec) => power_cap_component_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not get power cap of node/s '{:?}'. Reason:\n{}",
+                xname_vec,
+                pcs_utils::get_error_message(error)
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let (mut current_value_vec, error_vec) =
+        validate(&power_cap_component_vec, control_name, value);
+
+    if !error_vec.is_empty() {
+        eprintln!(
+            "ERROR - Power cap can't be set on some nodes:\n{}\nExit",
+            error_vec.join("\n")
+        );
+        std::process::exit(1);
+    }
+
+    current_value_vec.sort();
+
+    let mut table = Table::new();
+
+    table.set_header(vec!["XNAME", "Control", "Current (W)", "New (W)"]);
+
+    for (xname, current_value_opt) in &current_value_vec {
+        table.add_row(vec![
+            xname.clone(),
+            control_name.to_string(),
+            current_value_opt
+                .map(|current_value| current_value.to_string())
+                .unwrap_or_default(),
+            value.to_string(),
+        ]);
+    }
+
+    println!("{table}");
+
+    if !yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Please review the power cap changes above and confirm to proceed")
+            .interact()
+            .unwrap()
+    {
+        println!("Cancelled by user. Aborting.");
+        std::process::exit(0);
+    }
+
+    let xname_to_update_vec: Vec<String> = current_value_vec
+        .into_iter()
+        .map(|(xname, _)| xname)
+        .collect();
+
+    let mut power_cap_component_vec = match pcs_utils::set_power_cap(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &xname_to_update_vec,
+        control_name,
+        value,
+    )
+    .await
+    {
+        Ok(power_cap_component_vec) => power_cap_component_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not set power cap of node/s '{:?}'. Reason:\n{}",
+                xname_to_update_vec,
+                pcs_utils::get_error_message(error)
+            );
+            std::process::exit(1);
+        }
+    };
+
+    power_cap_component_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&power_cap_component_vec).unwrap()
+        );
+    } else {
+        power_cap_get::print_table(&power_cap_component_vec);
+    }
+
+    // Audit
+    let jwt_claims = get_claims_from_jwt_token(shasta_token).unwrap();
+
+    log::info!(target: "app::audit", "User: {} ({}) ; Operation: Power cap set '{}' to {}W on nodes {:?}", jwt_claims["name"].as_str().unwrap(), jwt_claims["preferred_username"].as_str().unwrap(), control_name, value, xname_to_update_vec);
+
+    let num_failed = power_cap_component_vec
+        .iter()
+        .filter(|power_cap_component| {
+            power_cap_component
+                .error
+                .as_ref()
+                .is_some_and(|error| !error.is_empty())
+        })
+        .count();
+
+    if num_failed > 0 {
+        eprintln!(
+            "ERROR - Could not set power cap on {} of {} node/s",
+            num_failed,
+            power_cap_component_vec.len()
+        );
+        std::process::exit(1);
+    }
+}
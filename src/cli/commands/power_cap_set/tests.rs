@@ -0,0 +1,88 @@
+use crate::common::pcs_utils::{PowerCapComponent, PowerCapControl};
+
+use super::command::validate;
+
+fn get_power_cap_component(xname: &str, error_opt: Option<&str>) -> PowerCapComponent {
+    PowerCapComponent {
+        xname: xname.to_string(),
+        error: error_opt.map(|error| error.to_string()),
+        power_cap_limits: vec![PowerCapControl {
+            name: "Node Power Limit".to_string(),
+            current_value: Some(2000),
+            maximum_value: Some(2500),
+            minimum_value: Some(500),
+        }],
+    }
+}
+
+/// Test values within the control limits are accepted and the current value is returned
+#[test]
+fn test_validate() {
+    let power_cap_component_vec = vec![
+        get_power_cap_component("x1000c0s0b0n0", None),
+        get_power_cap_component("x1000c0s0b0n1", Some("")),
+    ];
+
+    let (current_value_vec, error_vec) =
+        validate(&power_cap_component_vec, "Node Power Limit", 2500);
+
+    assert_eq!(
+        current_value_vec,
+        vec![
+            ("x1000c0s0b0n0".to_string(), Some(2000)),
+            ("x1000c0s0b0n1".to_string(), Some(2000)),
+        ]
+    );
+    assert!(error_vec.is_empty());
+}
+
+#[test]
+fn test_validate_value_out_of_range() {
+    let power_cap_component_vec = vec![get_power_cap_component("x1000c0s0b0n0", None)];
+
+    for value in [499, 2501] {
+        let (current_value_vec, error_vec) =
+            validate(&power_cap_component_vec, "Node Power Limit", value);
+
+        assert!(current_value_vec.is_empty());
+        assert_eq!(
+            error_vec,
+            vec![format!(
+                "x1000c0s0b0n0: value {}W out of range [500W, 2500W]",
+                value
+            )]
+        );
+    }
+}
+
+#[test]
+fn test_validate_unknown_control() {
+    let power_cap_component_vec = vec![get_power_cap_component("x1000c0s0b0n0", None)];
+
+    let (current_value_vec, error_vec) =
+        validate(&power_cap_component_vec, "Accelerator0 Power Limit", 1000);
+
+    assert!(current_value_vec.is_empty());
+    assert_eq!(
+        error_vec,
+        vec!["x1000c0s0b0n0: power cap control 'Accelerator0 Power Limit' not found. Controls available: [\"Node Power Limit\"]"]
+    );
+}
+
+/// Test errors reported by PCS for a node are returned and the rest of nodes are still validated
+#[test]
+fn test_validate_node_error() {
+    let power_cap_component_vec = vec![
+        get_power_cap_component("x1000c0s0b0n0", Some("Unable to reach BMC")),
+        get_power_cap_component("x1000c0s0b0n1", None),
+    ];
+
+    let (current_value_vec, error_vec) =
+        validate(&power_cap_component_vec, "Node Power Limit", 1000);
+
+    assert_eq!(
+        current_value_vec,
+        vec![("x1000c0s0b0n1".to_string(), Some(2000))]
+    );
+    assert_eq!(error_vec, vec!["x1000c0s0b0n0: Unable to reach BMC"]);
+}
@@ -1,6 +1,6 @@
-use mesa::{common::jwt_ops::get_claims_from_jwt_token, error::Error, pcs};
+use mesa::common::jwt_ops::get_claims_from_jwt_token;
 
-use crate::common;
+use crate::common::{self, pcs_utils::PowerTransitionOpts};
 
 pub async fn exec(
     shasta_token: &str,
@@ -10,6 +10,7 @@ pub async fn exec(
     reason_opt: Option<String>,
     force: bool,
     output: &str,
+    power_transition_opts: &PowerTransitionOpts,
 ) {
     /* post_sync(
         shasta_token,
@@ -29,42 +30,33 @@ pub async fn exec(
         "soft-restart"
     };
 
-    let power_mgmt_summary_rslt = pcs::transitions::http_client::post_block(
-        shasta_base_url,
+    let power_task_result_vec = match common::pcs_utils::transition_with_retries(
         shasta_token,
+        shasta_base_url,
         shasta_root_cert,
         operation,
         xname_vec,
+        power_transition_opts,
     )
-    .await;
-
-    let power_mgmt_summary = match power_mgmt_summary_rslt {
-        Ok(value) => value,
-        Err(e) => {
-            /* eprintln!(
-                "ERROR - Could not restart node/s '{:?}'. Reason:\n{}",
-                xname_vec, error_msg
-            );
-            std::process::exit(1); */
-            let error_msg = match e {
-                Error::CsmError(value) => serde_json::to_string_pretty(&value).unwrap(),
-                Error::SerdeError(value) => value.to_string(),
-                Error::IoError(value) => value.to_string(),
-                Error::NetError(value) => value.to_string(),
-                Error::Message(value) => value.to_string(),
-            };
+    .await
+    {
+        Ok(power_task_result_vec) => power_task_result_vec,
+        Err(error) => {
             eprintln!(
                 "ERROR - Could not restart node/s '{:?}'. Reason:\n{}",
-                xname_vec, error_msg
+                xname_vec,
+                common::pcs_utils::get_error_message(error)
             );
             std::process::exit(1);
         }
     };
 
-    common::pcs_utils::print_summary_table(power_mgmt_summary, output);
+    common::pcs_utils::print_task_result_table(&power_task_result_vec, output);
 
     // Audit
     let jwt_claims = get_claims_from_jwt_token(shasta_token).unwrap();
 
     log::info!(target: "app::audit", "User: {} ({}) ; Operation: Power reset nodes {:?}", jwt_claims["name"].as_str().unwrap(), jwt_claims["preferred_username"].as_str().unwrap(), xname_vec);
+
+    common::pcs_utils::exit_on_failure(&power_task_result_vec);
 }
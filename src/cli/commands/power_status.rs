@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use comfy_table::{Cell, Color, Table};
+
+use crate::common::pcs_utils::{self, PowerStatus};
+
+/// Prints the power state of a list of nodes reported by PCS
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+    output: &str,
+) {
+    let mut power_status_vec = match pcs_utils::get_power_status_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        xname_vec,
+    )
+    .await
+    {
+        Ok(power_status_vec) => power_status_vec,
+        Err(error) => {
+            eprintln!(
+                "ERROR - Could not get power status of node/s '{:?}'. Reason:\n{}",
+                xname_vec,
+                pcs_utils::get_error_message(error)
+            );
+            std::process::exit(1);
+        }
+    };
+
+    power_status_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&power_status_vec).unwrap()
+        );
+    } else {
+        print_table(&power_status_vec);
+    }
+}
+
+pub fn print_table(power_status_vec: &[PowerStatus]) {
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "XNAME",
+        "Power state",
+        "Management state",
+        "Last updated",
+        "Error",
+    ]);
+
+    let mut power_state_count_map: BTreeMap<&str, usize> = BTreeMap::new();
+
+    for power_status in power_status_vec {
+        *power_state_count_map
+            .entry(power_status.power_state.as_str())
+            .or_default() += 1;
+
+        let color = match power_status.power_state.as_str() {
+            "on" => Color::Green,
+            "off" => Color::Red,
+            _ => Color::Yellow,
+        };
+
+        table.add_row(vec![
+            Cell::new(&power_status.xname),
+            Cell::new(&power_status.power_state).fg(color),
+            Cell::new(&power_status.management_state),
+            Cell::new(&power_status.last_updated),
+            Cell::new(power_status.error.as_deref().unwrap_or_default()),
+        ]);
+    }
+
+    println!("{table}");
+
+    println!(
+        "Summary: {}",
+        power_state_count_map
+            .iter()
+            .map(|(power_state, count)| format!("{}: {}", power_state, count))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use mesa::{
     bss::{self, bootparameters::BootParameters},
@@ -5,10 +7,13 @@ use mesa::{
     error::Error,
 };
 
+use crate::common::{boot_readiness_ops, pcs_utils::PowerTransitionOpts};
+
 /// Set boot image to a set of nodes. This function updates the desired_configuration for the node
 /// boot params.
 /// If the new image is different than existing one, then the nodes will reboot. This is mandatory
-/// to keep CSM data as a true source of truth
+/// to keep CSM data as a true source of truth. If a wait timeout is provided, waits for the
+/// rebooted nodes to boot the new image
 pub async fn exec(
     shasta_token: &str,
     shasta_base_url: &str,
@@ -17,6 +22,7 @@ pub async fn exec(
     hsm_group_name_opt: Option<&Vec<String>>,
     xname_vec_opt: Option<&Vec<String>>,
     output: &str,
+    wait_timeout_opt: Option<chrono::Duration>,
 ) -> Result<(), Error> {
     let xname_to_reboot_vec: Vec<String>;
 
@@ -116,8 +122,26 @@ pub async fn exec(
                 None,
                 true,
                 output,
+                &PowerTransitionOpts::default(),
             )
             .await;
+
+            if let Some(wait_timeout) = wait_timeout_opt {
+                let expected_image_map: BTreeMap<String, String> = xname_to_reboot_vec
+                    .iter()
+                    .map(|xname| (xname.clone(), image_id.to_string()))
+                    .collect();
+
+                boot_readiness_ops::wait_and_report(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &expected_image_map,
+                    None,
+                    wait_timeout,
+                )
+                .await;
+            }
         }
     } else {
         println!("Boot image did not change. No need to reboot.");
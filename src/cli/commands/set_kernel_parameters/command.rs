@@ -0,0 +1,488 @@
+use std::fmt;
+
+use comfy_table::Table;
+use dialoguer::theme::ColorfulTheme;
+use mesa::{
+    bss::{self, bootparameters::BootParameters},
+    common::jwt_ops::get_claims_from_jwt_token,
+    error::Error,
+};
+
+use crate::common::pcs_utils::PowerTransitionOpts;
+
+/// Kernel parameters related to the boot image. These are managed by `manta set boot-image` and
+/// `manta set boot-configuration` and are never modified by this command
+pub const IMAGE_KERNEL_PARAM_KEY_VEC: [&str; 3] = ["root", "metal.server", "nmd_data"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelParam {
+    pub key: String,
+    pub value_opt: Option<String>,
+}
+
+impl KernelParam {
+    pub fn is_image_param(&self) -> bool {
+        IMAGE_KERNEL_PARAM_KEY_VEC.contains(&self.key.as_str())
+    }
+}
+
+impl fmt::Display for KernelParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value_opt {
+            Some(value) => write!(f, "{}={}", self.key, value),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+/// Parses a space separated list of kernel parameters keeping its order and duplicates
+pub fn parse_kernel_params(kernel_params: &str) -> Vec<KernelParam> {
+    kernel_params
+        .split_whitespace()
+        .map(|kernel_param| match kernel_param.split_once('=') {
+            Some((key, value)) => KernelParam {
+                key: key.to_string(),
+                value_opt: Some(value.to_string()),
+            },
+            None => KernelParam {
+                key: kernel_param.to_string(),
+                value_opt: None,
+            },
+        })
+        .collect()
+}
+
+pub fn kernel_params_to_string(kernel_param_vec: &[KernelParam]) -> String {
+    kernel_param_vec
+        .iter()
+        .map(|kernel_param| kernel_param.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Returns the keys defined more than once with different values
+pub fn get_conflicting_key_vec(kernel_param_vec: &[KernelParam]) -> Vec<String> {
+    let mut key_vec: Vec<String> = Vec::new();
+
+    for (index, kernel_param) in kernel_param_vec.iter().enumerate() {
+        if !key_vec.contains(&kernel_param.key)
+            && kernel_param_vec[index + 1..].iter().any(|other| {
+                other.key == kernel_param.key && other.value_opt != kernel_param.value_opt
+            })
+        {
+            key_vec.push(kernel_param.key.clone());
+        }
+    }
+
+    key_vec
+}
+
+/// Changes to apply to the kernel parameters of a node. Either the non image related kernel
+/// parameters are replaced ('set') or they are edited with add/remove/replace operations
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KernelParamsEdit {
+    pub set_opt: Option<Vec<KernelParam>>,
+    pub add_vec: Vec<KernelParam>,
+    /// Kernel parameters to remove. If a value is provided, only the parameter with that value is
+    /// removed, otherwise all parameters with the key are removed
+    pub remove_vec: Vec<KernelParam>,
+    pub replace_vec: Vec<KernelParam>,
+}
+
+impl KernelParamsEdit {
+    /// Validates the kernel parameters provided by the user. Image related kernel parameters,
+    /// conflicting values and keys used in more than one operation are rejected
+    pub fn new(
+        set_opt: Option<&str>,
+        add_vec: &[String],
+        remove_vec: &[String],
+        replace_vec: &[String],
+    ) -> Result<Self, String> {
+        let set_opt = set_opt.map(parse_kernel_params);
+        let add_vec = parse_kernel_params(&add_vec.join(" "));
+        let remove_vec = parse_kernel_params(&remove_vec.join(" "));
+        let replace_vec = parse_kernel_params(&replace_vec.join(" "));
+
+        for (operation, kernel_param_vec) in [
+            ("kernel-parameters", set_opt.as_deref().unwrap_or_default()),
+            ("add", add_vec.as_slice()),
+            ("remove", remove_vec.as_slice()),
+            ("replace", replace_vec.as_slice()),
+        ] {
+            if let Some(kernel_param) = kernel_param_vec
+                .iter()
+                .find(|kernel_param| kernel_param.is_image_param())
+            {
+                return Err(format!(
+                    "Kernel parameter '{}' is related to the boot image and can't be changed with this command. Please use `manta set boot-image` or `manta set boot-configuration`",
+                    kernel_param.key
+                ));
+            }
+
+            // Removing or replacing several values of the same key is valid, eg
+            // 'console=tty0 console=ttyS0,115200'
+            if operation != "remove" && operation != "replace" {
+                let conflicting_key_vec = get_conflicting_key_vec(kernel_param_vec);
+
+                if !conflicting_key_vec.is_empty() {
+                    return Err(format!(
+                        "Kernel parameters {:?} defined more than once with different values in '--{}'",
+                        conflicting_key_vec, operation
+                    ));
+                }
+            }
+        }
+
+        for kernel_param in &add_vec {
+            if remove_vec.iter().any(|elem| elem.key == kernel_param.key)
+                || replace_vec.iter().any(|elem| elem.key == kernel_param.key)
+            {
+                return Err(format!(
+                    "Kernel parameter '{}' can't be used in more than one operation",
+                    kernel_param.key
+                ));
+            }
+        }
+
+        if let Some(kernel_param) = remove_vec
+            .iter()
+            .find(|kernel_param| replace_vec.iter().any(|elem| elem.key == kernel_param.key))
+        {
+            return Err(format!(
+                "Kernel parameter '{}' can't be used in more than one operation",
+                kernel_param.key
+            ));
+        }
+
+        let kernel_params_edit = KernelParamsEdit {
+            set_opt,
+            add_vec,
+            remove_vec,
+            replace_vec,
+        };
+
+        if kernel_params_edit.is_empty() {
+            return Err("No kernel parameters to change".to_string());
+        }
+
+        Ok(kernel_params_edit)
+    }
+
+    /// Changes applying a kernel parameter profile. Kernel parameters in the profile are added or
+    /// their values replaced, the rest of kernel parameters are kept
+    pub fn from_profile(profile_kernel_params: &str) -> Result<Self, String> {
+        Self::new(None, &[], &[], &[profile_kernel_params.to_string()])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set_opt.is_none()
+            && self.add_vec.is_empty()
+            && self.remove_vec.is_empty()
+            && self.replace_vec.is_empty()
+    }
+
+    /// Returns the new kernel parameters after applying the changes. Image related kernel
+    /// parameters are kept untouched
+    pub fn apply(&self, kernel_params: &str) -> Result<String, String> {
+        let mut kernel_param_vec = parse_kernel_params(kernel_params);
+
+        if let Some(set_vec) = &self.set_opt {
+            let (image_kernel_param_vec, current_kernel_param_vec): (Vec<KernelParam>, Vec<_>) =
+                kernel_param_vec
+                    .iter()
+                    .cloned()
+                    .partition(|kernel_param| kernel_param.is_image_param());
+
+            let mut new_kernel_param_vec = set_vec.clone();
+            new_kernel_param_vec.dedup();
+
+            // Keep the original order if nothing changes
+            if new_kernel_param_vec != current_kernel_param_vec {
+                new_kernel_param_vec.extend(image_kernel_param_vec);
+                kernel_param_vec = new_kernel_param_vec;
+            }
+        }
+
+        kernel_param_vec.retain(|kernel_param| {
+            !self.remove_vec.iter().any(|remove| {
+                remove.key == kernel_param.key
+                    && (remove.value_opt.is_none() || remove.value_opt == kernel_param.value_opt)
+            })
+        });
+
+        let mut replaced_key_vec: Vec<&str> = Vec::new();
+
+        for replace in &self.replace_vec {
+            if replaced_key_vec.contains(&replace.key.as_str()) {
+                continue;
+            }
+
+            replaced_key_vec.push(&replace.key);
+
+            // All values of the key are replaced by the values in the operation
+            let mut new_value_vec: Vec<KernelParam> = Vec::new();
+
+            for kernel_param in self
+                .replace_vec
+                .iter()
+                .filter(|kernel_param| kernel_param.key == replace.key)
+            {
+                if !new_value_vec.contains(kernel_param) {
+                    new_value_vec.push(kernel_param.clone());
+                }
+            }
+
+            let get_sorted_value_vec = |kernel_param_vec: &[KernelParam]| -> Vec<String> {
+                let mut value_vec: Vec<String> = kernel_param_vec
+                    .iter()
+                    .filter(|kernel_param| kernel_param.key == replace.key)
+                    .map(|kernel_param| kernel_param.to_string())
+                    .collect();
+                value_vec.sort();
+                value_vec.dedup();
+                value_vec
+            };
+
+            // Keep the original order if the values do not change
+            if get_sorted_value_vec(&kernel_param_vec) == get_sorted_value_vec(&new_value_vec) {
+                continue;
+            }
+
+            match kernel_param_vec
+                .iter()
+                .position(|kernel_param| kernel_param.key == replace.key)
+            {
+                Some(position) => {
+                    kernel_param_vec = kernel_param_vec
+                        .into_iter()
+                        .enumerate()
+                        .flat_map(|(index, kernel_param)| {
+                            if index == position {
+                                new_value_vec.clone()
+                            } else if kernel_param.key == replace.key {
+                                Vec::new()
+                            } else {
+                                vec![kernel_param]
+                            }
+                        })
+                        .collect();
+                }
+                None => kernel_param_vec.extend(new_value_vec),
+            }
+        }
+
+        for add in &self.add_vec {
+            if kernel_param_vec.contains(add) {
+                log::debug!("Kernel parameter '{}' already exists", add);
+                continue;
+            }
+
+            if let Some(kernel_param) = kernel_param_vec
+                .iter()
+                .find(|kernel_param| kernel_param.key == add.key)
+            {
+                return Err(format!(
+                    "Kernel parameter '{}' already exists as '{}'. Use '--replace' to change its value",
+                    add.key, kernel_param
+                ));
+            }
+
+            kernel_param_vec.push(add.clone());
+        }
+
+        Ok(kernel_params_to_string(&kernel_param_vec))
+    }
+}
+
+/// Updates the kernel parameters for a set of nodes
+/// reboots the nodes which kernel params have changed
+pub async fn exec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    kernel_params_edit: &KernelParamsEdit,
+    hsm_group_name_opt: Option<&Vec<String>>,
+    xname_vec_opt: Option<&Vec<String>>,
+) -> Result<(), Error> {
+    println!("Set kernel parameters");
+
+    let xnames = if let Some(hsm_group_name_vec) = hsm_group_name_opt {
+        mesa::hsm::group::utils::get_member_vec_from_hsm_name_vec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_group_name_vec.clone(),
+        )
+        .await
+    } else if let Some(xname_vec) = xname_vec_opt {
+        xname_vec.clone()
+    } else {
+        return Err(Error::Message(
+            "ERROR - Setting kernel parameters without a list of nodes".to_string(),
+        ));
+    };
+
+    // Get current node boot params
+    let current_node_boot_params: Vec<BootParameters> = bss::bootparameters::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &xnames
+            .iter()
+            .map(|xname| xname.to_string())
+            .collect::<Vec<String>>(),
+    )
+    .await
+    .unwrap();
+
+    // Calculate new kernel parameters
+    let mut boot_parameter_changed_vec: Vec<(BootParameters, String)> = Vec::new();
+    let mut error_vec: Vec<String> = Vec::new();
+
+    for boot_parameter in current_node_boot_params {
+        let conflicting_key_vec =
+            get_conflicting_key_vec(&parse_kernel_params(&boot_parameter.params));
+
+        if !conflicting_key_vec.is_empty() {
+            eprintln!(
+                "WARNING - Nodes {:?} have kernel parameters {:?} defined more than once with different values",
+                boot_parameter.hosts, conflicting_key_vec
+            );
+        }
+
+        match kernel_params_edit.apply(&boot_parameter.params) {
+            Ok(new_kernel_params) => {
+                if parse_kernel_params(&new_kernel_params)
+                    != parse_kernel_params(&boot_parameter.params)
+                {
+                    boot_parameter_changed_vec.push((boot_parameter, new_kernel_params));
+                }
+            }
+            Err(error) => error_vec.push(format!("{:?}: {}", boot_parameter.hosts, error)),
+        }
+    }
+
+    if !error_vec.is_empty() {
+        return Err(Error::Message(format!(
+            "ERROR - Could not update kernel parameters:\n{}",
+            error_vec.join("\n")
+        )));
+    }
+
+    if boot_parameter_changed_vec.is_empty() {
+        println!("Nothing to change. Exit");
+        return Ok(());
+    }
+
+    print_table(&boot_parameter_changed_vec);
+
+    let proceed = dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("This operation will reboot the nodes above. Please confirm to proceed")
+        .interact()
+        .unwrap();
+
+    if !proceed {
+        println!("Operation canceled by the user. Exit");
+        std::process::exit(1);
+    }
+
+    // Update kernel parameters
+    let mut xname_to_reboot_vec: Vec<String> = Vec::new();
+    let mut xname_failed_vec: Vec<String> = Vec::new();
+
+    for (mut boot_parameter, new_kernel_params) in boot_parameter_changed_vec {
+        println!(
+            "Updating '{:?}' kernel parameters to '{}'",
+            boot_parameter.hosts, new_kernel_params
+        );
+
+        boot_parameter.params = new_kernel_params;
+
+        let component_patch_rep = mesa::bss::bootparameters::http_client::patch(
+            shasta_base_url,
+            shasta_token,
+            shasta_root_cert,
+            &boot_parameter,
+        )
+        .await;
+
+        log::debug!(
+            "Component boot parameters resp:\n{:#?}",
+            component_patch_rep
+        );
+
+        // Nodes whose kernel parameters could not be updated are not rebooted
+        if let Err(error) = component_patch_rep {
+            eprintln!(
+                "ERROR - Could not update kernel parameters for nodes {:?}. Reason:\n{}",
+                boot_parameter.hosts, error
+            );
+            xname_failed_vec.extend(boot_parameter.hosts);
+            continue;
+        }
+
+        xname_to_reboot_vec.extend(
+            boot_parameter
+                .hosts
+                .into_iter()
+                .filter(|host| xnames.contains(host)),
+        );
+    }
+
+    // Audit
+    let jwt_claims = get_claims_from_jwt_token(shasta_token).unwrap();
+
+    log::info!(target: "app::audit", "User: {} ({}) ; Operation: Set kernel parameters on {:?}", jwt_claims["name"].as_str().unwrap(), jwt_claims["preferred_username"].as_str().unwrap(), xname_to_reboot_vec);
+
+    if xname_to_reboot_vec.is_empty() && !xname_failed_vec.is_empty() {
+        return Err(Error::Message(format!(
+            "ERROR - Could not update kernel parameters of nodes {:?}",
+            xname_failed_vec
+        )));
+    }
+
+    // Reboot nodes which kernel parameters have changed
+    crate::cli::commands::power_reset_nodes::exec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &xname_to_reboot_vec,
+        None,
+        true,
+        "table",
+        &PowerTransitionOpts::default(),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Prints the kernel parameters removed and added for each node
+pub fn print_table(boot_parameter_changed_vec: &[(BootParameters, String)]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["Xnames", "Removed", "Added"]);
+
+    for (boot_parameter, new_kernel_params) in boot_parameter_changed_vec {
+        let current_kernel_param_vec = parse_kernel_params(&boot_parameter.params);
+        let new_kernel_param_vec = parse_kernel_params(new_kernel_params);
+
+        let removed = current_kernel_param_vec
+            .iter()
+            .filter(|kernel_param| !new_kernel_param_vec.contains(kernel_param))
+            .map(|kernel_param| kernel_param.to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let added = new_kernel_param_vec
+            .iter()
+            .filter(|kernel_param| !current_kernel_param_vec.contains(kernel_param))
+            .map(|kernel_param| kernel_param.to_string())
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        table.add_row(vec![boot_parameter.hosts.join("\n"), removed, added]);
+    }
+
+    println!("{table}");
+}
@@ -0,0 +1,4 @@
+pub mod command;
+pub mod profile;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,232 @@
+use std::{collections::HashMap, path::Path};
+
+use comfy_table::{Cell, Color, Table};
+use config::Config;
+use mesa::bss::bootparameters::BootParameters;
+use serde::Serialize;
+
+use super::command::{parse_kernel_params, KernelParam, KernelParamsEdit};
+
+/// Section in the configuration file where kernel parameter profiles are defined.
+/// eg:
+/// [kernel_parameter_profiles]
+/// gpu-hugepages = "hugepagelist=2m-2g default_hugepagesz=2M intel_iommu=on"
+pub const PROFILES_SETTINGS_KEY: &str = "kernel_parameter_profiles";
+
+/// Returns the kernel parameters of a profile. Profiles defined in the profile file (YAML file
+/// with profile names as keys and kernel parameters as values) take precedence over the ones in
+/// the configuration file. Profiles can't contain kernel parameters related to the boot image
+pub fn get_profile(
+    settings: &Config,
+    profile_name: &str,
+    profile_file_opt: Option<&Path>,
+) -> Result<String, String> {
+    let mut profile_map: HashMap<String, String> = settings
+        .get_table(PROFILES_SETTINGS_KEY)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, value)| value.into_string().ok().map(|value| (name, value)))
+        .collect();
+
+    if let Some(profile_file) = profile_file_opt {
+        let file_content = std::fs::read_to_string(profile_file).map_err(|error| {
+            format!(
+                "Could not read profile file '{}'. Reason: {}",
+                profile_file.display(),
+                error
+            )
+        })?;
+
+        let file_profile_map: HashMap<String, String> = serde_yaml::from_str(&file_content)
+            .map_err(|error| {
+                format!(
+                    "Could not parse profile file '{}'. Reason: {}",
+                    profile_file.display(),
+                    error
+                )
+            })?;
+
+        profile_map.extend(file_profile_map);
+    }
+
+    let kernel_params = profile_map.remove(profile_name).ok_or_else(|| {
+        let mut profile_name_vec: Vec<&String> = profile_map.keys().collect();
+        profile_name_vec.sort();
+
+        format!(
+            "Kernel parameter profile '{}' not found. Profiles available: {:?}",
+            profile_name, profile_name_vec
+        )
+    })?;
+
+    // Validate profile
+    KernelParamsEdit::from_profile(&kernel_params).map_err(|error| {
+        format!(
+            "Kernel parameter profile '{}' not valid. {}",
+            profile_name, error
+        )
+    })?;
+
+    Ok(kernel_params)
+}
+
+/// Differences between the kernel parameters of a node and a profile. Kernel parameters related
+/// to the boot image are ignored
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct KernelParamsDrift {
+    pub xname: String,
+    /// Kernel parameters in the profile not found in the node
+    pub missing: Vec<String>,
+    /// Kernel parameters in the node not found in the profile. Only in strict comparisons
+    pub extra: Vec<String>,
+    /// Kernel parameters with different values. eg 'crashkernel: 360M -> 512M'
+    pub different: Vec<String>,
+}
+
+impl KernelParamsDrift {
+    pub fn is_in_sync(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.different.is_empty()
+    }
+}
+
+/// Compares the kernel parameters of a node against a profile. Only the kernel parameters in the
+/// profile are compared unless 'strict' is set, in which case kernel parameters not in the
+/// profile are reported as extra
+pub fn compare(xname: &str, profile: &str, kernel_params: &str, strict: bool) -> KernelParamsDrift {
+    let profile_kernel_param_vec = parse_kernel_params(profile);
+    let node_kernel_param_vec: Vec<KernelParam> = parse_kernel_params(kernel_params)
+        .into_iter()
+        .filter(|kernel_param| !kernel_param.is_image_param())
+        .filter(|kernel_param| {
+            strict
+                || profile_kernel_param_vec
+                    .iter()
+                    .any(|profile_kernel_param| profile_kernel_param.key == kernel_param.key)
+        })
+        .collect();
+
+    let get_value_vec = |kernel_param_vec: &[KernelParam], key: &str| -> Vec<String> {
+        kernel_param_vec
+            .iter()
+            .filter(|kernel_param| kernel_param.key == key)
+            .map(|kernel_param| kernel_param.value_opt.clone().unwrap_or_default())
+            .collect()
+    };
+
+    let mut kernel_params_drift = KernelParamsDrift {
+        xname: xname.to_string(),
+        ..Default::default()
+    };
+
+    let mut key_processed_vec: Vec<&str> = Vec::new();
+
+    for kernel_param in profile_kernel_param_vec
+        .iter()
+        .chain(node_kernel_param_vec.iter())
+    {
+        if key_processed_vec.contains(&kernel_param.key.as_str()) {
+            continue;
+        }
+
+        key_processed_vec.push(&kernel_param.key);
+
+        let mut expected_value_vec = get_value_vec(&profile_kernel_param_vec, &kernel_param.key);
+        let mut current_value_vec = get_value_vec(&node_kernel_param_vec, &kernel_param.key);
+
+        if current_value_vec.is_empty() {
+            kernel_params_drift.missing.extend(
+                profile_kernel_param_vec
+                    .iter()
+                    .filter(|elem| elem.key == kernel_param.key)
+                    .map(|elem| elem.to_string()),
+            );
+        } else if expected_value_vec.is_empty() {
+            kernel_params_drift.extra.extend(
+                node_kernel_param_vec
+                    .iter()
+                    .filter(|elem| elem.key == kernel_param.key)
+                    .map(|elem| elem.to_string()),
+            );
+        } else {
+            expected_value_vec.sort();
+            current_value_vec.sort();
+
+            if expected_value_vec != current_value_vec {
+                kernel_params_drift.different.push(format!(
+                    "{}: {} -> {}",
+                    kernel_param.key,
+                    current_value_vec.join(","),
+                    expected_value_vec.join(",")
+                ));
+            }
+        }
+    }
+
+    kernel_params_drift
+}
+
+/// Compares the kernel parameters of each node against a profile, see 'compare'. Only the nodes
+/// in 'xname_vec' are compared, boot parameters may be shared with other nodes
+pub fn get_drift_vec(
+    boot_parameter_vec: &[BootParameters],
+    xname_vec: &[String],
+    profile: &str,
+    strict: bool,
+) -> Vec<KernelParamsDrift> {
+    let mut kernel_params_drift_vec: Vec<KernelParamsDrift> = boot_parameter_vec
+        .iter()
+        .flat_map(|boot_parameter| {
+            boot_parameter
+                .hosts
+                .iter()
+                .filter(|xname| xname_vec.contains(xname))
+                .map(|xname| compare(xname, profile, &boot_parameter.params, strict))
+        })
+        .collect();
+
+    kernel_params_drift_vec.sort_by(|drift_1, drift_2| drift_1.xname.cmp(&drift_2.xname));
+
+    kernel_params_drift_vec
+}
+
+/// Prints nodes grouped by drift against the profile
+pub fn print_drift_table(profile_name: &str, kernel_params_drift_vec: &[KernelParamsDrift]) {
+    let mut drift_group_vec: Vec<(Vec<&str>, &KernelParamsDrift)> = Vec::new();
+
+    for kernel_params_drift in kernel_params_drift_vec {
+        match drift_group_vec.iter_mut().find(|(_, drift)| {
+            drift.missing == kernel_params_drift.missing
+                && drift.extra == kernel_params_drift.extra
+                && drift.different == kernel_params_drift.different
+        }) {
+            Some((xname_vec, _)) => xname_vec.push(&kernel_params_drift.xname),
+            None => drift_group_vec.push((vec![&kernel_params_drift.xname], kernel_params_drift)),
+        }
+    }
+
+    let mut table = Table::new();
+
+    table.set_header(vec!["XNAME", "Status", "Missing", "Extra", "Different"]);
+
+    for (xname_vec, kernel_params_drift) in drift_group_vec {
+        let status_cell = if kernel_params_drift.is_in_sync() {
+            Cell::new("In sync").fg(Color::Green)
+        } else {
+            Cell::new("Drift").fg(Color::Red)
+        };
+
+        table.add_row(vec![
+            Cell::new(xname_vec.join("\n")),
+            status_cell,
+            Cell::new(kernel_params_drift.missing.join("\n")),
+            Cell::new(kernel_params_drift.extra.join("\n")),
+            Cell::new(kernel_params_drift.different.join("\n")),
+        ]);
+    }
+
+    println!(
+        "Kernel parameters compared against profile '{}'",
+        profile_name
+    );
+    println!("{table}");
+}
@@ -0,0 +1,285 @@
+use config::Config;
+use mesa::bss::bootparameters::BootParameters;
+
+use super::{
+    command::{get_conflicting_key_vec, parse_kernel_params, KernelParamsEdit},
+    profile::{compare, get_drift_vec, get_profile},
+};
+
+const KERNEL_PARAMS: &str = "console=ttyS0,115200 quiet root=craycps-s3:s3://boot-images/1234/rootfs:etag:dvs:api-gw-service-nmn.local:300:nmn0 nmd_data=url=s3://boot-images/1234/rootfs,etag=abcd crashkernel=360M console=tty0";
+
+fn get_edit(add: &str, remove: &str, replace: &str) -> KernelParamsEdit {
+    let to_vec = |kernel_params: &str| -> Vec<String> {
+        if kernel_params.is_empty() {
+            Vec::new()
+        } else {
+            vec![kernel_params.to_string()]
+        }
+    };
+
+    KernelParamsEdit::new(None, &to_vec(add), &to_vec(remove), &to_vec(replace)).unwrap()
+}
+
+#[test]
+fn test_parse_kernel_params_keeps_order_and_duplicates() {
+    let kernel_param_vec = parse_kernel_params(KERNEL_PARAMS);
+
+    assert_eq!(kernel_param_vec.len(), 6);
+    assert_eq!(kernel_param_vec[0].key, "console");
+    assert_eq!(kernel_param_vec[1].value_opt, None);
+    assert_eq!(
+        kernel_param_vec[3].value_opt.as_deref(),
+        Some("url=s3://boot-images/1234/rootfs,etag=abcd")
+    );
+    assert_eq!(kernel_param_vec[5].to_string(), "console=tty0");
+
+    assert_eq!(get_conflicting_key_vec(&kernel_param_vec), vec!["console"]);
+    assert!(get_conflicting_key_vec(&parse_kernel_params("quiet quiet a=1 a=1")).is_empty());
+}
+
+#[test]
+fn test_kernel_params_add_remove_replace() {
+    let new_kernel_params = get_edit("bad_page=panic", "quiet", "crashkernel=512M")
+        .apply(KERNEL_PARAMS)
+        .unwrap();
+
+    assert_eq!(
+        new_kernel_params,
+        "console=ttyS0,115200 root=craycps-s3:s3://boot-images/1234/rootfs:etag:dvs:api-gw-service-nmn.local:300:nmn0 nmd_data=url=s3://boot-images/1234/rootfs,etag=abcd crashkernel=512M console=tty0 bad_page=panic"
+    );
+
+    // Remove a specific value
+    assert_eq!(
+        get_edit("", "console=tty0", "")
+            .apply("console=ttyS0,115200 quiet console=tty0")
+            .unwrap(),
+        "console=ttyS0,115200 quiet"
+    );
+
+    // Replace all values of a key
+    assert_eq!(
+        get_edit("", "", "console=ttyS1")
+            .apply("console=ttyS0,115200 quiet console=tty0")
+            .unwrap(),
+        "console=ttyS1 quiet"
+    );
+
+    // Adding an existing kernel parameter does not change anything
+    assert_eq!(
+        get_edit("quiet", "", "").apply(KERNEL_PARAMS).unwrap(),
+        KERNEL_PARAMS
+    );
+
+    // Adding an existing kernel parameter with a different value fails
+    assert!(get_edit("crashkernel=512M", "", "")
+        .apply(KERNEL_PARAMS)
+        .is_err());
+}
+
+#[test]
+fn test_kernel_params_set_preserves_image_params() {
+    let kernel_params_edit =
+        KernelParamsEdit::new(Some("quiet console=ttyS0,115200"), &[], &[], &[]).unwrap();
+
+    assert_eq!(
+        kernel_params_edit.apply(KERNEL_PARAMS).unwrap(),
+        "quiet console=ttyS0,115200 root=craycps-s3:s3://boot-images/1234/rootfs:etag:dvs:api-gw-service-nmn.local:300:nmn0 nmd_data=url=s3://boot-images/1234/rootfs,etag=abcd"
+    );
+
+    // Same kernel parameters, original order kept
+    assert_eq!(
+        kernel_params_edit
+            .apply("quiet root=abcd console=ttyS0,115200")
+            .unwrap(),
+        "quiet root=abcd console=ttyS0,115200"
+    );
+}
+
+#[test]
+fn test_kernel_params_edit_validation() {
+    // Image related kernel parameters
+    assert!(KernelParamsEdit::new(Some("quiet root=abcd"), &[], &[], &[]).is_err());
+    assert!(KernelParamsEdit::new(None, &[], &["nmd_data".to_string()], &[]).is_err());
+
+    // Conflicting values
+    assert!(KernelParamsEdit::new(
+        None,
+        &["crashkernel=1G crashkernel=2G".to_string()],
+        &[],
+        &[]
+    )
+    .is_err());
+
+    // Same key in more than one operation
+    assert!(
+        KernelParamsEdit::new(None, &["quiet".to_string()], &[], &["quiet".to_string()]).is_err()
+    );
+
+    // Nothing to change
+    assert!(KernelParamsEdit::new(None, &[], &[], &[]).is_err());
+}
+
+#[test]
+fn test_kernel_params_compare_profile() {
+    let kernel_params_drift = compare(
+        "x1000c0s0b0n0",
+        "console=ttyS0,115200 console=tty0 crashkernel=512M hugepagelist=2m-2g",
+        KERNEL_PARAMS,
+        true,
+    );
+
+    assert_eq!(kernel_params_drift.missing, vec!["hugepagelist=2m-2g"]);
+    // Image related kernel parameters are ignored
+    assert_eq!(kernel_params_drift.extra, vec!["quiet"]);
+    assert_eq!(
+        kernel_params_drift.different,
+        vec!["crashkernel: 360M -> 512M"]
+    );
+    assert!(!kernel_params_drift.is_in_sync());
+
+    // Order does not matter
+    assert!(compare(
+        "x1000c0s0b0n0",
+        "console=tty0 crashkernel=360M quiet console=ttyS0,115200",
+        KERNEL_PARAMS,
+        true,
+    )
+    .is_in_sync());
+}
+
+/// Test only the kernel parameters in the profile are compared unless the comparison is strict
+#[test]
+fn test_kernel_params_compare_profile_keys_only() {
+    let kernel_params_drift = compare(
+        "x1000c0s0b0n0",
+        "crashkernel=512M hugepagelist=2m-2g",
+        KERNEL_PARAMS,
+        false,
+    );
+
+    assert_eq!(kernel_params_drift.missing, vec!["hugepagelist=2m-2g"]);
+    assert!(kernel_params_drift.extra.is_empty());
+    assert_eq!(
+        kernel_params_drift.different,
+        vec!["crashkernel: 360M -> 512M"]
+    );
+
+    assert!(compare("x1000c0s0b0n0", "crashkernel=360M", KERNEL_PARAMS, false).is_in_sync());
+    assert!(!compare("x1000c0s0b0n0", "crashkernel=360M", KERNEL_PARAMS, true).is_in_sync());
+}
+
+/// Test applying a profile adds or replaces the kernel parameters in the profile and keeps the
+/// rest
+#[test]
+fn test_kernel_params_apply_profile() {
+    let kernel_params_edit =
+        KernelParamsEdit::from_profile("crashkernel=512M hugepagelist=2m-2g").unwrap();
+
+    assert_eq!(
+        kernel_params_edit
+            .apply("console=ttyS0,115200 crashkernel=360M quiet root=craycps-s3:s3://boot-images/abcd/rootfs")
+            .unwrap(),
+        "console=ttyS0,115200 crashkernel=512M quiet root=craycps-s3:s3://boot-images/abcd/rootfs hugepagelist=2m-2g"
+    );
+
+    // Profiles can't change kernel parameters related to the boot image
+    assert!(KernelParamsEdit::from_profile("root=abcd").is_err());
+
+    // Keys with several values replace all the values of the key
+    let kernel_params_edit =
+        KernelParamsEdit::from_profile("console=tty0 console=ttyS0,115200").unwrap();
+
+    assert_eq!(
+        kernel_params_edit
+            .apply("quiet console=ttyS1 crashkernel=360M")
+            .unwrap(),
+        "quiet console=tty0 console=ttyS0,115200 crashkernel=360M"
+    );
+
+    // Same values, original order kept
+    assert_eq!(
+        kernel_params_edit
+            .apply("console=ttyS0,115200 quiet console=tty0")
+            .unwrap(),
+        "console=ttyS0,115200 quiet console=tty0"
+    );
+}
+
+/// Test only the nodes requested are compared against the profile
+#[test]
+fn test_kernel_params_get_drift_vec() {
+    let boot_parameter_vec = vec![
+        BootParameters {
+            hosts: vec!["x1000c0s0b0n1".to_string(), "x1000c0s0b0n0".to_string()],
+            params: "crashkernel=360M".to_string(),
+            ..Default::default()
+        },
+        BootParameters {
+            hosts: vec!["x1000c0s1b0n0".to_string()],
+            params: "crashkernel=512M".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let kernel_params_drift_vec = get_drift_vec(
+        &boot_parameter_vec,
+        &["x1000c0s0b0n0".to_string(), "x1000c0s1b0n0".to_string()],
+        "crashkernel=512M",
+        false,
+    );
+
+    assert_eq!(
+        kernel_params_drift_vec
+            .iter()
+            .map(|kernel_params_drift| kernel_params_drift.xname.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["x1000c0s0b0n0", "x1000c0s1b0n0"]
+    );
+    assert!(!kernel_params_drift_vec[0].is_in_sync());
+    assert!(kernel_params_drift_vec[1].is_in_sync());
+}
+
+#[test]
+fn test_kernel_params_get_profile() {
+    let settings = Config::builder()
+        .set_override(
+            "kernel_parameter_profiles.gpu-hugepages",
+            "hugepagelist=2m-2g",
+        )
+        .unwrap()
+        .set_override("kernel_parameter_profiles.debug", "loglevel=7")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        get_profile(&settings, "gpu-hugepages", None).unwrap(),
+        "hugepagelist=2m-2g"
+    );
+    assert!(get_profile(&settings, "missing", None).is_err());
+
+    // Profile file takes precedence over the configuration file
+    let profile_file = std::env::temp_dir().join(format!(
+        "manta-kernel-parameter-profiles-{}.yaml",
+        uuid::Uuid::new_v4()
+    ));
+
+    std::fs::write(
+        &profile_file,
+        "gpu-hugepages: \"hugepagelist=2m-2g intel_iommu=on\"\nnot-valid: \"quiet root=abcd\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        get_profile(&settings, "gpu-hugepages", Some(profile_file.as_path())).unwrap(),
+        "hugepagelist=2m-2g intel_iommu=on"
+    );
+    assert_eq!(
+        get_profile(&settings, "debug", Some(profile_file.as_path())).unwrap(),
+        "loglevel=7"
+    );
+    // Profiles can't change kernel parameters related to the boot image
+    assert!(get_profile(&settings, "not-valid", Some(profile_file.as_path())).is_err());
+
+    std::fs::remove_file(&profile_file).unwrap();
+}
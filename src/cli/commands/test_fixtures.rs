@@ -0,0 +1,90 @@
+use mesa::{
+    cfs::{
+        component::shasta::r#struct::v2::ComponentResponse,
+        configuration::mesa::r#struct::cfs_configuration_response::v3::CfsConfigurationResponse,
+        session::mesa::r#struct::v3::{
+            Artifact, CfsSessionGetResponse, Configuration, Group, Session, Status, Target,
+        },
+    },
+    ims::image::r#struct::Image,
+};
+
+pub fn get_cfs_configuration(name: &str, last_updated: &str) -> CfsConfigurationResponse {
+    CfsConfigurationResponse {
+        name: name.to_string(),
+        last_updated: last_updated.to_string(),
+        layers: Vec::new(),
+        additional_inventory: None,
+    }
+}
+
+pub fn get_cfs_session(
+    name: &str,
+    cfs_configuration_name: &str,
+    target_hsm: &str,
+    definition: &str,
+    succeeded: &str,
+    start_time: &str,
+    result_id_opt: Option<&str>,
+) -> CfsSessionGetResponse {
+    CfsSessionGetResponse {
+        name: Some(name.to_string()),
+        configuration: Some(Configuration {
+            name: Some(cfs_configuration_name.to_string()),
+            limit: None,
+        }),
+        ansible: None,
+        target: Some(Target {
+            definition: Some(definition.to_string()),
+            groups: Some(vec![Group {
+                name: target_hsm.to_string(),
+                members: Vec::new(),
+            }]),
+            image_map: None,
+        }),
+        status: Some(Status {
+            artifacts: result_id_opt.map(|result_id| {
+                vec![Artifact {
+                    image_id: None,
+                    result_id: Some(result_id.to_string()),
+                    r#type: None,
+                }]
+            }),
+            session: Some(Session {
+                job: None,
+                ims_job: None,
+                completion_time: None,
+                start_time: Some(start_time.to_string()),
+                status: Some("complete".to_string()),
+                succeeded: Some(succeeded.to_string()),
+            }),
+        }),
+        tags: None,
+        debug_on_failure: false,
+        logs: None,
+    }
+}
+
+pub fn get_cfs_component(xname: &str, desired_config: &str) -> ComponentResponse {
+    ComponentResponse {
+        id: Some(xname.to_string()),
+        state: None,
+        state_append: None,
+        desired_config: Some(desired_config.to_string()),
+        error_count: Some(0),
+        retry_policy: Some(0),
+        enabled: Some(true),
+        configuration_status: Some("configured".to_string()),
+        tags: None,
+    }
+}
+
+pub fn get_image(id: &str, name: &str, created: &str) -> Image {
+    Image {
+        id: Some(id.to_string()),
+        created: Some(created.to_string()),
+        name: name.to_string(),
+        link: None,
+        arch: None,
+    }
+}
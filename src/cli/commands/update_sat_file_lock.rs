@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use mesa::common::kubernetes;
+
+use crate::cli::commands::apply_sat_file::{
+    lock::{self, SatFileLock},
+    utils,
+};
+
+/// Resolves branches, tags and product versions in the 'configurations' section of a SAT file
+/// to commit ids and stores them in the lock file next to the SAT file
+pub async fn exec(
+    vault_base_url: &str,
+    vault_secret_path: &str,
+    vault_role_id: &str,
+    k8s_api_url: &str,
+    shasta_root_cert: &[u8],
+    gitea_base_url: &str,
+    gitea_token: &str,
+    sat_file_path: &Path,
+    sat_file_content: String,
+    values_file_content_opt: Option<String>,
+    values_cli_opt: Option<Vec<String>>,
+) {
+    let sat_file_yaml = utils::render_jinja2_sat_file_yaml(
+        &sat_file_content,
+        values_file_content_opt.as_ref(),
+        values_cli_opt,
+    );
+
+    let configuration_yaml_vec = sat_file_yaml["configurations"]
+        .as_sequence()
+        .cloned()
+        .unwrap_or_default();
+
+    // Get HPE product catalog from k8s
+    let shasta_k8s_secrets = crate::common::vault::http_client::fetch_shasta_k8s_secrets(
+        vault_base_url,
+        vault_secret_path,
+        vault_role_id,
+    )
+    .await;
+
+    let kube_client = kubernetes::get_k8s_client_programmatically(k8s_api_url, shasta_k8s_secrets)
+        .await
+        .unwrap();
+
+    let cray_product_catalog = kubernetes::get_configmap(kube_client, "cray-product-catalog")
+        .await
+        .unwrap();
+
+    let lock_entry_vec = lock::resolve(
+        shasta_root_cert,
+        gitea_base_url,
+        gitea_token,
+        &cray_product_catalog,
+        &configuration_yaml_vec,
+    )
+    .await;
+
+    let lock_file_path = lock::get_lock_file_path(sat_file_path);
+
+    let mut sat_file_lock = match SatFileLock::read(&lock_file_path) {
+        Ok(sat_file_lock_opt) => sat_file_lock_opt.unwrap_or_default(),
+        Err(error) => {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        }
+    };
+
+    let lock_entry_diff_vec = sat_file_lock.diff(&lock_entry_vec);
+
+    // Lock file also needs an update if layers were removed from the SAT file
+    if lock_entry_diff_vec.is_empty() && sat_file_lock.layers.len() == lock_entry_vec.len() {
+        println!(
+            "Lock file '{}' already up to date",
+            lock_file_path.display()
+        );
+        return;
+    }
+
+    if !lock_entry_diff_vec.is_empty() {
+        lock::print_diff_table(&lock_entry_diff_vec);
+    }
+
+    sat_file_lock.merge(&lock_entry_vec);
+
+    if let Err(error) = sat_file_lock.write(&lock_file_path) {
+        eprintln!("ERROR - {}. Exit", error);
+        std::process::exit(1);
+    }
+
+    println!("Lock file '{}' updated", lock_file_path.display());
+}
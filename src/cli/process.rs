@@ -4,24 +4,43 @@ use clap::ArgMatches;
 use config::Config;
 use k8s_openapi::chrono;
 use mesa::{common::authentication, error::Error};
-
-use crate::cli::commands::validate_local_repo;
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    cli::commands::{
+        gc::policy::GcPolicy,
+        log::{
+            archive::LogArchive,
+            containers::{self, ContainerLogOptions},
+        },
+        set_kernel_parameters::{command::KernelParamsEdit, profile},
+        validate_local_repo,
+    },
+    common::{
+        node_health_ops::HealthPolicy,
+        pcs_utils::{PowerStaggerOpts, PowerTransitionOpts, StaggerGroup},
+        watch_ops::WatchCondition,
+    },
+};
 
 use super::commands::{
     self, add_hw_component_cluster, add_nodes_to_hsm_groups, apply_boot_node, apply_cluster,
     apply_configuration, apply_ephemeral_env, apply_hw_cluster_pin, apply_hw_cluster_unpin,
-    apply_image, apply_sat_file, apply_session, apply_template, config_set_hsm, config_set_log,
-    config_set_parent_hsm, config_set_site,
+    apply_image, apply_sat_file, apply_session, apply_template, audit_artefacts, bootparams,
+    config_set_hsm, config_set_log, config_set_parent_hsm, config_set_site,
     config_show::{self, get_hsm_name_available_from_jwt, get_hsm_name_available_from_jwt_or_all},
     config_unset_auth, config_unset_hsm, config_unset_parent_hsm,
-    console_cfs_session_image_target_ansible, console_node,
+    console_cfs_session_image_target_ansible, console_node, console_recording, console_tail,
+    dashboard,
     delete_data_related_to_cfs_configuration::delete_data_related_cfs_configuration,
-    delete_sessions, get_cluster, get_configuration, get_hsm, get_hw_configuration_node,
-    get_images, get_kernel_parameters, get_nodes, get_session, get_template, migrate_backup,
-    migrate_nodes_between_hsm_groups, power_off_cluster, power_off_nodes, power_on_cluster,
-    power_on_nodes, power_reset_cluster, power_reset_nodes, remove_hw_component_cluster,
-    remove_nodes_from_hsm_groups, set_boot_configuration, set_boot_image, set_kernel_parameters,
-    set_runtime_configuration, update_hsm_group,
+    delete_sessions, diff_configuration, gc, get_cluster, get_configuration, get_hsm,
+    get_hw_configuration_node, get_images, get_kernel_parameters, get_nodes, get_session,
+    get_template, hw_diff, hw_snapshot, lease_create, lease_list, lease_release, migrate_backup,
+    migrate_nodes_between_hsm_groups, power_cap_get, power_cap_set, power_off_cluster,
+    power_off_nodes, power_on_cluster, power_on_nodes, power_reset_cluster, power_reset_nodes,
+    power_status, remove_hw_component_cluster, remove_nodes_from_hsm_groups,
+    set_boot_configuration, set_boot_image, set_kernel_parameters, set_runtime_configuration,
+    update_hsm_group, update_sat_file_lock,
 };
 
 pub async fn process_cli(
@@ -187,6 +206,8 @@ pub async fn process_cli(
                         shasta_root_cert,
                         target_hsm_group,
                         output,
+                        &get_power_transition_opts(cli_power_on_cluster),
+                        get_power_stagger_opts_opt(cli_power_on_cluster).as_ref(),
                     )
                     .await;
                 } else if let Some(cli_power_on_node) = cli_power_on.subcommand_matches("nodes") {
@@ -215,6 +236,8 @@ pub async fn process_cli(
                         &xname_vec,
                         reason,
                         output,
+                        &get_power_transition_opts(cli_power_on_node),
+                        get_power_stagger_opts_opt(cli_power_on_node).as_ref(),
                     )
                     .await;
                 }
@@ -249,6 +272,7 @@ pub async fn process_cli(
                         target_hsm_group,
                         *force,
                         output,
+                        &get_power_transition_opts(cli_power_off_cluster),
                     )
                     .await;
                 } else if let Some(cli_power_off_node) = cli_power_off.subcommand_matches("nodes") {
@@ -282,6 +306,7 @@ pub async fn process_cli(
                         reason,
                         *force,
                         output,
+                        &get_power_transition_opts(cli_power_off_node),
                     )
                     .await;
                 }
@@ -318,6 +343,7 @@ pub async fn process_cli(
                         target_hsm_group,
                         *force,
                         output,
+                        &get_power_transition_opts(cli_power_reset_cluster),
                     )
                     .await;
                 } else if let Some(cli_power_reset_node) =
@@ -353,6 +379,68 @@ pub async fn process_cli(
                         reason,
                         *force,
                         output,
+                        &get_power_transition_opts(cli_power_reset_node),
+                    )
+                    .await;
+                }
+            } else if let Some(cli_power_status) = cli_power.subcommand_matches("status") {
+                let xname_vec = get_target_xname_vec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_power_status,
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                power_status::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &xname_vec,
+                    cli_power_status.get_one::<String>("output").unwrap(),
+                )
+                .await;
+            } else if let Some(cli_power_cap) = cli_power.subcommand_matches("cap") {
+                if let Some(cli_power_cap_get) = cli_power_cap.subcommand_matches("get") {
+                    let xname_vec = get_target_xname_vec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        cli_power_cap_get,
+                        settings_hsm_group_name_opt,
+                    )
+                    .await;
+
+                    power_cap_get::exec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        &xname_vec,
+                        cli_power_cap_get.get_one::<String>("output").unwrap(),
+                    )
+                    .await;
+                } else if let Some(cli_power_cap_set) = cli_power_cap.subcommand_matches("set") {
+                    let xname_vec = get_target_xname_vec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        cli_power_cap_set,
+                        settings_hsm_group_name_opt,
+                    )
+                    .await;
+
+                    power_cap_set::command::exec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        &xname_vec,
+                        cli_power_cap_set.get_one::<String>("control").unwrap(),
+                        *cli_power_cap_set
+                            .get_one::<i64>("value")
+                            .expect("The 'value' argument must have a value"),
+                        cli_power_cap_set.get_flag("yes"),
+                        cli_power_cap_set.get_one::<String>("output").unwrap(),
                     )
                     .await;
                 }
@@ -452,6 +540,7 @@ pub async fn process_cli(
                     target_hsm_group_vec_opt.as_ref(),
                     xname_vec_opt.as_ref(),
                     output,
+                    get_wait_timeout_opt(cli_set_boot_image),
                 )
                 .await;
 
@@ -541,15 +630,59 @@ pub async fn process_cli(
                     None
                 };
 
-                let kernel_parameters = cli_set_kernel_parameters
-                    .get_one::<String>("kernel-parameters")
-                    .unwrap(); // clap should validate the argument
+                let get_kernel_parameter_vec = |arg_name: &str| -> Vec<String> {
+                    cli_set_kernel_parameters
+                        .get_many::<String>(arg_name)
+                        .unwrap_or_default()
+                        .cloned()
+                        .collect()
+                };
 
-                let result = set_kernel_parameters::exec(
+                let profile_kernel_params_opt = cli_set_kernel_parameters
+                    .get_one::<String>("profile")
+                    .map(|profile_name| {
+                        match profile::get_profile(
+                            settings,
+                            profile_name,
+                            cli_set_kernel_parameters
+                                .get_one::<PathBuf>("profile-file")
+                                .map(PathBuf::as_path),
+                        ) {
+                            Ok(profile_kernel_params) => profile_kernel_params,
+                            Err(error) => {
+                                eprintln!("ERROR - {}", error);
+                                std::process::exit(1);
+                            }
+                        }
+                    });
+
+                let kernel_params_edit_rslt = match profile_kernel_params_opt {
+                    Some(profile_kernel_params) => {
+                        KernelParamsEdit::from_profile(&profile_kernel_params)
+                    }
+                    None => KernelParamsEdit::new(
+                        cli_set_kernel_parameters
+                            .get_one::<String>("kernel-parameters")
+                            .map(String::as_str),
+                        &get_kernel_parameter_vec("add"),
+                        &get_kernel_parameter_vec("remove"),
+                        &get_kernel_parameter_vec("replace"),
+                    ),
+                };
+
+                let kernel_params_edit = match kernel_params_edit_rslt {
+                    Ok(kernel_params_edit) => kernel_params_edit,
+                    Err(error) => {
+                        eprintln!("ERROR - {}", error);
+                        std::process::exit(1);
+                    }
+                };
+
+                let result = set_kernel_parameters::command::exec(
                     shasta_token,
                     shasta_base_url,
                     shasta_root_cert,
-                    kernel_parameters,
+                    &kernel_params_edit,
                     target_hsm_group_vec_opt.as_ref(),
                     xname_vec_opt.as_ref(),
                 )
@@ -770,6 +903,8 @@ pub async fn process_cli(
                     .get_one::<String>("xnames")
                     .map(|xname_str| xname_str.split(',').map(|xname| xname.trim()).collect());
 
+                let until_opt = get_watch_condition_opt(cli_get_session);
+
                 get_session::exec(
                     shasta_token,
                     shasta_base_url,
@@ -782,6 +917,8 @@ pub async fn process_cli(
                     cli_get_session.get_one::<String>("name"),
                     limit,
                     cli_get_session.get_one("output"),
+                    cli_get_session.get_one::<u64>("watch"),
+                    until_opt.as_ref(),
                 )
                 .await;
             } else if let Some(cli_get_template) = cli_get.subcommand_matches("templates") {
@@ -839,6 +976,8 @@ pub async fn process_cli(
                 )
                 .await;
 
+                let until_opt = get_watch_condition_opt(cli_get_cluster);
+
                 get_cluster::exec(
                     shasta_token,
                     shasta_base_url,
@@ -852,6 +991,8 @@ pub async fn process_cli(
                         .unwrap_or(&false),
                     cli_get_cluster.get_one::<String>("output"),
                     *cli_get_cluster.get_one::<bool>("status").unwrap_or(&false),
+                    cli_get_cluster.get_one::<u64>("watch"),
+                    until_opt.as_ref(),
                 )
                 .await;
             } else if let Some(cli_get_nodes) = cli_get.subcommand_matches("nodes") {
@@ -873,6 +1014,8 @@ pub async fn process_cli(
                 )
                 .await;
 
+                let until_opt = get_watch_condition_opt(cli_get_nodes);
+
                 get_nodes::exec(
                     shasta_token,
                     shasta_base_url,
@@ -884,6 +1027,8 @@ pub async fn process_cli(
                     false,
                     cli_get_nodes.get_one::<String>("output"),
                     *cli_get_nodes.get_one::<bool>("status").unwrap_or(&false),
+                    cli_get_nodes.get_one::<u64>("watch"),
+                    until_opt.as_ref(),
                 )
                 .await;
             } else if let Some(cli_get_hsm_groups) = cli_get.subcommand_matches("hsm-groups") {
@@ -966,16 +1111,345 @@ pub async fn process_cli(
                         .collect()
                 };
 
+                let profile_name_opt =
+                    cli_get_kernel_parameters.get_one::<String>("compare-profile");
+
+                let profile_kernel_params_opt = profile_name_opt.map(|profile_name| {
+                    match profile::get_profile(
+                        settings,
+                        profile_name,
+                        cli_get_kernel_parameters
+                            .get_one::<PathBuf>("profile-file")
+                            .map(PathBuf::as_path),
+                    ) {
+                        Ok(profile_kernel_params) => profile_kernel_params,
+                        Err(error) => {
+                            eprintln!("ERROR - {}", error);
+                            std::process::exit(1);
+                        }
+                    }
+                });
+
                 let _ = get_kernel_parameters::exec(
                     shasta_token,
                     shasta_base_url,
                     shasta_root_cert,
                     xnames,
                     filter_opt,
+                    profile_name_opt
+                        .map(String::as_str)
+                        .zip(profile_kernel_params_opt.as_deref()),
+                    cli_get_kernel_parameters.get_flag("strict"),
+                    output,
+                )
+                .await;
+            }
+        } else if let Some(cli_hw) = cli_root.subcommand_matches("hw") {
+            if let Some(cli_hw_snapshot) = cli_hw.subcommand_matches("snapshot") {
+                let snapshot_name = cli_hw_snapshot
+                    .get_one::<String>("hsm-group")
+                    .cloned()
+                    .unwrap_or("nodes".to_string());
+
+                let xname_vec = get_target_xname_vec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_hw_snapshot,
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                hw_snapshot::command::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &snapshot_name,
+                    &xname_vec,
+                    cli_hw_snapshot.get_one::<PathBuf>("file"),
+                )
+                .await;
+            } else if let Some(cli_hw_diff) = cli_hw.subcommand_matches("diff") {
+                let output: &String = cli_hw_diff
+                    .get_one("output")
+                    .expect("ERROR - output value missing");
+
+                hw_diff::command::exec(
+                    cli_hw_diff.get_one::<String>("SNAPSHOT_A").unwrap(),
+                    cli_hw_diff.get_one::<String>("SNAPSHOT_B").unwrap(),
+                    output,
+                )
+                .await;
+            }
+        } else if let Some(cli_bootparams) = cli_root.subcommand_matches("bootparams") {
+            if let Some(cli_bootparams_snapshot) = cli_bootparams.subcommand_matches("snapshot") {
+                let snapshot_name = cli_bootparams_snapshot
+                    .get_one::<String>("hsm-group")
+                    .cloned()
+                    .unwrap_or("nodes".to_string());
+
+                let xname_vec = get_target_xname_vec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_bootparams_snapshot,
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                bootparams::snapshot::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &snapshot_name,
+                    &xname_vec,
+                    cli_bootparams_snapshot.get_one::<PathBuf>("file"),
+                )
+                .await;
+            } else if let Some(cli_bootparams_restore) =
+                cli_bootparams.subcommand_matches("restore")
+            {
+                let boot_params_snapshot = match bootparams::snapshot::read_snapshot(
+                    cli_bootparams_restore
+                        .get_one::<String>("SNAPSHOT")
+                        .unwrap(),
+                ) {
+                    Ok(boot_params_snapshot) => boot_params_snapshot,
+                    Err(error) => {
+                        eprintln!("ERROR - {}", error);
+                        std::process::exit(1);
+                    }
+                };
+
+                let xname_vec_opt: Option<Vec<String>> = cli_bootparams_restore
+                    .get_one::<String>("xnames")
+                    .map(|xnames| {
+                        xnames
+                            .split(',')
+                            .map(|xname| xname.trim().to_string())
+                            .collect()
+                    });
+
+                // Validate user has access to the nodes to restore
+                validate_target_hsm_members(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    xname_vec_opt.clone().unwrap_or(
+                        boot_params_snapshot
+                            .nodes
+                            .iter()
+                            .map(|node_boot_params| node_boot_params.xname.clone())
+                            .collect(),
+                    ),
+                )
+                .await;
+
+                bootparams::restore::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &boot_params_snapshot,
+                    xname_vec_opt.as_ref(),
+                    cli_bootparams_restore.get_flag("yes"),
+                    cli_bootparams_restore.get_flag("reboot"),
+                )
+                .await;
+            }
+        } else if let Some(cli_diff) = cli_root.subcommand_matches("diff") {
+            if let Some(cli_diff_configuration) = cli_diff.subcommand_matches("configuration") {
+                let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_diff_configuration.get_one::<String>("hsm-group"),
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                diff_configuration::command::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    gitea_base_url,
+                    gitea_token,
+                    cli_diff_configuration
+                        .get_one::<String>("CONFIGURATION_A")
+                        .unwrap(),
+                    cli_diff_configuration
+                        .get_one::<String>("CONFIGURATION_B")
+                        .unwrap(),
+                    &target_hsm_group_vec,
+                    cli_diff_configuration.get_one::<String>("output").unwrap(),
+                )
+                .await;
+            }
+        } else if let Some(cli_audit) = cli_root.subcommand_matches("audit") {
+            if let Some(cli_audit_artefacts) = cli_audit.subcommand_matches("artefacts") {
+                let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_audit_artefacts.get_one::<String>("hsm-group"),
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                audit_artefacts::command::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &target_hsm_group_vec,
+                    cli_audit_artefacts.get_flag("skip-s3"),
+                    cli_audit_artefacts.get_one::<String>("output").unwrap(),
+                )
+                .await;
+            }
+        } else if let Some(cli_lease) = cli_root.subcommand_matches("lease") {
+            if let Some(cli_lease_create) = cli_lease.subcommand_matches("create") {
+                let dry_run: bool = *cli_lease_create.get_one("dry-run").unwrap();
+
+                let target_hsm_name: &String = cli_lease_create
+                    .get_one("target")
+                    .expect("target value is mandatory");
+
+                let parent_hsm_name: String = match cli_lease_create.get_one::<String>("parent") {
+                    Some(parent_hsm_name) => parent_hsm_name.clone(),
+                    None => match settings.get_string("parent_hsm_group") {
+                        Ok(parent_hsm_name) if !parent_hsm_name.is_empty() => parent_hsm_name,
+                        _ => {
+                            eprintln!("ERROR - Parent HSM group not provided and 'parent_hsm_group' value in configuration file is missing. Exit");
+                            std::process::exit(1);
+                        }
+                    },
+                };
+
+                let duration_str: &String = cli_lease_create
+                    .get_one("duration")
+                    .expect("duration value is mandatory");
+
+                let duration = match crate::common::time_ops::parse_duration(duration_str) {
+                    Some(duration) => duration,
+                    _ => {
+                        eprintln!(
+                            "ERROR - Invalid lease duration '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                            duration_str
+                        );
+                        std::process::exit(1);
+                    }
+                };
+
+                let owner: String = match cli_lease_create.get_one::<String>("owner") {
+                    Some(owner) => owner.clone(),
+                    None => std::env::var("USER").unwrap_or("unknown".to_string()),
+                };
+
+                // Validate user has access to both target and parent HSM groups
+                for hsm_name in [target_hsm_name, &parent_hsm_name] {
+                    if let Err(e) = get_target_hsm_name_group_vec(
+                        shasta_token,
+                        shasta_base_url,
+                        shasta_root_cert,
+                        Some(hsm_name),
+                        settings_hsm_group_name_opt,
+                    )
+                    .await
+                    {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+
+                let xnames_string: &String = cli_lease_create.get_one("XNAMES").unwrap();
+
+                let hsm_group_available_vec = get_target_hsm_group_vec_or_all(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    None,
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                lease_create::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &hsm_group_available_vec,
+                    &owner,
+                    target_hsm_name,
+                    &parent_hsm_name,
+                    duration,
+                    xnames_string,
+                    dry_run,
+                )
+                .await;
+            } else if let Some(cli_lease_list) = cli_lease.subcommand_matches("list") {
+                let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    cli_lease_list.get_one::<String>("hsm-group"),
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                let output: &String = cli_lease_list
+                    .get_one("output")
+                    .expect("ERROR - output value missing");
+
+                lease_list::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &target_hsm_group_vec,
                     output,
                 )
                 .await;
+            } else if let Some(cli_lease_release) = cli_lease.subcommand_matches("release") {
+                let dry_run: bool = *cli_lease_release.get_one("dry-run").unwrap();
+
+                let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    None,
+                    settings_hsm_group_name_opt,
+                )
+                .await;
+
+                lease_release::exec(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &target_hsm_group_vec,
+                    cli_lease_release.get_one::<String>("LEASE_NAME"),
+                    dry_run,
+                )
+                .await;
             }
+        } else if let Some(cli_dashboard) = cli_root.subcommand_matches("dashboard") {
+            let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                cli_dashboard.get_one::<String>("hsm-group"),
+                settings_hsm_group_name_opt,
+            )
+            .await;
+
+            dashboard::command::exec(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                vault_base_url,
+                vault_secret_path,
+                vault_role_id,
+                k8s_api_url,
+                &target_hsm_group_vec,
+                *cli_dashboard.get_one::<u64>("interval").unwrap(),
+            )
+            .await;
         } else if let Some(cli_apply) = cli_root.subcommand_matches("apply") {
             if let Some(cli_apply_hw) = cli_apply.subcommand_matches("hw-configuration") {
                 if let Some(cli_apply_hw_cluster) = cli_apply_hw.subcommand_matches("cluster") {
@@ -1018,6 +1492,10 @@ pub async fn process_cli(
                         .get_one::<bool>("unpin-nodes")
                         .unwrap_or(&false);
 
+                    let health_policy_opt: Option<HealthPolicy> = cli_apply_hw_cluster
+                        .get_one::<String>("health-check")
+                        .map(|health_policy| health_policy.parse().unwrap());
+
                     if *is_unpin {
                         apply_hw_cluster_unpin::command::exec(
                             shasta_token,
@@ -1029,6 +1507,7 @@ pub async fn process_cli(
                             nodryrun,
                             create_target_hsm_group,
                             delete_empty_parent_hsm_group,
+                            health_policy_opt,
                         )
                         .await;
                     } else {
@@ -1042,6 +1521,7 @@ pub async fn process_cli(
                             nodryrun,
                             create_target_hsm_group,
                             delete_empty_parent_hsm_group,
+                            health_policy_opt,
                         )
                         .await;
                     }
@@ -1158,6 +1638,14 @@ pub async fn process_cli(
                     *cli_apply_session
                         .get_one::<bool>("watch-logs")
                         .unwrap_or(&false),
+                    *cli_apply_session
+                        .get_one::<bool>("skip-preflight")
+                        .unwrap_or(&false),
+                    *cli_apply_session.get_one::<bool>("push").unwrap_or(&false),
+                    *cli_apply_session
+                        .get_one::<bool>("structured-logs")
+                        .unwrap_or(&false),
+                    &LogArchive::from_settings(settings),
                 )
                 .await;
             /* } else if let Some(cli_apply_image) = cli_apply.subcommand_matches("image") {
@@ -1330,12 +1818,17 @@ pub async fn process_cli(
                         )
                     });
 
-                let sat_file_content: String = std::fs::read_to_string(
-                    cli_apply_sat_file
-                        .get_one::<PathBuf>("sat-template-file")
-                        .expect("ERROR: SAT file not found. Exit"),
-                )
-                .expect("ERROR: reading SAT file template. Exit");
+                let sat_file_path = cli_apply_sat_file
+                    .get_one::<PathBuf>("sat-template-file")
+                    .expect("ERROR: SAT file not found. Exit");
+
+                let sat_file_content: String = std::fs::read_to_string(sat_file_path)
+                    .expect("ERROR: reading SAT file template. Exit");
+
+                // Lock file is only used if the user asks for it
+                let locked_file_path_opt = cli_apply_sat_file
+                    .get_flag("locked")
+                    .then(|| apply_sat_file::lock::get_lock_file_path(sat_file_path));
 
                 let ansible_passthrough_env = settings.get::<String>("ansible_passthrough").ok();
                 let ansible_passthrough_cli_arg = cli_apply_sat_file
@@ -1384,6 +1877,7 @@ pub async fn process_cli(
                     cli_apply_sat_file.get_flag("sessiontemplate-only"),
                     true,
                     false,
+                    locked_file_path_opt.as_deref(),
                 )
                 .await;
             } else if let Some(cli_apply_template) = cli_apply.subcommand_matches("template") {
@@ -1445,6 +1939,8 @@ pub async fn process_cli(
                         &xname_vec,
                         cli_apply_node_on.get_one::<String>("reason").cloned(),
                         output,
+                        &PowerTransitionOpts::default(),
+                        None,
                     )
                     .await;
                 } else if let Some(cli_apply_node_off) = cli_apply_node.subcommand_matches("off") {
@@ -1492,6 +1988,7 @@ pub async fn process_cli(
                         cli_apply_node_off.get_one::<String>("reason").cloned(),
                         *cli_apply_node_off.get_one::<bool>("force").unwrap(),
                         output,
+                        &PowerTransitionOpts::default(),
                     )
                     .await;
                 } else if let Some(cli_apply_node_reset) =
@@ -1542,6 +2039,7 @@ pub async fn process_cli(
                         cli_apply_node_reset.get_one::<String>("reason").cloned(),
                         *cli_apply_node_reset.get_one::<bool>("force").unwrap(),
                         output,
+                        &PowerTransitionOpts::default(),
                     )
                     .await;
                 }
@@ -1596,6 +2094,7 @@ pub async fn process_cli(
                             .split(',')
                             .map(|xname| xname.trim())
                             .collect(),
+                        get_wait_timeout_opt(cli_apply_boot_nodes),
                     )
                     .await;
                 } else if let Some(cli_apply_boot_cluster) =
@@ -1622,6 +2121,7 @@ pub async fn process_cli(
                         cli_apply_boot_cluster.get_one::<String>("runtime-configuration"),
                         cli_apply_boot_cluster.get_one::<String>("kernel-parameters"),
                         target_hsm_group_vec.first().unwrap(),
+                        get_wait_timeout_opt(cli_apply_boot_cluster),
                     )
                     .await;
                 }
@@ -1658,6 +2158,7 @@ pub async fn process_cli(
                         .split(',')
                         .map(|xname| xname.trim())
                         .collect(),
+                    None,
                 )
                 .await;
             } else if let Some(cli_update_hsm_group) = cli_update.subcommand_matches("hsm-group") {
@@ -1684,6 +2185,49 @@ pub async fn process_cli(
                     cli_update_hsm_group.get_one::<String>("desired-configuration"),
                     cli_update_hsm_group.get_one::<String>("kernel-parameters"),
                     target_hsm_group_vec.first().unwrap(),
+                    None,
+                )
+                .await;
+            } else if let Some(cli_update_sat_file_lock) =
+                cli_update.subcommand_matches("sat-file-lock")
+            {
+                let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
+
+                let cli_value_vec_opt: Option<Vec<String>> = cli_update_sat_file_lock
+                    .get_many("values")
+                    .map(|value_vec| {
+                        value_vec
+                            .map(|value: &String| value.replace("__DATE__", &timestamp))
+                            .collect()
+                    });
+
+                let cli_values_file_content_opt: Option<String> = cli_update_sat_file_lock
+                    .get_one("values-file")
+                    .and_then(|values_file_path: &PathBuf| {
+                        std::fs::read_to_string(values_file_path).ok().map(
+                            |cli_value_file: String| cli_value_file.replace("__DATE__", &timestamp),
+                        )
+                    });
+
+                let sat_file_path = cli_update_sat_file_lock
+                    .get_one::<PathBuf>("sat-template-file")
+                    .expect("ERROR: SAT file not found. Exit");
+
+                let sat_file_content: String = std::fs::read_to_string(sat_file_path)
+                    .expect("ERROR: reading SAT file template. Exit");
+
+                update_sat_file_lock::exec(
+                    vault_base_url,
+                    vault_secret_path,
+                    vault_role_id,
+                    k8s_api_url,
+                    shasta_root_cert,
+                    gitea_base_url,
+                    gitea_token,
+                    sat_file_path,
+                    sat_file_content,
+                    cli_values_file_content_opt,
+                    cli_value_vec_opt,
                 )
                 .await;
             }
@@ -1707,20 +2251,87 @@ pub async fn process_cli(
             )
             .await; */
 
-            commands::log::exec(
-                // cli_log,
-                shasta_token,
-                shasta_base_url,
-                shasta_root_cert,
-                vault_base_url,
-                vault_secret_path,
-                vault_role_id,
-                k8s_api_url,
-                &target_hsm_group_vec,
-                cli_log.get_one::<String>("SESSION_NAME"),
-                settings_hsm_group_name_opt,
-            )
-            .await;
+            let log_archive = LogArchive::from_settings(settings);
+
+            if let Some(pattern) = cli_log.get_one::<String>("search") {
+                commands::log::command::exec_search(
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    &target_hsm_group_vec,
+                    &log_archive,
+                    pattern,
+                    cli_log.get_one::<String>("SESSION_NAME"),
+                    cli_log.get_one::<String>("configuration"),
+                    cli_log.get_one::<String>("xname"),
+                    cli_log.get_one::<String>("since"),
+                )
+                .await;
+            } else {
+                let all_containers = *cli_log.get_one::<bool>("all-containers").unwrap_or(&false);
+                let container_vec: Vec<String> = cli_log
+                    .get_many::<String>("container")
+                    .map(|container_vec| container_vec.cloned().collect())
+                    .unwrap_or_default();
+                let since_opt = cli_log.get_one::<String>("since");
+                let tail_lines_opt = cli_log.get_one::<i64>("tail").cloned();
+
+                // Default log output unless containers or a time window are requested
+                let container_log_options_opt = if all_containers
+                    || !container_vec.is_empty()
+                    || since_opt.is_some()
+                    || tail_lines_opt.is_some()
+                {
+                    let since_seconds_opt = since_opt.map(|since| {
+                        match crate::common::time_ops::parse_duration(since) {
+                            Some(duration) => duration.num_seconds(),
+                            None => {
+                                eprintln!(
+                                    "ERROR - Invalid duration '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                                    since
+                                );
+                                std::process::exit(1);
+                            }
+                        }
+                    });
+
+                    Some(ContainerLogOptions {
+                        container_vec: if all_containers {
+                            Vec::new()
+                        } else if container_vec.is_empty() {
+                            containers::DEFAULT_CONTAINER_VEC
+                                .iter()
+                                .map(|container| container.to_string())
+                                .collect()
+                        } else {
+                            container_vec
+                        },
+                        since_seconds_opt,
+                        tail_lines_opt,
+                        timestamps: *cli_log.get_one::<bool>("timestamps").unwrap_or(&false),
+                    })
+                } else {
+                    None
+                };
+
+                commands::log::command::exec(
+                    // cli_log,
+                    shasta_token,
+                    shasta_base_url,
+                    shasta_root_cert,
+                    vault_base_url,
+                    vault_secret_path,
+                    vault_role_id,
+                    k8s_api_url,
+                    &target_hsm_group_vec,
+                    cli_log.get_one::<String>("SESSION_NAME"),
+                    settings_hsm_group_name_opt,
+                    *cli_log.get_one::<bool>("structured").unwrap_or(&false),
+                    &log_archive,
+                    container_log_options_opt,
+                )
+                .await;
+            }
         } else if let Some(cli_console) = cli_root.subcommand_matches("console") {
             if let Some(cli_console_node) = cli_console.subcommand_matches("node") {
                 if !std::io::stdout().is_terminal() {
@@ -1728,7 +2339,18 @@ pub async fn process_cli(
                     std::process::exit(1);
                 }
 
-                console_node::exec(
+                let xname = cli_console_node.get_one::<String>("XNAME").unwrap();
+
+                let record_file_opt: Option<PathBuf> = cli_console_node
+                    .get_one::<PathBuf>("record-file")
+                    .cloned()
+                    .or_else(|| {
+                        cli_console_node.get_flag("record").then(|| {
+                            console_recording::recorder::get_default_recording_file_path(xname)
+                        })
+                    });
+
+                console_node::command::exec(
                     settings_hsm_group_name_opt,
                     // cli_console,
                     shasta_token,
@@ -1738,7 +2360,97 @@ pub async fn process_cli(
                     vault_secret_path,
                     vault_role_id,
                     k8s_api_url,
-                    cli_console_node.get_one::<String>("XNAME").unwrap(),
+                    xname,
+                    record_file_opt,
+                )
+                .await;
+            } else if let Some(cli_console_tail) = cli_console.subcommand_matches("tail") {
+                let xname_vec =
+                    if let Some(xname_hostlist) = cli_console_tail.get_one::<String>("xnames") {
+                        let xname_vec = match hostlist_parser::parse(xname_hostlist) {
+                            Ok(xname_vec) => xname_vec,
+                            Err(error) => {
+                                eprintln!(
+                                    "ERROR - Could not parse hostlist '{}'. Reason:\n{}\nExit",
+                                    xname_hostlist, error
+                                );
+                                std::process::exit(1);
+                            }
+                        };
+
+                        validate_target_hsm_members(
+                            shasta_token,
+                            shasta_base_url,
+                            shasta_root_cert,
+                            xname_vec,
+                        )
+                        .await
+                    } else {
+                        get_target_xname_vec(
+                            shasta_token,
+                            shasta_base_url,
+                            shasta_root_cert,
+                            cli_console_tail,
+                            settings_hsm_group_name_opt,
+                        )
+                        .await
+                    };
+
+                let ignore_case = cli_console_tail.get_flag("ignore-case");
+
+                let filter_vec: Vec<Regex> = cli_console_tail
+                    .get_many::<String>("grep")
+                    .unwrap_or_default()
+                    .map(|pattern| {
+                        match RegexBuilder::new(pattern)
+                            .case_insensitive(ignore_case)
+                            .build()
+                        {
+                            Ok(regex) => regex,
+                            Err(error) => {
+                                eprintln!(
+                                    "ERROR - Invalid regular expression '{}'. Reason:\n{}\nExit",
+                                    pattern, error
+                                );
+                                std::process::exit(1);
+                            }
+                        }
+                    })
+                    .collect();
+
+                let duration_opt = cli_console_tail
+                    .get_one::<String>("duration")
+                    .map(|duration| match crate::common::time_ops::parse_duration(duration) {
+                        Some(duration) => duration,
+                        None => {
+                            eprintln!(
+                                "ERROR - Invalid duration '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                                duration
+                            );
+                            std::process::exit(1);
+                        }
+                    });
+
+                console_tail::command::exec(
+                    vault_base_url,
+                    vault_secret_path,
+                    vault_role_id,
+                    k8s_api_url,
+                    &xname_vec,
+                    &filter_vec,
+                    cli_console_tail
+                        .get_one::<PathBuf>("log-dir")
+                        .map(|log_dir| log_dir.as_path()),
+                    duration_opt,
+                )
+                .await;
+            } else if let Some(cli_console_replay) = cli_console.subcommand_matches("replay") {
+                console_recording::replay::exec(
+                    cli_console_replay.get_one::<String>("FILE").unwrap(),
+                    *cli_console_replay.get_one::<f64>("speed").unwrap(),
+                    cli_console_replay
+                        .get_one::<f64>("idle-time-limit")
+                        .copied(),
                 )
                 .await;
             } else if let Some(cli_console_target_ansible) =
@@ -1784,6 +2496,10 @@ pub async fn process_cli(
 
                 let xnames_string: &String = cli_migrate_nodes.get_one("XNAMES").unwrap();
 
+                let health_policy_opt: Option<HealthPolicy> = cli_migrate_nodes
+                    .get_one::<String>("health-check")
+                    .map(|health_policy| health_policy.parse().unwrap());
+
                 // Get target hsm group from either cli arguments or config and validate
                 let from_rslt = get_target_hsm_name_group_vec(
                     shasta_token,
@@ -1830,6 +2546,7 @@ pub async fn process_cli(
                     xnames_string,
                     !dry_run,
                     false,
+                    health_policy_opt,
                 )
                 .await;
             } else if let Some(_cli_migrate_vcluster) = cli_migrate.subcommand_matches("vCluster") {
@@ -1941,6 +2658,44 @@ pub async fn process_cli(
                 yes,
             )
             .await;
+        } else if let Some(cli_gc) = cli_root.subcommand_matches("gc") {
+            let target_hsm_group_vec = get_target_hsm_group_vec_or_all(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                cli_gc.get_one::<String>("hsm-group"),
+                settings_hsm_group_name_opt,
+            )
+            .await;
+
+            let gc_policy = match GcPolicy::from_settings(settings).and_then(|gc_policy| {
+                gc_policy.merge(
+                    cli_gc.get_one::<usize>("keep-last").copied(),
+                    cli_gc
+                        .get_many::<String>("prefix")
+                        .map(|prefix_vec| prefix_vec.cloned().collect()),
+                    cli_gc.get_one::<String>("failed-session-max-age"),
+                    cli_gc.get_one::<String>("orphan-image-max-age"),
+                )
+            }) {
+                Ok(gc_policy) => gc_policy,
+                Err(error) => {
+                    eprintln!("ERROR - {}. Exit", error);
+                    std::process::exit(1);
+                }
+            };
+
+            gc::command::exec(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                &target_hsm_group_vec,
+                &gc_policy,
+                cli_gc.get_one::<String>("output").unwrap(),
+                cli_gc.get_flag("dry-run"),
+                cli_gc.get_flag("yes"),
+            )
+            .await;
         } else if let Some(cli_validate_local_repo) =
             cli_root.subcommand_matches("validate-local-repo")
         {
@@ -2051,6 +2806,99 @@ pub fn validate_hsm_groups(
 
     Ok(())
 }
+
+/// Parses the '--until' condition used to stop watching resources
+pub fn get_watch_condition_opt(cli_matches: &ArgMatches) -> Option<WatchCondition> {
+    cli_matches.get_one::<String>("until").map(|condition| {
+        condition.parse::<WatchCondition>().unwrap_or_else(|error| {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        })
+    })
+}
+
+/// Parses the '--timeout' and '--retries' arguments of power commands
+pub fn get_power_transition_opts(cli_matches: &ArgMatches) -> PowerTransitionOpts {
+    let timeout_str: &String = cli_matches
+        .get_one("timeout")
+        .expect("timeout has a default value");
+
+    let timeout = match crate::common::time_ops::parse_duration(timeout_str) {
+        Some(timeout) => timeout,
+        _ => {
+            eprintln!(
+                "ERROR - Invalid timeout '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                timeout_str
+            );
+            std::process::exit(1);
+        }
+    };
+
+    PowerTransitionOpts {
+        timeout,
+        retries: *cli_matches
+            .get_one::<usize>("retries")
+            .expect("retries has a default value"),
+    }
+}
+
+/// Parses the '--stagger', '--batch-size' and '--batch-delay' arguments of power on commands
+pub fn get_power_stagger_opts_opt(cli_matches: &ArgMatches) -> Option<PowerStaggerOpts> {
+    let stagger_group_str = cli_matches.get_one::<String>("stagger")?;
+
+    let group = stagger_group_str
+        .parse::<StaggerGroup>()
+        .unwrap_or_else(|error| {
+            eprintln!("ERROR - {}. Exit", error);
+            std::process::exit(1);
+        });
+
+    let batch_delay_str: &String = cli_matches
+        .get_one("batch-delay")
+        .expect("batch-delay has a default value");
+
+    let delay = match crate::common::time_ops::parse_duration(batch_delay_str) {
+        Some(delay) => delay,
+        _ => {
+            eprintln!(
+                "ERROR - Invalid batch delay '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                batch_delay_str
+            );
+            std::process::exit(1);
+        }
+    };
+
+    Some(PowerStaggerOpts {
+        group,
+        batch_size: *cli_matches
+            .get_one::<usize>("batch-size")
+            .expect("batch-size has a default value"),
+        delay,
+    })
+}
+
+/// Parses the '--wait-timeout' argument if the user asked to wait for the nodes to boot
+pub fn get_wait_timeout_opt(cli_matches: &ArgMatches) -> Option<chrono::Duration> {
+    if !cli_matches.get_flag("wait") {
+        return None;
+    }
+
+    let wait_timeout_str: &String = cli_matches
+        .get_one("wait-timeout")
+        .expect("wait-timeout has a default value");
+
+    match crate::common::time_ops::parse_duration(wait_timeout_str) {
+        Some(wait_timeout) => Some(wait_timeout),
+        _ => {
+            eprintln!(
+                "ERROR - Invalid wait timeout '{}'. Use a number followed by 's', 'm', 'h', 'd' or 'w'. Exit",
+                wait_timeout_str
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
 /// Returns a list of HSM groups the user is expected to work with.
 /// This method will exit if the user is asking for HSM group not allowed
 /// If the user did not requested any HSM group, then it will return all HSM
@@ -2174,6 +3022,54 @@ pub async fn get_target_hsm_group_vec(
     }
 }
 
+/// Returns the nodes targeted by a command with either '--hsm-group' or '--xnames' arguments.
+/// Exits if the user does not have access to the HSM group or the nodes
+pub async fn get_target_xname_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    cli_matches: &ArgMatches,
+    settings_hsm_group_name_opt: Option<&String>,
+) -> Vec<String> {
+    let hsm_group_name_arg_opt = cli_matches.get_one::<String>("hsm-group");
+
+    if hsm_group_name_arg_opt.is_some() {
+        let hsm_group_name_vec = get_target_hsm_group_vec_or_all(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_group_name_arg_opt,
+            settings_hsm_group_name_opt,
+        )
+        .await;
+
+        mesa::hsm::group::utils::get_member_vec_from_hsm_name_vec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            hsm_group_name_vec,
+        )
+        .await
+    } else {
+        let xname_vec: Vec<String> = cli_matches
+            .get_one::<String>("xnames")
+            .expect("Neither HSM group nor xnames defined")
+            .split(',')
+            .map(|xname| xname.trim().to_string())
+            .collect();
+
+        validate_target_hsm_members(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            xname_vec.clone(),
+        )
+        .await;
+
+        xname_vec
+    }
+}
+
 /// Validate user has access to a list of HSM group members provided.
 /// HSM members user is asking for are taken from cli command
 /// Exit if user does not have access to any of the members provided. By not having access to a HSM
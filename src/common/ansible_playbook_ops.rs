@@ -0,0 +1,499 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use comfy_table::{Cell, Color, Table};
+use serde_yaml::Value;
+use strum_macros::Display;
+
+/// Groups always available in an ansible inventory
+const BUILTIN_INVENTORY_GROUP_VEC: [&str; 3] = ["all", "ungrouped", "localhost"];
+
+const TASK_LIST_KEY_VEC: [&str; 7] = [
+    "tasks",
+    "pre_tasks",
+    "post_tasks",
+    "handlers",
+    "block",
+    "rescue",
+    "always",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Severity {
+    #[strum(serialize = "ERROR")]
+    Error,
+    #[strum(serialize = "WARNING")]
+    Warning,
+}
+
+/// Problem found in a local ansible repo
+#[derive(Debug, Clone)]
+pub struct PreflightIssue {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl PreflightIssue {
+    fn error(file: &Path, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            file: file.to_path_buf(),
+            message,
+        }
+    }
+
+    fn warning(file: &Path, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            file: file.to_path_buf(),
+            message,
+        }
+    }
+}
+
+/// Returns the groups the CFS dynamic inventory creates for a list of nodes. These are the HSM
+/// groups the nodes belong to, their roles and roles and subroles combined (eg 'Application_UAN')
+pub async fn get_cfs_inventory_group_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[&str],
+) -> Vec<String> {
+    let mut inventory_group_vec = mesa::hsm::group::utils::get_hsm_group_vec_from_xname_vec(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        xname_vec,
+    )
+    .await;
+
+    let hsm_component_vec = mesa::hsm::component_status::http_client::get(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &xname_vec
+            .iter()
+            .map(|xname| xname.to_string())
+            .collect::<Vec<String>>(),
+    )
+    .await
+    .unwrap_or_default();
+
+    for hsm_component in hsm_component_vec {
+        if let Some(role) = hsm_component["Role"].as_str() {
+            inventory_group_vec.push(role.to_string());
+
+            if let Some(subrole) = hsm_component["SubRole"].as_str() {
+                inventory_group_vec.push(format!("{}_{}", role, subrole));
+            }
+        }
+    }
+
+    inventory_group_vec.sort();
+    inventory_group_vec.dedup();
+
+    inventory_group_vec
+}
+
+/// Checks a local ansible repo before it is used by a CFS session:
+///  - playbook exists and is valid YAML
+///  - playbooks imported, roles, task files and vars files referenced exist
+///  - groups in plays 'hosts' and in 'group_vars' exist in the CFS inventory
+/// Paths with jinja2 expressions are ignored since they can only be resolved by ansible
+pub fn preflight(
+    repo_path: &Path,
+    playbook: &str,
+    inventory_group_vec: &[String],
+) -> Vec<PreflightIssue> {
+    let mut preflight_issue_vec = Vec::new();
+
+    let playbook_path = repo_path.join(playbook);
+
+    if !playbook_path.is_file() {
+        preflight_issue_vec.push(PreflightIssue::error(
+            &playbook_path,
+            format!("playbook '{}' not found", playbook),
+        ));
+
+        return preflight_issue_vec;
+    }
+
+    let mut playbook_visited_vec = HashSet::new();
+
+    check_playbook(
+        repo_path,
+        &playbook_path,
+        inventory_group_vec,
+        &mut playbook_visited_vec,
+        &mut preflight_issue_vec,
+    );
+
+    check_group_vars(repo_path, inventory_group_vec, &mut preflight_issue_vec);
+
+    preflight_issue_vec
+}
+
+fn is_templated(value: &str) -> bool {
+    value.contains("{{") || value.contains("{%")
+}
+
+fn read_yaml(file: &Path, preflight_issue_vec: &mut Vec<PreflightIssue>) -> Option<Value> {
+    let content = match std::fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(error) => {
+            preflight_issue_vec.push(PreflightIssue::error(
+                file,
+                format!("could not read file: {}", error),
+            ));
+            return None;
+        }
+    };
+
+    match serde_yaml::from_str::<Value>(&content) {
+        Ok(yaml) => Some(yaml),
+        Err(error) => {
+            preflight_issue_vec.push(PreflightIssue::error(
+                file,
+                format!("invalid YAML: {}", error),
+            ));
+            None
+        }
+    }
+}
+
+/// Returns the value of a module in a task, either using its short name or its fully qualified
+/// collection name (eg 'include_tasks' or 'ansible.builtin.include_tasks')
+fn get_module<'a>(task: &'a Value, module: &str) -> Option<&'a Value> {
+    task.get(module)
+        .or_else(|| task.get(format!("ansible.builtin.{}", module)))
+        .or_else(|| task.get(format!("ansible.legacy.{}", module)))
+}
+
+fn check_playbook(
+    repo_path: &Path,
+    playbook_path: &Path,
+    inventory_group_vec: &[String],
+    playbook_visited_vec: &mut HashSet<PathBuf>,
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    if !playbook_visited_vec.insert(playbook_path.to_path_buf()) {
+        return;
+    }
+
+    let Some(playbook_yaml) = read_yaml(playbook_path, preflight_issue_vec) else {
+        return;
+    };
+
+    let Some(play_vec) = playbook_yaml.as_sequence() else {
+        preflight_issue_vec.push(PreflightIssue::error(
+            playbook_path,
+            "playbook must be a list of plays".to_string(),
+        ));
+        return;
+    };
+
+    let playbook_dir = playbook_path.parent().unwrap_or(repo_path);
+
+    for play in play_vec {
+        // Imported playbooks
+        if let Some(imported_playbook) = get_module(play, "import_playbook")
+            .or_else(|| play.get("include"))
+            .and_then(Value::as_str)
+        {
+            if is_templated(imported_playbook) {
+                continue;
+            }
+
+            let imported_playbook_path = playbook_dir.join(imported_playbook);
+
+            if imported_playbook_path.is_file() {
+                check_playbook(
+                    repo_path,
+                    &imported_playbook_path,
+                    inventory_group_vec,
+                    playbook_visited_vec,
+                    preflight_issue_vec,
+                );
+            } else {
+                preflight_issue_vec.push(PreflightIssue::error(
+                    playbook_path,
+                    format!("imported playbook '{}' not found", imported_playbook),
+                ));
+            }
+
+            continue;
+        }
+
+        // Inventory groups
+        if let Some(hosts) = play.get("hosts").and_then(Value::as_str) {
+            check_hosts(
+                playbook_path,
+                hosts,
+                inventory_group_vec,
+                preflight_issue_vec,
+            );
+        }
+
+        // Vars files
+        for vars_file in play
+            .get("vars_files")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !is_templated(vars_file) && !playbook_dir.join(vars_file).is_file() {
+                preflight_issue_vec.push(PreflightIssue::error(
+                    playbook_path,
+                    format!("vars file '{}' not found", vars_file),
+                ));
+            }
+        }
+
+        // Roles
+        for role in play
+            .get("roles")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+        {
+            let role_name_opt = role
+                .as_str()
+                .or_else(|| role.get("role").and_then(Value::as_str))
+                .or_else(|| role.get("name").and_then(Value::as_str));
+
+            match role_name_opt {
+                Some(role_name) => check_role(
+                    repo_path,
+                    playbook_dir,
+                    playbook_path,
+                    role_name,
+                    preflight_issue_vec,
+                ),
+                None => preflight_issue_vec.push(PreflightIssue::error(
+                    playbook_path,
+                    "role without name".to_string(),
+                )),
+            }
+        }
+
+        // Tasks
+        check_task_list(
+            repo_path,
+            playbook_dir,
+            playbook_path,
+            play,
+            preflight_issue_vec,
+        );
+    }
+}
+
+/// Checks groups in a play 'hosts' pattern exist in the inventory. Patterns with wildcards or
+/// regular expressions are ignored
+fn check_hosts(
+    playbook_path: &Path,
+    hosts: &str,
+    inventory_group_vec: &[String],
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    if is_templated(hosts) {
+        return;
+    }
+
+    for host_pattern in hosts.split([':', ',']) {
+        let group = host_pattern.trim().trim_start_matches(['!', '&']);
+
+        if group.is_empty()
+            || group.contains(['*', '?', '~', '['])
+            || BUILTIN_INVENTORY_GROUP_VEC.contains(&group)
+            // xnames
+            || group.starts_with('x') && group.chars().nth(1).is_some_and(|c| c.is_ascii_digit())
+        {
+            continue;
+        }
+
+        if !inventory_group_vec
+            .iter()
+            .any(|inventory_group| inventory_group.eq(group))
+        {
+            preflight_issue_vec.push(PreflightIssue::warning(
+                playbook_path,
+                format!(
+                    "hosts pattern '{}' references group '{}' which is not in the CFS inventory for the target nodes, plays will be skipped",
+                    hosts, group
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks a role exists either next to the playbook or in the repo 'roles' folder. Roles in
+/// collections (eg 'namespace.collection.role') are ignored
+fn check_role(
+    repo_path: &Path,
+    playbook_dir: &Path,
+    file: &Path,
+    role_name: &str,
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    if is_templated(role_name) || role_name.contains('.') {
+        return;
+    }
+
+    let role_path_opt = [playbook_dir.join("roles"), repo_path.join("roles")]
+        .into_iter()
+        .map(|roles_path| roles_path.join(role_name))
+        .find(|role_path| role_path.is_dir());
+
+    let Some(role_path) = role_path_opt else {
+        preflight_issue_vec.push(PreflightIssue::error(
+            file,
+            format!("role '{}' not found", role_name),
+        ));
+        return;
+    };
+
+    for role_task_file in ["tasks/main.yml", "tasks/main.yaml", "handlers/main.yml"] {
+        let role_task_path = role_path.join(role_task_file);
+
+        if !role_task_path.is_file() {
+            continue;
+        }
+
+        if let Some(task_vec) = read_yaml(&role_task_path, preflight_issue_vec) {
+            check_task_vec(
+                repo_path,
+                role_task_path.parent().unwrap(),
+                &role_task_path,
+                &task_vec,
+                preflight_issue_vec,
+            );
+        }
+    }
+}
+
+/// Checks the task lists ('tasks', 'pre_tasks', 'block', ...) in a play or a task
+fn check_task_list(
+    repo_path: &Path,
+    base_dir: &Path,
+    file: &Path,
+    value: &Value,
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    for task_list_key in TASK_LIST_KEY_VEC {
+        if let Some(task_vec) = value.get(task_list_key) {
+            check_task_vec(repo_path, base_dir, file, task_vec, preflight_issue_vec);
+        }
+    }
+}
+
+fn check_task_vec(
+    repo_path: &Path,
+    base_dir: &Path,
+    file: &Path,
+    task_vec: &Value,
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    let Some(task_vec) = task_vec.as_sequence() else {
+        return;
+    };
+
+    for task in task_vec {
+        for module in ["include_tasks", "import_tasks"] {
+            let Some(task_file_value) = get_module(task, module) else {
+                continue;
+            };
+
+            let task_file_opt = task_file_value
+                .as_str()
+                .or_else(|| task_file_value.get("file").and_then(Value::as_str));
+
+            if let Some(task_file) = task_file_opt {
+                if !is_templated(task_file) && !base_dir.join(task_file).is_file() {
+                    preflight_issue_vec.push(PreflightIssue::error(
+                        file,
+                        format!("{} file '{}' not found", module, task_file),
+                    ));
+                }
+            }
+        }
+
+        for module in ["include_role", "import_role"] {
+            if let Some(role_name) = get_module(task, module)
+                .and_then(|role| role.get("name"))
+                .and_then(Value::as_str)
+            {
+                check_role(repo_path, base_dir, file, role_name, preflight_issue_vec);
+            }
+        }
+
+        check_task_list(repo_path, base_dir, file, task, preflight_issue_vec);
+    }
+}
+
+/// Checks files and folders in 'group_vars' are related to groups in the inventory
+fn check_group_vars(
+    repo_path: &Path,
+    inventory_group_vec: &[String],
+    preflight_issue_vec: &mut Vec<PreflightIssue>,
+) {
+    let Ok(group_vars_dir) = std::fs::read_dir(repo_path.join("group_vars")) else {
+        return;
+    };
+
+    for group_vars_entry in group_vars_dir.flatten() {
+        let group_vars_path = group_vars_entry.path();
+
+        let group_opt = if group_vars_path.is_dir() {
+            group_vars_path.file_name()
+        } else {
+            group_vars_path.file_stem()
+        }
+        .and_then(|group| group.to_str());
+
+        let Some(group) = group_opt else {
+            continue;
+        };
+
+        if group.starts_with('.') || BUILTIN_INVENTORY_GROUP_VEC.contains(&group) {
+            continue;
+        }
+
+        if !inventory_group_vec
+            .iter()
+            .any(|inventory_group| inventory_group.eq(group))
+        {
+            preflight_issue_vec.push(PreflightIssue::warning(
+                &group_vars_path,
+                format!(
+                    "variables for group '{}' which is not in the CFS inventory for the target nodes, they won't be applied",
+                    group
+                ),
+            ));
+        }
+    }
+}
+
+pub fn print_table(preflight_issue_vec: &[PreflightIssue]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["Severity", "File", "Problem"]);
+
+    for preflight_issue in preflight_issue_vec {
+        let color = match preflight_issue.severity {
+            Severity::Error => Color::Red,
+            Severity::Warning => Color::Yellow,
+        };
+
+        table.add_row(vec![
+            Cell::new(preflight_issue.severity).fg(color),
+            Cell::new(preflight_issue.file.display()),
+            Cell::new(&preflight_issue.message),
+        ]);
+    }
+
+    println!("{table}");
+}
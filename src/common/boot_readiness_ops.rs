@@ -0,0 +1,502 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Instant,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use comfy_table::{Cell, Color, Table};
+use mesa::error::Error;
+use serde_json::Value;
+use strum_macros::Display;
+
+use crate::common::{http_client, time_ops::parse_date};
+
+/// Seconds between polls while waiting for nodes to be ready
+const POLL_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum BootReadinessStatus {
+    /// Node not rebooted or not powered on yet
+    #[strum(serialize = "PENDING")]
+    Pending,
+    /// Node powered on but the image reported by the node is not the expected one yet
+    #[strum(serialize = "BOOTING")]
+    Booting,
+    /// Node powered on and configured but there is no BOS reporter data to check whether it
+    /// rebooted or which image it booted
+    #[strum(serialize = "NOT VERIFIABLE")]
+    NotVerifiable,
+    #[strum(serialize = "READY")]
+    Ready,
+    #[strum(serialize = "MISMATCH")]
+    Mismatch,
+    #[strum(serialize = "CONFIGURATION FAILED")]
+    ConfigurationFailed,
+    #[strum(serialize = "TIMEOUT")]
+    Timeout,
+}
+
+impl BootReadinessStatus {
+    /// Nodes not verifiable are still polled in case they are seen rebooting, see
+    /// 'is_only_not_verifiable_left'
+    pub fn is_final(&self) -> bool {
+        !matches!(
+            self,
+            BootReadinessStatus::Pending
+                | BootReadinessStatus::Booting
+                | BootReadinessStatus::NotVerifiable
+        )
+    }
+}
+
+/// State of a node collected from HSM, BSS, CFS and BOS
+#[derive(Debug, Clone, Default)]
+pub struct NodeBootState {
+    pub xname: String,
+    pub power_status: String,
+    /// Image id in the node boot parameters
+    pub bss_image_id: String,
+    /// Kernel and initrd the node booted with, as reported by the node to BOS
+    pub booted_kernel_opt: Option<String>,
+    pub booted_initrd_opt: Option<String>,
+    pub booted_last_updated_opt: Option<DateTime<Utc>>,
+    pub desired_configuration: String,
+    pub configuration_status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootReadiness {
+    pub xname: String,
+    pub status: BootReadinessStatus,
+    pub detail: String,
+}
+
+/// Returns the image id in a boot artefact path like 's3://boot-images/<image id>/kernel'
+pub fn get_image_id_from_boot_artifact_path(path: &str) -> String {
+    let path_elem_vec: Vec<&str> = path.split('/').skip(3).collect();
+
+    match path_elem_vec.split_last() {
+        Some((_, image_path_elem_vec)) if !image_path_elem_vec.is_empty() => {
+            image_path_elem_vec.join("/")
+        }
+        _ => String::new(),
+    }
+}
+
+/// Checks if a node booted the expected image and, if provided, the expected CFS configuration
+/// was applied. 'rebooted' tells whether the node went through a reboot since the wait started.
+/// Nodes without BOS reporter data fall back to HSM power state and CFS configuration status
+pub fn evaluate(
+    node_boot_state: &NodeBootState,
+    expected_image_id: &str,
+    expected_configuration_opt: Option<&str>,
+    rebooted: bool,
+) -> BootReadiness {
+    let boot_readiness = |status: BootReadinessStatus, detail: String| BootReadiness {
+        xname: node_boot_state.xname.clone(),
+        status,
+        detail,
+    };
+
+    if node_boot_state.bss_image_id != expected_image_id {
+        return boot_readiness(
+            BootReadinessStatus::Mismatch,
+            format!(
+                "boot parameters point to image '{}' instead of '{}'",
+                node_boot_state.bss_image_id, expected_image_id
+            ),
+        );
+    }
+
+    if let Some(expected_configuration) = expected_configuration_opt {
+        if node_boot_state.desired_configuration != expected_configuration {
+            return boot_readiness(
+                BootReadinessStatus::Mismatch,
+                format!(
+                    "desired configuration is '{}' instead of '{}'",
+                    node_boot_state.desired_configuration, expected_configuration
+                ),
+            );
+        }
+    }
+
+    let has_bos_reporter =
+        node_boot_state.booted_kernel_opt.is_some() || node_boot_state.booted_initrd_opt.is_some();
+
+    let is_powered_on = ["ON", "READY"].contains(&node_boot_state.power_status.as_str());
+
+    if !rebooted {
+        if !has_bos_reporter
+            && is_powered_on
+            && ["configured", ""].contains(&node_boot_state.configuration_status.as_str())
+        {
+            return boot_readiness(
+                BootReadinessStatus::NotVerifiable,
+                "node is up but it did not report to BOS, could not check it rebooted".to_string(),
+            );
+        }
+
+        return boot_readiness(
+            BootReadinessStatus::Pending,
+            "waiting for the node to reboot".to_string(),
+        );
+    }
+
+    if !is_powered_on {
+        return boot_readiness(
+            BootReadinessStatus::Pending,
+            format!("power state is {}", node_boot_state.power_status),
+        );
+    }
+
+    for (artifact, path_opt) in [
+        ("kernel", &node_boot_state.booted_kernel_opt),
+        ("initrd", &node_boot_state.booted_initrd_opt),
+    ] {
+        if let Some(path) = path_opt {
+            let booted_image_id = get_image_id_from_boot_artifact_path(path);
+
+            if booted_image_id != expected_image_id {
+                return boot_readiness(
+                    BootReadinessStatus::Booting,
+                    format!("node reports {} from image '{}'", artifact, booted_image_id),
+                );
+            }
+        }
+    }
+
+    match node_boot_state.configuration_status.as_str() {
+        "failed" => boot_readiness(
+            BootReadinessStatus::ConfigurationFailed,
+            format!(
+                "CFS configuration '{}' failed",
+                node_boot_state.desired_configuration
+            ),
+        ),
+        "configured" | "" if has_bos_reporter => boot_readiness(
+            BootReadinessStatus::Ready,
+            format!("booted image '{}'", expected_image_id),
+        ),
+        "configured" | "" => boot_readiness(
+            BootReadinessStatus::Ready,
+            format!(
+                "rebooted with boot parameters pointing to image '{}', node did not report to BOS",
+                expected_image_id
+            ),
+        ),
+        configuration_status => boot_readiness(
+            BootReadinessStatus::Pending,
+            format!("CFS configuration status is {}", configuration_status),
+        ),
+    }
+}
+
+/// Returns true if all the nodes still being polled are not verifiable. Not verifiable nodes are
+/// powered on with the expected image in the boot parameters, without BOS reporter data nothing
+/// else will change, so there is no point waiting for them until the timeout
+pub fn is_only_not_verifiable_left(
+    xname_pending_vec: &[String],
+    boot_readiness_map: &BTreeMap<String, BootReadiness>,
+) -> bool {
+    !xname_pending_vec.is_empty()
+        && xname_pending_vec.iter().all(|xname| {
+            boot_readiness_map.get(xname).is_some_and(|boot_readiness| {
+                boot_readiness.status == BootReadinessStatus::NotVerifiable
+            })
+        })
+}
+
+/// Converts the readiness of nodes still not ready when the wait times out
+pub fn on_timeout(boot_readiness: BootReadiness) -> BootReadiness {
+    match boot_readiness.status {
+        BootReadinessStatus::Booting => BootReadiness {
+            status: BootReadinessStatus::Mismatch,
+            ..boot_readiness
+        },
+        BootReadinessStatus::Pending => BootReadiness {
+            status: BootReadinessStatus::Timeout,
+            ..boot_readiness
+        },
+        _ => boot_readiness,
+    }
+}
+
+/// Returns the BOS components of a list of nodes. The BOS component 'actual_state' contains the
+/// boot artefacts the node booted with
+pub async fn get_bos_component_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+) -> Result<Vec<Value>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = shasta_base_url.to_string() + "/bos/v2/components";
+
+    let response = client
+        .get(api_url)
+        .query(&[("ids", xname_vec.join(","))])
+        .bearer_auth(shasta_token)
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json().await?)
+    } else {
+        Err(Error::Message(response.text().await?))
+    }
+}
+
+async fn get_node_boot_state_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+) -> Vec<NodeBootState> {
+    let (node_details_vec, bos_component_vec_rslt) = tokio::join!(
+        mesa::node::utils::get_node_details(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            xname_vec.to_vec(),
+        ),
+        get_bos_component_vec(shasta_token, shasta_base_url, shasta_root_cert, xname_vec)
+    );
+
+    let bos_component_vec = bos_component_vec_rslt.unwrap_or_else(|error| {
+        log::warn!(
+            "Could not fetch BOS components, booted image not checked. Reason:\n{}",
+            error
+        );
+        Vec::new()
+    });
+
+    node_details_vec
+        .into_iter()
+        .map(|node_details| {
+            let actual_state_opt = bos_component_vec
+                .iter()
+                .find(|bos_component| bos_component["id"].as_str() == Some(&node_details.xname))
+                .map(|bos_component| &bos_component["actual_state"]);
+
+            let get_actual_state_value = |pointer: &str| -> Option<String> {
+                actual_state_opt
+                    .and_then(|actual_state| actual_state.pointer(pointer))
+                    .and_then(|value| value.as_str())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            };
+
+            NodeBootState {
+                xname: node_details.xname.clone(),
+                power_status: node_details.power_status.clone(),
+                bss_image_id: node_details.boot_image_id.clone(),
+                booted_kernel_opt: get_actual_state_value("/boot_artifacts/kernel"),
+                booted_initrd_opt: get_actual_state_value("/boot_artifacts/initrd"),
+                booted_last_updated_opt: get_actual_state_value("/last_updated")
+                    .and_then(|last_updated| parse_date(&last_updated)),
+                desired_configuration: node_details.desired_configuration.clone(),
+                configuration_status: node_details.configuration_status.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Polls the nodes until all of them are ready or failed, or the timeout is reached. A node is
+/// considered rebooted once it has been seen powered off or BOS reports a boot after the wait
+/// started
+pub async fn wait(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    expected_image_map: &BTreeMap<String, String>,
+    expected_configuration_opt: Option<&str>,
+    timeout: Duration,
+) -> Vec<BootReadiness> {
+    let start = Utc::now();
+    let start_instant = Instant::now();
+
+    let xname_vec: Vec<String> = expected_image_map.keys().cloned().collect();
+
+    let mut xname_rebooted_set: HashSet<String> = HashSet::new();
+    let mut boot_readiness_map: BTreeMap<String, BootReadiness> = BTreeMap::new();
+
+    println!(
+        "Waiting up to {} minutes for {} nodes to boot",
+        timeout.num_minutes(),
+        xname_vec.len()
+    );
+
+    loop {
+        let xname_pending_vec: Vec<String> = xname_vec
+            .iter()
+            .filter(|xname| {
+                boot_readiness_map
+                    .get(*xname)
+                    .is_none_or(|boot_readiness| !boot_readiness.status.is_final())
+            })
+            .cloned()
+            .collect();
+
+        if xname_pending_vec.is_empty() {
+            break;
+        }
+
+        if is_only_not_verifiable_left(&xname_pending_vec, &boot_readiness_map) {
+            log::info!("Only nodes not verifiable left, stop waiting");
+            break;
+        }
+
+        if start_instant.elapsed().as_secs() as i64 >= timeout.num_seconds() {
+            for xname in xname_pending_vec {
+                if let Some(boot_readiness) = boot_readiness_map.remove(&xname) {
+                    boot_readiness_map.insert(xname, on_timeout(boot_readiness));
+                } else {
+                    boot_readiness_map.insert(
+                        xname.clone(),
+                        BootReadiness {
+                            xname,
+                            status: BootReadinessStatus::Timeout,
+                            detail: "node state not available".to_string(),
+                        },
+                    );
+                }
+            }
+
+            break;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+
+        let node_boot_state_vec = get_node_boot_state_vec(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &xname_pending_vec,
+        )
+        .await;
+
+        for node_boot_state in node_boot_state_vec {
+            let Some(expected_image_id) = expected_image_map.get(&node_boot_state.xname) else {
+                continue;
+            };
+
+            if !["ON", "READY"].contains(&node_boot_state.power_status.as_str())
+                || node_boot_state
+                    .booted_last_updated_opt
+                    .is_some_and(|last_updated| last_updated > start)
+            {
+                xname_rebooted_set.insert(node_boot_state.xname.clone());
+            }
+
+            let boot_readiness = evaluate(
+                &node_boot_state,
+                expected_image_id,
+                expected_configuration_opt,
+                xname_rebooted_set.contains(&node_boot_state.xname),
+            );
+
+            log::info!(
+                "Node '{}' {}: {}",
+                boot_readiness.xname,
+                boot_readiness.status,
+                boot_readiness.detail
+            );
+
+            boot_readiness_map.insert(node_boot_state.xname.clone(), boot_readiness);
+        }
+
+        let num_ready = boot_readiness_map
+            .values()
+            .filter(|boot_readiness| boot_readiness.status == BootReadinessStatus::Ready)
+            .count();
+
+        println!(
+            "{}/{} nodes ready ({}s elapsed)",
+            num_ready,
+            xname_vec.len(),
+            start_instant.elapsed().as_secs()
+        );
+    }
+
+    boot_readiness_map.into_values().collect()
+}
+
+/// Waits for the nodes to boot, prints the result per node and exits with an error if any node
+/// is not ready. Nodes not verifiable only raise a warning
+pub async fn wait_and_report(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    expected_image_map: &BTreeMap<String, String>,
+    expected_configuration_opt: Option<&str>,
+    timeout: Duration,
+) {
+    let boot_readiness_vec = wait(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        expected_image_map,
+        expected_configuration_opt,
+        timeout,
+    )
+    .await;
+
+    print_table(&boot_readiness_vec);
+
+    let num_not_verifiable = boot_readiness_vec
+        .iter()
+        .filter(|boot_readiness| boot_readiness.status == BootReadinessStatus::NotVerifiable)
+        .count();
+
+    let num_failed = boot_readiness_vec
+        .iter()
+        .filter(|boot_readiness| {
+            ![
+                BootReadinessStatus::Ready,
+                BootReadinessStatus::NotVerifiable,
+            ]
+            .contains(&boot_readiness.status)
+        })
+        .count();
+
+    if num_not_verifiable > 0 {
+        eprintln!(
+            "WARNING - {} nodes are up but could not be verified, please check them manually",
+            num_not_verifiable
+        );
+    }
+
+    if num_failed > 0 {
+        eprintln!("ERROR - {} nodes not ready. Exit", num_failed);
+        std::process::exit(1);
+    }
+
+    if num_not_verifiable == 0 {
+        println!("All nodes ready");
+    }
+}
+
+pub fn print_table(boot_readiness_vec: &[BootReadiness]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["XNAME", "Status", "Detail"]);
+
+    for boot_readiness in boot_readiness_vec {
+        let color = match boot_readiness.status {
+            BootReadinessStatus::Ready => Color::Green,
+            BootReadinessStatus::Pending
+            | BootReadinessStatus::Booting
+            | BootReadinessStatus::NotVerifiable => Color::Yellow,
+            _ => Color::Red,
+        };
+
+        table.add_row(vec![
+            Cell::new(&boot_readiness.xname),
+            Cell::new(boot_readiness.status).fg(color),
+            Cell::new(&boot_readiness.detail),
+        ]);
+    }
+
+    println!("{table}");
+}
@@ -148,13 +148,14 @@ pub async fn create_from_repos(
     gitea_base_url: &str,
     shasta_root_cert: &[u8],
     repos: Vec<PathBuf>,
+    commit_id_opt_vec: Vec<Option<String>>,
     cfs_configuration_name: &String,
 ) -> CfsConfigurationRequest {
     // Create CFS configuration
     let mut cfs_configuration = CfsConfigurationRequest::new();
     // cfs_configuration.name = cfs_configuration_name.to_string();
 
-    for repo_path in &repos {
+    for (i, repo_path) in repos.iter().enumerate() {
         // Get repo from path
         let repo = match local_git_repo::get_repo(&repo_path.to_string_lossy()) {
             Ok(repo) => repo,
@@ -167,8 +168,14 @@ pub async fn create_from_repos(
             }
         };
 
-        // Get last (most recent) commit
-        let local_last_commit = local_git_repo::get_last_commit(&repo).unwrap();
+        // Get commit to use in the layer, last (most recent) commit if not provided
+        let commit_id = match commit_id_opt_vec.get(i).cloned().flatten() {
+            Some(commit_id) => commit_id,
+            None => local_git_repo::get_last_commit(&repo)
+                .unwrap()
+                .id()
+                .to_string(),
+        };
 
         // Get repo name
         let repo_ref_origin = repo.find_remote("origin").unwrap();
@@ -189,7 +196,7 @@ pub async fn create_from_repos(
             gitea::http_client::get_commit_details_from_internal_url(
                 &api_url,
                 // &format!("/cray/{}", repo_name),
-                &local_last_commit.id().to_string(),
+                &commit_id,
                 gitea_token,
                 shasta_root_cert,
             )
@@ -199,8 +206,8 @@ pub async fn create_from_repos(
         let shasta_commitid_details: serde_json::Value = match shasta_commitid_details_resp {
             Ok(_) => {
                 log::debug!(
-                    "Local latest commit id {} for repo {} exists in shasta",
-                    local_last_commit.id(),
+                    "Local commit id {} for repo {} exists in shasta",
+                    commit_id,
                     repo_name
                 );
                 shasta_commitid_details_resp.unwrap()
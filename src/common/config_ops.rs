@@ -69,6 +69,37 @@ pub fn get_default_manta_audit_file_path() -> PathBuf {
     log_file_path
 }
 
+/// Returns the path of a folder in manta's data directory
+pub fn get_default_data_dir_path(dir_name: &str) -> PathBuf {
+    // XDG Base Directory Specification
+    let project_dirs = ProjectDirs::from(
+        "local", /*qualifier*/
+        "cscs",  /*organization*/
+        "manta", /*application*/
+    );
+
+    let mut data_dir_path = PathBuf::from(project_dirs.unwrap().data_dir());
+    data_dir_path.push(dir_name);
+
+    data_dir_path
+}
+
+pub fn get_default_hw_snapshot_dir_path() -> PathBuf {
+    get_default_data_dir_path("hw_snapshots")
+}
+
+pub fn get_default_boot_parameters_snapshot_dir_path() -> PathBuf {
+    get_default_data_dir_path("boot_parameters_snapshots")
+}
+
+pub fn get_default_console_recording_dir_path() -> PathBuf {
+    get_default_data_dir_path("console_recordings")
+}
+
+pub fn get_default_cfs_session_log_archive_dir_path() -> PathBuf {
+    get_default_data_dir_path("cfs_session_logs")
+}
+
 pub fn get_default_mgmt_plane_ca_cert_file_path() -> PathBuf {
     // XDG Base Directory Specification
     let project_dirs = ProjectDirs::from(
@@ -0,0 +1,121 @@
+use mesa::error::Error;
+use serde_json::{json, Value};
+
+use crate::common::http_client;
+
+/// Organization owning the repos used by CFS configurations
+pub const GITEA_ORGANIZATION: &str = "cray";
+
+/// Returns the repo details or None if the repo does not exists in Shasta VCS
+pub async fn get_repo(
+    gitea_base_url: &str,
+    gitea_token: &str,
+    shasta_root_cert: &[u8],
+    repo_name: &str,
+) -> Result<Option<Value>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = format!(
+        "{}/api/v1/repos/{}/{}",
+        gitea_base_url, GITEA_ORGANIZATION, repo_name
+    );
+
+    log::debug!("Get repo in gitea using through API call: {}", api_url);
+
+    let response = client
+        .get(api_url)
+        .header("Authorization", format!("token {}", gitea_token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        Ok(None)
+    } else if response.status().is_success() {
+        Ok(Some(response.json().await?))
+    } else {
+        Err(Error::Message(response.text().await?))
+    }
+}
+
+/// Creates an empty private repo in Shasta VCS
+pub async fn create_repo(
+    gitea_base_url: &str,
+    gitea_token: &str,
+    shasta_root_cert: &[u8],
+    repo_name: &str,
+) -> Result<Value, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = format!(
+        "{}/api/v1/orgs/{}/repos",
+        gitea_base_url, GITEA_ORGANIZATION
+    );
+
+    log::debug!("Create repo in gitea using through API call: {}", api_url);
+
+    let response = client
+        .post(api_url)
+        .header("Authorization", format!("token {}", gitea_token))
+        .json(&json!({
+            "name": repo_name,
+            "private": true,
+            "auto_init": false,
+        }))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json().await?)
+    } else {
+        Err(Error::Message(response.text().await?))
+    }
+}
+
+/// Returns the commits reachable from 'head' but not from 'base', oldest first
+pub async fn compare_commits(
+    gitea_base_url: &str,
+    gitea_token: &str,
+    shasta_root_cert: &[u8],
+    repo_name: &str,
+    base: &str,
+    head: &str,
+) -> Result<Vec<Value>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = format!(
+        "{}/api/v1/repos/{}/{}/compare/{}...{}",
+        gitea_base_url, GITEA_ORGANIZATION, repo_name, base, head
+    );
+
+    log::debug!(
+        "Compare commits in gitea using through API call: {}",
+        api_url
+    );
+
+    let response = client
+        .get(api_url)
+        .header("Authorization", format!("token {}", gitea_token))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(response.json::<Value>().await?["commits"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    } else {
+        Err(Error::Message(response.text().await?))
+    }
+}
+
+/// Returns the repo name from a repo url, eg
+/// 'https://api-gw-service-nmn.local/vcs/cray/uan-config-management.git' ->
+/// 'uan-config-management'
+pub fn get_repo_name_from_url(repo_url: &str) -> &str {
+    repo_url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(repo_url)
+        .trim_end_matches(".git")
+}
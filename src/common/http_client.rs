@@ -0,0 +1,27 @@
+use mesa::error::Error;
+
+/// Returns the socks5 proxy defined in 'SOCKS5' env var, if any. Same env var mesa uses
+pub fn get_socks5_proxy_opt() -> Option<String> {
+    std::env::var("SOCKS5")
+        .ok()
+        .filter(|socks5_env| !socks5_env.is_empty())
+}
+
+/// Returns a client trusting Shasta root CA certificate. Requests go through the socks5 proxy
+/// defined in 'SOCKS5' env var, if any
+pub fn get_client(shasta_root_cert: &[u8]) -> Result<reqwest::Client, Error> {
+    let client_builder = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(shasta_root_cert)?);
+
+    // Build client
+    let client = if let Some(socks5_env) = get_socks5_proxy_opt() {
+        // socks5 proxy
+        let socks5proxy = reqwest::Proxy::all(socks5_env)?;
+
+        client_builder.proxy(socks5proxy).build()?
+    } else {
+        client_builder.build()?
+    };
+
+    Ok(client)
+}
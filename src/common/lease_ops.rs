@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use comfy_table::{Cell, Color, Table};
+use mesa::hsm::group::r#struct::HsmGroup;
+use serde::{Deserialize, Serialize};
+
+/// Tag used to identify HSM groups used to store node leases
+pub const LEASE_TAG: &str = "manta-lease";
+
+/// HSM groups storing leases share the same exclusive group so HSM rejects a node being leased
+/// twice by operators working simultaneously
+pub const LEASE_EXCLUSIVE_GROUP: &str = "manta-lease";
+
+/// A lease is a list of nodes taken from a parent HSM group and assigned to a target HSM group
+/// until it expires. Leases are stored as HSM groups tagged with `LEASE_TAG`, the lease details
+/// are stored in the HSM group description
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lease {
+    #[serde(default)]
+    pub name: String,
+    pub owner: String,
+    pub target_hsm_group: String,
+    pub parent_hsm_group: String,
+    pub created: String,
+    pub expires: String,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl Lease {
+    pub fn from_hsm_group(hsm_group: &HsmGroup) -> Option<Self> {
+        if !hsm_group
+            .tags
+            .as_ref()
+            .is_some_and(|tag_vec| tag_vec.iter().any(|tag| tag.eq(LEASE_TAG)))
+        {
+            return None;
+        }
+
+        let mut lease: Lease = serde_json::from_str(hsm_group.description.as_ref()?).ok()?;
+
+        lease.name = hsm_group.label.clone();
+        lease.members = hsm_group
+            .members
+            .as_ref()
+            .and_then(|member| member.ids.clone())
+            .unwrap_or_default();
+
+        Some(lease)
+    }
+
+    /// Returns the name of the HSM group storing a lease. The owner is restricted to lowercase
+    /// letters, digits, '_' and '-' since it usually comes from '$USER'
+    pub fn get_name(owner: &str, created: &DateTime<Utc>) -> String {
+        let owner: String = owner
+            .to_lowercase()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        format!("lease_{}_{}", owner, created.format("%Y%m%d%H%M%S"))
+    }
+
+    pub fn is_expired(&self, now: &DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.expires)
+            .map(|expires| expires.with_timezone(&Utc) <= *now)
+            .unwrap_or(false)
+    }
+}
+
+/// Returns the list of leases
+pub async fn get_lease_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+) -> Result<Vec<Lease>, mesa::error::Error> {
+    let hsm_group_vec =
+        mesa::hsm::group::http_client::get_all(shasta_token, shasta_base_url, shasta_root_cert)
+            .await?;
+
+    let mut lease_vec: Vec<Lease> = hsm_group_vec
+        .iter()
+        .filter_map(Lease::from_hsm_group)
+        .collect();
+
+    lease_vec.sort_by(|a, b| a.expires.cmp(&b.expires));
+
+    Ok(lease_vec)
+}
+
+/// Returns the nodes in a lease to its parent HSM group and deletes the lease. Nodes no longer in
+/// the target HSM group are ignored
+pub async fn release_lease(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    lease: &Lease,
+    dry_run: bool,
+) -> Result<(), mesa::error::Error> {
+    let target_hsm_group_member_vec = mesa::hsm::group::utils::get_member_vec_from_hsm_group_name(
+        shasta_token,
+        shasta_base_url,
+        shasta_root_cert,
+        &lease.target_hsm_group,
+    )
+    .await;
+
+    let xname_to_return_vec: Vec<&str> = lease
+        .members
+        .iter()
+        .filter(|xname| target_hsm_group_member_vec.contains(xname))
+        .map(|xname| xname.as_str())
+        .collect();
+
+    if !xname_to_return_vec.is_empty() {
+        mesa::hsm::group::utils::migrate_hsm_members(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &lease.parent_hsm_group,
+            &lease.target_hsm_group,
+            xname_to_return_vec,
+            !dry_run,
+        )
+        .await?;
+    }
+
+    if dry_run {
+        println!(
+            "dry-run enabled, lease '{}' not released. Nodes to return to HSM group '{}': {}",
+            lease.name,
+            lease.parent_hsm_group,
+            lease.members.join(", ")
+        );
+    } else {
+        mesa::hsm::group::http_client::delete_hsm_group(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &lease.name,
+        )
+        .await
+        .map_err(mesa::error::Error::NetError)?;
+
+        println!(
+            "Lease '{}' released. Nodes returned to HSM group '{}': {}",
+            lease.name,
+            lease.parent_hsm_group,
+            lease.members.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Releases the expired leases of the HSM groups the user has access to
+pub async fn reclaim_expired_leases(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    target_hsm_group_vec: &[String],
+    dry_run: bool,
+) {
+    let now = Utc::now();
+
+    let expired_lease_vec: Vec<Lease> =
+        get_lease_vec(shasta_token, shasta_base_url, shasta_root_cert)
+            .await
+            .unwrap_or_else(|error| {
+                eprintln!("ERROR - Could not fetch leases. Reason:\n{}\nExit", error);
+                std::process::exit(1);
+            })
+            .into_iter()
+            .filter(|lease| target_hsm_group_vec.contains(&lease.target_hsm_group))
+            .filter(|lease| lease.is_expired(&now))
+            .collect();
+
+    for lease in expired_lease_vec {
+        log::info!("Lease '{}' expired on {}", lease.name, lease.expires);
+
+        if let Err(error) = release_lease(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &lease,
+            dry_run,
+        )
+        .await
+        {
+            eprintln!(
+                "ERROR - Could not release expired lease '{}'. Reason:\n{}",
+                lease.name, error
+            );
+        }
+    }
+}
+
+pub fn print_table(lease_vec: &[Lease]) {
+    let now = Utc::now();
+
+    let mut table = Table::new();
+
+    table.set_header(vec![
+        "Name",
+        "Owner",
+        "Target HSM",
+        "Parent HSM",
+        "Created",
+        "Expires",
+        "Nodes",
+    ]);
+
+    for lease in lease_vec {
+        let expires_cell = if lease.is_expired(&now) {
+            Cell::new(format!("{} (expired)", lease.expires)).fg(Color::Red)
+        } else {
+            Cell::new(lease.expires.clone())
+        };
+
+        table.add_row(vec![
+            Cell::new(lease.name.clone()),
+            Cell::new(lease.owner.clone()),
+            Cell::new(lease.target_hsm_group.clone()),
+            Cell::new(lease.parent_hsm_group.clone()),
+            Cell::new(lease.created.clone()),
+            expires_cell,
+            Cell::new(
+                crate::common::node_ops::nodes_to_string_format_discrete_columns(
+                    Some(&lease.members),
+                    2,
+                ),
+            ),
+        ]);
+    }
+
+    println!("{table}");
+}
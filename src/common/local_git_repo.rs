@@ -1,6 +1,7 @@
 use std::error::Error;
 // Code below inspired on https://github.com/rust-lang/git2-rs/issues/561
 use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 
 use dialoguer::{Input, Password};
@@ -132,9 +133,9 @@ pub fn commit(repo: &Repository) {
     .unwrap();
 }
 
-/// Equivalent to `git push`
-pub fn push(mut remote: Remote) -> Result<(), git2::Error> {
-    // Configure callbacks for push operation
+/// Remote callbacks asking for credentials interactively, or using '~/.ssh/gitlab_vcef' key if
+/// the remote uses ssh
+pub fn get_interactive_remote_callbacks<'a>() -> git2::RemoteCallbacks<'a> {
     let mut callbacks = git2::RemoteCallbacks::new();
 
     // TODO: CLEAN THIS!!!
@@ -174,14 +175,27 @@ pub fn push(mut remote: Remote) -> Result<(), git2::Error> {
         }
     });
 
-    callbacks.push_update_reference(|_reference_name, callback_status| {
-        log::debug!("reference name: {}", _reference_name);
-        log::debug!(
-            "callback status: {}",
-            callback_status.unwrap_or("Not defined")
-        );
+    callbacks
+}
 
-        Ok(())
+/// Equivalent to `git push <remote> <refspecs>`. Fails if the remote rejects any of the
+/// references
+pub fn push(
+    mut remote: Remote,
+    refspec_vec: &[String],
+    mut callbacks: git2::RemoteCallbacks,
+) -> Result<(), git2::Error> {
+    callbacks.push_update_reference(|reference_name, status_opt| {
+        log::debug!("reference name: {}", reference_name);
+        log::debug!("callback status: {}", status_opt.unwrap_or("Not defined"));
+
+        match status_opt {
+            Some(status) => Err(git2::Error::from_str(&format!(
+                "push of '{}' rejected by remote: {}",
+                reference_name, status
+            ))),
+            None => Ok(()),
+        }
     });
 
     // Configure push options
@@ -189,13 +203,7 @@ pub fn push(mut remote: Remote) -> Result<(), git2::Error> {
     po.remote_callbacks(callbacks);
 
     // Push
-    remote.push(
-        &[
-            "+refs/heads/main",
-            "+refs/heads/apply-dynamic-target-session",
-        ],
-        Some(po),
-    )
+    remote.push(refspec_vec, Some(po))
 }
 
 /// Equivalent to `git fetch`
@@ -298,6 +306,150 @@ pub fn fetch<'a>(
     Ok(repo.reference_to_annotated_commit(&fetch_head)?)
 }
 
+/// Remote callbacks authenticating against Shasta VCS (gitea) with an access token. Gitea
+/// accepts the token as username if no password is provided
+fn get_gitea_remote_callbacks(gitea_token: &str) -> git2::RemoteCallbacks<'_> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+
+    callbacks.credentials(move |_url, _username_from_url, _allowed_types| {
+        git2::Cred::userpass_plaintext(gitea_token, "")
+    });
+
+    callbacks
+}
+
+/// Trust Shasta root CA certificate when fetching or pushing to Shasta VCS. The certificate is
+/// written to a temporary file only readable by the current user, which is removed once libgit2
+/// loaded it
+pub fn set_ssl_root_cert(shasta_root_cert: &[u8]) -> Result<(), Box<dyn Error>> {
+    let root_cert_file_path =
+        std::env::temp_dir().join(format!("manta-root-cert-{}.pem", uuid::Uuid::new_v4()));
+
+    // Fails if the file already exists so nobody can make us trust a certificate of their own
+    let mut root_cert_file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&root_cert_file_path)?;
+
+    let set_rslt = root_cert_file
+        .write_all(shasta_root_cert)
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|_| {
+            // SAFETY: called before any git2 network operation, no other thread is using
+            // libgit2. The certificates are loaded straight away, the file is not needed
+            // afterwards
+            unsafe { git2::opts::set_ssl_cert_file(&root_cert_file_path) }
+                .map_err(Box::<dyn Error>::from)
+        });
+
+    std::fs::remove_file(&root_cert_file_path)?;
+
+    set_rslt
+}
+
+/// Returns the name of the branch HEAD points to. Fails if HEAD is detached
+pub fn get_head_branch_name(repo: &Repository) -> Result<String, git2::Error> {
+    let head = repo.head()?;
+
+    if !head.is_branch() {
+        return Err(git2::Error::from_str(
+            "HEAD is detached, please checkout a branch",
+        ));
+    }
+
+    head.shorthand()
+        .map(|branch_name| branch_name.to_string())
+        .ok_or(git2::Error::from_str("HEAD branch name is not valid UTF-8"))
+}
+
+/// Creates a new branch with a commit containing all changes in the working tree, including new
+/// files, on top of HEAD. Neither HEAD, the index nor the working tree are modified
+pub fn commit_work_tree_to_branch(
+    repo: &Repository,
+    branch_name: &str,
+    message: &str,
+) -> Result<git2::Oid, git2::Error> {
+    let mut index = repo.index()?;
+
+    index.add_all(["."], git2::IndexAddOption::DEFAULT, None)?;
+    index.update_all(["."], None)?;
+
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    // Discard in memory changes to the index
+    index.read(true)?;
+
+    let signature = repo.signature()?;
+    let parent_commit = repo.head()?.peel_to_commit()?;
+
+    let commit_id = repo.commit(
+        None,
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&parent_commit],
+    )?;
+
+    repo.branch(branch_name, &repo.find_commit(commit_id)?, false)?;
+
+    Ok(commit_id)
+}
+
+/// Equivalent to `git fetch <remote url> <branch>`. Returns None if the branch does not exist in
+/// the remote repo
+pub fn fetch_branch<'a>(
+    repo: &'a Repository,
+    remote_url: &str,
+    branch_name: &str,
+    gitea_token: &str,
+) -> Result<Option<git2::AnnotatedCommit<'a>>, git2::Error> {
+    let mut remote = repo.remote_anonymous(remote_url)?;
+
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(get_gitea_remote_callbacks(gitea_token));
+
+    remote.connect_auth(
+        git2::Direction::Fetch,
+        Some(get_gitea_remote_callbacks(gitea_token)),
+        None,
+    )?;
+
+    let branch_ref = format!("refs/heads/{}", branch_name);
+
+    let branch_exists = remote
+        .list()?
+        .iter()
+        .any(|remote_head| remote_head.name() == branch_ref);
+
+    remote.disconnect()?;
+
+    if !branch_exists {
+        return Ok(None);
+    }
+
+    remote.fetch(&[&branch_ref], Some(&mut fetch_options), None)?;
+
+    let fetch_head = repo.find_reference("FETCH_HEAD")?;
+
+    repo.reference_to_annotated_commit(&fetch_head).map(Some)
+}
+
+/// Equivalent to `git push <remote url> <branch>`. Non fast-forward pushes are rejected
+pub fn push_branch(
+    repo: &Repository,
+    remote_url: &str,
+    branch_name: &str,
+    gitea_token: &str,
+) -> Result<(), git2::Error> {
+    push(
+        repo.remote_anonymous(remote_url)?,
+        &[format!("refs/heads/{0}:refs/heads/{0}", branch_name)],
+        get_gitea_remote_callbacks(gitea_token),
+    )
+}
+
 pub fn has_conflicts(
     repo: &Repository,
     local: &git2::AnnotatedCommit,
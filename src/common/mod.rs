@@ -1,14 +1,26 @@
+pub mod ansible_playbook_ops;
+pub mod boot_readiness_ops;
 pub mod bos_sessiontemplate_utils;
 pub mod cfs_configuration_utils;
 pub mod cfs_session_utils;
 pub mod cluster_ops;
 pub mod config_ops;
+pub mod gitea_ops;
 pub mod hooks;
+pub mod http_client;
 pub mod ims_ops;
 pub mod kernel_parameters_ops;
+pub mod lease_ops;
 pub mod local_git_repo;
 pub mod log_ops;
+pub mod node_health_ops;
 pub mod node_ops;
 pub mod pcs_utils;
+pub mod s3_ops;
+pub mod snapshot_ops;
 pub mod terminal_ops;
+#[cfg(test)]
+mod tests;
+pub mod time_ops;
 pub mod vault;
+pub mod watch_ops;
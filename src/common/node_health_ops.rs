@@ -0,0 +1,248 @@
+use comfy_table::{Cell, Color, Table};
+use mesa::node::r#struct::NodeDetails;
+use serde_json::Value;
+use strum_macros::{Display, EnumString};
+
+/// What to do with nodes failing the health checks before moving them to another HSM group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum HealthPolicy {
+    /// Do not move unhealthy nodes, move the rest
+    Skip,
+    /// Report unhealthy nodes and move them anyway
+    Warn,
+    /// Do not move any node if at least one is unhealthy
+    Abort,
+}
+
+/// Node failing health checks and the list of reasons
+#[derive(Debug, Clone)]
+pub struct NodeHealthIssue {
+    pub xname: String,
+    pub reason_vec: Vec<String>,
+}
+
+/// Returns the reasons why a node should not be handed over to another HSM group. An empty list
+/// means the node is healthy
+///  - power state must be either ON or READY
+///  - CFS configuration must not be failed or still pending
+///  - HSM component must be enabled and its flag must be OK
+///  - no running CFS session targets the node, either by xname or by any HSM group the node
+///  belongs to
+pub fn get_node_health_issue_vec(
+    node_details: &NodeDetails,
+    hsm_component_opt: Option<&Value>,
+    running_cfs_session_target_vec: &[(String, Vec<String>)],
+) -> Vec<String> {
+    let mut reason_vec = Vec::new();
+
+    if !["ON", "READY"].contains(&node_details.power_status.as_str()) {
+        reason_vec.push(format!("power state is {}", node_details.power_status));
+    }
+
+    if ["failed", "pending"].contains(&node_details.configuration_status.as_str()) {
+        reason_vec.push(format!(
+            "CFS configuration '{}' status is {}",
+            node_details.desired_configuration, node_details.configuration_status
+        ));
+    }
+
+    match hsm_component_opt {
+        Some(hsm_component) => {
+            if hsm_component["Enabled"].as_bool() == Some(false) {
+                reason_vec.push("HSM component disabled".to_string());
+            }
+
+            if let Some(flag) = hsm_component["Flag"].as_str() {
+                if !flag.eq_ignore_ascii_case("ok") {
+                    reason_vec.push(format!("HSM component flag is {}", flag));
+                }
+            }
+        }
+        None => reason_vec.push("HSM component not found".to_string()),
+    }
+
+    let node_hsm_group_vec: Vec<&str> = node_details
+        .hsm
+        .split(',')
+        .map(|hsm_group| hsm_group.trim())
+        .filter(|hsm_group| !hsm_group.is_empty())
+        .collect();
+
+    for (cfs_session_name, target_vec) in running_cfs_session_target_vec {
+        if target_vec.iter().any(|target| {
+            target.eq(&node_details.xname) || node_hsm_group_vec.contains(&target.as_str())
+        }) {
+            reason_vec.push(format!("CFS session '{}' running", cfs_session_name));
+        }
+    }
+
+    reason_vec
+}
+
+/// Runs health checks against a list of nodes and returns the ones failing
+pub async fn check_node_health(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+) -> Vec<NodeHealthIssue> {
+    if xname_vec.is_empty() {
+        return Vec::new();
+    }
+
+    let cfs_session_status = "running".to_string();
+
+    let (node_details_vec, hsm_component_vec_rslt, cfs_session_vec_rslt) = tokio::join!(
+        mesa::node::utils::get_node_details(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            xname_vec.to_vec(),
+        ),
+        mesa::hsm::component_status::http_client::get(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            xname_vec,
+        ),
+        mesa::cfs::session::mesa::http_client::get(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            None,
+            None,
+            Some(&cfs_session_status),
+            None,
+            None,
+        )
+    );
+
+    let hsm_component_vec = hsm_component_vec_rslt.unwrap_or_else(|error| {
+        eprintln!(
+            "ERROR - Could not fetch HSM component status. Reason:\n{}\nExit",
+            error
+        );
+        std::process::exit(1);
+    });
+
+    let cfs_session_vec = cfs_session_vec_rslt.unwrap_or_else(|error| {
+        eprintln!(
+            "ERROR - Could not fetch CFS sessions. Reason:\n{}\nExit",
+            error
+        );
+        std::process::exit(1);
+    });
+
+    // CFS sessions building images do not target nodes
+    let running_cfs_session_target_vec: Vec<(String, Vec<String>)> = cfs_session_vec
+        .iter()
+        .filter(|cfs_session| !cfs_session.is_target_def_image())
+        .map(|cfs_session| {
+            let mut target_vec = cfs_session.get_target_hsm().unwrap_or_default();
+            target_vec.extend(cfs_session.get_target_xname().unwrap_or_default());
+
+            (cfs_session.name.clone().unwrap_or_default(), target_vec)
+        })
+        .collect();
+
+    // Nodes without details can't be checked, hence they are not healthy
+    let mut node_health_issue_vec: Vec<NodeHealthIssue> = xname_vec
+        .iter()
+        .map(|xname| {
+            let reason_vec = match node_details_vec
+                .iter()
+                .find(|node_details| node_details.xname.eq(xname))
+            {
+                Some(node_details) => get_node_health_issue_vec(
+                    node_details,
+                    hsm_component_vec
+                        .iter()
+                        .find(|hsm_component| hsm_component["ID"].as_str() == Some(xname)),
+                    &running_cfs_session_target_vec,
+                ),
+                None => vec!["node details not found".to_string()],
+            };
+
+            NodeHealthIssue {
+                xname: xname.clone(),
+                reason_vec,
+            }
+        })
+        .filter(|node_health_issue| !node_health_issue.reason_vec.is_empty())
+        .collect();
+
+    node_health_issue_vec.sort_by(|a, b| a.xname.cmp(&b.xname));
+
+    node_health_issue_vec
+}
+
+/// Checks the health of the nodes about to be moved and applies the policy. Returns the list of
+/// nodes which must not be moved. Exits if policy is 'abort' and any node is unhealthy
+pub async fn gate_nodes(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+    health_policy: HealthPolicy,
+) -> Vec<String> {
+    log::info!(
+        "Checking health of nodes to move (policy '{}'): {:?}",
+        health_policy,
+        xname_vec
+    );
+
+    let node_health_issue_vec =
+        check_node_health(shasta_token, shasta_base_url, shasta_root_cert, xname_vec).await;
+
+    if node_health_issue_vec.is_empty() {
+        log::info!("All nodes passed health checks");
+        return Vec::new();
+    }
+
+    print_table(&node_health_issue_vec);
+
+    match health_policy {
+        HealthPolicy::Skip => {
+            eprintln!(
+                "WARNING - {} unhealthy nodes will not be moved",
+                node_health_issue_vec.len()
+            );
+
+            node_health_issue_vec
+                .into_iter()
+                .map(|node_health_issue| node_health_issue.xname)
+                .collect()
+        }
+        HealthPolicy::Warn => {
+            eprintln!(
+                "WARNING - {} unhealthy nodes will be moved anyway",
+                node_health_issue_vec.len()
+            );
+
+            Vec::new()
+        }
+        HealthPolicy::Abort => {
+            eprintln!(
+                "ERROR - {} nodes failed health checks. No nodes moved. Exit",
+                node_health_issue_vec.len()
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn print_table(node_health_issue_vec: &[NodeHealthIssue]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["XNAME", "Health issues"]);
+
+    for node_health_issue in node_health_issue_vec {
+        table.add_row(vec![
+            Cell::new(node_health_issue.xname.clone()),
+            Cell::new(node_health_issue.reason_vec.join("\n")).fg(Color::Red),
+        ]);
+    }
+
+    println!("{table}");
+}
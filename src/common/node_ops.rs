@@ -53,6 +53,46 @@ pub async fn get_curated_hsm_group_from_hostlist(
     hsm_group_summary
 }
 
+/// Returns the cluster status:
+///  - OK: All nodes are operational (booted and configured)
+///  - OFF: At least one node is OFF
+///  - ON: No nodes OFF and at least one is ON
+///  - STANDBY: At least one node's heartbeat is lost
+///  - UNCONFIGURED: All nodes are READY but at least one of them is being configured
+///  - FAILED: At least one node configuration failed
+pub fn get_cluster_status(node_details_list: &[NodeDetails]) -> &'static str {
+    if node_details_list.iter().any(|node_details| {
+        node_details
+            .configuration_status
+            .eq_ignore_ascii_case("failed")
+    }) {
+        "FAILED"
+    } else if node_details_list
+        .iter()
+        .any(|node_detail| node_detail.power_status.eq_ignore_ascii_case("OFF"))
+    {
+        "OFF"
+    } else if node_details_list
+        .iter()
+        .any(|node_details| node_details.power_status.eq_ignore_ascii_case("on"))
+    {
+        "ON"
+    } else if node_details_list
+        .iter()
+        .any(|node_details| node_details.power_status.eq_ignore_ascii_case("standby"))
+    {
+        "STANDBY"
+    } else if node_details_list.iter().any(|node_details| {
+        !node_details
+            .configuration_status
+            .eq_ignore_ascii_case("configured")
+    }) {
+        "UNCONFIGURED"
+    } else {
+        "OK"
+    }
+}
+
 pub fn print_table(nodes_status: Vec<NodeDetails>) {
     let mut table = Table::new();
 
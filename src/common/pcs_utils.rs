@@ -1,35 +1,752 @@
-use comfy_table::Table;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::LazyLock,
+    time::Instant,
+};
+
+use comfy_table::{Cell, Color, Table};
+use mesa::{error::Error, pcs};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-pub fn print_summary_table(transition: Value, output: &str) {
-    if output == "table" {
-        let tasks: Vec<Value> = transition["tasks"].as_array().unwrap().to_vec();
+use crate::common::http_client;
 
-        let mut table = Table::new();
+/// Seconds between checks of the PCS transition status
+const POLL_INTERVAL_SECS: u64 = 3;
 
-        println!(
-            "\nTransition ID: {}",
-            transition["transitionID"].as_str().unwrap()
-        );
-        println!(
-            "Transition Status: {}",
-            transition["transitionStatus"].as_str().unwrap()
+/// Max number of checks of a PCS power cap task before giving up
+const POWER_CAP_TASK_MAX_ATTEMPTS: usize = 100;
+
+/// Timeout and retries applied to PCS power transitions
+#[derive(Debug, Clone, Copy)]
+pub struct PowerTransitionOpts {
+    pub timeout: chrono::Duration,
+    /// Number of times nodes failing the transition are retried. Retries escalate soft operations
+    /// to their hard version
+    pub retries: usize,
+}
+
+impl Default for PowerTransitionOpts {
+    fn default() -> Self {
+        Self {
+            timeout: chrono::Duration::minutes(15),
+            retries: 1,
+        }
+    }
+}
+
+/// Result of a power transition for a node
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerTaskResult {
+    pub xname: String,
+    /// Operation used in the last attempt
+    pub operation: String,
+    pub status: String,
+    pub description: String,
+    pub attempts: usize,
+    /// PCS transition of the last attempt
+    pub transition_id: String,
+}
+
+impl PowerTaskResult {
+    pub fn is_succeeded(&self) -> bool {
+        self.status == "succeeded"
+    }
+
+    /// Unsupported tasks are not retried since they will fail again
+    pub fn is_retryable(&self) -> bool {
+        ["failed", "timeout"].contains(&self.status.as_str())
+    }
+}
+
+/// Returns the operation used to retry nodes which failed a power transition
+pub fn get_escalation_operation(operation: &str) -> &str {
+    match operation {
+        "soft-restart" => "hard-restart",
+        "soft-off" | "off" => "force-off",
+        _ => operation,
+    }
+}
+
+/// Marks the tasks not finished when the timeout of a transition is reached as 'timeout'
+pub fn mark_timed_out_tasks(
+    task_vec: Vec<(String, String, String)>,
+) -> Vec<(String, String, String)> {
+    task_vec
+        .into_iter()
+        .map(|(xname, status, description)| {
+            if ["new", "in-progress"].contains(&status.as_str()) {
+                (
+                    xname,
+                    "timeout".to_string(),
+                    format!("still {} when the timeout was reached", status),
+                )
+            } else {
+                (xname, status, description)
+            }
+        })
+        .collect()
+}
+
+/// Aborts a PCS transition so the reservations on its nodes are released
+async fn abort_transition(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    transition_id: &str,
+) -> Result<(), Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = format!(
+        "{}/power-control/v1/transitions/{}",
+        shasta_base_url, transition_id
+    );
+
+    let response = client
+        .delete(api_url)
+        .bearer_auth(shasta_token)
+        .send()
+        .await?;
+
+    get_response_payload(response).await.map(|_| ())
+}
+
+/// Creates a PCS transition and waits for it to complete or the timeout. Prints the status of each
+/// node as it changes. Transitions not completed when the timeout is reached are aborted and
+/// their unfinished tasks are marked as 'timeout'. Returns the transition ID and the status of
+/// each task
+async fn run_transition(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    operation: &str,
+    xname_vec: &Vec<String>,
+    timeout: chrono::Duration,
+) -> Result<(String, Vec<(String, String, String)>), Error> {
+    let transition = pcs::transitions::http_client::post(
+        shasta_base_url,
+        shasta_token,
+        shasta_root_cert,
+        operation,
+        xname_vec,
+    )
+    .await?;
+
+    let transition_id = transition["transitionID"]
+        .as_str()
+        .ok_or_else(|| Error::Message("PCS transition without ID".to_string()))?
+        .to_string();
+
+    log::info!("PCS transition ID: {}", transition_id);
+
+    let start_instant = Instant::now();
+
+    let mut task_status_map: HashMap<String, String> = HashMap::new();
+
+    loop {
+        let transition = pcs::transitions::http_client::get_by_id(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            &transition_id,
+        )
+        .await?;
+
+        let task_vec: Vec<(String, String, String)> = transition["tasks"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .map(|task| {
+                (
+                    task["xname"].as_str().unwrap_or_default().to_string(),
+                    task["taskStatus"].as_str().unwrap_or_default().to_string(),
+                    task["taskStatusDescription"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            })
+            .collect();
+
+        for (xname, status, description) in &task_vec {
+            if task_status_map.get(xname) != Some(status) {
+                eprintln!(
+                    "Power '{}' {} - {}: {}",
+                    operation, xname, status, description
+                );
+                task_status_map.insert(xname.clone(), status.clone());
+            }
+        }
+
+        let transition_status = transition["transitionStatus"].as_str().unwrap_or_default();
+
+        if ["completed", "aborted"].contains(&transition_status) {
+            return Ok((transition_id, task_vec));
+        }
+
+        if start_instant.elapsed().as_secs() as i64 >= timeout.num_seconds() {
+            eprintln!(
+                "Power '{}' transition '{}' did not complete after {} seconds. Aborting it",
+                operation,
+                transition_id,
+                timeout.num_seconds()
+            );
+
+            // Release the nodes before retrying them in a new transition
+            if let Err(error) = abort_transition(
+                shasta_token,
+                shasta_base_url,
+                shasta_root_cert,
+                &transition_id,
+            )
+            .await
+            {
+                eprintln!(
+                    "ERROR - Could not abort power transition '{}'. Reason:\n{}",
+                    transition_id,
+                    get_error_message(error)
+                );
+            }
+
+            return Ok((transition_id, mark_timed_out_tasks(task_vec)));
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
+    }
+}
+
+/// Runs a power transition and retries the nodes which failed or timed out. Each retry escalates
+/// the operation (eg 'soft-restart' -> 'hard-restart'). Returns the result of the last attempt
+/// for each node
+pub async fn transition_with_retries(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    operation: &str,
+    xname_vec: &[String],
+    power_transition_opts: &PowerTransitionOpts,
+) -> Result<Vec<PowerTaskResult>, Error> {
+    let mut power_task_result_map: BTreeMap<String, PowerTaskResult> = BTreeMap::new();
+
+    let mut xname_pending_vec = xname_vec.to_vec();
+    let mut operation = operation;
+
+    for attempt in 1..=power_transition_opts.retries + 1 {
+        if attempt > 1 {
+            operation = get_escalation_operation(operation);
+
+            eprintln!(
+                "Retrying power '{}' on {} node/s (attempt {} of {}): {}",
+                operation,
+                xname_pending_vec.len(),
+                attempt,
+                power_transition_opts.retries + 1,
+                xname_pending_vec.join(", ")
+            );
+        }
+
+        let task_vec_rslt = run_transition(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            operation,
+            &xname_pending_vec,
+            power_transition_opts.timeout,
+        )
+        .await;
+
+        let (transition_id, task_vec) = match task_vec_rslt {
+            Ok(transition) => transition,
+            // Keep the results of previous attempts if a retry fails
+            Err(error) if attempt > 1 => {
+                eprintln!(
+                    "ERROR - Could not retry power '{}'. Reason:\n{}",
+                    operation, error
+                );
+                break;
+            }
+            Err(error) => return Err(error),
+        };
+
+        for (xname, status, description) in task_vec {
+            power_task_result_map.insert(
+                xname.clone(),
+                PowerTaskResult {
+                    xname,
+                    operation: operation.to_string(),
+                    status,
+                    description,
+                    attempts: attempt,
+                    transition_id: transition_id.clone(),
+                },
+            );
+        }
+
+        xname_pending_vec = power_task_result_map
+            .values()
+            .filter(|power_task_result| power_task_result.is_retryable())
+            .map(|power_task_result| power_task_result.xname.clone())
+            .collect();
+
+        if xname_pending_vec.is_empty() {
+            break;
+        }
+    }
+
+    Ok(power_task_result_map.into_values().collect())
+}
+
+/// Cabinet and chassis of a node xname, eg 'x1000' and 'c1' in 'x1000c1s0b0n0'
+static XNAME_LOCATION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(x\d+)(c\d+)?").unwrap());
+
+/// Location used to group nodes when powering them in batches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaggerGroup {
+    Cabinet,
+    Chassis,
+}
+
+impl FromStr for StaggerGroup {
+    type Err = String;
+
+    fn from_str(stagger_group: &str) -> Result<Self, Self::Err> {
+        match stagger_group {
+            "cabinet" => Ok(StaggerGroup::Cabinet),
+            "chassis" => Ok(StaggerGroup::Chassis),
+            _ => Err(format!(
+                "Invalid stagger group '{}'. Valid values are 'cabinet' or 'chassis'",
+                stagger_group
+            )),
+        }
+    }
+}
+
+impl StaggerGroup {
+    /// Returns the cabinet (eg 'x1000') or chassis (eg 'x1000c1') of a node. Xnames not following
+    /// the 'x<cabinet>c<chassis>...' format are returned as they are
+    pub fn get_location(&self, xname: &str) -> String {
+        match XNAME_LOCATION_REGEX.captures(xname) {
+            Some(captures) => match (self, captures.get(2)) {
+                (StaggerGroup::Chassis, Some(chassis)) => {
+                    format!("{}{}", &captures[1], chassis.as_str())
+                }
+                _ => captures[1].to_string(),
+            },
+            None => xname.to_string(),
+        }
+    }
+}
+
+/// Batches used to power nodes progressively
+#[derive(Debug, Clone, Copy)]
+pub struct PowerStaggerOpts {
+    pub group: StaggerGroup,
+    pub batch_size: usize,
+    /// Time to wait between batches
+    pub delay: chrono::Duration,
+}
+
+/// Splits a list of nodes in batches of nodes in the same cabinet or chassis. Batches have at most
+/// 'batch_size' nodes and are sorted by location
+pub fn get_batch_vec(
+    xname_vec: &[String],
+    stagger_group: StaggerGroup,
+    batch_size: usize,
+) -> Vec<(String, Vec<String>)> {
+    let mut location_xname_map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for xname in xname_vec {
+        location_xname_map
+            .entry(stagger_group.get_location(xname))
+            .or_default()
+            .push(xname.clone());
+    }
+
+    location_xname_map
+        .into_iter()
+        .flat_map(|(location, mut xname_vec)| {
+            xname_vec.sort();
+
+            xname_vec
+                .chunks(batch_size.max(1))
+                .map(|xname_batch| (location.clone(), xname_batch.to_vec()))
+                .collect::<Vec<(String, Vec<String>)>>()
+        })
+        .collect()
+}
+
+/// Runs a power transition in batches of nodes grouped by cabinet or chassis, waiting between
+/// batches to avoid power inrush. Nodes in a batch which could not be processed are reported as
+/// failed and the remaining batches are still processed
+pub async fn transition_staggered(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    operation: &str,
+    xname_vec: &[String],
+    power_transition_opts: &PowerTransitionOpts,
+    power_stagger_opts: &PowerStaggerOpts,
+) -> Vec<PowerTaskResult> {
+    let batch_vec = get_batch_vec(
+        xname_vec,
+        power_stagger_opts.group,
+        power_stagger_opts.batch_size,
+    );
+
+    let mut power_task_result_vec: Vec<PowerTaskResult> = Vec::new();
+
+    for (i, (location, xname_batch_vec)) in batch_vec.iter().enumerate() {
+        if i > 0 {
+            eprintln!(
+                "Waiting {} seconds before next batch",
+                power_stagger_opts.delay.num_seconds()
+            );
+
+            tokio::time::sleep(
+                power_stagger_opts
+                    .delay
+                    .to_std()
+                    .unwrap_or(std::time::Duration::ZERO),
+            )
+            .await;
+        }
+
+        eprintln!(
+            "Power '{}' batch {} of {} in '{}': {}",
+            operation,
+            i + 1,
+            batch_vec.len(),
+            location,
+            xname_batch_vec.join(", ")
         );
 
+        match transition_with_retries(
+            shasta_token,
+            shasta_base_url,
+            shasta_root_cert,
+            operation,
+            xname_batch_vec,
+            power_transition_opts,
+        )
+        .await
+        {
+            Ok(batch_power_task_result_vec) => {
+                power_task_result_vec.extend(batch_power_task_result_vec)
+            }
+            Err(error) => {
+                let error_msg = get_error_message(error);
+
+                eprintln!(
+                    "ERROR - Could not power '{}' batch in '{}'. Reason:\n{}",
+                    operation, location, error_msg
+                );
+
+                power_task_result_vec.extend(xname_batch_vec.iter().map(|xname| PowerTaskResult {
+                    xname: xname.clone(),
+                    operation: operation.to_string(),
+                    status: "failed".to_string(),
+                    description: error_msg.clone(),
+                    attempts: 1,
+                    transition_id: String::new(),
+                }));
+            }
+        }
+    }
+
+    power_task_result_vec
+}
+
+pub fn print_task_result_table(power_task_result_vec: &[PowerTaskResult], output: &str) {
+    if output == "table" {
+        let mut table = Table::new();
+
         table
             .load_preset(comfy_table::presets::ASCII_FULL_CONDENSED)
-            .set_header(vec!["XNAME", "Status", "Description"]);
+            .set_header(vec![
+                "XNAME",
+                "Operation",
+                "Status",
+                "Attempts",
+                "Description",
+            ]);
+
+        for power_task_result in power_task_result_vec {
+            let color = if power_task_result.is_succeeded() {
+                Color::Green
+            } else {
+                Color::Red
+            };
 
-        for task in tasks {
             table.add_row(vec![
-                task["xname"].as_str().unwrap(),
-                task["taskStatus"].as_str().unwrap(),
-                task["taskStatusDescription"].as_str().unwrap(),
+                Cell::new(&power_task_result.xname),
+                Cell::new(&power_task_result.operation),
+                Cell::new(&power_task_result.status).fg(color),
+                Cell::new(power_task_result.attempts),
+                Cell::new(&power_task_result.description),
             ]);
         }
 
         println!("{table}");
     } else if output.to_lowercase() == "json" {
-        println!("{}", serde_json::to_string_pretty(&transition).unwrap());
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&get_transition_summary(power_task_result_vec)).unwrap()
+        );
+    }
+}
+
+/// Returns the results of a power operation with the same shape as a PCS transition so existing
+/// consumers of the JSON output keep working. Each task also has the operation, attempts and
+/// transition of the last attempt since nodes retried are processed in a new transition. The
+/// transition status is 'aborted' if any node timed out
+pub fn get_transition_summary(power_task_result_vec: &[PowerTaskResult]) -> Value {
+    let transition_status = if power_task_result_vec
+        .iter()
+        .any(|power_task_result| power_task_result.status == "timeout")
+    {
+        "aborted"
+    } else {
+        "completed"
+    };
+
+    let task_vec: Vec<Value> = power_task_result_vec
+        .iter()
+        .map(|power_task_result| {
+            serde_json::json!({
+                "xname": power_task_result.xname,
+                "taskStatus": power_task_result.status,
+                "taskStatusDescription": power_task_result.description,
+                "operation": power_task_result.operation,
+                "attempts": power_task_result.attempts,
+                "transitionID": power_task_result.transition_id,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "transitionStatus": transition_status,
+        "tasks": task_vec,
+    })
+}
+
+/// Exits with code 1 if no node succeeded and with code 2 if only some nodes succeeded
+pub fn exit_on_failure(power_task_result_vec: &[PowerTaskResult]) {
+    let num_failed = power_task_result_vec
+        .iter()
+        .filter(|power_task_result| !power_task_result.is_succeeded())
+        .count();
+
+    if num_failed == 0 {
+        return;
+    }
+
+    eprintln!(
+        "ERROR - Power operation failed for {} of {} node/s",
+        num_failed,
+        power_task_result_vec.len()
+    );
+
+    if num_failed == power_task_result_vec.len() {
+        std::process::exit(1);
+    } else {
+        std::process::exit(2);
+    }
+}
+
+/// Returns the error message of a failed power operation
+pub fn get_error_message(error: Error) -> String {
+    match error {
+        Error::CsmError(value) => serde_json::to_string_pretty(&value).unwrap(),
+        Error::SerdeError(value) => value.to_string(),
+        Error::IoError(value) => value.to_string(),
+        Error::NetError(value) => value.to_string(),
+        Error::Message(value) => value.to_string(),
+    }
+}
+
+/// Power state of a node reported by PCS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerStatus {
+    pub xname: String,
+    #[serde(rename = "powerState", default)]
+    pub power_state: String,
+    #[serde(rename = "managementState", default)]
+    pub management_state: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(rename = "supportedPowerTransitions", default)]
+    pub supported_power_transitions: Vec<String>,
+    #[serde(rename = "lastUpdated", default)]
+    pub last_updated: String,
+}
+
+/// Power cap control of a node. eg 'Node Power Limit' or 'Accelerator0 Power Limit'. Values are
+/// in watts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerCapControl {
+    pub name: String,
+    #[serde(rename = "currentValue", default)]
+    pub current_value: Option<i64>,
+    #[serde(rename = "maximumValue", default)]
+    pub maximum_value: Option<i64>,
+    #[serde(rename = "minimumValue", default)]
+    pub minimum_value: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerCapComponent {
+    pub xname: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(rename = "powerCapLimits", default)]
+    pub power_cap_limits: Vec<PowerCapControl>,
+}
+
+/// Returns the json payload of a successful response. Error bodies which are not json (eg
+/// errors from the API gateway) are returned as text
+async fn get_response_payload(response: reqwest::Response) -> Result<Value, Error> {
+    if response.status().is_success() {
+        return Ok(response.json().await?);
+    }
+
+    let error_body = response.text().await?;
+
+    Err(serde_json::from_str(&error_body)
+        .map(Error::CsmError)
+        .unwrap_or(Error::Message(error_body)))
+}
+
+/// Returns the power state of a list of nodes
+pub async fn get_power_status_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+) -> Result<Vec<PowerStatus>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = shasta_base_url.to_string() + "/power-control/v1/power-status";
+
+    let query: Vec<(&str, &str)> = xname_vec
+        .iter()
+        .map(|xname| ("xname", xname.as_str()))
+        .collect();
+
+    let response = client
+        .get(api_url)
+        .query(&query)
+        .bearer_auth(shasta_token)
+        .send()
+        .await?;
+
+    let payload = get_response_payload(response).await?;
+
+    Ok(serde_json::from_value(payload["status"].clone())?)
+}
+
+/// Waits for a PCS power cap task to complete and returns the power cap of its components
+async fn wait_power_cap_task(
+    client: &reqwest::Client,
+    shasta_token: &str,
+    shasta_base_url: &str,
+    task_id: &str,
+) -> Result<Vec<PowerCapComponent>, Error> {
+    let api_url = format!("{}/power-control/v1/power-cap/{}", shasta_base_url, task_id);
+
+    for _ in 0..POWER_CAP_TASK_MAX_ATTEMPTS {
+        let response = client
+            .get(&api_url)
+            .bearer_auth(shasta_token)
+            .send()
+            .await?;
+
+        let payload = get_response_payload(response).await?;
+
+        log::debug!("PCS power cap task:\n{:#?}", payload);
+
+        if payload["taskStatus"].as_str() == Some("completed") {
+            return Ok(serde_json::from_value(
+                payload
+                    .get("components")
+                    .cloned()
+                    .unwrap_or(Value::Array(Vec::new())),
+            )?);
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS)).await;
     }
+
+    Err(Error::Message(format!(
+        "PCS power cap task '{}' did not complete",
+        task_id
+    )))
+}
+
+/// Returns the power cap controls of a list of nodes
+pub async fn get_power_cap_vec(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+) -> Result<Vec<PowerCapComponent>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = shasta_base_url.to_string() + "/power-control/v1/power-cap/snapshot";
+
+    let response = client
+        .post(api_url)
+        .json(&serde_json::json!({ "xnames": xname_vec }))
+        .bearer_auth(shasta_token)
+        .send()
+        .await?;
+
+    let payload = get_response_payload(response).await?;
+
+    let task_id = payload["taskID"]
+        .as_str()
+        .ok_or_else(|| Error::Message("PCS power cap snapshot without task ID".to_string()))?;
+
+    wait_power_cap_task(&client, shasta_token, shasta_base_url, task_id).await
+}
+
+/// Sets the value (watts) of a power cap control for a list of nodes. Returns the result per node
+pub async fn set_power_cap(
+    shasta_token: &str,
+    shasta_base_url: &str,
+    shasta_root_cert: &[u8],
+    xname_vec: &[String],
+    control_name: &str,
+    value: i64,
+) -> Result<Vec<PowerCapComponent>, Error> {
+    let client = http_client::get_client(shasta_root_cert)?;
+
+    let api_url = shasta_base_url.to_string() + "/power-control/v1/power-cap";
+
+    let component_vec: Vec<Value> = xname_vec
+        .iter()
+        .map(|xname| {
+            serde_json::json!({
+                "xname": xname,
+                "controls": [{ "name": control_name, "value": value }]
+            })
+        })
+        .collect();
+
+    let response = client
+        .patch(api_url)
+        .json(&serde_json::json!({ "components": component_vec }))
+        .bearer_auth(shasta_token)
+        .send()
+        .await?;
+
+    let payload = get_response_payload(response).await?;
+
+    let task_id = payload["taskID"]
+        .as_str()
+        .ok_or_else(|| Error::Message("PCS power cap without task ID".to_string()))?;
+
+    wait_power_cap_task(&client, shasta_token, shasta_base_url, task_id).await
 }
@@ -0,0 +1,118 @@
+use aws_sdk_s3::{
+    config::{BehaviorVersion, Credentials, Region},
+    Client,
+};
+use serde_json::Value;
+
+use crate::common::http_client;
+
+/// Returns an S3 client authenticated with the STS token returned by 'mesa::ims::s3::s3_auth'.
+/// Requests go through the socks5 proxy defined in 'SOCKS5' env var, if any. The client is
+/// configured like the one in 'mesa::ims::s3', which is not public and whose helpers panic or
+/// download the whole object when it is missing
+pub fn get_client(sts_value: &Value) -> Result<Client, String> {
+    let get_credential = |field: &str| -> Result<String, String> {
+        sts_value
+            .pointer(&format!("/Credentials/{}", field))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+            .ok_or(format!("STS token without '{}'", field))
+    };
+
+    let credentials = Credentials::new(
+        get_credential("AccessKeyId")?,
+        get_credential("SecretAccessKey")?,
+        Some(get_credential("SessionToken")?),
+        None,
+        "manta",
+    );
+
+    // CSM doesn't use the concept of regions
+    let mut config_builder = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new("us-east-1"))
+        .endpoint_url(get_credential("EndpointURL")?)
+        .credentials_provider(credentials)
+        .force_path_style(true);
+
+    if let Some(socks5_env) = http_client::get_socks5_proxy_opt() {
+        let mut http_connector = hyper::client::HttpConnector::new();
+        http_connector.enforce_http(false);
+
+        let socks_http_connector = hyper_socks2::SocksConnector {
+            proxy_addr: socks5_env
+                .parse::<hyper::Uri>()
+                .map_err(|error| format!("SOCKS5 proxy '{}' not valid: {}", socks5_env, error))?,
+            auth: None,
+            connector: http_connector,
+        };
+
+        // Same connector mesa uses for S3 through a socks5 proxy. hyper-socks2 only supports
+        // hyper 0.14, hence the deprecated hyper 0.14 client builder
+        #[allow(deprecated)]
+        let http_client = aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder::new()
+            .build(socks_http_connector);
+
+        config_builder = config_builder.http_client(http_client);
+    }
+
+    Ok(Client::from_conf(config_builder.build()))
+}
+
+/// Returns whether an object exists in S3 without downloading it
+pub async fn object_exists(client: &Client, bucket: &str, key: &str) -> Result<bool, String> {
+    match client.head_object().bucket(bucket).key(key).send().await {
+        Ok(_) => Ok(true),
+        Err(error)
+            if error
+                .as_service_error()
+                .is_some_and(|service_error| service_error.is_not_found()) =>
+        {
+            Ok(false)
+        }
+        Err(error) => Err(format!(
+            "Could not check S3 object 's3://{}/{}'. Reason: {}",
+            bucket,
+            key,
+            aws_sdk_s3::error::DisplayErrorContext(error)
+        )),
+    }
+}
+
+/// Returns the content of an object in S3, None if the object does not exist
+pub async fn get_object_content(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let object = match client.get_object().bucket(bucket).key(key).send().await {
+        Ok(object) => object,
+        Err(error)
+            if error
+                .as_service_error()
+                .is_some_and(|service_error| service_error.is_no_such_key()) =>
+        {
+            return Ok(None);
+        }
+        Err(error) => {
+            return Err(format!(
+                "Could not download S3 object 's3://{}/{}'. Reason: {}",
+                bucket,
+                key,
+                aws_sdk_s3::error::DisplayErrorContext(error)
+            ))
+        }
+    };
+
+    object
+        .body
+        .collect()
+        .await
+        .map(|aggregated_bytes| Some(aggregated_bytes.into_bytes().to_vec()))
+        .map_err(|error| {
+            format!(
+                "Could not download S3 object 's3://{}/{}'. Reason: {}",
+                bucket, key, error
+            )
+        })
+}
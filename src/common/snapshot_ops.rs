@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+
+/// Format of the timestamp appended to the name of snapshot files
+const SNAPSHOT_TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S";
+
+/// Returns the path of a new snapshot file '<snapshot dir>/<name>_<timestamp>.json'
+pub fn get_new_snapshot_file_path(
+    snapshot_dir_path: &Path,
+    snapshot_name: &str,
+    timestamp: &DateTime<Utc>,
+) -> PathBuf {
+    snapshot_dir_path.join(format!(
+        "{}_{}.json",
+        snapshot_name,
+        timestamp.format(SNAPSHOT_TIMESTAMP_FORMAT)
+    ))
+}
+
+/// Returns the path of a snapshot. The snapshot can be either a path to a file, the name of a file
+/// in the snapshot folder or the name of a snapshot, in which case the latest
+/// '<name>_<timestamp>.json' file in the snapshot folder is picked
+pub fn get_snapshot_file_path(snapshot: &str, snapshot_dir_path: &Path) -> Option<PathBuf> {
+    let file_path = PathBuf::from(snapshot);
+
+    if file_path.is_file() {
+        return Some(file_path);
+    }
+
+    let mut file_path = snapshot_dir_path.join(snapshot);
+
+    if file_path.extension().is_none() {
+        file_path.set_extension("json");
+    }
+
+    if file_path.is_file() {
+        return Some(file_path);
+    }
+
+    let snapshot_file_prefix = format!("{}_", snapshot);
+
+    std::fs::read_dir(snapshot_dir_path)
+        .ok()?
+        .filter_map(|dir_entry| dir_entry.ok())
+        .map(|dir_entry| dir_entry.file_name().to_string_lossy().to_string())
+        .filter(|file_name| {
+            file_name
+                .strip_prefix(&snapshot_file_prefix)
+                .and_then(|file_name| file_name.strip_suffix(".json"))
+                .is_some_and(|timestamp| {
+                    timestamp.len() == 14 && timestamp.chars().all(|c| c.is_ascii_digit())
+                })
+        })
+        // Timestamps sort chronologically
+        .max()
+        .map(|file_name| snapshot_dir_path.join(file_name))
+}
+
+/// Reads a snapshot, see `get_snapshot_file_path` for the values 'snapshot' can take.
+/// 'snapshot_type' is only used in error messages
+pub fn read_snapshot<T: DeserializeOwned>(
+    snapshot: &str,
+    snapshot_dir_path: &Path,
+    snapshot_type: &str,
+) -> Result<T, String> {
+    let file_path = get_snapshot_file_path(snapshot, snapshot_dir_path).ok_or(format!(
+        "Could not find {} snapshot '{}' in '{}'",
+        snapshot_type,
+        snapshot,
+        snapshot_dir_path.to_string_lossy()
+    ))?;
+
+    let file_content = std::fs::read_to_string(&file_path).map_err(|error| {
+        format!(
+            "Could not read {} snapshot '{}'. Reason: {}",
+            snapshot_type,
+            file_path.to_string_lossy(),
+            error
+        )
+    })?;
+
+    serde_json::from_str(&file_content).map_err(|error| {
+        format!(
+            "Could not parse {} snapshot '{}'. Reason: {}",
+            snapshot_type,
+            file_path.to_string_lossy(),
+            error
+        )
+    })
+}
@@ -0,0 +1,899 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use git2::Repository;
+use mesa::node::r#struct::NodeDetails;
+use serde_json::json;
+
+use super::{
+    ansible_playbook_ops::{preflight, PreflightIssue, Severity},
+    boot_readiness_ops::{
+        evaluate, get_image_id_from_boot_artifact_path, is_only_not_verifiable_left, on_timeout,
+        BootReadiness, BootReadinessStatus, NodeBootState,
+    },
+    lease_ops::Lease,
+    local_git_repo::{commit_work_tree_to_branch, fetch_branch, push_branch},
+    node_health_ops::get_node_health_issue_vec,
+    pcs_utils::{
+        get_batch_vec, get_escalation_operation, get_transition_summary, mark_timed_out_tasks,
+        PowerTaskResult, StaggerGroup,
+    },
+    snapshot_ops::{get_new_snapshot_file_path, get_snapshot_file_path},
+    time_ops::parse_duration,
+    watch_ops::{diff, WatchChange, WatchCondition, WatchSnapshot},
+};
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30s"), Some(Duration::seconds(30)));
+    assert_eq!(parse_duration("15m"), Some(Duration::minutes(15)));
+    assert_eq!(parse_duration(" 6h "), Some(Duration::hours(6)));
+    assert_eq!(parse_duration("2d"), Some(Duration::days(2)));
+    assert_eq!(parse_duration("1w"), Some(Duration::weeks(1)));
+}
+
+#[test]
+fn test_parse_duration_invalid() {
+    assert_eq!(parse_duration(""), None);
+    assert_eq!(parse_duration("m"), None);
+    assert_eq!(parse_duration("30"), None);
+    assert_eq!(parse_duration("30x"), None);
+    assert_eq!(parse_duration("1.5h"), None);
+    assert_eq!(parse_duration("h30"), None);
+    // Multibyte unit
+    assert_eq!(parse_duration("5µ"), None);
+    assert_eq!(parse_duration("µ"), None);
+    // Not positive
+    assert_eq!(parse_duration("0m"), None);
+    assert_eq!(parse_duration("-5m"), None);
+    // Out of range
+    assert_eq!(parse_duration("99999999999999w"), None);
+    assert_eq!(parse_duration("9223372036854775807s"), None);
+}
+
+fn get_lease(expires: &str) -> Lease {
+    Lease {
+        name: "lease-zinal-1".to_string(),
+        owner: "jdoe".to_string(),
+        target_hsm_group: "zinal".to_string(),
+        parent_hsm_group: "nodes_free".to_string(),
+        created: "2024-05-01T00:00:00Z".to_string(),
+        expires: expires.to_string(),
+        members: vec!["x1000c0s0b0n0".to_string()],
+    }
+}
+
+#[test]
+fn test_lease_get_name() {
+    let created: DateTime<Utc> = "2024-05-02T12:00:00Z".parse().unwrap();
+
+    assert_eq!(
+        Lease::get_name("jdoe", &created),
+        "lease_jdoe_20240502120000"
+    );
+    assert_eq!(
+        Lease::get_name("J.Doe@CSCS ch-1", &created),
+        "lease_j_doe_cscs_ch-1_20240502120000"
+    );
+}
+
+#[test]
+fn test_lease_is_expired() {
+    let now: DateTime<Utc> = "2024-05-02T12:00:00Z".parse().unwrap();
+
+    assert!(get_lease("2024-05-02T11:59:59Z").is_expired(&now));
+    assert!(get_lease("2024-05-02T12:00:00Z").is_expired(&now));
+    assert!(!get_lease("2024-05-02T12:00:01Z").is_expired(&now));
+    // Timezone offsets are taken into account
+    assert!(get_lease("2024-05-02T13:00:00+02:00").is_expired(&now));
+    assert!(!get_lease("2024-05-02T13:00:00-02:00").is_expired(&now));
+}
+
+#[test]
+fn test_lease_with_invalid_expiration_date_never_expires() {
+    let now: DateTime<Utc> = "2024-05-02T12:00:00Z".parse().unwrap();
+
+    assert!(!get_lease("").is_expired(&now));
+    assert!(!get_lease("tomorrow").is_expired(&now));
+}
+
+fn get_power_task_result(xname: &str, status: &str) -> PowerTaskResult {
+    PowerTaskResult {
+        xname: xname.to_string(),
+        operation: "soft-restart".to_string(),
+        status: status.to_string(),
+        description: String::new(),
+        attempts: 1,
+        transition_id: "f7a7b5c4".to_string(),
+    }
+}
+
+#[test]
+fn test_get_escalation_operation() {
+    assert_eq!(get_escalation_operation("soft-restart"), "hard-restart");
+    assert_eq!(get_escalation_operation("soft-off"), "force-off");
+    assert_eq!(get_escalation_operation("off"), "force-off");
+    // Operations already hard or without a hard version are retried as they are
+    assert_eq!(get_escalation_operation("hard-restart"), "hard-restart");
+    assert_eq!(get_escalation_operation("force-off"), "force-off");
+    assert_eq!(get_escalation_operation("on"), "on");
+}
+
+#[test]
+fn test_power_task_result_is_retryable() {
+    assert!(get_power_task_result("x1000c0s0b0n0", "failed").is_retryable());
+    assert!(get_power_task_result("x1000c0s0b0n0", "timeout").is_retryable());
+    assert!(!get_power_task_result("x1000c0s0b0n0", "succeeded").is_retryable());
+    assert!(!get_power_task_result("x1000c0s0b0n0", "unsupported").is_retryable());
+}
+
+#[test]
+fn test_mark_timed_out_tasks() {
+    let task_vec = vec![
+        (
+            "x1000c0s0b0n0".to_string(),
+            "succeeded".to_string(),
+            "Transition confirmed, on".to_string(),
+        ),
+        (
+            "x1000c0s0b0n1".to_string(),
+            "in-progress".to_string(),
+            "Confirming successful transition, on".to_string(),
+        ),
+        (
+            "x1000c0s0b0n2".to_string(),
+            "new".to_string(),
+            String::new(),
+        ),
+        (
+            "x1000c0s0b0n3".to_string(),
+            "failed".to_string(),
+            "Failed to achieve transition".to_string(),
+        ),
+    ];
+
+    assert_eq!(
+        mark_timed_out_tasks(task_vec),
+        vec![
+            (
+                "x1000c0s0b0n0".to_string(),
+                "succeeded".to_string(),
+                "Transition confirmed, on".to_string(),
+            ),
+            (
+                "x1000c0s0b0n1".to_string(),
+                "timeout".to_string(),
+                "still in-progress when the timeout was reached".to_string(),
+            ),
+            (
+                "x1000c0s0b0n2".to_string(),
+                "timeout".to_string(),
+                "still new when the timeout was reached".to_string(),
+            ),
+            (
+                "x1000c0s0b0n3".to_string(),
+                "failed".to_string(),
+                "Failed to achieve transition".to_string(),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_get_transition_summary() {
+    let transition_summary = get_transition_summary(&[
+        get_power_task_result("x1000c0s0b0n0", "succeeded"),
+        get_power_task_result("x1000c0s0b0n1", "failed"),
+    ]);
+
+    assert_eq!(transition_summary["transitionStatus"], "completed");
+    assert_eq!(transition_summary["tasks"][1]["xname"], "x1000c0s0b0n1");
+    assert_eq!(transition_summary["tasks"][1]["taskStatus"], "failed");
+    assert_eq!(transition_summary["tasks"][1]["transitionID"], "f7a7b5c4");
+
+    let transition_summary =
+        get_transition_summary(&[get_power_task_result("x1000c0s0b0n0", "timeout")]);
+
+    assert_eq!(transition_summary["transitionStatus"], "aborted");
+}
+
+/// Creates a repo in a new temporary directory with one commit adding 'site.yml'
+fn init_repo_with_commit() -> (PathBuf, Repository) {
+    let repo_path = std::env::temp_dir().join(format!("manta-test-repo-{}", uuid::Uuid::new_v4()));
+
+    let repo = Repository::init(&repo_path).unwrap();
+
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "manta").unwrap();
+    config.set_str("user.email", "manta@example.com").unwrap();
+
+    std::fs::write(repo_path.join("site.yml"), "---\n").unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("site.yml")).unwrap();
+    let tree_id = index.write_tree().unwrap();
+    index.write().unwrap();
+
+    {
+        let tree = repo.find_tree(tree_id).unwrap();
+        let signature = repo.signature().unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+
+    (repo_path, repo)
+}
+
+#[test]
+fn test_commit_work_tree_to_branch_leaves_head_untouched() {
+    let (repo_path, repo) = init_repo_with_commit();
+
+    let head_commit_id = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    std::fs::write(repo_path.join("site.yml"), "---\n- hosts: all\n").unwrap();
+    std::fs::write(repo_path.join("new_role.yml"), "---\n").unwrap();
+
+    let commit_id = commit_work_tree_to_branch(&repo, "manta/test", "test").unwrap();
+
+    assert_eq!(
+        repo.head().unwrap().peel_to_commit().unwrap().id(),
+        head_commit_id
+    );
+    assert!(repo
+        .statuses(None)
+        .unwrap()
+        .iter()
+        .all(|status| !status.status().is_index_new() && !status.status().is_index_modified()));
+
+    let branch_commit = repo.find_commit(commit_id).unwrap();
+    assert_eq!(branch_commit.parent_id(0).unwrap(), head_commit_id);
+    assert!(branch_commit
+        .tree()
+        .unwrap()
+        .get_name("new_role.yml")
+        .is_some());
+
+    std::fs::remove_dir_all(repo_path).unwrap();
+}
+
+#[test]
+fn test_push_and_fetch_branch() {
+    let (repo_path, repo) = init_repo_with_commit();
+
+    let remote_path =
+        std::env::temp_dir().join(format!("manta-test-remote-{}", uuid::Uuid::new_v4()));
+    Repository::init_bare(&remote_path).unwrap();
+    let remote_url = remote_path.to_string_lossy().to_string();
+
+    let branch_name = repo.head().unwrap().shorthand().unwrap().to_string();
+    let commit_id = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+    assert!(fetch_branch(&repo, &remote_url, &branch_name, "token")
+        .unwrap()
+        .is_none());
+
+    push_branch(&repo, &remote_url, &branch_name, "token").unwrap();
+
+    let remote_commit = fetch_branch(&repo, &remote_url, &branch_name, "token")
+        .unwrap()
+        .unwrap();
+    assert_eq!(remote_commit.id(), commit_id);
+
+    std::fs::remove_dir_all(repo_path).unwrap();
+    std::fs::remove_dir_all(remote_path).unwrap();
+}
+
+#[test]
+fn test_get_snapshot_file_path_picks_latest_snapshot() {
+    let snapshot_dir_path =
+        std::env::temp_dir().join(format!("manta-test-snapshots-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&snapshot_dir_path).unwrap();
+
+    let older_file_path = get_new_snapshot_file_path(
+        &snapshot_dir_path,
+        "zinal",
+        &"2024-01-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap(),
+    );
+    let latest_file_path = get_new_snapshot_file_path(
+        &snapshot_dir_path,
+        "zinal",
+        &"2024-03-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap(),
+    );
+    // Shares the prefix but belongs to a different snapshot
+    let other_file_path = get_new_snapshot_file_path(
+        &snapshot_dir_path,
+        "zinal_test",
+        &"2024-06-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap(),
+    );
+
+    for file_path in [&older_file_path, &latest_file_path, &other_file_path] {
+        std::fs::write(file_path, "{}").unwrap();
+    }
+
+    assert_eq!(
+        latest_file_path.file_name().unwrap(),
+        "zinal_20240301100000.json"
+    );
+    assert_eq!(
+        get_snapshot_file_path("zinal", &snapshot_dir_path),
+        Some(latest_file_path)
+    );
+    // File name in the snapshot folder, with or without extension
+    assert_eq!(
+        get_snapshot_file_path("zinal_20240101100000", &snapshot_dir_path),
+        Some(older_file_path.clone())
+    );
+    // Path to a file
+    assert_eq!(
+        get_snapshot_file_path(
+            &older_file_path.to_string_lossy(),
+            Path::new("/nonexistent")
+        ),
+        Some(older_file_path)
+    );
+    assert_eq!(get_snapshot_file_path("eiger", &snapshot_dir_path), None);
+
+    std::fs::remove_dir_all(snapshot_dir_path).unwrap();
+}
+
+#[test]
+fn test_get_image_id_from_boot_artifact_path() {
+    assert_eq!(
+        get_image_id_from_boot_artifact_path(
+            "s3://boot-images/1e5b9fc4-8b1f-4b54-9a2c-3b3f7d6a1f00/kernel"
+        ),
+        "1e5b9fc4-8b1f-4b54-9a2c-3b3f7d6a1f00"
+    );
+    assert_eq!(
+        get_image_id_from_boot_artifact_path("s3://boot-images/nested/image/initrd"),
+        "nested/image"
+    );
+    assert_eq!(
+        get_image_id_from_boot_artifact_path("s3://boot-images/kernel"),
+        ""
+    );
+    assert_eq!(get_image_id_from_boot_artifact_path(""), "");
+}
+
+fn get_node_boot_state(booted_image_id_opt: Option<&str>) -> NodeBootState {
+    NodeBootState {
+        xname: "x1000c0s0b0n0".to_string(),
+        power_status: "ON".to_string(),
+        bss_image_id: "image-b".to_string(),
+        booted_kernel_opt: booted_image_id_opt
+            .map(|image_id| format!("s3://boot-images/{}/kernel", image_id)),
+        booted_initrd_opt: booted_image_id_opt
+            .map(|image_id| format!("s3://boot-images/{}/initrd", image_id)),
+        booted_last_updated_opt: None,
+        desired_configuration: "config-b".to_string(),
+        configuration_status: "configured".to_string(),
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    let node_boot_state = get_node_boot_state(Some("image-b"));
+
+    assert_eq!(
+        evaluate(&node_boot_state, "image-b", Some("config-b"), true).status,
+        BootReadinessStatus::Ready
+    );
+    assert_eq!(
+        evaluate(&node_boot_state, "image-c", None, true).status,
+        BootReadinessStatus::Mismatch
+    );
+    assert_eq!(
+        evaluate(&node_boot_state, "image-b", Some("config-c"), true).status,
+        BootReadinessStatus::Mismatch
+    );
+    assert_eq!(
+        evaluate(&node_boot_state, "image-b", None, false).status,
+        BootReadinessStatus::Pending
+    );
+
+    // Node still running the previous image
+    assert_eq!(
+        evaluate(&get_node_boot_state(Some("image-a")), "image-b", None, true).status,
+        BootReadinessStatus::Booting
+    );
+
+    let node_boot_state_off = NodeBootState {
+        power_status: "OFF".to_string(),
+        ..node_boot_state.clone()
+    };
+    assert_eq!(
+        evaluate(&node_boot_state_off, "image-b", None, true).status,
+        BootReadinessStatus::Pending
+    );
+
+    let node_boot_state_failed = NodeBootState {
+        configuration_status: "failed".to_string(),
+        ..node_boot_state.clone()
+    };
+    assert_eq!(
+        evaluate(&node_boot_state_failed, "image-b", None, true).status,
+        BootReadinessStatus::ConfigurationFailed
+    );
+
+    let node_boot_state_pending = NodeBootState {
+        configuration_status: "pending".to_string(),
+        ..node_boot_state
+    };
+    assert_eq!(
+        evaluate(&node_boot_state_pending, "image-b", None, true).status,
+        BootReadinessStatus::Pending
+    );
+}
+
+/// Nodes without BOS reporter data fall back to HSM power state and CFS configuration status
+#[test]
+fn test_evaluate_without_bos_reporter() {
+    let node_boot_state = get_node_boot_state(None);
+
+    assert_eq!(
+        evaluate(&node_boot_state, "image-b", None, false).status,
+        BootReadinessStatus::NotVerifiable
+    );
+    assert_eq!(
+        evaluate(&node_boot_state, "image-b", None, true).status,
+        BootReadinessStatus::Ready
+    );
+
+    let node_boot_state_pending = NodeBootState {
+        configuration_status: "pending".to_string(),
+        ..node_boot_state
+    };
+    assert_eq!(
+        evaluate(&node_boot_state_pending, "image-b", None, false).status,
+        BootReadinessStatus::Pending
+    );
+}
+
+#[test]
+fn test_is_only_not_verifiable_left() {
+    let boot_readiness_map: BTreeMap<String, BootReadiness> = [
+        ("x1000c0s0b0n0", BootReadinessStatus::NotVerifiable),
+        ("x1000c0s0b0n1", BootReadinessStatus::Ready),
+        ("x1000c0s0b0n2", BootReadinessStatus::Booting),
+    ]
+    .into_iter()
+    .map(|(xname, status)| {
+        (
+            xname.to_string(),
+            BootReadiness {
+                xname: xname.to_string(),
+                status,
+                detail: String::new(),
+            },
+        )
+    })
+    .collect();
+
+    assert!(is_only_not_verifiable_left(
+        &["x1000c0s0b0n0".to_string()],
+        &boot_readiness_map
+    ));
+    assert!(!is_only_not_verifiable_left(
+        &["x1000c0s0b0n0".to_string(), "x1000c0s0b0n2".to_string()],
+        &boot_readiness_map
+    ));
+    // Nodes not polled yet
+    assert!(!is_only_not_verifiable_left(
+        &["x1000c0s0b0n3".to_string()],
+        &boot_readiness_map
+    ));
+    assert!(!is_only_not_verifiable_left(&[], &boot_readiness_map));
+}
+
+#[test]
+fn test_on_timeout() {
+    let boot_readiness = |status: BootReadinessStatus| BootReadiness {
+        xname: "x1000c0s0b0n0".to_string(),
+        status,
+        detail: String::new(),
+    };
+
+    for (status, status_on_timeout) in [
+        (BootReadinessStatus::Booting, BootReadinessStatus::Mismatch),
+        (BootReadinessStatus::Pending, BootReadinessStatus::Timeout),
+        (
+            BootReadinessStatus::NotVerifiable,
+            BootReadinessStatus::NotVerifiable,
+        ),
+        (BootReadinessStatus::Ready, BootReadinessStatus::Ready),
+        (
+            BootReadinessStatus::ConfigurationFailed,
+            BootReadinessStatus::ConfigurationFailed,
+        ),
+    ] {
+        assert_eq!(on_timeout(boot_readiness(status)).status, status_on_timeout);
+    }
+}
+
+#[test]
+fn test_stagger_group_get_location() {
+    assert_eq!(StaggerGroup::Cabinet.get_location("x1000c1s0b0n0"), "x1000");
+    assert_eq!(
+        StaggerGroup::Chassis.get_location("x1000c1s0b0n0"),
+        "x1000c1"
+    );
+    // Cabinet level xname has no chassis
+    assert_eq!(StaggerGroup::Chassis.get_location("x1000"), "x1000");
+    assert_eq!(StaggerGroup::Cabinet.get_location("nid000001"), "nid000001");
+}
+
+#[test]
+fn test_get_batch_vec() {
+    let xname_vec: Vec<String> = [
+        "x1001c0s0b0n0",
+        "x1000c1s0b0n1",
+        "x1000c0s0b0n0",
+        "x1000c0s1b0n0",
+        "x1000c0s0b0n1",
+    ]
+    .iter()
+    .map(|xname| xname.to_string())
+    .collect();
+
+    let to_batch = |location: &str, xname_vec: &[&str]| -> (String, Vec<String>) {
+        (
+            location.to_string(),
+            xname_vec.iter().map(|xname| xname.to_string()).collect(),
+        )
+    };
+
+    assert_eq!(
+        get_batch_vec(&xname_vec, StaggerGroup::Cabinet, 2),
+        vec![
+            to_batch("x1000", &["x1000c0s0b0n0", "x1000c0s0b0n1"]),
+            to_batch("x1000", &["x1000c0s1b0n0", "x1000c1s0b0n1"]),
+            to_batch("x1001", &["x1001c0s0b0n0"]),
+        ]
+    );
+
+    assert_eq!(
+        get_batch_vec(&xname_vec, StaggerGroup::Chassis, 16),
+        vec![
+            to_batch(
+                "x1000c0",
+                &["x1000c0s0b0n0", "x1000c0s0b0n1", "x1000c0s1b0n0"]
+            ),
+            to_batch("x1000c1", &["x1000c1s0b0n1"]),
+            to_batch("x1001c0", &["x1001c0s0b0n0"]),
+        ]
+    );
+
+    // Batch size 0 is handled as 1
+    assert_eq!(
+        get_batch_vec(&xname_vec[..1], StaggerGroup::Cabinet, 0).len(),
+        1
+    );
+}
+
+fn get_watch_snapshot(
+    summary: &[(&str, &str)],
+    row_vec: &[(&str, &[(&str, &str)])],
+) -> WatchSnapshot {
+    let to_map = |field_vec: &[(&str, &str)]| -> BTreeMap<String, String> {
+        field_vec
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect()
+    };
+
+    WatchSnapshot {
+        summary: to_map(summary),
+        row_map: row_vec
+            .iter()
+            .map(|(key, field_vec)| (key.to_string(), to_map(field_vec)))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_watch_condition_from_str() {
+    assert_eq!(
+        " Status = OK ".parse::<WatchCondition>(),
+        Ok(WatchCondition {
+            field: "status".to_string(),
+            value: "OK".to_string(),
+        })
+    );
+
+    assert!("status".parse::<WatchCondition>().is_err());
+    assert!("=OK".parse::<WatchCondition>().is_err());
+    assert!("status=".parse::<WatchCondition>().is_err());
+}
+
+#[test]
+fn test_watch_condition_is_met() {
+    let watch_condition: WatchCondition = "status=ok".parse().unwrap();
+
+    // Summary field takes precedence over rows
+    let watch_snapshot = get_watch_snapshot(
+        &[("status", "OK")],
+        &[("x1000c0s0b0n0", &[("status", "FAILED")])],
+    );
+    assert!(watch_condition.is_met(&watch_snapshot));
+
+    let watch_snapshot = get_watch_snapshot(&[("status", "FAILED")], &[]);
+    assert!(!watch_condition.is_met(&watch_snapshot));
+
+    // Every row must match
+    let watch_snapshot = get_watch_snapshot(
+        &[],
+        &[
+            ("session-1", &[("status", "ok")]),
+            ("session-2", &[("status", "OK")]),
+        ],
+    );
+    assert!(watch_condition.is_met(&watch_snapshot));
+
+    let watch_snapshot = get_watch_snapshot(
+        &[],
+        &[
+            ("session-1", &[("status", "ok")]),
+            ("session-2", &[("succeeded", "true")]),
+        ],
+    );
+    assert!(!watch_condition.is_met(&watch_snapshot));
+
+    // Nothing to watch
+    assert!(!watch_condition.is_met(&WatchSnapshot::default()));
+}
+
+#[test]
+fn test_watch_condition_validate() {
+    let watch_snapshot = get_watch_snapshot(
+        &[("status", "OK")],
+        &[("x1000c0s0b0n0", &[("power", "on"), ("enabled", "true")])],
+    );
+
+    let watch_condition: WatchCondition = "power=ON".parse().unwrap();
+    assert!(watch_condition.validate(&watch_snapshot).is_ok());
+
+    let watch_condition: WatchCondition = "status=OK".parse().unwrap();
+    assert!(watch_condition.validate(&watch_snapshot).is_ok());
+
+    let watch_condition: WatchCondition = "powr=ON".parse().unwrap();
+    assert_eq!(
+        watch_condition.validate(&watch_snapshot),
+        Err("field 'powr' not valid. Use one of: enabled, power, status".to_string())
+    );
+
+    // Fields not known yet
+    assert!(watch_condition.validate(&WatchSnapshot::default()).is_ok());
+}
+
+#[test]
+fn test_watch_diff() {
+    let old = get_watch_snapshot(
+        &[("status", "OK")],
+        &[
+            ("x1000c0s0b0n0", &[("power", "on"), ("enabled", "true")]),
+            ("x1000c0s0b0n1", &[("power", "on")]),
+        ],
+    );
+
+    let new = get_watch_snapshot(
+        &[("status", "FAILED")],
+        &[
+            ("x1000c0s0b0n0", &[("power", "off"), ("enabled", "true")]),
+            ("x1000c0s1b0n0", &[("power", "on")]),
+        ],
+    );
+
+    let to_change =
+        |key: &str, field: &str, old_opt: Option<&str>, new_opt: Option<&str>| WatchChange {
+            key: key.to_string(),
+            field: field.to_string(),
+            old_opt: old_opt.map(str::to_string),
+            new_opt: new_opt.map(str::to_string),
+        };
+
+    assert_eq!(
+        diff(&old, &new),
+        vec![
+            to_change("summary", "status", Some("OK"), Some("FAILED")),
+            to_change("x1000c0s0b0n0", "power", Some("on"), Some("off")),
+            to_change("x1000c0s1b0n0", "power", None, Some("on")),
+            to_change("x1000c0s0b0n1", "-", Some("present"), None),
+        ]
+    );
+
+    assert!(diff(&new, &new).is_empty());
+}
+
+/// Creates an ansible repo in a temporary folder with the files provided
+fn init_ansible_repo(file_vec: &[(&str, &str)]) -> PathBuf {
+    let repo_path =
+        std::env::temp_dir().join(format!("manta-test-ansible-{}", uuid::Uuid::new_v4()));
+
+    for (file, content) in file_vec {
+        let file_path = repo_path.join(file);
+        std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        std::fs::write(file_path, content).unwrap();
+    }
+
+    repo_path
+}
+
+fn get_preflight_message_vec(preflight_issue_vec: &[PreflightIssue]) -> Vec<(Severity, String)> {
+    preflight_issue_vec
+        .iter()
+        .map(|preflight_issue| (preflight_issue.severity, preflight_issue.message.clone()))
+        .collect()
+}
+
+#[test]
+fn test_preflight_valid_repo() {
+    let repo_path = init_ansible_repo(&[
+        (
+            "site.yml",
+            "- import_playbook: compute.yml\n- hosts: Compute:!x1000c0s0b0n0\n  roles:\n    - common\n",
+        ),
+        (
+            "compute.yml",
+            "- hosts: Compute\n  tasks:\n    - ansible.builtin.include_tasks: tasks/setup.yml\n",
+        ),
+        ("tasks/setup.yml", "- debug:\n    msg: setup\n"),
+        (
+            "roles/common/tasks/main.yml",
+            "- include_tasks: packages.yml\n- include_tasks: \"{{ os }}.yml\"\n",
+        ),
+        ("roles/common/tasks/packages.yml", "- debug:\n    msg: packages\n"),
+        ("group_vars/Compute.yml", "foo: bar\n"),
+        ("group_vars/all/main.yml", "foo: bar\n"),
+    ]);
+
+    let preflight_issue_vec = preflight(&repo_path, "site.yml", &["Compute".to_string()]);
+
+    std::fs::remove_dir_all(&repo_path).unwrap();
+
+    assert!(preflight_issue_vec.is_empty(), "{:?}", preflight_issue_vec);
+}
+
+#[test]
+fn test_preflight_missing_playbook() {
+    let repo_path = init_ansible_repo(&[]);
+
+    let preflight_issue_vec = preflight(&repo_path, "site.yml", &[]);
+
+    assert_eq!(
+        get_preflight_message_vec(&preflight_issue_vec),
+        vec![(Severity::Error, "playbook 'site.yml' not found".to_string())]
+    );
+}
+
+#[test]
+fn test_preflight_issues() {
+    let repo_path = init_ansible_repo(&[
+        (
+            "site.yml",
+            "- hosts: Compute,Application_UAN\n  roles:\n    - common\n    - role: missing\n    - vendor.collection.role\n  tasks:\n    - block:\n        - include_tasks: tasks/missing.yml\n",
+        ),
+        ("roles/common/tasks/main.yml", "- import_tasks: missing.yml\n"),
+        ("group_vars/Compute/main.yml", "foo: bar\n"),
+        ("group_vars/Application_UAN.yml", "foo: bar\n"),
+    ]);
+
+    let preflight_issue_vec = preflight(&repo_path, "site.yml", &["Compute".to_string()]);
+
+    std::fs::remove_dir_all(&repo_path).unwrap();
+
+    let preflight_message_vec = get_preflight_message_vec(&preflight_issue_vec);
+
+    assert_eq!(
+        preflight_message_vec.len(),
+        5,
+        "{:?}",
+        preflight_message_vec
+    );
+
+    for preflight_message in [
+        (
+            Severity::Warning,
+            "hosts pattern 'Compute,Application_UAN' references group 'Application_UAN' which is not in the CFS inventory for the target nodes, plays will be skipped".to_string(),
+        ),
+        (
+            Severity::Error,
+            "import_tasks file 'missing.yml' not found".to_string(),
+        ),
+        (Severity::Error, "role 'missing' not found".to_string()),
+        (
+            Severity::Error,
+            "include_tasks file 'tasks/missing.yml' not found".to_string(),
+        ),
+        (
+            Severity::Warning,
+            "variables for group 'Application_UAN' which is not in the CFS inventory for the target nodes, they won't be applied".to_string(),
+        ),
+    ] {
+        assert!(
+            preflight_message_vec.contains(&preflight_message),
+            "{:?} not in {:?}",
+            preflight_message,
+            preflight_message_vec
+        );
+    }
+}
+
+fn get_node_details(power_status: &str, configuration_status: &str) -> NodeDetails {
+    NodeDetails {
+        xname: "x1000c0s0b0n0".to_string(),
+        nid: "nid000001".to_string(),
+        hsm: "zinal, compute".to_string(),
+        power_status: power_status.to_string(),
+        desired_configuration: "cfg-zinal".to_string(),
+        configuration_status: configuration_status.to_string(),
+        enabled: "true".to_string(),
+        error_count: "0".to_string(),
+        boot_image_id: "image-id".to_string(),
+        boot_configuration: "cfg-zinal".to_string(),
+        kernel_params: "".to_string(),
+    }
+}
+
+#[test]
+fn test_get_node_health_issue_vec_healthy() {
+    let hsm_component = json!({"ID": "x1000c0s0b0n0", "Enabled": true, "Flag": "OK"});
+
+    let running_cfs_session_target_vec = vec![(
+        "session-other".to_string(),
+        vec!["x1000c0s1b0n0".to_string(), "other".to_string()],
+    )];
+
+    assert!(get_node_health_issue_vec(
+        &get_node_details("ON", "configured"),
+        Some(&hsm_component),
+        &running_cfs_session_target_vec,
+    )
+    .is_empty());
+
+    assert!(get_node_health_issue_vec(
+        &get_node_details("READY", "configured"),
+        Some(&hsm_component),
+        &[],
+    )
+    .is_empty());
+}
+
+#[test]
+fn test_get_node_health_issue_vec_unhealthy() {
+    let hsm_component = json!({"ID": "x1000c0s0b0n0", "Enabled": false, "Flag": "Warning"});
+
+    let running_cfs_session_target_vec = vec![
+        (
+            "session-xname".to_string(),
+            vec!["x1000c0s0b0n0".to_string()],
+        ),
+        ("session-hsm".to_string(), vec!["compute".to_string()]),
+    ];
+
+    assert_eq!(
+        get_node_health_issue_vec(
+            &get_node_details("OFF", "failed"),
+            Some(&hsm_component),
+            &running_cfs_session_target_vec,
+        ),
+        vec![
+            "power state is OFF",
+            "CFS configuration 'cfg-zinal' status is failed",
+            "HSM component disabled",
+            "HSM component flag is Warning",
+            "CFS session 'session-xname' running",
+            "CFS session 'session-hsm' running",
+        ]
+    );
+
+    assert_eq!(
+        get_node_health_issue_vec(&get_node_details("ON", "pending"), None, &[]),
+        vec![
+            "CFS configuration 'cfg-zinal' status is pending",
+            "HSM component not found",
+        ]
+    );
+}
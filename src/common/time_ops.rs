@@ -0,0 +1,36 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+/// Parses a duration like '30m', '6h', '2d' or '1w'. Returns None if the value is not positive
+/// or out of range
+pub fn parse_duration(duration: &str) -> Option<Duration> {
+    let duration = duration.trim();
+
+    let unit = duration.chars().last()?;
+
+    let value: i64 = duration[..duration.len() - unit.len_utf8()].parse().ok()?;
+
+    if value <= 0 {
+        return None;
+    }
+
+    match unit {
+        's' => Duration::try_seconds(value),
+        'm' => Duration::try_minutes(value),
+        'h' => Duration::try_hours(value),
+        'd' => Duration::try_days(value),
+        'w' => Duration::try_weeks(value),
+        _ => None,
+    }
+}
+
+/// Parses dates in RFC 3339 format or without timezone (UTC), eg '2024-05-01T10:00:00'
+pub fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .map(|date| date.and_utc())
+        })
+}
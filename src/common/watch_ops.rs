@@ -0,0 +1,291 @@
+use std::{collections::BTreeMap, future::Future, str::FromStr, time::Duration};
+
+use chrono::Local;
+use comfy_table::{Cell, Color, Table};
+use mesa::{cfs::session::mesa::r#struct::v3::CfsSessionGetResponse, node::r#struct::NodeDetails};
+
+use crate::common::node_ops;
+
+/// Condition to stop watching, like 'status=OK'. The condition is met if either the summary
+/// field or the field of every row matches the value (case insensitive)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchCondition {
+    pub field: String,
+    pub value: String,
+}
+
+impl FromStr for WatchCondition {
+    type Err = String;
+
+    fn from_str(condition: &str) -> Result<Self, Self::Err> {
+        match condition.split_once('=') {
+            Some((field, value)) if !field.trim().is_empty() && !value.trim().is_empty() => {
+                Ok(WatchCondition {
+                    field: field.trim().to_lowercase(),
+                    value: value.trim().to_string(),
+                })
+            }
+            _ => Err(format!(
+                "condition '{}' not valid. Use <field>=<value> eg 'status=OK'",
+                condition
+            )),
+        }
+    }
+}
+
+impl WatchCondition {
+    /// Checks the field exists in the snapshot. Snapshots without rows nor summary can't be
+    /// checked and are accepted
+    pub fn validate(&self, watch_snapshot: &WatchSnapshot) -> Result<(), String> {
+        let field_vec = watch_snapshot.get_field_vec();
+
+        if field_vec.is_empty() || field_vec.contains(&self.field.as_str()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "field '{}' not valid. Use one of: {}",
+                self.field,
+                field_vec.join(", ")
+            ))
+        }
+    }
+
+    pub fn is_met(&self, watch_snapshot: &WatchSnapshot) -> bool {
+        if let Some(value) = watch_snapshot.summary.get(&self.field) {
+            return value.eq_ignore_ascii_case(&self.value);
+        }
+
+        !watch_snapshot.row_map.is_empty()
+            && watch_snapshot.row_map.values().all(|field_map| {
+                field_map
+                    .get(&self.field)
+                    .is_some_and(|value| value.eq_ignore_ascii_case(&self.value))
+            })
+    }
+}
+
+/// State of the resources watched. Summary contains values aggregated across all rows (eg cluster
+/// status), rows contain the values of each resource (eg node or CFS session)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchSnapshot {
+    pub summary: BTreeMap<String, String>,
+    pub row_map: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl WatchSnapshot {
+    /// Returns the names of the fields in the summary and rows, sorted and without duplicates
+    pub fn get_field_vec(&self) -> Vec<&str> {
+        let mut field_vec: Vec<&str> = self
+            .summary
+            .keys()
+            .chain(self.row_map.values().flat_map(|field_map| field_map.keys()))
+            .map(String::as_str)
+            .collect();
+
+        field_vec.sort();
+        field_vec.dedup();
+
+        field_vec
+    }
+
+    pub fn from_node_details_vec(node_details_vec: &[NodeDetails]) -> Self {
+        let mut watch_snapshot = WatchSnapshot::default();
+
+        watch_snapshot.summary.insert(
+            "status".to_string(),
+            node_ops::get_cluster_status(node_details_vec).to_string(),
+        );
+
+        for node_details in node_details_vec {
+            watch_snapshot.row_map.insert(
+                node_details.xname.clone(),
+                BTreeMap::from([
+                    ("power".to_string(), node_details.power_status.clone()),
+                    (
+                        "configuration_status".to_string(),
+                        node_details.configuration_status.clone(),
+                    ),
+                    (
+                        "desired_configuration".to_string(),
+                        node_details.desired_configuration.clone(),
+                    ),
+                    ("enabled".to_string(), node_details.enabled.clone()),
+                    ("error_count".to_string(), node_details.error_count.clone()),
+                    (
+                        "boot_configuration".to_string(),
+                        node_details.boot_configuration.clone(),
+                    ),
+                    ("boot_image".to_string(), node_details.boot_image_id.clone()),
+                ]),
+            );
+        }
+
+        watch_snapshot
+    }
+
+    pub fn from_cfs_session_vec(cfs_session_vec: &[CfsSessionGetResponse]) -> Self {
+        let mut watch_snapshot = WatchSnapshot::default();
+
+        for cfs_session in cfs_session_vec {
+            let session_status_opt = cfs_session
+                .status
+                .as_ref()
+                .and_then(|status| status.session.as_ref());
+
+            watch_snapshot.row_map.insert(
+                cfs_session.name.clone().unwrap_or_default(),
+                BTreeMap::from([
+                    (
+                        "status".to_string(),
+                        session_status_opt
+                            .and_then(|session| session.status.clone())
+                            .unwrap_or_default(),
+                    ),
+                    (
+                        "succeeded".to_string(),
+                        session_status_opt
+                            .and_then(|session| session.succeeded.clone())
+                            .unwrap_or_default(),
+                    ),
+                    (
+                        "image".to_string(),
+                        cfs_session.get_first_result_id().unwrap_or_default(),
+                    ),
+                ]),
+            );
+        }
+
+        watch_snapshot
+    }
+}
+
+/// A value which changed between two snapshots. A missing old value means the row is new, a
+/// missing new value means the row is gone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchChange {
+    pub key: String,
+    pub field: String,
+    pub old_opt: Option<String>,
+    pub new_opt: Option<String>,
+}
+
+/// Returns the values which changed between two snapshots
+pub fn diff(old: &WatchSnapshot, new: &WatchSnapshot) -> Vec<WatchChange> {
+    let mut watch_change_vec = Vec::new();
+
+    for (field, new_value) in &new.summary {
+        let old_value_opt = old.summary.get(field);
+
+        if old_value_opt != Some(new_value) {
+            watch_change_vec.push(WatchChange {
+                key: "summary".to_string(),
+                field: field.clone(),
+                old_opt: old_value_opt.cloned(),
+                new_opt: Some(new_value.clone()),
+            });
+        }
+    }
+
+    for (key, new_field_map) in &new.row_map {
+        let old_field_map_opt = old.row_map.get(key);
+
+        for (field, new_value) in new_field_map {
+            let old_value_opt =
+                old_field_map_opt.and_then(|old_field_map| old_field_map.get(field));
+
+            if old_value_opt != Some(new_value) {
+                watch_change_vec.push(WatchChange {
+                    key: key.clone(),
+                    field: field.clone(),
+                    old_opt: old_value_opt.cloned(),
+                    new_opt: Some(new_value.clone()),
+                });
+            }
+        }
+    }
+
+    for key in old.row_map.keys() {
+        if !new.row_map.contains_key(key) {
+            watch_change_vec.push(WatchChange {
+                key: key.clone(),
+                field: "-".to_string(),
+                old_opt: Some("present".to_string()),
+                new_opt: None,
+            });
+        }
+    }
+
+    watch_change_vec
+}
+
+fn get_value_color(value: &str) -> Color {
+    match value.to_lowercase().as_str() {
+        "on" | "ready" | "configured" | "complete" | "true" | "ok" => Color::Green,
+        "off" | "failed" | "false" | "standby" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+pub fn print_changes(watch_change_vec: &[WatchChange]) {
+    let mut table = Table::new();
+
+    table.set_header(vec!["Time", "Name", "Field", "Old", "New"]);
+
+    let now = Local::now().format("%H:%M:%S").to_string();
+
+    for watch_change in watch_change_vec {
+        let new_cell = match &watch_change.new_opt {
+            Some(new_value) => Cell::new(new_value).fg(get_value_color(new_value)),
+            None => Cell::new("(gone)").fg(Color::Red),
+        };
+
+        table.add_row(vec![
+            Cell::new(&now),
+            Cell::new(&watch_change.key),
+            Cell::new(&watch_change.field),
+            Cell::new(watch_change.old_opt.clone().unwrap_or("(new)".to_string())),
+            new_cell,
+        ]);
+    }
+
+    println!("{table}");
+}
+
+/// Polls resources every 'interval' seconds and prints the values which changed. Stops when the
+/// condition is met, if provided
+pub async fn watch<F, Fut>(
+    interval: u64,
+    until_opt: Option<&WatchCondition>,
+    mut watch_snapshot: WatchSnapshot,
+    mut get_watch_snapshot: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = WatchSnapshot>,
+{
+    loop {
+        if let Some(Err(error)) = until_opt.map(|until| until.validate(&watch_snapshot)) {
+            eprintln!("ERROR - Condition {}. Exit", error);
+            std::process::exit(1);
+        }
+
+        if until_opt.is_some_and(|until| until.is_met(&watch_snapshot)) {
+            let until = until_opt.unwrap();
+            println!("Condition '{}={}' met. Exit", until.field, until.value);
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+
+        let new_watch_snapshot = get_watch_snapshot().await;
+
+        let watch_change_vec = diff(&watch_snapshot, &new_watch_snapshot);
+
+        if !watch_change_vec.is_empty() {
+            print_changes(&watch_change_vec);
+        } else {
+            log::info!("No changes");
+        }
+
+        watch_snapshot = new_watch_snapshot;
+    }
+}